@@ -0,0 +1,79 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use libks::map_bin::{self, AssetIds, LayerData, ScreenData, Tile};
+use libks_ini::Ini;
+
+/// Builds a square map of `side * side` screens with varied tiles.
+fn sample_screens(side: i64) -> Vec<ScreenData> {
+    let mut screens = Vec::new();
+    for y in 0..side {
+        for x in 0..side {
+            let seed = (x * 31 + y * 17) as u8;
+            let layer = |offset: u8| LayerData(core::array::from_fn(|i| {
+                Tile(offset % 2, (i as u8).wrapping_add(seed).wrapping_add(offset) % 128)
+            }));
+
+            screens.push(ScreenData {
+                position: (1000 + x, 1000 + y),
+                layers: core::array::from_fn(|i| layer(i as u8)),
+                assets: AssetIds {
+                    tileset_a: seed,
+                    tileset_b: seed.wrapping_add(1),
+                    ambiance_a: 0,
+                    ambiance_b: 0,
+                    music: seed,
+                    gradient: seed,
+                },
+            });
+        }
+    }
+
+    screens
+}
+
+/// Builds a World.ini with `count` screen sections.
+fn sample_ini(count: usize) -> String {
+    let mut ini = String::from("[World]\nName=Benchmark\nAuthor=libks\n\n");
+    for i in 0..count {
+        ini.push_str(&format!(
+            "[x{}y1000]\nSign(A)=Sign number {i}\nShiftXMap(A)=1000\nShiftYMap(A)=1000\n\n",
+            1000 + i,
+        ));
+    }
+
+    ini
+}
+
+fn bench_map(c: &mut Criterion) {
+    let data = map_bin::encode_map(&sample_screens(20));
+
+    let mut group = c.benchmark_group("map_bin");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("parse_map_bytes", |b| {
+        b.iter(|| map_bin::parse_map_bytes(black_box(&data)))
+    });
+    group.bench_function("parse_map_uncompressed", |b| {
+        b.iter(|| map_bin::parse_map_uncompressed(&mut black_box(&data[..])).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_ini(c: &mut Criterion) {
+    let source = sample_ini(400);
+    let ini = Ini::new(&source);
+
+    let mut group = c.benchmark_group("ini");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("parse", |b| {
+        b.iter(|| Ini::new(black_box(&source)))
+    });
+    group.bench_function("get_in", |b| {
+        b.iter(|| ini.get_in(black_box("x1200y1000"), black_box("Sign(A)")))
+    });
+    group.bench_function("to_string", |b| {
+        b.iter(|| black_box(&ini).to_string())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_map, bench_ini);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    map_bin::AssetId,
+    world::{Bounds, World},
+};
+
+/// The screens on which each music and ambiance track plays. See [`audio_map`].
+///
+/// ID 0 is presumed to mean silence, so it's recorded like any other ID to show where nothing
+/// plays.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioMap {
+    /// The screens using each music ID.
+    pub music: BTreeMap<AssetId, Vec<(i64, i64)>>,
+    /// The screens using each ambiance ID in either slot. A screen that uses the same ID in
+    /// both slots is only listed once.
+    pub ambiance: BTreeMap<AssetId, Vec<(i64, i64)>>,
+}
+
+impl AudioMap {
+    /// Returns the smallest bounds containing every screen, or `None` if there are none.
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut positions = self.music.values().flatten();
+        let first = *positions.next()?;
+
+        Some(positions.fold(Bounds::new(first, first), |bounds, &position| {
+            Bounds::new(
+                (bounds.min.0.min(position.0), bounds.min.1.min(position.1)),
+                (bounds.max.0.max(position.0), bounds.max.1.max(position.1)),
+            )
+        }))
+    }
+}
+
+/// Groups the screens of `world` by the music and ambiance they play, in the order they appear
+/// in Map.bin. With the `image` feature, `draw::draw_track_map` renders the result.
+pub fn audio_map(world: &World) -> AudioMap {
+    let mut map = AudioMap::default();
+
+    for screen in &world.screens {
+        let assets = &screen.assets;
+        map.music.entry(assets.music)
+            .or_default()
+            .push(screen.position);
+
+        map.ambiance.entry(assets.ambiance_a)
+            .or_default()
+            .push(screen.position);
+        if assets.ambiance_b != assets.ambiance_a {
+            map.ambiance.entry(assets.ambiance_b)
+                .or_default()
+                .push(screen.position);
+        }
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, SCREEN_DATA_LEN};
+
+    #[test]
+    fn screens_are_grouped_by_track() {
+        let mut screens = vec![
+            parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000)),
+            parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1001, 999)),
+        ];
+        screens[0].assets.music = 3;
+        screens[0].assets.ambiance_a = 5;
+        screens[0].assets.ambiance_b = 5;
+        screens[1].assets.ambiance_b = 5;
+
+        let world = World {
+            dir: "Test - Level".into(),
+            ini: Ini::new(""),
+            screens,
+        };
+
+        let map = audio_map(&world);
+        assert_eq!(map.music[&3], [(1000, 1000)]);
+        assert_eq!(map.music[&0], [(1001, 999)]);
+        assert_eq!(map.ambiance[&5], [(1000, 1000), (1001, 999)]);
+        assert_eq!(map.bounds(), Some(Bounds::new((1000, 999), (1001, 1000))));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
+    map_bin::{ScreenData, Tile},
+};
+pub use crate::constants::COLLISION_LAYER;
+
+/// The number of tiles in a tileset (16 columns by 8 rows).
+pub const TILESET_LEN: usize = 128;
+
+/// How a tile on the collision layer interacts with the player.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Collision {
+    /// The tile is fully opaque. The player can't enter it.
+    Solid,
+    /// The tile is partially transparent. The engine collides with its opaque pixels only, so
+    /// whether the player fits depends on the shape.
+    Partial,
+    /// The tile is fully transparent or empty. The player passes through it.
+    Hollow,
+    /// The tileset image wasn't available, so the tile could be anything.
+    Unknown,
+}
+
+/// Collision classification for every index of a tileset.
+///
+/// KS collides per pixel against the tiles on [`COLLISION_LAYER`], so a tile's collision depends
+/// on the tileset image. Without the image, nothing is known except that the empty tile (index
+/// 0) is hollow, which is what [`CollisionTable::default`] provides. With the `image` feature,
+/// [`CollisionTable::from_tileset`] classifies tiles by their transparency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionTable(pub [Collision; TILESET_LEN]);
+
+impl Default for CollisionTable {
+    fn default() -> Self {
+        let mut table = [Collision::Unknown; TILESET_LEN];
+        table[0] = Collision::Hollow;
+        Self(table)
+    }
+}
+
+impl CollisionTable {
+    /// Returns the collision of the tile at `index`.
+    pub fn get(&self, index: u8) -> Collision {
+        self.0.get(usize::from(index))
+            .copied()
+            .unwrap_or(Collision::Hollow)
+    }
+
+    /// Classifies each tile of `tileset` by its alpha channel. Pixels with alpha of 128 or more
+    /// are considered opaque.
+    #[cfg(feature = "image")]
+    pub fn from_tileset(tileset: &image::DynamicImage) -> CollisionTable {
+        use image::GenericImageView;
+
+        let mut table = [Collision::Hollow; TILESET_LEN];
+        for (i, collision) in table.iter_mut().enumerate().skip(1) {
+            let tile_x = (i % 16) as u32 * 24;
+            let tile_y = (i / 16) as u32 * 24;
+            if tile_x + 24 > tileset.width() || tile_y + 24 > tileset.height() {
+                continue;
+            }
+
+            let tile = tileset.view(tile_x, tile_y, 24, 24);
+            let opaque = tile.pixels()
+                .filter(|(_, _, pixel)| pixel.0[3] >= 128)
+                .count();
+
+            *collision = match opaque {
+                0 => Collision::Hollow,
+                n if n == 24 * 24 => Collision::Solid,
+                _ => Collision::Partial,
+            };
+        }
+
+        CollisionTable(table)
+    }
+}
+
+/// Collision tables for the two tilesets a screen uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScreenCollision {
+    pub tileset_a: CollisionTable,
+    pub tileset_b: CollisionTable,
+}
+
+impl ScreenCollision {
+    /// Returns the collision of `tile`, where `tile.0` selects the tileset.
+    pub fn get(&self, tile: Tile) -> Collision {
+        match tile.0 {
+            0 => self.tileset_a.get(tile.1),
+            1 => self.tileset_b.get(tile.1),
+            _ => Collision::Hollow,
+        }
+    }
+}
+
+/// Returns the collision at the tile position (`x`, `y`) of `screen`, using the tables in
+/// `tables`. Positions off the screen are hollow.
+pub fn collision_at(screen: &ScreenData, x: usize, y: usize, tables: &ScreenCollision) -> Collision {
+    if x >= SCREEN_WIDTH || y >= SCREEN_HEIGHT {
+        return Collision::Hollow;
+    }
+
+    let tile = screen.layers[COLLISION_LAYER].0[x + y * SCREEN_WIDTH];
+    tables.get(tile)
+}
+
+/// Returns `true` if the tile position (`x`, `y`) of `screen` is occupied by a fully opaque tile
+/// on the collision layer, according to `tables`. Tiles of [`Collision::Unknown`] aren't solid.
+pub fn is_solid(screen: &ScreenData, x: usize, y: usize, tables: &ScreenCollision) -> bool {
+    collision_at(screen, x, y, tables) == Collision::Solid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, SCREEN_DATA_LEN};
+
+    #[test]
+    fn only_known_solid_tiles_are_solid() {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.layers[COLLISION_LAYER].0[0] = Tile(0, 5);
+        screen.layers[COLLISION_LAYER].0[1] = Tile(1, 5);
+
+        let unknown = ScreenCollision::default();
+        assert_eq!(collision_at(&screen, 0, 0, &unknown), Collision::Unknown);
+        assert_eq!(collision_at(&screen, 2, 0, &unknown), Collision::Hollow);
+        assert!(!is_solid(&screen, 0, 0, &unknown));
+
+        let mut tables = ScreenCollision::default();
+        tables.tileset_a.0[5] = Collision::Solid;
+        assert!(is_solid(&screen, 0, 0, &tables));
+        assert!(!is_solid(&screen, 1, 0, &tables));
+        assert!(!is_solid(&screen, SCREEN_WIDTH, 0, &tables));
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+
+use crate::{
+    world::World,
+    world_ini::{self, Target},
+};
+
+/// A disagreement between World.ini and Map.bin found by [`cross_check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrossCheckIssue {
+    /// A World.ini section belongs to a screen that doesn't exist in Map.bin, so KS never
+    /// reads it.
+    OrphanedSection {
+        section: String,
+        screen: (i64, i64),
+    },
+    /// A screen that exists in Map.bin references a screen that doesn't, e.g. with a warp,
+    /// shift, or trigger.
+    MissingTarget(Target),
+}
+
+impl std::fmt::Display for CrossCheckIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrossCheckIssue::OrphanedSection { section, .. } =>
+                write!(f, "The section [{section}] belongs to a screen that isn't in Map.bin."),
+            CrossCheckIssue::MissingTarget(target) => {
+                let (x, y) = target.source;
+                let keys = target.keys.join("`, `");
+                write!(f, "`{keys}` on screen x{x}y{y} leads to x{}y{}, which isn't in Map.bin.", target.screen.0, target.screen.1)
+            },
+        }
+    }
+}
+
+/// Checks that the screen sections of World.ini and the references in them agree with the
+/// screens in Map.bin.
+///
+/// References from orphaned sections aren't checked, since KS never reads them. See
+/// [`world_ini::targets`] for the references that are checked.
+pub fn cross_check(world: &World) -> Vec<CrossCheckIssue> {
+    let screens: HashSet<_> = world.screens.iter()
+        .map(|screen| screen.position)
+        .collect();
+    let mut issues = Vec::new();
+
+    for section in world.ini.iter_sections() {
+        let Some(screen) = section.kind().screen() else {
+            continue;
+        };
+
+        if !screens.contains(&screen) {
+            issues.push(CrossCheckIssue::OrphanedSection {
+                section: section.key().to_owned(),
+                screen,
+            });
+        }
+    }
+
+    for target in world_ini::targets(&world.ini) {
+        if screens.contains(&target.source) && !screens.contains(&target.screen) {
+            issues.push(CrossCheckIssue::MissingTarget(target));
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, SCREEN_DATA_LEN};
+
+    #[test]
+    fn orphans_and_missing_targets_are_found() {
+        let world = World {
+            dir: "Test - Level".into(),
+            ini: Ini::new("[x1000y1000]\nWarpUpY=-2\n[X5Y5]\nWarpUpY=-1\n"),
+            screens: vec![parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000))],
+        };
+
+        let issues = cross_check(&world);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0], CrossCheckIssue::OrphanedSection {
+            section: "X5Y5".to_owned(),
+            screen: (5, 5),
+        });
+        assert!(matches!(&issues[1], CrossCheckIssue::MissingTarget(target) if target.screen == (1000, 998)));
+    }
+}
@@ -0,0 +1,128 @@
+use std::{collections::BTreeSet, path::PathBuf};
+
+use crate::{
+    constants::objects::{ADVANCED_BANK, CUSTOM_OBJECT_BANK},
+    world::World,
+};
+
+/// The files a single screen needs. Paths are relative to the level directory or, for assets the
+/// level doesn't override, the KS Data directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScreenDependencies {
+    /// Tilesets A and B (`Tilesets/TilesetN.png`).
+    pub tilesets: Vec<PathBuf>,
+    /// The background gradient (`Gradients/GradientN.png`).
+    pub gradient: PathBuf,
+    /// The music (`Music/SongN.ogg`), unless the screen has none.
+    pub music: Option<PathBuf>,
+    /// Ambiance A and B (`Ambiance/AmbiN.ogg`), except those set to none.
+    pub ambiance: Vec<PathBuf>,
+    /// The images of the custom objects placed on the screen (`Custom Objects/...`).
+    pub custom_object_images: Vec<PathBuf>,
+}
+
+impl ScreenDependencies {
+    /// Iterates over every file, without duplicates.
+    pub fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        let unique: BTreeSet<&PathBuf> = self.tilesets.iter()
+            .chain(std::iter::once(&self.gradient))
+            .chain(self.music.iter())
+            .chain(self.ambiance.iter())
+            .chain(self.custom_object_images.iter())
+            .collect();
+        unique.into_iter()
+    }
+}
+
+/// Lists the files the screen at `position` of `world` needs, or `None` if the screen doesn't
+/// exist.
+///
+/// Music and ambiance 0 are presumed to mean none. Custom objects are looked up in
+/// `[Custom Object N]` sections, or `[Custom Object BN]` for KS Plus objects in bank 254.
+pub fn screen_dependencies(world: &World, position: (i64, i64)) -> Option<ScreenDependencies> {
+    let screen = world.screen(position)?;
+    let assets = &screen.assets;
+
+    let tilesets = vec![
+        PathBuf::from(format!("Tilesets/Tileset{}.png", assets.tileset_a)),
+        PathBuf::from(format!("Tilesets/Tileset{}.png", assets.tileset_b)),
+    ];
+    let gradient = PathBuf::from(format!("Gradients/Gradient{}.png", assets.gradient));
+    let music = (assets.music != 0)
+        .then(|| PathBuf::from(format!("Music/Song{}.ogg", assets.music)));
+    let ambiance = [assets.ambiance_a, assets.ambiance_b].into_iter()
+        .filter(|&id| id != 0)
+        .map(|id| PathBuf::from(format!("Ambiance/Ambi{id}.ogg")))
+        .collect();
+
+    let mut custom_objects = BTreeSet::new();
+    for layer in screen.layers.iter().skip(4) {
+        for tile in &layer.0 {
+            match tile.0 {
+                CUSTOM_OBJECT_BANK if tile.1 != 0 => {
+                    custom_objects.insert(format!("Custom Object {}", tile.1));
+                },
+                ADVANCED_BANK if tile.1 != 0 => {
+                    custom_objects.insert(format!("Custom Object B{}", tile.1));
+                },
+                _ => (),
+            }
+        }
+    }
+
+    let custom_object_images = custom_objects.iter()
+        .filter_map(|section| world.ini.get_in(section, "Image"))
+        .map(|image| PathBuf::from("Custom Objects").join(image.trim()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    Some(ScreenDependencies {
+        tilesets,
+        gradient,
+        music,
+        ambiance,
+        custom_object_images,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, AssetIds, Tile, SCREEN_DATA_LEN};
+
+    #[test]
+    fn files_are_listed() {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.assets = AssetIds {
+            tileset_a: 3,
+            tileset_b: 3,
+            ambiance_a: 0,
+            ambiance_b: 7,
+            music: 0,
+            gradient: 12,
+        };
+        screen.layers[4].0[0] = Tile(CUSTOM_OBJECT_BANK, 1);
+        screen.layers[5].0[0] = Tile(CUSTOM_OBJECT_BANK, 2);
+        screen.layers[7].0[0] = Tile(ADVANCED_BANK, 1);
+        screen.layers[3].0[0] = Tile(CUSTOM_OBJECT_BANK, 9);
+        let world = World {
+            dir: Default::default(),
+            ini: Ini::new("[Custom Object 1]\nImage=Door.png\n[Custom Object 2]\nImage= Door.png \n[Custom Object B1]\nImage=Lamp.png\n"),
+            screens: vec![screen],
+        };
+
+        let dependencies = screen_dependencies(&world, (1000, 1000)).unwrap();
+        assert_eq!(dependencies, ScreenDependencies {
+            tilesets: vec!["Tilesets/Tileset3.png".into(), "Tilesets/Tileset3.png".into()],
+            gradient: "Gradients/Gradient12.png".into(),
+            music: None,
+            ambiance: vec!["Ambiance/Ambi7.ogg".into()],
+            custom_object_images: vec!["Custom Objects/Door.png".into(), "Custom Objects/Lamp.png".into()],
+        });
+        assert_eq!(dependencies.files().count(), 5);
+        assert_eq!(screen_dependencies(&world, (1001, 1000)), None);
+    }
+}
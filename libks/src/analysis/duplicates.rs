@@ -0,0 +1,129 @@
+use crate::map_bin::{encode_screen, fnv1a, ScreenData, FNV_OFFSET_BASIS, SCREEN_DATA_LEN};
+
+/// A pair of screens that are identical or nearly so.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateScreens {
+    pub first: (i64, i64),
+    pub second: (i64, i64),
+    /// The number of tile positions, across all layers, whose tiles differ.
+    pub differences: usize,
+    /// Whether the screens use the same tilesets, ambiance, music, and gradient.
+    pub same_assets: bool,
+}
+
+/// Finds pairs of screens whose tiles differ in at most `threshold` positions. A threshold of 0
+/// finds exact copies. Pairs are ordered by their position in `screens`.
+pub fn duplicate_screens(screens: &[ScreenData], threshold: usize) -> Vec<DuplicateScreens> {
+    let hashes: Vec<u64> = screens.iter()
+        .map(screen_hash)
+        .collect();
+    let mut duplicates = Vec::new();
+
+    for (i, first) in screens.iter().enumerate() {
+        for (j, second) in screens.iter().enumerate().skip(i + 1) {
+            // Identical hashes are confirmed below; different hashes can't be exact copies
+            if threshold == 0 && hashes[i] != hashes[j] {
+                continue;
+            }
+
+            if let Some(differences) = difference_within(first, second, threshold) {
+                duplicates.push(DuplicateScreens {
+                    first: first.position,
+                    second: second.position,
+                    differences,
+                    same_assets: first.assets == second.assets,
+                });
+            }
+        }
+    }
+
+    duplicates
+}
+
+/// Returns the number of tile positions, across all layers, where `a` and `b` differ.
+pub fn screen_difference(a: &ScreenData, b: &ScreenData) -> usize {
+    difference_within(a, b, usize::MAX).unwrap_or(usize::MAX)
+}
+
+/// Hashes the tiles of `screen`, ignoring its position and assets.
+///
+/// Like [`ScreenData::content_hash`], this is 64-bit FNV-1a over the output of
+/// [`encode_screen`], but without the position and the trailing asset bytes. It won't change
+/// between libks versions.
+pub fn screen_hash(screen: &ScreenData) -> u64 {
+    let tiles_len = SCREEN_DATA_LEN - ASSET_BYTES;
+    fnv1a(FNV_OFFSET_BASIS, &encode_screen(screen)[..tiles_len])
+}
+
+/// The number of bytes at the end of a screen's data that hold its asset IDs.
+const ASSET_BYTES: usize = 6;
+
+/// Counts the differing tiles of `a` and `b`, stopping early once the count exceeds `limit`.
+fn difference_within(a: &ScreenData, b: &ScreenData, limit: usize) -> Option<usize> {
+    let mut differences = 0;
+
+    for (layer_a, layer_b) in a.layers.iter().zip(&b.layers) {
+        for (tile_a, tile_b) in layer_a.0.iter().zip(&layer_b.0) {
+            if tile_a != tile_b {
+                differences += 1;
+                if differences > limit {
+                    return None;
+                }
+            }
+        }
+    }
+
+    Some(differences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_bin::{AssetIds, LayerData, Tile};
+    use crate::constants::{LAYER_COUNT, TILES_PER_LAYER};
+
+    fn screen(position: (i64, i64)) -> ScreenData {
+        ScreenData {
+            position,
+            layers: std::array::from_fn::<_, LAYER_COUNT, _>(|_| LayerData([Tile(0, 0); TILES_PER_LAYER])),
+            assets: AssetIds {
+                tileset_a: 1,
+                tileset_b: 2,
+                ambiance_a: 0,
+                ambiance_b: 0,
+                music: 0,
+                gradient: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn duplicate_screens_respects_threshold() {
+        let a = screen((1000, 1000));
+        let b = screen((1001, 1000));
+        let mut c = screen((1002, 1000));
+        c.layers[3].0[0] = Tile(0, 5);
+        c.layers[3].0[1] = Tile(0, 5);
+        let screens = [a, b, c];
+
+        let exact = duplicate_screens(&screens, 0);
+        assert_eq!(exact.len(), 1);
+        assert_eq!((exact[0].first, exact[0].second), ((1000, 1000), (1001, 1000)));
+
+        assert_eq!(duplicate_screens(&screens, 1).len(), 1);
+        assert_eq!(duplicate_screens(&screens, 2).len(), 3);
+        assert_eq!(screen_difference(&screens[0], &screens[2]), 2);
+    }
+
+    #[test]
+    fn screen_hash_ignores_position_and_assets() {
+        let a = screen((1000, 1000));
+        let mut b = screen((1001, 1000));
+        b.assets.music = 3;
+        assert_eq!(screen_hash(&a), screen_hash(&b));
+        assert_ne!(a.content_hash(), b.content_hash());
+
+        b.layers[7].0[249] = Tile(1, 1);
+        assert_ne!(screen_hash(&a), screen_hash(&b));
+    }
+}
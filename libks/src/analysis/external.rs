@@ -0,0 +1,117 @@
+use std::fs;
+
+use crate::{world::World, Result};
+
+/// Where an [`ExternalReference`] was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceSource {
+    /// A World.ini property.
+    Ini {
+        section: String,
+        key: String,
+    },
+    /// A line of Script.lua, starting from 1.
+    Script {
+        line: usize,
+    },
+}
+
+/// A reference to another level's directory. See [`external_references`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalReference {
+    /// The name of the other level's directory.
+    pub world: String,
+    pub source: ReferenceSource,
+    /// The path that refers to the other level.
+    pub path: String,
+}
+
+/// Finds paths in World.ini and Script.lua that lead into other levels' directories, e.g. a
+/// custom object image at `../../Author - Part 1/Custom Objects/Boss.png`. Levels that share
+/// assets this way only work if the other level is installed too.
+///
+/// World.ini values are presumed to be relative to an asset directory such as `Custom Objects`,
+/// and Script.lua paths to the level's directory. Paths through a `Worlds` directory are also
+/// recognized. Script.lua is scanned as plain text, so paths in comments are reported too.
+pub fn external_references(world: &World) -> Result<Vec<ExternalReference>> {
+    let own_name = world.dir_name().unwrap_or_default();
+    let mut references = Vec::new();
+
+    for section in world.ini.iter_sections() {
+        for (key, value) in section.iter() {
+            if let Some(other) = referenced_world(value, 1).filter(|other| !other.eq_ignore_ascii_case(own_name)) {
+                references.push(ExternalReference {
+                    world: other,
+                    source: ReferenceSource::Ini {
+                        section: section.key().to_owned(),
+                        key: key.to_owned(),
+                    },
+                    path: value.to_owned(),
+                });
+            }
+        }
+    }
+
+    let script_path = world.dir.join("Script.lua");
+    if script_path.is_file() {
+        let source = String::from_utf8_lossy(&fs::read(script_path)?).into_owned();
+        for (i, line) in source.lines().enumerate() {
+            for path in line.split(['"', '\'']) {
+                if let Some(other) = referenced_world(path, 0).filter(|other| !other.eq_ignore_ascii_case(own_name)) {
+                    references.push(ExternalReference {
+                        world: other,
+                        source: ReferenceSource::Script { line: i + 1 },
+                        path: path.to_owned(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(references)
+}
+
+/// Returns the name of the level directory that `path` leads into, if it leaves the level.
+/// `depth` is how many directories below the level's directory `path` is relative to.
+fn referenced_world(path: &str, depth: usize) -> Option<String> {
+    let segments: Vec<&str> = path.trim()
+        .split(['/', '\\'])
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect();
+
+    let mut depth = depth as isize;
+    for (i, segment) in segments.iter().enumerate() {
+        let next = segments.get(i + 1).filter(|next| **next != "..");
+        if segment.eq_ignore_ascii_case("Worlds") {
+            if let Some(next) = next {
+                return Some((*next).to_owned());
+            }
+        }
+
+        if *segment == ".." {
+            depth -= 1;
+            if depth < 0 {
+                // Now in the Worlds directory
+                return next.map(|next| (*next).to_owned());
+            }
+        }
+        else {
+            depth += 1;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_leaving_the_level_are_found() {
+        assert_eq!(referenced_world("../../Me - Part 1/Custom Objects/Boss.png", 1).as_deref(), Some("Me - Part 1"));
+        assert_eq!(referenced_world("..\\Tilesets\\Tileset3.png", 1), None);
+        assert_eq!(referenced_world("C:\\KS\\Worlds\\Me - Part 1\\Music", 0).as_deref(), Some("Me - Part 1"));
+        assert_eq!(referenced_world("Boss.png", 1), None);
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    common::split_slot,
+    constants::objects::COIN,
+    saves::FLAG_COUNT,
+    world::World,
+};
+
+/// A property in a screen section of World.ini that refers to a flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagRef {
+    /// The screen whose section contains the property.
+    pub screen: (i64, i64),
+    /// The property key, e.g. `ShiftFlagOn(A)`.
+    pub key: String,
+}
+
+/// A problem with how a level uses flags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlagIssue {
+    /// A flag is turned on or off, but no screen ever checks it.
+    NeverChecked {
+        flag: usize,
+        setters: Vec<FlagRef>,
+    },
+    /// A flag is checked, but nothing ever turns it on.
+    NeverSet {
+        flag: usize,
+        checks: Vec<FlagRef>,
+    },
+    /// A screen turns the same flag both on and off in the same slot.
+    Contradictory {
+        screen: (i64, i64),
+        flag: usize,
+        on_key: String,
+        off_key: String,
+    },
+    /// A KS Plus coin flag requires more coins than exist in the level.
+    NotEnoughCoins {
+        check: FlagRef,
+        required: usize,
+        available: usize,
+    },
+    /// A flag property has a value that isn't a valid flag.
+    InvalidFlag {
+        property: FlagRef,
+        value: String,
+    },
+}
+
+impl std::fmt::Display for FlagIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use FlagIssue::*;
+        match self {
+            NeverChecked { flag, setters } =>
+                write!(f, "Flag {flag} is changed {} time(s) but never checked.", setters.len()),
+            NeverSet { flag, checks } =>
+                write!(f, "Flag {flag} is checked {} time(s) but never turned on.", checks.len()),
+            Contradictory { screen, flag, on_key, off_key } =>
+                write!(f, "The screen x{}y{} turns flag {flag} on with `{on_key}` and off with `{off_key}`.", screen.0, screen.1),
+            NotEnoughCoins { check, required, available } =>
+                write!(f, "`{}` on screen x{}y{} requires {required} coins, but the level only has {available}.", check.key, check.screen.0, check.screen.1),
+            InvalidFlag { property, value } =>
+                write!(f, "`{}` on screen x{}y{} has the invalid flag `{value}`.", property.key, property.screen.0, property.screen.1),
+        }
+    }
+}
+
+/// What a `Flag(X)` property checks.
+enum FlagCondition {
+    Flag(usize),
+    Power,
+    Coins(usize),
+}
+
+/// Checks that the flags used by `world` are consistent.
+///
+/// Flags are turned on and off by shifts (`ShiftFlagOn(X)`/`ShiftFlagOff(X)`) and KS Plus
+/// triggers (`TrigFlagOn(X)`/`TrigFlagOff(X)`) and checked by `Flag(X)`. Flags that a level
+/// expects to be set by its DefaultSavegame.ini will be reported as [`FlagIssue::NeverSet`].
+pub fn check_flags(world: &World) -> Vec<FlagIssue> {
+    let mut issues = Vec::new();
+    let mut turned_on: BTreeMap<usize, Vec<FlagRef>> = BTreeMap::new();
+    let mut turned_off: BTreeMap<usize, Vec<FlagRef>> = BTreeMap::new();
+    let mut checked: BTreeMap<usize, Vec<FlagRef>> = BTreeMap::new();
+    let mut coin_checks = Vec::new();
+
+    for section in world.ini.iter_sections() {
+        let Some(screen) = section.kind().screen() else {
+            continue;
+        };
+
+        let mut screen_on = Vec::new();
+        let mut screen_off = Vec::new();
+
+        for (key, value) in section.iter() {
+            let Some((name, slot)) = split_slot(key) else { continue };
+            let flag_ref = || FlagRef {
+                screen,
+                key: key.to_owned(),
+            };
+
+            let is_on = name.eq_ignore_ascii_case("ShiftFlagOn")
+                || name.eq_ignore_ascii_case("TrigFlagOn");
+            let is_off = name.eq_ignore_ascii_case("ShiftFlagOff")
+                || name.eq_ignore_ascii_case("TrigFlagOff");
+            let is_check = name.eq_ignore_ascii_case("Flag");
+
+            if is_on || is_off {
+                let Some(flag) = parse_flag(value) else {
+                    issues.push(FlagIssue::InvalidFlag { property: flag_ref(), value: value.to_owned() });
+                    continue;
+                };
+
+                if is_on {
+                    screen_on.push((flag, slot, key));
+                    turned_on.entry(flag).or_default().push(flag_ref());
+                }
+                else {
+                    screen_off.push((flag, slot, key));
+                    turned_off.entry(flag).or_default().push(flag_ref());
+                }
+            }
+            else if is_check {
+                match parse_condition(value) {
+                    Some(FlagCondition::Flag(flag)) => {
+                        checked.entry(flag).or_default().push(flag_ref());
+                    },
+                    Some(FlagCondition::Coins(required)) => {
+                        coin_checks.push((flag_ref(), required));
+                    },
+                    Some(FlagCondition::Power) => (),
+                    None => {
+                        issues.push(FlagIssue::InvalidFlag { property: flag_ref(), value: value.to_owned() });
+                    },
+                }
+            }
+        }
+
+        for (flag, slot, on_key) in &screen_on {
+            let contradicts = |(other_flag, other_slot, _): &&(usize, &str, &str)| {
+                other_flag == flag && other_slot.eq_ignore_ascii_case(slot)
+            };
+            if let Some((_, _, off_key)) = screen_off.iter().find(contradicts) {
+                issues.push(FlagIssue::Contradictory {
+                    screen,
+                    flag: *flag,
+                    on_key: on_key.to_string(),
+                    off_key: off_key.to_string(),
+                });
+            }
+        }
+    }
+
+    // Flags that are changed but never checked
+    let changed: BTreeSet<usize> = turned_on.keys()
+        .chain(turned_off.keys())
+        .copied()
+        .collect();
+    for flag in changed {
+        if checked.contains_key(&flag) {
+            continue;
+        }
+
+        let setters = turned_on.get(&flag).into_iter()
+            .chain(turned_off.get(&flag))
+            .flatten()
+            .cloned()
+            .collect();
+        issues.push(FlagIssue::NeverChecked { flag, setters });
+    }
+
+    // Flags that are checked but never turned on
+    for (flag, checks) in checked {
+        if !turned_on.contains_key(&flag) {
+            issues.push(FlagIssue::NeverSet { flag, checks });
+        }
+    }
+
+    // Coin flags requiring more coins than exist
+    if !coin_checks.is_empty() {
+        let available = count_coins(world);
+        for (check, required) in coin_checks {
+            if required > available {
+                issues.push(FlagIssue::NotEnoughCoins { check, required, available });
+            }
+        }
+    }
+
+    issues
+}
+
+fn parse_flag(value: &str) -> Option<usize> {
+    value.trim()
+        .parse()
+        .ok()
+        .filter(|&flag| flag < FLAG_COUNT)
+}
+
+fn parse_condition(value: &str) -> Option<FlagCondition> {
+    let lower = value.trim().to_ascii_lowercase();
+
+    if let Some(power) = lower.strip_prefix("power") {
+        power.parse::<usize>().ok()?;
+        Some(FlagCondition::Power)
+    }
+    else if let Some(coins) = lower.strip_prefix("coin") {
+        coins.parse().ok().map(FlagCondition::Coins)
+    }
+    else {
+        parse_flag(&lower).map(FlagCondition::Flag)
+    }
+}
+
+fn count_coins(world: &World) -> usize {
+    world.iter_objects()
+        .filter(|(_, object)| object.tile == COIN)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, SCREEN_DATA_LEN};
+
+    #[test]
+    fn every_issue_is_found() {
+        let mut screens: Vec<_> = [(1000, 1000), (1001, 1000)].into_iter()
+            .map(|position| parse_screen_bytes(&[0; SCREEN_DATA_LEN], position))
+            .collect();
+        screens[1].layers[4].0[0] = COIN;
+        let world = World {
+            dir: Default::default(),
+            ini: Ini::new("\
+[x1000y1000]
+ShiftFlagOn(A)=1
+ShiftFlagOff(B)=1
+TrigFlagOn(C)=2
+TrigFlagOff(C)=1
+TrigFlagOn(E)=1
+TrigFlagOff(E)=1
+ShiftFlagOn(D)=10
+[x1001y1000]
+Flag(A)=1
+Flag(B)=3
+Flag(C)=Power4
+Flag(D)=Coin2
+Flag(E)=Banana
+"),
+            screens,
+        };
+        let flag_ref = |screen, key: &str| FlagRef { screen, key: key.to_owned() };
+
+        assert_eq!(check_flags(&world), [
+            FlagIssue::InvalidFlag {
+                property: flag_ref((1000, 1000), "ShiftFlagOn(D)"),
+                value: "10".to_owned(),
+            },
+            FlagIssue::Contradictory {
+                screen: (1000, 1000),
+                flag: 1,
+                on_key: "TrigFlagOn(E)".to_owned(),
+                off_key: "TrigFlagOff(E)".to_owned(),
+            },
+            FlagIssue::InvalidFlag {
+                property: flag_ref((1001, 1000), "Flag(E)"),
+                value: "Banana".to_owned(),
+            },
+            FlagIssue::NeverChecked {
+                flag: 2,
+                setters: vec![flag_ref((1000, 1000), "TrigFlagOn(C)")],
+            },
+            FlagIssue::NeverSet {
+                flag: 3,
+                checks: vec![flag_ref((1001, 1000), "Flag(B)")],
+            },
+            FlagIssue::NotEnoughCoins {
+                check: flag_ref((1001, 1000), "Flag(D)"),
+                required: 2,
+                available: 1,
+            },
+        ]);
+    }
+}
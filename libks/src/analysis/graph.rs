@@ -0,0 +1,124 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    world::World,
+    world_ini::{screen_targets, Edge, TargetKind},
+};
+use super::adjacent;
+
+/// How the player gets from one screen to another.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConnectionKind {
+    /// Leaving through an edge into the adjacent screen.
+    Walk(Edge),
+    /// Leaving through an edge that has a warp.
+    Warp(Edge),
+    /// A shift in the given slot.
+    Shift(String),
+    /// A flag warp in the given slot, which only applies while its flag condition holds.
+    FlagWarp(String),
+}
+
+impl ConnectionKind {
+    /// Returns `true` if the connection only exists while a flag condition holds.
+    pub fn is_flag_gated(&self) -> bool {
+        matches!(self, ConnectionKind::FlagWarp(_))
+    }
+}
+
+/// An edge of the level graph. See [`level_graph`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    pub from: (i64, i64),
+    pub to: (i64, i64),
+    /// The tile position the player arrives at, if it's known statically.
+    pub position: Option<(i64, i64)>,
+    pub kind: ConnectionKind,
+}
+
+/// Lists the connections between the screens of `world`, ordered by source screen.
+///
+/// Every edge of every screen produces one connection: a [`Warp`](ConnectionKind::Warp) if its
+/// section in World.ini warps that edge, or else a [`Walk`](ConnectionKind::Walk) if there's an
+/// adjacent screen. Shifts and flag warps are included as well. Warps, shifts, and flag warps
+/// are listed even when their destination screen doesn't exist; see
+/// [`check_targets`](super::check_targets). KS Plus trigger spawns stay on the same screen and
+/// aren't connections.
+pub fn level_graph(world: &World) -> Vec<Connection> {
+    let positions: BTreeSet<_> = world.screens.iter()
+        .map(|screen| screen.position)
+        .collect();
+
+    let mut connections = Vec::new();
+    for &from in &positions {
+        let targets = screen_targets(&world.ini, from);
+
+        for edge in Edge::ALL {
+            let warp = targets.iter().find(|target| target.kind == TargetKind::Warp(edge));
+            match warp {
+                Some(target) => connections.push(Connection {
+                    from,
+                    to: target.screen,
+                    position: None,
+                    kind: ConnectionKind::Warp(edge),
+                }),
+                None if positions.contains(&adjacent(from, edge)) => connections.push(Connection {
+                    from,
+                    to: adjacent(from, edge),
+                    position: None,
+                    kind: ConnectionKind::Walk(edge),
+                }),
+                None => {},
+            }
+        }
+
+        for target in targets {
+            let kind = match target.kind {
+                TargetKind::Shift(slot) => ConnectionKind::Shift(slot),
+                TargetKind::FlagWarp(slot) => ConnectionKind::FlagWarp(slot),
+                TargetKind::Warp(_) | TargetKind::TriggerSpawn(_) => continue,
+            };
+            connections.push(Connection {
+                from,
+                to: target.screen,
+                position: target.position,
+                kind,
+            });
+        }
+    }
+
+    connections
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, SCREEN_DATA_LEN};
+
+    #[test]
+    fn warps_replace_walks() {
+        let world = World {
+            dir: Default::default(),
+            ini: Ini::new("[x1000y1000]\nWarpRightX=2\nFlagWarpY(B)=1\nTrigSpawnX(A)=3\n"),
+            screens: [(1000, 1000), (1001, 1000), (1000, 1001)].into_iter()
+                .map(|position| parse_screen_bytes(&[0; SCREEN_DATA_LEN], position))
+                .collect(),
+        };
+
+        let from_first: Vec<_> = level_graph(&world).into_iter()
+            .filter(|connection| connection.from == (1000, 1000))
+            .map(|connection| (connection.kind, connection.to))
+            .collect();
+        assert_eq!(from_first, [
+            (ConnectionKind::Walk(Edge::Down), (1000, 1001)),
+            (ConnectionKind::Warp(Edge::Right), (1002, 1000)),
+            (ConnectionKind::FlagWarp("B".to_owned()), (1000, 1001)),
+        ]);
+        assert!(from_first[2].0.is_flag_gated());
+        assert!(!from_first[1].0.is_flag_gated());
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    assets::AssetSource,
+    world::World,
+};
+use super::{
+    check_flags,
+    check_targets,
+    cross_check,
+    texts,
+    CrossCheckIssue,
+    FlagIssue,
+    FlagRef,
+    TargetIssue,
+    TextKind,
+};
+
+/// The number of characters that fit on one line of a sign. Presumed from the size of the
+/// vanilla sign box and font; this hasn't been confirmed.
+pub const SIGN_LINE_CHARS: usize = 40;
+/// The number of lines that fit in a sign. Presumed; see [`SIGN_LINE_CHARS`].
+pub const SIGN_MAX_LINES: usize = 7;
+
+/// How serious a lint is. Ordered from least to most serious.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// Probably intentional, but worth a look.
+    Info,
+    /// Likely a mistake, but the level still works.
+    Warning,
+    /// Breaks the level or makes it unplayable in places.
+    Error,
+}
+
+/// Where a lint applies.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintLocation {
+    /// The screen, if the lint concerns one.
+    pub screen: Option<(i64, i64)>,
+    /// The World.ini section, if the lint concerns one.
+    pub section: Option<String>,
+    /// The World.ini property keys, if the lint concerns any.
+    pub keys: Vec<String>,
+}
+
+/// A problem found by [`lint`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// A stable identifier for the rule, e.g. `KS001`.
+    pub code: &'static str,
+    pub severity: Severity,
+    pub location: LintLocation,
+    pub message: String,
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}[{}]: {}", self.code, self.message)
+    }
+}
+
+/// Checks `world` against every lint rule:
+///
+/// | Code  | Severity | Rule |
+/// |-------|----------|------|
+/// | KS001 | Error    | A shift, warp, or flag warp leads to a screen that doesn't exist |
+/// | KS002 | Error    | A shift or trigger leads to a tile off the screen |
+/// | KS003 | Warning  | A shift or trigger leads to a solid tile |
+/// | KS004 | Warning  | A trigger spawns on a screen that doesn't exist |
+/// | KS005 | Info     | A World.ini section belongs to a screen that isn't in Map.bin |
+/// | KS006 | Info     | A screen or World.ini section is outside x0-9999, y0-9999 |
+/// | KS007 | Warning  | A World.ini section has the same key more than once |
+/// | KS010 | Warning  | A sign's text is too long to fit in the sign box |
+/// | KS020 | Error    | A screen's music, ambiance, tileset, or gradient file is missing |
+/// | KS030 | Error    | A flag property has an invalid value |
+/// | KS031 | Info     | A flag is changed but never checked |
+/// | KS032 | Warning  | A flag is checked but never turned on |
+/// | KS033 | Warning  | A screen turns a flag both on and off |
+/// | KS034 | Error    | A coin flag requires more coins than exist |
+///
+/// KS006 is a heuristic: KS is thought to only load screens whose coordinates are at most four
+/// digits without a sign, but that hasn't been checked against the engine.
+///
+/// KS020 needs the KS Data directory to tell missing files from built-in ones. It's found by
+/// assuming the level is installed in the `Worlds` directory of a KS installation; if it isn't,
+/// the rule is skipped. KS003 also needs the tileset images, so it's only checked with the
+/// `image` feature and when the Data directory is found.
+pub fn lint(world: &World) -> Vec<Lint> {
+    let mut lints = Vec::new();
+
+    lint_targets(world, &mut lints);
+    lint_sections(world, &mut lints);
+    lint_ranges(world, &mut lints);
+    lint_duplicate_keys(world, &mut lints);
+    lint_signs(world, &mut lints);
+    lint_assets(world, &mut lints);
+    lint_flags(world, &mut lints);
+
+    lints
+}
+
+fn lint_targets(world: &World, lints: &mut Vec<Lint>) {
+    use crate::world_ini::TargetKind;
+
+    for issue in targets_with_collision(world) {
+        let target = issue.target();
+        let (code, severity) = match (&issue, &target.kind) {
+            (TargetIssue::MissingScreen(_), TargetKind::TriggerSpawn(_)) => ("KS004", Severity::Warning),
+            (TargetIssue::MissingScreen(_), _) => ("KS001", Severity::Error),
+            (TargetIssue::OutOfBounds(_), _) => ("KS002", Severity::Error),
+            (TargetIssue::InsideSolid(_), _) => ("KS003", Severity::Warning),
+        };
+
+        lints.push(Lint {
+            code,
+            severity,
+            location: screen_location(target.source, target.keys.clone()),
+            message: issue.to_string(),
+        });
+    }
+}
+
+/// Checks targets against the collision of the screens' tilesets, if they can be loaded.
+#[cfg(feature = "image")]
+fn targets_with_collision(world: &World) -> Vec<TargetIssue> {
+    use std::collections::HashMap;
+
+    use super::{check_targets_with_collision, collision::{CollisionTable, ScreenCollision}};
+
+    let Some(source) = asset_source(world) else {
+        return check_targets(world);
+    };
+
+    let mut tables = HashMap::new();
+    let mut table = |id: u8| -> CollisionTable {
+        tables.entry(id)
+            .or_insert_with(|| {
+                source.tileset_read(id)
+                    .and_then(|bytes| bytes.ok())
+                    .and_then(|bytes| image::load_from_memory(&bytes).ok())
+                    .map(|tileset| CollisionTable::from_tileset(&tileset))
+                    .unwrap_or_default()
+            })
+            .clone()
+    };
+
+    check_targets_with_collision(world, |screen| ScreenCollision {
+        tileset_a: table(screen.assets.tileset_a),
+        tileset_b: table(screen.assets.tileset_b),
+    })
+}
+
+#[cfg(not(feature = "image"))]
+fn targets_with_collision(world: &World) -> Vec<TargetIssue> {
+    check_targets(world)
+}
+
+fn lint_sections(world: &World, lints: &mut Vec<Lint>) {
+    for issue in cross_check(world) {
+        // Missing targets are covered by KS001 and KS004
+        if let CrossCheckIssue::OrphanedSection { section, screen } = &issue {
+            lints.push(Lint {
+                code: "KS005",
+                severity: Severity::Info,
+                location: LintLocation {
+                    screen: Some(*screen),
+                    section: Some(section.clone()),
+                    keys: Vec::new(),
+                },
+                message: issue.to_string(),
+            });
+        }
+    }
+}
+
+/// Returns `false` if a screen at `position` might not be loaded by KS. See KS006 in [`lint`].
+fn screen_in_range((x, y): (i64, i64)) -> bool {
+    (0..=9999).contains(&x) && (0..=9999).contains(&y)
+}
+
+fn lint_ranges(world: &World, lints: &mut Vec<Lint>) {
+    let mut reported = BTreeSet::new();
+
+    for screen in &world.screens {
+        let (x, y) = screen.position;
+        if !screen_in_range(screen.position) && reported.insert(screen.position) {
+            lints.push(Lint {
+                code: "KS006",
+                severity: Severity::Info,
+                location: screen_location(screen.position, Vec::new()),
+                message: format!("The screen x{x}y{y} is outside x0-9999, y0-9999, so KS might not load it."),
+            });
+        }
+    }
+
+    for section in world.ini.iter_sections() {
+        let Some(screen) = section.kind().screen() else { continue };
+        if !screen_in_range(screen) && reported.insert(screen) {
+            lints.push(Lint {
+                code: "KS006",
+                severity: Severity::Info,
+                location: LintLocation {
+                    screen: Some(screen),
+                    section: Some(section.key().to_owned()),
+                    keys: Vec::new(),
+                },
+                message: format!("The section [{}] is outside x0-9999, y0-9999, so KS might not load it.", section.key()),
+            });
+        }
+    }
+}
+
+fn lint_duplicate_keys(world: &World, lints: &mut Vec<Lint>) {
+    for section in world.ini.iter_sections() {
+        for duplicate in section.duplicate_keys() {
+            lints.push(Lint {
+                code: "KS007",
+                severity: Severity::Warning,
+                location: LintLocation {
+                    screen: section.kind().screen(),
+                    section: Some(section.key().to_owned()),
+                    keys: vec![duplicate.key.clone()],
+                },
+                message: format!("`{}` appears {} times in [{}]. KS uses the last value.", duplicate.key, duplicate.spans.len(), section.key()),
+            });
+        }
+    }
+}
+
+fn lint_signs(world: &World, lints: &mut Vec<Lint>) {
+    for text in texts(world) {
+        if !matches!(text.kind, TextKind::Sign(_)) {
+            continue;
+        }
+
+        let lines = wrapped_line_count(&text.text, SIGN_LINE_CHARS);
+        if lines > SIGN_MAX_LINES {
+            lints.push(Lint {
+                code: "KS010",
+                severity: Severity::Warning,
+                location: LintLocation {
+                    screen: text.screen,
+                    section: Some(text.section.clone()),
+                    keys: vec![text.key.clone()],
+                },
+                message: format!("`{}` in [{}] wraps to {lines} lines, but signs only fit {SIGN_MAX_LINES}.", text.key, text.section),
+            });
+        }
+    }
+}
+
+/// Finds the assets of `world`, assuming it's installed in the `Worlds` directory of a KS
+/// installation.
+fn asset_source(world: &World) -> Option<AssetSource> {
+    let ks_dir = world.dir.parent()?.parent()?;
+    let data_folder = ks_dir.join("Data");
+
+    data_folder.is_dir().then(|| AssetSource {
+        data_folder,
+        world_folder: world.dir.clone(),
+    })
+}
+
+fn lint_assets(world: &World, lints: &mut Vec<Lint>) {
+    let Some(source) = asset_source(world) else {
+        return;
+    };
+
+    let mut reported = BTreeSet::new();
+    for screen in &world.screens {
+        let assets = &screen.assets;
+        let mut check = |kind: &str, id: u8, exists: bool| {
+            if !exists && reported.insert((kind.to_owned(), id)) {
+                lints.push(Lint {
+                    code: "KS020",
+                    severity: Severity::Error,
+                    location: screen_location(screen.position, Vec::new()),
+                    message: format!("The screen x{}y{} uses {kind} {id}, but the file is missing.", screen.position.0, screen.position.1),
+                });
+            }
+        };
+
+        check("tileset", assets.tileset_a, source.tileset_path(assets.tileset_a).is_some());
+        check("tileset", assets.tileset_b, source.tileset_path(assets.tileset_b).is_some());
+        check("gradient", assets.gradient, source.gradient_path(assets.gradient).is_some());
+        if assets.music != 0 {
+            check("music", assets.music, source.music_path(assets.music).is_some());
+        }
+        for ambiance in [assets.ambiance_a, assets.ambiance_b] {
+            if ambiance != 0 {
+                check("ambiance", ambiance, source.ambiance_path(ambiance).is_some());
+            }
+        }
+    }
+}
+
+fn lint_flags(world: &World, lints: &mut Vec<Lint>) {
+    for issue in check_flags(world) {
+        let (code, severity, location) = match &issue {
+            FlagIssue::InvalidFlag { property, .. } =>
+                ("KS030", Severity::Error, screen_location(property.screen, vec![property.key.clone()])),
+            FlagIssue::NeverChecked { setters, .. } =>
+                ("KS031", Severity::Info, refs_location(setters)),
+            FlagIssue::NeverSet { checks, .. } =>
+                ("KS032", Severity::Warning, refs_location(checks)),
+            FlagIssue::Contradictory { screen, on_key, off_key, .. } =>
+                ("KS033", Severity::Warning, screen_location(*screen, vec![on_key.clone(), off_key.clone()])),
+            FlagIssue::NotEnoughCoins { check, .. } =>
+                ("KS034", Severity::Error, screen_location(check.screen, vec![check.key.clone()])),
+        };
+
+        lints.push(Lint {
+            code,
+            severity,
+            location,
+            message: issue.to_string(),
+        });
+    }
+}
+
+fn screen_location(screen: (i64, i64), keys: Vec<String>) -> LintLocation {
+    LintLocation {
+        screen: Some(screen),
+        section: Some(format!("x{}y{}", screen.0, screen.1)),
+        keys,
+    }
+}
+
+/// Locates a lint by the first of several flag properties.
+fn refs_location(refs: &[FlagRef]) -> LintLocation {
+    match refs.first() {
+        Some(first) => screen_location(first.screen, vec![first.key.clone()]),
+        None => LintLocation::default(),
+    }
+}
+
+/// Counts the lines `text` occupies when greedily wrapped at `width` characters. Words longer
+/// than a line are broken, and literal `\n` sequences are treated as line breaks.
+fn wrapped_line_count(text: &str, width: usize) -> usize {
+    let mut lines = 0;
+
+    for paragraph in text.split("\\n") {
+        let mut line_len = 0;
+        lines += 1;
+
+        for word in paragraph.split_whitespace() {
+            let word_len = word.chars().count();
+            let needed = if line_len == 0 { word_len } else { line_len + 1 + word_len };
+
+            if needed <= width {
+                line_len = needed;
+            }
+            else {
+                if line_len > 0 {
+                    lines += 1;
+                }
+                lines += (word_len.max(1) - 1) / width;
+                line_len = (word_len - 1) % width + 1;
+            }
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapped_line_count_works() {
+        assert_eq!(wrapped_line_count("", 10), 1);
+        assert_eq!(wrapped_line_count("aaaa bbbb cc", 10), 2);
+        assert_eq!(wrapped_line_count("aaaa\\nbbbb", 10), 2);
+        assert_eq!(wrapped_line_count("aaaaaaaaaaaaaaaaaaaaaaaaa", 10), 3);
+    }
+}
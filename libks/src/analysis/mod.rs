@@ -0,0 +1,52 @@
+pub mod collision;
+pub mod route;
+
+mod lint;
+pub use lint::{lint, Lint, LintLocation, Severity, SIGN_LINE_CHARS, SIGN_MAX_LINES};
+
+mod progress;
+pub use progress::{progress, Progress};
+
+mod spawns;
+pub use spawns::{spawn_points, SpawnKind, SpawnPoint};
+
+mod stats;
+pub use stats::{level_stats, Difficulty, LevelStats};
+
+mod audio_map;
+pub use audio_map::{audio_map, AudioMap};
+
+mod cross_check;
+pub use cross_check::{cross_check, CrossCheckIssue};
+
+mod dependencies;
+pub use dependencies::{screen_dependencies, ScreenDependencies};
+
+mod duplicates;
+pub use duplicates::{duplicate_screens, screen_difference, screen_hash, DuplicateScreens};
+
+mod external;
+pub use external::{external_references, ExternalReference, ReferenceSource};
+
+mod graph;
+pub use graph::{level_graph, Connection, ConnectionKind};
+
+mod flags;
+pub use flags::{check_flags, FlagIssue, FlagRef};
+
+mod targets;
+pub use targets::{check_targets, check_targets_with_collision, TargetIssue};
+
+mod texts;
+pub use texts::{apply_texts, texts, LevelText, TextKind};
+
+mod transition;
+pub use transition::{adjacent, entry_position, transition};
+
+mod usage;
+pub use usage::{tile_usage, TileUsage};
+
+#[cfg(feature = "lua")]
+mod script;
+#[cfg(feature = "lua")]
+pub use script::{analyze_script, find_references, script, ScriptAnalysis, ScriptIssue, ScriptRef, ScriptReferences};
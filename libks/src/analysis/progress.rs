@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    constants::objects::{self, COIN},
+    saves::{SaveGame, POWER_COUNT},
+    world::World,
+};
+
+/// A player's progress through a level, as recorded by a save file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// Indices of the powers the player has collected.
+    pub powers_collected: Vec<usize>,
+    /// Indices of the powers that can be collected somewhere in the level.
+    pub powers_available: Vec<usize>,
+    /// The number of coins the player has collected.
+    pub coins_found: usize,
+    /// The number of coins placed in the level.
+    pub coins_total: usize,
+    /// The number of distinct artifacts the player has collected.
+    pub artifacts_found: usize,
+    /// The number of distinct artifacts placed in the level.
+    pub artifacts_total: usize,
+    /// The screen the player saved on.
+    pub screen: Option<(i64, i64)>,
+}
+
+/// Summarizes the progress recorded by `save` in the context of `world`.
+///
+/// Totals are computed by counting the collectibles placed in Map.bin, so collectibles that are
+/// never reachable in-game are still counted.
+pub fn progress(world: &World, save: &SaveGame) -> Progress {
+    let mut powers_available = BTreeSet::new();
+    let mut coins_total = 0;
+    let mut artifacts_seen = BTreeSet::new();
+
+    for (_, object) in world.iter_objects() {
+        let tile = object.tile;
+        if let Some(power) = objects::power_index(tile) {
+            powers_available.insert(power);
+        }
+        else if tile == COIN {
+            coins_total += 1;
+        }
+        else if let Some(artifact) = objects::artifact_index(tile) {
+            artifacts_seen.insert(artifact);
+        }
+    }
+
+    let powers_collected = (0..POWER_COUNT)
+        .filter(|&power| save.has_power(power))
+        .collect();
+
+    Progress {
+        powers_collected,
+        powers_available: powers_available.into_iter().collect(),
+        coins_found: save.coins(),
+        coins_total,
+        artifacts_found: save.artifacts(),
+        artifacts_total: artifacts_seen.len(),
+        screen: save.screen(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::{
+        constants::objects::MAP_POWER,
+        map_bin::{parse_screen_bytes, Tile, SCREEN_DATA_LEN},
+    };
+
+    #[test]
+    fn collectibles_are_counted() {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.layers[4].0[0] = Tile(0, 2);
+        screen.layers[4].0[1] = MAP_POWER;
+        screen.layers[4].0[2] = COIN;
+        screen.layers[5].0[2] = COIN;
+        screen.layers[4].0[3] = Tile(0, 35);
+        screen.layers[4].0[4] = Tile(0, 35);
+        screen.layers[6].0[5] = Tile(0, 37);
+
+        let world = World {
+            dir: "Me - Level".into(),
+            ini: Ini::new(""),
+            screens: vec![screen],
+        };
+        let save = SaveGame::from_ini(Ini::new(concat!(
+            "[Positions]\nX Map=1001\nY Map=999\n",
+            "[Powers]\nPower0=1\nPower5=1\nPower12=0\n",
+            "[Coins]\nCoin1=True\n",
+            "[Artifacts]\nArtifact1=2\nArtifact3=0\n",
+        )));
+
+        assert_eq!(progress(&world, &save), Progress {
+            powers_collected: vec![0, 5],
+            powers_available: vec![0, 12],
+            coins_found: 1,
+            coins_total: 2,
+            artifacts_found: 1,
+            artifacts_total: 2,
+            screen: Some((1001, 999)),
+        });
+    }
+}
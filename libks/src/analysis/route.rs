@@ -0,0 +1,113 @@
+use crate::world::World;
+
+/// A stop along a [`Route`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Waypoint {
+    pub screen: (i64, i64),
+    /// The tile position within the screen, or `None` to mean the screen as a whole.
+    pub position: Option<(i64, i64)>,
+    /// A description of what happens here, e.g. "Get the double jump".
+    pub note: Option<String>,
+}
+
+impl Waypoint {
+    /// Creates a waypoint at tile `position` on `screen` without a note.
+    pub fn new(screen: (i64, i64), position: (i64, i64)) -> Self {
+        Self {
+            screen,
+            position: Some(position),
+            note: None,
+        }
+    }
+}
+
+/// A path through a level as an ordered list of waypoints, e.g. a speedrun route.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Route {
+    pub name: String,
+    /// The name of the directory of the level the route is for.
+    pub world: String,
+    pub waypoints: Vec<Waypoint>,
+}
+
+impl Route {
+    /// Creates an empty route named `name` for `world`.
+    pub fn new(name: &str, world: &World) -> Self {
+        Self {
+            name: name.to_owned(),
+            world: world.dir_name().unwrap_or_default().to_owned(),
+            waypoints: Vec::new(),
+        }
+    }
+
+    /// Returns the indices of the waypoints on screens that don't exist in `world`.
+    pub fn missing_screens(&self, world: &World) -> Vec<usize> {
+        self.waypoints.iter()
+            .enumerate()
+            .filter(|(_, waypoint)| world.screen(waypoint.screen).is_none())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Writes the route as pretty-printed JSON, e.g.:
+    ///
+    /// ```json
+    /// {
+    ///   "name": "Any%",
+    ///   "world": "Nifflas - The Machine",
+    ///   "waypoints": [
+    ///     {
+    ///       "screen": [1000, 1000],
+    ///       "position": [3, 5],
+    ///       "note": "Start"
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// Tile positions count from the top left of the screen, and missing positions and notes
+    /// are `null`. The route can be read back with `serde_json`.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self)
+            .expect("routes should always be serializable")
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_are_written_as_json() {
+        let route = Route {
+            name: "Any% \"glitchless\"".to_owned(),
+            world: "Me - Level".to_owned(),
+            waypoints: vec![
+                Waypoint {
+                    note: Some("Start".to_owned()),
+                    ..Waypoint::new((1000, 1000), (3, 5))
+                },
+                Waypoint {
+                    screen: (1001, 1000),
+                    position: None,
+                    note: None,
+                },
+            ],
+        };
+
+        let json = route.to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, serde_json::json!({
+            "name": "Any% \"glitchless\"",
+            "world": "Me - Level",
+            "waypoints": [
+                {"screen": [1000, 1000], "position": [3, 5], "note": "Start"},
+                {"screen": [1001, 1000], "position": null, "note": null},
+            ],
+        }));
+        assert_eq!(serde_json::from_str::<Route>(&json).unwrap(), route);
+    }
+}
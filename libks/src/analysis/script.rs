@@ -0,0 +1,364 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    common::{parse_xy, split_slot},
+    saves::FLAG_COUNT,
+    world::World,
+    Result,
+};
+
+/// A value referenced by Script.lua, with the line it appears on (starting from 1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptRef<T> {
+    pub value: T,
+    pub line: usize,
+}
+
+/// The screens, flags, and sign labels referenced by Script.lua.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptReferences {
+    /// Identifiers and strings of the form `xNyN`, optionally followed by `_` and a suffix.
+    pub screens: Vec<ScriptRef<(i64, i64)>>,
+    /// Integer arguments to functions whose names contain `flag`.
+    pub flags: Vec<ScriptRef<i64>>,
+    /// String arguments to functions whose names contain `sign`.
+    pub labels: Vec<ScriptRef<String>>,
+}
+
+/// A problem found by cross-checking Script.lua against World.ini and Map.bin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptIssue {
+    /// The script refers to a screen that doesn't exist in Map.bin.
+    MissingScreen(ScriptRef<(i64, i64)>),
+    /// The script refers to a flag outside the range the game supports.
+    InvalidFlag(ScriptRef<i64>),
+    /// The script refers to a sign label that no screen defines.
+    UnknownLabel(ScriptRef<String>),
+    /// A screen defines a custom sign label, which only works with a script, but the script
+    /// never refers to it.
+    UnusedLabel {
+        screen: (i64, i64),
+        label: String,
+    },
+}
+
+impl std::fmt::Display for ScriptIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptIssue::MissingScreen(ScriptRef { value: (x, y), line }) =>
+                write!(f, "Script.lua line {line} refers to the screen x{x}y{y}, which doesn't exist."),
+            ScriptIssue::InvalidFlag(ScriptRef { value, line }) =>
+                write!(f, "Script.lua line {line} refers to the flag {value}, which is out of range."),
+            ScriptIssue::UnknownLabel(ScriptRef { value, line }) =>
+                write!(f, "Script.lua line {line} refers to the sign label `{value}`, which no screen defines."),
+            ScriptIssue::UnusedLabel { screen: (x, y), label } =>
+                write!(f, "The screen x{x}y{y} defines the sign label `{label}`, but Script.lua never refers to it."),
+        }
+    }
+}
+
+/// The result of analyzing Script.lua.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptAnalysis {
+    pub references: ScriptReferences,
+    pub issues: Vec<ScriptIssue>,
+}
+
+/// Statically analyzes the Script.lua of the KS Ex level in `world_dir`. Returns `None` if the
+/// level has no Script.lua.
+///
+/// The script is tokenized, not executed, so references built at runtime (e.g. by
+/// concatenating strings) aren't detected.
+pub fn script<P>(world_dir: P) -> Result<Option<ScriptAnalysis>>
+where
+    P: AsRef<Path>
+{
+    let world_dir = world_dir.as_ref();
+    let script_path = world_dir.join("Script.lua");
+    if !script_path.is_file() {
+        return Ok(None);
+    }
+
+    // Lua source is bytes; non-UTF-8 characters can only occur in strings and comments
+    let source = String::from_utf8_lossy(&fs::read(script_path)?).into_owned();
+    let world = World::load(world_dir)?;
+
+    Ok(Some(analyze_script(&source, &world)))
+}
+
+/// Analyzes the Lua `source` in the context of `world`. See [`script`].
+pub fn analyze_script(source: &str, world: &World) -> ScriptAnalysis {
+    let references = find_references(source);
+    let mut issues = Vec::new();
+
+    let screens: HashSet<_> = world.screens.iter()
+        .map(|screen| screen.position)
+        .collect();
+    for screen_ref in &references.screens {
+        if !screens.contains(&screen_ref.value) {
+            issues.push(ScriptIssue::MissingScreen(screen_ref.clone()));
+        }
+    }
+
+    for flag_ref in &references.flags {
+        if !(0..FLAG_COUNT as i64).contains(&flag_ref.value) {
+            issues.push(ScriptIssue::InvalidFlag(flag_ref.clone()));
+        }
+    }
+
+    let defined_labels = custom_sign_labels(world);
+    let defined_lower: HashSet<_> = defined_labels.iter()
+        .map(|(_, label)| label.to_ascii_lowercase())
+        .collect();
+    let used_lower: HashSet<_> = references.labels.iter()
+        .map(|label_ref| label_ref.value.to_ascii_lowercase())
+        .collect();
+
+    for label_ref in &references.labels {
+        let lower = label_ref.value.to_ascii_lowercase();
+        let is_builtin = ["a", "b", "c"].contains(&lower.as_str());
+        if !is_builtin && !defined_lower.contains(&lower) {
+            issues.push(ScriptIssue::UnknownLabel(label_ref.clone()));
+        }
+    }
+
+    for (screen, label) in defined_labels {
+        if !used_lower.contains(&label.to_ascii_lowercase()) {
+            issues.push(ScriptIssue::UnusedLabel { screen, label });
+        }
+    }
+
+    ScriptAnalysis { references, issues }
+}
+
+/// Lists the sign labels other than A, B, and C defined by `Sign(X)` keys in screen sections.
+fn custom_sign_labels(world: &World) -> BTreeSet<((i64, i64), String)> {
+    let mut labels = BTreeSet::new();
+
+    for section in world.ini.iter_sections() {
+        let Some(screen) = section.kind().screen() else {
+            continue;
+        };
+
+        for (key, _) in section.iter() {
+            let Some((name, slot)) = split_slot(key) else { continue };
+            let is_builtin = ["A", "B", "C"].iter()
+                .any(|builtin| slot.eq_ignore_ascii_case(builtin));
+
+            if name.eq_ignore_ascii_case("Sign") && !is_builtin {
+                labels.insert((screen, slot.to_owned()));
+            }
+        }
+    }
+
+    labels
+}
+
+/// Scans `source` for screen, flag, and sign label references.
+pub fn find_references(source: &str) -> ScriptReferences {
+    let tokens = tokenize(source);
+    let mut references = ScriptReferences::default();
+
+    for (i, token) in tokens.iter().enumerate() {
+        match &token.kind {
+            TokenKind::Name(name) | TokenKind::String(name) => {
+                let base = name.split('_').next().unwrap_or_default();
+                if let Some(screen) = parse_xy(&base.to_ascii_lowercase()) {
+                    references.screens.push(ScriptRef { value: screen, line: token.line });
+                }
+            },
+            _ => (),
+        }
+
+        // Look for calls of the form `name(argument`
+        let TokenKind::Name(name) = &token.kind else { continue };
+        let Some(Token { kind: TokenKind::Punct('('), .. }) = tokens.get(i + 1) else { continue };
+        let Some(argument) = tokens.get(i + 2) else { continue };
+        let name = name.to_ascii_lowercase();
+
+        match &argument.kind {
+            TokenKind::Number(number) if name.contains("flag") => {
+                if let Ok(flag) = number.parse() {
+                    references.flags.push(ScriptRef { value: flag, line: argument.line });
+                }
+            },
+            TokenKind::String(label) if name.contains("sign") => {
+                references.labels.push(ScriptRef { value: label.clone(), line: argument.line });
+            },
+            _ => (),
+        }
+    }
+
+    references
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Name(String),
+    String(String),
+    Number(String),
+    Punct(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+}
+
+/// Splits Lua source into names, strings, numbers, and punctuation, discarding comments and
+/// whitespace. Escape sequences in strings are left as written.
+fn tokenize(source: &str) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start_line = line;
+
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        }
+        else if c.is_whitespace() {
+            i += 1;
+        }
+        else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            i += 2;
+            if let Some((_, end)) = long_bracket(&chars, i) {
+                line += count_newlines(&chars[i..end]);
+                i = end;
+            }
+            else {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+        }
+        else if c == '[' && long_bracket(&chars, i).is_some() {
+            let (content, end) = long_bracket(&chars, i).unwrap();
+            line += count_newlines(&chars[i..end]);
+            tokens.push(Token { kind: TokenKind::String(content), line: start_line });
+            i = end;
+        }
+        else if c == '"' || c == '\'' {
+            let mut content = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != c && chars[i] != '\n' {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    content.push(chars[i]);
+                    i += 1;
+                }
+                content.push(chars[i]);
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token { kind: TokenKind::String(content), line: start_line });
+        }
+        else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            let number = chars[start..i].iter().collect();
+            tokens.push(Token { kind: TokenKind::Number(number), line: start_line });
+        }
+        else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name = chars[start..i].iter().collect();
+            tokens.push(Token { kind: TokenKind::Name(name), line: start_line });
+        }
+        else {
+            tokens.push(Token { kind: TokenKind::Punct(c), line: start_line });
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+/// If a long bracket (`[[`, `[==[`, etc.) opens at `start`, returns its content and the index
+/// just past its closing bracket.
+fn long_bracket(chars: &[char], start: usize) -> Option<(String, usize)> {
+    if chars.get(start) != Some(&'[') {
+        return None;
+    }
+
+    let mut level = 0;
+    let mut i = start + 1;
+    while chars.get(i) == Some(&'=') {
+        level += 1;
+        i += 1;
+    }
+    if chars.get(i) != Some(&'[') {
+        return None;
+    }
+    let content_start = i + 1;
+
+    let mut j = content_start;
+    while j < chars.len() {
+        if chars[j] == ']'
+            && chars[j + 1..].iter().take(level).all(|&c| c == '=')
+            && chars.get(j + 1 + level) == Some(&']')
+        {
+            let content = chars[content_start..j].iter().collect();
+            return Some((content, j + level + 2));
+        }
+        j += 1;
+    }
+
+    // Unterminated; the rest of the file is consumed
+    let content = chars[content_start..].iter().collect();
+    Some((content, chars.len()))
+}
+
+fn count_newlines(chars: &[char]) -> usize {
+    chars.iter().filter(|&&c| c == '\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_skips_comments() {
+        let tokens = tokenize("a -- b\n--[[ c\nd ]] e");
+        assert_eq!(tokens, vec![
+            Token { kind: TokenKind::Name("a".to_owned()), line: 1 },
+            Token { kind: TokenKind::Name("e".to_owned()), line: 3 },
+        ]);
+    }
+
+    #[test]
+    fn tokenize_reads_strings() {
+        let tokens = tokenize("'a\\'b' [==[c]]d]==]");
+        assert_eq!(tokens, vec![
+            Token { kind: TokenKind::String("a\\'b".to_owned()), line: 1 },
+            Token { kind: TokenKind::String("c]]d".to_owned()), line: 1 },
+        ]);
+    }
+
+    #[test]
+    fn find_references_works() {
+        let source = "\
+function x1000y1001_enter()
+    if GetFlag(3) then
+        ShowSign(\"Intro\")
+    end
+end
+";
+        let references = find_references(source);
+        assert_eq!(references.screens, vec![ScriptRef { value: (1000, 1001), line: 1 }]);
+        assert_eq!(references.flags, vec![ScriptRef { value: 3, line: 2 }]);
+        assert_eq!(references.labels, vec![ScriptRef { value: "Intro".to_owned(), line: 3 }]);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    common::parse_bool,
+    constants::objects::SAVE_POINT,
+    saves::{default_savegame_path, SaveGame},
+    world::World,
+    world_ini::{screen_targets, TargetKind},
+    Result,
+};
+
+/// Where the player starts when KS finds no DefaultSavegame.ini.
+const DEFAULT_START: ((i64, i64), (i64, i64)) = ((1000, 1000), (0, 0));
+
+/// How the player comes to be at a [`SpawnPoint`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpawnKind {
+    /// The start of the level, from DefaultSavegame.ini.
+    Start,
+    /// A save point object.
+    SavePoint,
+    /// The destination of a KS Plus shift in the given slot with `ShiftSave(X)` set.
+    ShiftSave(String),
+}
+
+/// A place where the player can start or resume. See [`spawn_points`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnPoint {
+    pub kind: SpawnKind,
+    pub screen: (i64, i64),
+    /// The tile position within the screen, if it's known statically.
+    pub position: Option<(i64, i64)>,
+}
+
+/// Lists the start of `world` followed by every save point, in the order the screens appear in
+/// Map.bin.
+///
+/// The start comes from the level's DefaultSavegame.ini, or x1000y1000 at (0, 0) if it has none,
+/// as in [`saves::start_new_game`](crate::saves::start_new_game). KS Plus shifts with
+/// `ShiftSave(X)` set are presumed to save once the player arrives at the destination, so their
+/// targets are listed too, whether or not the shift object is placed.
+pub fn spawn_points(world: &World) -> Result<Vec<SpawnPoint>> {
+    let (screen, position) =
+        if default_savegame_path(&world.dir).is_file() {
+            let save = SaveGame::load_default(&world.dir)?;
+            (save.screen().unwrap_or(DEFAULT_START.0), save.position())
+        }
+        else {
+            (DEFAULT_START.0, Some(DEFAULT_START.1))
+        };
+
+    let mut points = vec![SpawnPoint {
+        kind: SpawnKind::Start,
+        screen,
+        position,
+    }];
+
+    for screen in &world.screens {
+        for object in screen.objects().filter(|object| object.tile == SAVE_POINT) {
+            points.push(SpawnPoint {
+                kind: SpawnKind::SavePoint,
+                screen: screen.position,
+                position: Some((object.x as i64, object.y as i64)),
+            });
+        }
+
+        let section = format!("x{}y{}", screen.position.0, screen.position.1);
+        for target in screen_targets(&world.ini, screen.position) {
+            let TargetKind::Shift(slot) = target.kind else { continue };
+            let saves = world.ini.get_in(&section, &format!("ShiftSave({slot})"))
+                .and_then(parse_bool)
+                .unwrap_or(false);
+            if saves {
+                points.push(SpawnPoint {
+                    kind: SpawnKind::ShiftSave(slot),
+                    screen: target.screen,
+                    position: target.position,
+                });
+            }
+        }
+    }
+
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, Tile, SCREEN_DATA_LEN};
+
+    #[test]
+    fn start_save_points_and_shifts_are_found() {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.layers[4].0[3 + 2 * 25] = SAVE_POINT;
+        screen.layers[5].0[0] = Tile(0, 2);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut world = World {
+            dir: temp_dir.path().to_owned(),
+            ini: Ini::new("[x1000y1000]\nShiftXMap(B)=2\nShiftSave(B)=True\nShiftYMap(A)=1\n"),
+            screens: vec![screen],
+        };
+
+        let points = spawn_points(&world).unwrap();
+        assert_eq!(points, [
+            SpawnPoint {
+                kind: SpawnKind::Start,
+                screen: (1000, 1000),
+                position: Some((0, 0)),
+            },
+            SpawnPoint {
+                kind: SpawnKind::SavePoint,
+                screen: (1000, 1000),
+                position: Some((3, 2)),
+            },
+            SpawnPoint {
+                kind: SpawnKind::ShiftSave("B".to_owned()),
+                screen: (1002, 1000),
+                position: None,
+            },
+        ]);
+
+        SaveGame::new("Me - Level", (1001, 999), (4, 5)).write(default_savegame_path(&world.dir)).unwrap();
+        world.screens.clear();
+        assert_eq!(spawn_points(&world).unwrap(), [SpawnPoint {
+            kind: SpawnKind::Start,
+            screen: (1001, 999),
+            position: Some((4, 5)),
+        }]);
+    }
+}
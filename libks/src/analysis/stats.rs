@@ -0,0 +1,240 @@
+use crate::{
+    common::parse_bool,
+    constants::objects::{self, CUSTOM_OBJECT_BANK, SAVE_POINT},
+    map_bin::Tile,
+    world::World,
+};
+
+/// The difficulty tags KS recognizes in the `Difficulty A`-`Difficulty C` properties of World.ini.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    VeryHard,
+    Lunatic,
+}
+
+impl Difficulty {
+    /// Returns the string KS uses for this difficulty in World.ini.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::VeryHard => "Very Hard",
+            Difficulty::Lunatic => "Lunatic",
+        }
+    }
+
+    /// Parses a difficulty tag as written in World.ini, ignoring case and surrounding
+    /// whitespace.
+    pub fn parse(s: &str) -> Option<Difficulty> {
+        [
+            Difficulty::Easy,
+            Difficulty::Normal,
+            Difficulty::Hard,
+            Difficulty::VeryHard,
+            Difficulty::Lunatic,
+        ].into_iter()
+            .find(|difficulty| difficulty.as_str().eq_ignore_ascii_case(s.trim()))
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Summary statistics for a level.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelStats {
+    /// The number of screens in Map.bin.
+    pub screen_count: usize,
+    /// The number of enemies placed in the level.
+    pub enemy_count: usize,
+    /// The number of hazards placed in the level. Every enemy is a hazard, as is every custom
+    /// object that hurts the player.
+    pub hazard_count: usize,
+    /// The number of save points placed in the level.
+    pub save_point_count: usize,
+    /// The average number of enemies per screen.
+    pub enemies_per_screen: f64,
+    /// The average number of hazards per screen.
+    pub hazards_per_screen: f64,
+    /// The number of screens per save point, or `None` if there are no save points.
+    pub screens_per_save: Option<f64>,
+    /// A rough difficulty score. Higher is harder. Most levels fall between 0 and 10.
+    pub difficulty_score: f64,
+    /// The difficulty tag suggested by [`LevelStats::difficulty_score`].
+    pub suggested_difficulty: Difficulty,
+}
+
+/// Computes summary statistics and a difficulty estimate for `world`.
+///
+/// The estimate only considers how densely hazards are placed and how far apart save points
+/// are, so it should be treated as a suggestion rather than a verdict.
+pub fn level_stats(world: &World) -> LevelStats {
+    let screen_count = world.screens.len();
+    let mut enemy_count = 0;
+    let mut hazard_count = 0;
+    let mut save_point_count = 0;
+
+    for (_, object) in world.iter_objects() {
+        let tile = object.tile;
+        if tile == SAVE_POINT {
+            save_point_count += 1;
+        }
+        else if objects::is_enemy(tile) {
+            enemy_count += 1;
+            hazard_count += 1;
+        }
+        else if is_harmful_custom_object(world, tile) {
+            hazard_count += 1;
+        }
+    }
+
+    let per_screen = |count: usize| {
+        if screen_count == 0 {
+            0.0
+        }
+        else {
+            count as f64 / screen_count as f64
+        }
+    };
+    let enemies_per_screen = per_screen(enemy_count);
+    let hazards_per_screen = per_screen(hazard_count);
+    let screens_per_save =
+        if save_point_count == 0 {
+            None
+        }
+        else {
+            Some(screen_count as f64 / save_point_count as f64)
+        };
+
+    let difficulty_score = difficulty_score(hazards_per_screen, screens_per_save, screen_count);
+    let suggested_difficulty = suggest_difficulty(difficulty_score);
+
+    LevelStats {
+        screen_count,
+        enemy_count,
+        hazard_count,
+        save_point_count,
+        enemies_per_screen,
+        hazards_per_screen,
+        screens_per_save,
+        difficulty_score,
+        suggested_difficulty,
+    }
+}
+
+/// Scores difficulty from hazard density and save point spacing.
+///
+/// Hazard density contributes up to 6 points (saturating at 12 hazards per screen) and save
+/// spacing contributes up to 4 (saturating at 20 screens per save). A level with no save points
+/// is treated as if its only save were the starting position.
+fn difficulty_score(hazards_per_screen: f64, screens_per_save: Option<f64>, screen_count: usize) -> f64 {
+    let hazard_points = (hazards_per_screen / 12.0).min(1.0) * 6.0;
+    let spacing = screens_per_save.unwrap_or(screen_count as f64);
+    let spacing_points = (spacing / 20.0).min(1.0) * 4.0;
+
+    hazard_points + spacing_points
+}
+
+fn suggest_difficulty(score: f64) -> Difficulty {
+    match score {
+        score if score < 2.0 => Difficulty::Easy,
+        score if score < 4.0 => Difficulty::Normal,
+        score if score < 6.0 => Difficulty::Hard,
+        score if score < 8.0 => Difficulty::VeryHard,
+        _ => Difficulty::Lunatic,
+    }
+}
+
+/// Custom objects (bank 255) hurt the player if their section sets `Hurts` (KS Plus).
+fn is_harmful_custom_object(world: &World, tile: Tile) -> bool {
+    if tile.0 != CUSTOM_OBJECT_BANK || tile.1 == 0 {
+        return false;
+    }
+
+    world.ini.get_in(&format!("Custom Object {}", tile.1), "Hurts")
+        .and_then(parse_bool)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, SCREEN_DATA_LEN};
+
+    #[test]
+    fn objects_are_counted() {
+        let mut screens: Vec<_> = [(0, 0), (1, 0), (2, 0), (3, 0)].into_iter()
+            .map(|position| parse_screen_bytes(&[0; SCREEN_DATA_LEN], position))
+            .collect();
+        let first = &mut screens[0].layers[4].0;
+        first[0] = SAVE_POINT;
+        first[1] = Tile(3, 1);
+        first[2] = Tile(19, 7);
+        first[3] = Tile(CUSTOM_OBJECT_BANK, 1);
+        first[4] = Tile(CUSTOM_OBJECT_BANK, 2);
+        screens[1].layers[7].0[10] = Tile(3, 1);
+        screens[1].layers[7].0[11] = Tile(3, 1);
+        let mut world = World {
+            dir: Default::default(),
+            ini: Ini::new("[Custom Object 1]\nHurts=True\n[Custom Object 2]\nHurts=False\n"),
+            screens,
+        };
+
+        let stats = level_stats(&world);
+        assert_eq!(stats, LevelStats {
+            screen_count: 4,
+            enemy_count: 4,
+            hazard_count: 5,
+            save_point_count: 1,
+            enemies_per_screen: 1.0,
+            hazards_per_screen: 1.25,
+            screens_per_save: Some(4.0),
+            difficulty_score: 1.25 / 12.0 * 6.0 + 4.0 / 20.0 * 4.0,
+            suggested_difficulty: Difficulty::Easy,
+        });
+
+        // Without the save point, the whole level counts as one stretch
+        world.screens[0].layers[4].0[0] = Tile(0, 0);
+        let stats = level_stats(&world);
+        assert_eq!(stats.save_point_count, 0);
+        assert_eq!(stats.screens_per_save, None);
+        assert_eq!(stats.difficulty_score, 1.25 / 12.0 * 6.0 + 4.0 / 20.0 * 4.0);
+    }
+
+    #[test]
+    fn difficulties_round_trip() {
+        assert_eq!(Difficulty::parse(" very HARD "), Some(Difficulty::VeryHard));
+        assert_eq!(Difficulty::parse("Impossible"), None);
+        assert_eq!(Difficulty::Lunatic.to_string(), "Lunatic");
+    }
+
+    #[test]
+    fn difficulty_score_saturates() {
+        assert_eq!(difficulty_score(100.0, Some(100.0), 100), 10.0);
+        assert_eq!(suggest_difficulty(10.0), Difficulty::Lunatic);
+    }
+
+    #[test]
+    fn difficulty_score_without_saves_uses_screen_count() {
+        assert_eq!(difficulty_score(0.0, None, 10), 2.0);
+        assert_eq!(difficulty_score(0.0, Some(10.0), 10), 2.0);
+    }
+
+    #[test]
+    fn empty_level_is_easy() {
+        let score = difficulty_score(0.0, None, 0);
+        assert_eq!(score, 0.0);
+        assert_eq!(suggest_difficulty(score), Difficulty::Easy);
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use crate::{
+    constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
+    map_bin::ScreenData,
+    world::World,
+    world_ini::{self, Target, TargetKind},
+};
+use super::collision::{self, ScreenCollision};
+
+/// A problem with a location referenced by World.ini.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetIssue {
+    /// The destination screen doesn't exist in Map.bin.
+    MissingScreen(Target),
+    /// The destination tile position lies outside the screen.
+    OutOfBounds(Target),
+    /// The destination tile position is occupied by a solid tile.
+    InsideSolid(Target),
+}
+
+impl TargetIssue {
+    /// Returns the target the issue concerns.
+    pub fn target(&self) -> &Target {
+        match self {
+            TargetIssue::MissingScreen(target)
+            | TargetIssue::OutOfBounds(target)
+            | TargetIssue::InsideSolid(target) => target,
+        }
+    }
+
+    /// Returns `true` if the issue could leave the player stuck with no way to continue, e.g. a
+    /// shift or warp into a screen that doesn't exist.
+    pub fn is_soft_lock_candidate(&self) -> bool {
+        match self {
+            TargetIssue::MissingScreen(target) =>
+                !matches!(target.kind, TargetKind::TriggerSpawn(_)),
+            TargetIssue::OutOfBounds(target)
+            | TargetIssue::InsideSolid(target) =>
+                matches!(target.kind, TargetKind::Shift(_)),
+        }
+    }
+}
+
+impl std::fmt::Display for TargetIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let target = self.target();
+        let (x, y) = target.source;
+        let keys = target.keys.join("`, `");
+
+        let (tile_x, tile_y) = target.position.unwrap_or_default();
+
+        match self {
+            TargetIssue::MissingScreen(_) =>
+                write!(f, "`{keys}` on screen x{x}y{y} leads to x{}y{}, which doesn't exist.", target.screen.0, target.screen.1),
+            TargetIssue::OutOfBounds(_) =>
+                write!(f, "`{keys}` on screen x{x}y{y} leads to the tile ({tile_x}, {tile_y}), which is off the screen."),
+            TargetIssue::InsideSolid(_) =>
+                write!(f, "`{keys}` on screen x{x}y{y} leads to the tile ({tile_x}, {tile_y}) of x{}y{}, which is solid.", target.screen.0, target.screen.1),
+        }
+    }
+}
+
+/// Checks that every location referenced by the World.ini of `world` exists and lies on its
+/// screen.
+///
+/// Solidity depends on the tileset images, so [`TargetIssue::InsideSolid`] is never reported.
+/// Use [`check_targets_with_collision`] for that. See [`world_ini::targets`] for how locations
+/// are resolved.
+pub fn check_targets(world: &World) -> Vec<TargetIssue> {
+    check_targets_with_collision(world, |_| ScreenCollision::default())
+}
+
+/// Like [`check_targets`], but also reports locations that are solid according to the tables
+/// `collision` returns for each destination screen. See [`collision::is_solid`].
+pub fn check_targets_with_collision<F>(world: &World, mut collision: F) -> Vec<TargetIssue>
+where
+    F: FnMut(&ScreenData) -> ScreenCollision
+{
+    let screens: HashMap<_, _> = world.screens.iter()
+        .map(|screen| (screen.position, screen))
+        .collect();
+    let mut issues = Vec::new();
+
+    for target in world_ini::targets(&world.ini) {
+        let Some(screen) = screens.get(&target.screen) else {
+            issues.push(TargetIssue::MissingScreen(target));
+            continue;
+        };
+
+        if let Some(position) = target.position {
+            if !is_in_bounds(position) {
+                issues.push(TargetIssue::OutOfBounds(target));
+            }
+            else if collision::is_solid(screen, position.0 as usize, position.1 as usize, &collision(screen)) {
+                issues.push(TargetIssue::InsideSolid(target));
+            }
+        }
+    }
+
+    issues
+}
+
+fn is_in_bounds((x, y): (i64, i64)) -> bool {
+    (0..SCREEN_WIDTH as i64).contains(&x)
+        && (0..SCREEN_HEIGHT as i64).contains(&y)
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::{
+        analysis::collision::{Collision, COLLISION_LAYER},
+        map_bin::{parse_screen_bytes, Tile, SCREEN_DATA_LEN},
+    };
+
+    #[test]
+    fn solid_targets_need_collision_tables() {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.layers[COLLISION_LAYER].0[2 + 3 * SCREEN_WIDTH] = Tile(0, 7);
+        let world = World {
+            dir: Default::default(),
+            ini: Ini::new("[x1000y1000]\nShiftAbsolute(A)=True\nShiftXMap(A)=1000\nShiftYMap(A)=1000\nShiftX(A)=2\nShiftY(A)=3\n"),
+            screens: vec![screen],
+        };
+
+        assert!(check_targets(&world).is_empty());
+
+        let issues = check_targets_with_collision(&world, |_| {
+            let mut tables = ScreenCollision::default();
+            tables.tileset_a.0[7] = Collision::Solid;
+            tables
+        });
+        assert!(matches!(&issues[..], [TargetIssue::InsideSolid(_)]));
+        assert!(issues[0].is_soft_lock_candidate());
+    }
+}
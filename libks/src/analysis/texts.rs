@@ -0,0 +1,178 @@
+use libks_ini::SectionKind;
+
+use crate::{
+    common::split_slot,
+    world::World,
+};
+
+/// What a piece of level text is used for.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextKind {
+    /// The level's name (`[World] Name`).
+    Name,
+    /// The level's author (`[World] Author`).
+    Author,
+    /// The level's description (`[World] Description`).
+    Description,
+    /// A sign in the given slot. KS Plus `Sign2(X)` pages are included.
+    Sign(String),
+    /// A KS Plus title, shown when entering a screen or the level.
+    Title,
+    /// A KS Plus subtitle, shown when entering a screen or the level.
+    Subtitle,
+    /// The name of a cutscene to play. These name a folder of the level rather than text
+    /// shown in-game, but they're included so that renamed cutscenes can be found.
+    Cutscene,
+}
+
+/// A piece of text from World.ini and where it came from.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelText {
+    /// The section containing the text.
+    pub section: String,
+    /// The property containing the text.
+    pub key: String,
+    /// The screen, if the text is in a screen section.
+    pub screen: Option<(i64, i64)>,
+    pub kind: TextKind,
+    pub text: String,
+}
+
+/// Collects every piece of prose in the World.ini of `world`, in file order.
+///
+/// The result can be exported, edited (e.g. translated or proofread), and written back with
+/// [`apply_texts`].
+pub fn texts(world: &World) -> Vec<LevelText> {
+    let mut texts = Vec::new();
+
+    for section in world.ini.iter_sections() {
+        let section_key = section.key();
+        let kind = section.kind();
+        let is_world = kind == SectionKind::World;
+        let screen = kind.screen();
+        if !is_world && screen.is_none() {
+            continue;
+        }
+
+        for (key, value) in section.iter() {
+            let kind = if is_world {
+                world_text_kind(key)
+            }
+            else {
+                screen_text_kind(key)
+            };
+
+            if let Some(kind) = kind {
+                texts.push(LevelText {
+                    section: section_key.to_owned(),
+                    key: key.to_owned(),
+                    screen,
+                    kind,
+                    text: value.to_owned(),
+                });
+            }
+        }
+    }
+
+    texts
+}
+
+/// Writes the text of each entry in `texts` back to the World.ini of `world`. Returns the number
+/// of properties that changed.
+pub fn apply_texts(world: &mut World, texts: &[LevelText]) -> usize {
+    let mut changed = 0;
+
+    for entry in texts {
+        if world.ini.get_in(&entry.section, &entry.key) != Some(entry.text.as_str()) {
+            world.ini.set_in(&entry.section, &entry.key, entry.text.clone());
+            changed += 1;
+        }
+    }
+
+    changed
+}
+
+fn world_text_kind(key: &str) -> Option<TextKind> {
+    let kind = match key.to_ascii_lowercase().as_str() {
+        "name" => TextKind::Name,
+        "author" => TextKind::Author,
+        "description" => TextKind::Description,
+        "title" => TextKind::Title,
+        "subtitle" => TextKind::Subtitle,
+        _ => return None,
+    };
+
+    Some(kind)
+}
+
+fn screen_text_kind(key: &str) -> Option<TextKind> {
+    match key.to_ascii_lowercase().as_str() {
+        "title" => return Some(TextKind::Title),
+        "subtitle" => return Some(TextKind::Subtitle),
+        _ => (),
+    }
+
+    let (name, slot) = split_slot(key).unwrap_or((key, ""));
+    if name.eq_ignore_ascii_case("Sign") || name.eq_ignore_ascii_case("Sign2") {
+        Some(TextKind::Sign(slot.to_owned()))
+    }
+    else if name.to_ascii_lowercase().contains("cutscene") {
+        Some(TextKind::Cutscene)
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+
+    #[test]
+    fn texts_round_trip() {
+        let mut world = World {
+            dir: Default::default(),
+            ini: Ini::new("\
+[World]
+Name=Level
+Author=Me
+Format=4
+[Custom Object 1]
+Image=Sign.png
+[x1000y1000]
+Sign(A)=Hello
+Sign2(A)=Page two
+Title=Cave
+ShiftCutscene(B)=Ending
+Tint=Red
+"),
+            screens: Vec::new(),
+        };
+
+        let mut texts = texts(&world);
+        let found: Vec<_> = texts.iter()
+            .map(|text| (text.key.as_str(), text.screen, &text.kind, text.text.as_str()))
+            .collect();
+        let screen = Some((1000, 1000));
+        assert_eq!(found, [
+            ("Name", None, &TextKind::Name, "Level"),
+            ("Author", None, &TextKind::Author, "Me"),
+            ("Sign(A)", screen, &TextKind::Sign("A".to_owned()), "Hello"),
+            ("Sign2(A)", screen, &TextKind::Sign("A".to_owned()), "Page two"),
+            ("Title", screen, &TextKind::Title, "Cave"),
+            ("ShiftCutscene(B)", screen, &TextKind::Cutscene, "Ending"),
+        ]);
+
+        texts[2].text = "Bonjour".to_owned();
+        texts[4].text = "Grotte".to_owned();
+        assert_eq!(apply_texts(&mut world, &texts), 2);
+        assert_eq!(world.ini.get_in("x1000y1000", "Sign(A)"), Some("Bonjour"));
+        assert_eq!(world.ini.get_in("x1000y1000", "Title"), Some("Grotte"));
+        assert_eq!(world.ini.get_in("World", "Name"), Some("Level"));
+        assert_eq!(apply_texts(&mut world, &texts), 0);
+    }
+}
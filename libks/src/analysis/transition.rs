@@ -0,0 +1,57 @@
+use libks_ini::Ini;
+
+use crate::{
+    constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
+    world_ini::{screen_targets, Edge, TargetKind},
+};
+
+/// Returns the screen next to `from` across `edge`, ignoring warps. Y increases downward, as
+/// it does in Map.bin.
+pub fn adjacent(from: (i64, i64), edge: Edge) -> (i64, i64) {
+    let (x, y) = from;
+    match edge {
+        Edge::Up => (x, y - 1),
+        Edge::Down => (x, y + 1),
+        Edge::Left => (x - 1, y),
+        Edge::Right => (x + 1, y),
+    }
+}
+
+/// Returns the screen the player enters when leaving `from` through `edge`.
+///
+/// If the section for `from` sets either of `Warp{edge}X` and `Warp{edge}Y`, the player goes to
+/// `from` offset by the pair instead of the [`adjacent`] screen. See [`screen_targets`].
+pub fn transition(ini: &Ini, from: (i64, i64), edge: Edge) -> (i64, i64) {
+    screen_targets(ini, from).into_iter()
+        .find(|target| target.kind == TargetKind::Warp(edge))
+        .map_or_else(|| adjacent(from, edge), |target| target.screen)
+}
+
+/// Returns the tile position where the player appears after leaving a screen through `edge` at
+/// tile `position`. The player wraps to the opposite edge of the next screen, keeping the other
+/// coordinate.
+pub fn entry_position(position: (i64, i64), edge: Edge) -> (i64, i64) {
+    let (x, y) = position;
+    match edge {
+        Edge::Up => (x, SCREEN_HEIGHT as i64 - 1),
+        Edge::Down => (x, 0),
+        Edge::Left => (SCREEN_WIDTH as i64 - 1, y),
+        Edge::Right => (0, y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warps_replace_the_adjacent_screen() {
+        let ini = Ini::new("[x1000y1000]\nWarpUpY=-3\n");
+
+        assert_eq!(transition(&ini, (1000, 1000), Edge::Up), (1000, 997));
+        assert_eq!(transition(&ini, (1000, 1000), Edge::Right), (1001, 1000));
+        assert_eq!(transition(&ini, (5, 5), Edge::Down), (5, 6));
+        assert_eq!(entry_position((24, 4), Edge::Right), (0, 4));
+        assert_eq!(entry_position((7, 0), Edge::Up), (7, 9));
+    }
+}
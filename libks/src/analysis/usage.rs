@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::map_bin::{AssetId, ScreenData};
+
+/// An index of which screens use each tile and object. See [`tile_usage`].
+///
+/// Tiles are identified by the tileset ID and the tile's index in it, rather than by the A/B
+/// slot recorded in the layer, so usage is combined across every screen that loads the tileset.
+/// Objects are identified by bank and index. Index 0 is empty in every tileset and bank, so it's
+/// never recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TileUsage {
+    tiles: BTreeMap<(AssetId, u8), Vec<(i64, i64)>>,
+    objects: BTreeMap<(u8, u8), Vec<(i64, i64)>>,
+}
+
+impl TileUsage {
+    /// Returns the positions of the screens that use tile `index` of tileset `tileset`, in the
+    /// order they were given to [`tile_usage`].
+    pub fn screens_using_tile(&self, tileset: AssetId, index: u8) -> &[(i64, i64)] {
+        self.tiles.get(&(tileset, index))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the positions of the screens that use object `index` of bank `bank`, in the order
+    /// they were given to [`tile_usage`].
+    pub fn screens_using_object(&self, bank: u8, index: u8) -> &[(i64, i64)] {
+        self.objects.get(&(bank, index))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the indices of the tiles used from tileset `tileset`, in ascending order.
+    ///
+    /// A tile that isn't listed here can be removed from the tileset without changing how any
+    /// screen looks.
+    pub fn used_tiles(&self, tileset: AssetId) -> impl Iterator<Item = u8> + '_ {
+        self.tiles.range((tileset, 0)..=(tileset, u8::MAX))
+            .map(|(&(_, index), _)| index)
+    }
+
+    /// Iterates over every used tile as `((tileset, index), screens)`, ordered by tileset and
+    /// index.
+    pub fn tiles(&self) -> impl Iterator<Item = ((AssetId, u8), &[(i64, i64)])> {
+        self.tiles.iter()
+            .map(|(&tile, screens)| (tile, screens.as_slice()))
+    }
+
+    /// Iterates over every used object as `((bank, index), screens)`, ordered by bank and index.
+    pub fn objects(&self) -> impl Iterator<Item = ((u8, u8), &[(i64, i64)])> {
+        self.objects.iter()
+            .map(|(&object, screens)| (object, screens.as_slice()))
+    }
+}
+
+/// Indexes the tiles and objects used by `screens`. See [`TileUsage`].
+///
+/// Tiles on a layer that refer to a slot other than A or B are ignored, as KS doesn't draw them.
+pub fn tile_usage(screens: &[ScreenData]) -> TileUsage {
+    let mut usage = TileUsage::default();
+
+    for screen in screens {
+        let mut tiles = BTreeSet::new();
+        let mut objects = BTreeSet::new();
+
+        for layer in &screen.layers[0..4] {
+            for tile in &layer.0 {
+                let tileset = match tile.0 {
+                    0 => screen.assets.tileset_a,
+                    1 => screen.assets.tileset_b,
+                    _ => continue,
+                };
+                if tile.1 != 0 {
+                    tiles.insert((tileset, tile.1));
+                }
+            }
+        }
+
+        for layer in &screen.layers[4..8] {
+            for object in &layer.0 {
+                if object.1 != 0 {
+                    objects.insert((object.0, object.1));
+                }
+            }
+        }
+
+        for tile in tiles {
+            usage.tiles.entry(tile).or_default().push(screen.position);
+        }
+        for object in objects {
+            usage.objects.entry(object).or_default().push(screen.position);
+        }
+    }
+
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, Tile, SCREEN_DATA_LEN};
+
+    #[test]
+    fn usage_is_combined_by_tileset() {
+        let mut first = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        first.assets.tileset_a = 3;
+        first.layers[0].0[0] = Tile(0, 5);
+        first.layers[1].0[7] = Tile(0, 5);
+        first.layers[4].0[0] = Tile(1, 2);
+
+        let mut second = first.clone();
+        second.position = (1001, 1000);
+        second.assets.tileset_a = 4;
+        second.assets.tileset_b = 3;
+        second.layers[0].0[0] = Tile(1, 5);
+
+        let usage = tile_usage(&[first, second]);
+        assert_eq!(usage.screens_using_tile(3, 5), [(1000, 1000), (1001, 1000)]);
+        assert_eq!(usage.screens_using_tile(4, 5), [(1001, 1000)]);
+        assert_eq!(usage.used_tiles(3).collect::<Vec<_>>(), [5]);
+        assert_eq!(usage.screens_using_object(1, 2).len(), 2);
+        assert!(usage.screens_using_object(0, 0).is_empty());
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{error::ResultExt, Result};
+
+/// The sample rates KS is presumed to play correctly. Other rates are flagged by
+/// [`audit_audio`], but the engine's actual requirements haven't been confirmed.
+pub const EXPECTED_SAMPLE_RATES: [u32; 2] = [22_050, 44_100];
+
+/// The stream parameters of an Ogg Vorbis file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VorbisInfo {
+    pub channels: u8,
+    /// The sample rate in Hz.
+    pub sample_rate: u32,
+    /// The length of the track, if the end of the stream could be found.
+    pub duration: Option<Duration>,
+    /// The track's loudness, if it has ReplayGain tags.
+    pub replay_gain: Option<ReplayGain>,
+}
+
+/// The loudness of a track, from the `REPLAYGAIN_TRACK_GAIN` and `REPLAYGAIN_TRACK_PEAK` tags
+/// that ReplayGain scanners add to the Vorbis comments.
+///
+/// libks doesn't decode audio, so untagged tracks have no loudness report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayGain {
+    /// The adjustment in dB that brings the track to the ReplayGain reference level. Negative
+    /// values mean the track is louder than the reference.
+    pub track_gain: f64,
+    /// The largest sample amplitude, where 1.0 is full scale.
+    pub track_peak: Option<f64>,
+}
+
+/// A music or ambiance file examined by [`audit_audio`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioTrack {
+    /// The path of the file, relative to the level directory.
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub len: u64,
+    /// The stream parameters, if the file is Ogg Vorbis.
+    pub info: Option<VorbisInfo>,
+}
+
+/// A problem with a music or ambiance file. See [`audit_audio`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioIssue {
+    /// The file isn't an Ogg file. Holds the format it appears to be in, if it's recognized.
+    /// KS crashes on these.
+    NotOgg {
+        path: PathBuf,
+        detected: Option<&'static str>,
+    },
+    /// The file is an Ogg file, but it doesn't start with a Vorbis stream, e.g. because it's Opus.
+    NotVorbis(PathBuf),
+    /// The sample rate isn't one of [`EXPECTED_SAMPLE_RATES`].
+    UnexpectedSampleRate {
+        path: PathBuf,
+        sample_rate: u32,
+    },
+}
+
+impl std::fmt::Display for AudioIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioIssue::NotOgg { path, detected: Some(format) } =>
+                write!(f, "{} is a renamed {format} file, not Ogg Vorbis.", path.display()),
+            AudioIssue::NotOgg { path, detected: None } =>
+                write!(f, "{} isn't an Ogg Vorbis file.", path.display()),
+            AudioIssue::NotVorbis(path) =>
+                write!(f, "{} is an Ogg file, but it isn't Vorbis.", path.display()),
+            AudioIssue::UnexpectedSampleRate { path, sample_rate } =>
+                write!(f, "{} has an unusual sample rate of {sample_rate} Hz.", path.display()),
+        }
+    }
+}
+
+/// The result of [`audit_audio`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioAudit {
+    /// Every .ogg file in the level's Music and Ambiance directories.
+    pub tracks: Vec<AudioTrack>,
+    pub issues: Vec<AudioIssue>,
+}
+
+impl AudioAudit {
+    /// Returns the total size of the tracks in bytes.
+    pub fn total_len(&self) -> u64 {
+        self.tracks.iter()
+            .map(|track| track.len)
+            .sum()
+    }
+
+    /// Returns the paths and ReplayGain track gains of the tracks that have them, loudest (most
+    /// negative gain) first.
+    pub fn loudness(&self) -> Vec<(&Path, f64)> {
+        let mut loudness: Vec<_> = self.tracks.iter()
+            .filter_map(|track| {
+                let gain = track.info.as_ref()?.replay_gain?.track_gain;
+                Some((track.path.as_path(), gain))
+            })
+            .collect();
+        loudness.sort_by(|a, b| a.1.total_cmp(&b.1));
+        loudness
+    }
+}
+
+/// Checks that the music and ambiance of the level in `world_dir` are Ogg Vorbis files with
+/// sample rates KS handles, and reports the size, length, and loudness of each track.
+///
+/// Only the headers are read; the audio isn't decoded. Loudness comes from ReplayGain tags, so
+/// it's only reported for tagged tracks. See [`AudioAudit::loudness`].
+pub fn audit_audio<P>(world_dir: P) -> Result<AudioAudit>
+where
+    P: AsRef<Path>
+{
+    let world_dir = world_dir.as_ref();
+    let mut audit = AudioAudit::default();
+
+    for dir_name in ["Ambiance", "Music"] {
+        let dir = world_dir.join(dir_name);
+        if !dir.is_dir() {
+            continue;
+        }
+
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(&dir).with_path(&dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ogg")) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        for path in paths {
+            let data = fs::read(&path).with_path(&path)?;
+            let relative = path.strip_prefix(world_dir).unwrap_or(&path).to_owned();
+
+            let info = if !data.starts_with(b"OggS") {
+                audit.issues.push(AudioIssue::NotOgg {
+                    path: relative.clone(),
+                    detected: detect_format(&data),
+                });
+                None
+            }
+            else if let Some(info) = parse_vorbis(&data) {
+                if !EXPECTED_SAMPLE_RATES.contains(&info.sample_rate) {
+                    audit.issues.push(AudioIssue::UnexpectedSampleRate {
+                        path: relative.clone(),
+                        sample_rate: info.sample_rate,
+                    });
+                }
+                Some(info)
+            }
+            else {
+                audit.issues.push(AudioIssue::NotVorbis(relative.clone()));
+                None
+            };
+
+            audit.tracks.push(AudioTrack {
+                path: relative,
+                len: data.len() as u64,
+                info,
+            });
+        }
+    }
+
+    Ok(audit)
+}
+
+/// Guesses the format of a file that isn't Ogg from its first bytes.
+fn detect_format(data: &[u8]) -> Option<&'static str> {
+    match data {
+        [b'I', b'D', b'3', ..] => Some("MP3"),
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 => Some("MP3"),
+        [b'R', b'I', b'F', b'F', ..] => Some("WAV"),
+        [b'f', b'L', b'a', b'C', ..] => Some("FLAC"),
+        _ => None,
+    }
+}
+
+/// The length of an Ogg page header, not counting the segment table.
+const PAGE_HEADER_LEN: usize = 27;
+
+/// Reads the Vorbis identification and comment headers from the first two packets of `data`,
+/// and the length of the stream from the granule position of the last page.
+fn parse_vorbis(data: &[u8]) -> Option<VorbisInfo> {
+    let packets = first_packets(data, 2);
+    let packet = packets.first()?.get(..16)?;
+    if &packet[..7] != b"\x01vorbis" {
+        return None;
+    }
+
+    let channels = packet[11];
+    let sample_rate = u32::from_le_bytes(packet[12..16].try_into().unwrap());
+    if sample_rate == 0 {
+        return None;
+    }
+
+    let duration = data.windows(4)
+        .rposition(|window| window == b"OggS")
+        .and_then(|last_page| data.get(last_page + 6..last_page + 14))
+        .map(|granule| i64::from_le_bytes(granule.try_into().unwrap()))
+        .filter(|&granule| granule > 0)
+        .map(|granule| Duration::from_secs_f64(granule as f64 / sample_rate as f64));
+
+    Some(VorbisInfo {
+        channels,
+        sample_rate,
+        duration,
+        replay_gain: packets.get(1).and_then(|packet| parse_replay_gain(packet)),
+    })
+}
+
+/// Reassembles up to `count` packets from the start of the Ogg stream in `data`.
+fn first_packets(data: &[u8], count: usize) -> Vec<Vec<u8>> {
+    let mut packets = Vec::new();
+    let mut packet = Vec::new();
+    let mut page = 0;
+
+    while let Some(header) = data.get(page..page + PAGE_HEADER_LEN) {
+        if &header[..4] != b"OggS" {
+            break;
+        }
+
+        let segment_table = page + PAGE_HEADER_LEN;
+        let segment_count = usize::from(header[PAGE_HEADER_LEN - 1]);
+        let Some(lacing) = data.get(segment_table..segment_table + segment_count) else { break };
+
+        // A segment shorter than 255 bytes ends a packet
+        let mut segment = segment_table + segment_count;
+        for &len in lacing {
+            let Some(bytes) = data.get(segment..segment + usize::from(len)) else { return packets };
+            packet.extend_from_slice(bytes);
+            segment += usize::from(len);
+            if len < 255 {
+                packets.push(std::mem::take(&mut packet));
+                if packets.len() == count {
+                    return packets;
+                }
+            }
+        }
+        page = segment;
+    }
+
+    packets
+}
+
+/// Reads the ReplayGain track tags from a Vorbis comment header. Tag names are case
+/// insensitive, and values may have a ` dB` suffix.
+fn parse_replay_gain(packet: &[u8]) -> Option<ReplayGain> {
+    let mut rest = packet.strip_prefix(b"\x03vorbis")?;
+    let _vendor = read_field(&mut rest)?;
+    let comment_count = read_u32(&mut rest)?;
+
+    let mut track_gain = None;
+    let mut track_peak = None;
+    for _ in 0..comment_count {
+        let comment = String::from_utf8_lossy(read_field(&mut rest)?);
+        let Some((name, value)) = comment.split_once('=') else { continue };
+        let value = value.trim().trim_end_matches("dB").trim().parse().ok();
+        if name.eq_ignore_ascii_case("REPLAYGAIN_TRACK_GAIN") {
+            track_gain = value;
+        }
+        else if name.eq_ignore_ascii_case("REPLAYGAIN_TRACK_PEAK") {
+            track_peak = value;
+        }
+    }
+
+    Some(ReplayGain {
+        track_gain: track_gain?,
+        track_peak,
+    })
+}
+
+/// Reads a little endian `u32` from the start of `rest` and advances past it.
+fn read_u32(rest: &mut &[u8]) -> Option<u32> {
+    let (bytes, remainder) = rest.split_first_chunk::<4>()?;
+    *rest = remainder;
+    Some(u32::from_le_bytes(*bytes))
+}
+
+/// Reads a field prefixed by its length as a `u32` from the start of `rest` and advances past
+/// it.
+fn read_field<'a>(rest: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = read_u32(rest)? as usize;
+    let field = rest.get(..len)?;
+    *rest = &rest[len..];
+    Some(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ogg_page(granule: i64, packet: &[u8]) -> Vec<u8> {
+        let mut page = b"OggS\0\0".to_vec();
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&[0; 12]);
+        page.push(1);
+        page.push(packet.len() as u8);
+        page.extend_from_slice(packet);
+        page
+    }
+
+    #[test]
+    fn vorbis_headers_are_read() {
+        let mut ident = b"\x01vorbis\0\0\0\0\x02".to_vec();
+        ident.extend_from_slice(&44_100u32.to_le_bytes());
+        ident.extend_from_slice(&[0; 14]);
+        let mut data = ogg_page(0, &ident);
+        data.extend(ogg_page(88_200, &[0; 8]));
+
+        assert_eq!(parse_vorbis(&data), Some(VorbisInfo {
+            channels: 2,
+            sample_rate: 44_100,
+            duration: Some(Duration::from_secs(2)),
+            replay_gain: None,
+        }));
+        assert_eq!(parse_vorbis(&ogg_page(0, b"OpusHead\x01\x02\0\0\0\0\0\0")), None);
+        assert_eq!(detect_format(b"ID3\x04"), Some("MP3"));
+    }
+
+    #[test]
+    fn replay_gain_is_read_from_comments() {
+        let mut ident = b"\x01vorbis\0\0\0\0\x01".to_vec();
+        ident.extend_from_slice(&22_050u32.to_le_bytes());
+        ident.extend_from_slice(&[0; 14]);
+
+        let mut comments = b"\x03vorbis".to_vec();
+        let vendor = "x".repeat(300);
+        let fields = [vendor.as_str(), "TITLE=Song", "replaygain_track_gain=-6.50 dB", "REPLAYGAIN_TRACK_PEAK=0.98"];
+        for (i, field) in fields.iter().enumerate() {
+            comments.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            comments.extend_from_slice(field.as_bytes());
+            if i == 0 {
+                comments.extend_from_slice(&(fields.len() as u32 - 1).to_le_bytes());
+            }
+        }
+
+        // The comment header spans two segments, one of them full
+        let mut data = ogg_page(0, &ident);
+        data.extend_from_slice(b"OggS\0\0");
+        data.extend_from_slice(&[0; 20]);
+        data.push(2);
+        data.push(255);
+        data.push((comments.len() - 255) as u8);
+        data.extend_from_slice(&comments);
+
+        let info = parse_vorbis(&data).unwrap();
+        assert_eq!(info.replay_gain, Some(ReplayGain { track_gain: -6.5, track_peak: Some(0.98) }));
+
+        let quiet = AudioTrack { path: "Music/Song1.ogg".into(), len: 0, info: Some(info) };
+        let mut loud = quiet.clone();
+        loud.path = "Music/Song2.ogg".into();
+        loud.info.as_mut().unwrap().replay_gain.as_mut().unwrap().track_gain = -9.0;
+        let audit = AudioAudit { tracks: vec![quiet, loud], issues: Vec::new() };
+        assert_eq!(audit.loudness(), [(Path::new("Music/Song2.ogg"), -9.0), (Path::new("Music/Song1.ogg"), -6.5)]);
+    }
+}
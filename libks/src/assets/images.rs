@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+
+use image::{imageops::{self, FilterType}, io::Reader as ImageReader, RgbaImage};
+
+use crate::{error::ResultExt, world, DrawError, Result};
+
+/// The dimensions of Icon.png, which is shown in the level list.
+pub const ICON_SIZE: (u32, u32) = (30, 30);
+/// The dimensions of Info.png, which is shown when a level is selected.
+pub const INFO_SIZE: (u32, u32) = (600, 240);
+
+/// Loads the Icon.png of the level in `world_dir`, checking that it's [`ICON_SIZE`].
+pub fn icon<P>(world_dir: P) -> Result<RgbaImage>
+where
+    P: AsRef<Path>
+{
+    load_checked(world_dir.as_ref().join("Icon.png"), ICON_SIZE)
+}
+
+/// Loads the Info.png of the level in `world_dir`, checking that it's [`INFO_SIZE`].
+pub fn info_image<P>(world_dir: P) -> Result<RgbaImage>
+where
+    P: AsRef<Path>
+{
+    load_checked(world_dir.as_ref().join("Info.png"), INFO_SIZE)
+}
+
+/// Creates the Icon.png of the level in `world_dir` from its Info.png if it doesn't have one.
+/// The largest centered square of Info.png is scaled down to [`ICON_SIZE`].
+///
+/// Returns `true` if an icon was created, or `false` if the level already had one. The level is
+/// locked while the icon is written (see [`World::lock`](crate::world::World::lock)).
+pub fn generate_icon<P>(world_dir: P) -> Result<bool>
+where
+    P: AsRef<Path>
+{
+    let world_dir = world_dir.as_ref();
+    let icon_path = world_dir.join("Icon.png");
+    if icon_path.exists() {
+        return Ok(false);
+    }
+    let _lock = world::lock_for_write(world_dir)?;
+
+    let info = info_image(world_dir)?;
+    let (width, height) = info.dimensions();
+    let side = width.min(height);
+    let square = imageops::crop_imm(&info, (width - side) / 2, (height - side) / 2, side, side)
+        .to_image();
+
+    imageops::resize(&square, ICON_SIZE.0, ICON_SIZE.1, FilterType::Lanczos3)
+        .save(&icon_path)
+        .map_err(|source| DrawError::Image {
+            source,
+            path: icon_path.clone(),
+        })?;
+
+    Ok(true)
+}
+
+fn load_checked(path: PathBuf, expected: (u32, u32)) -> Result<RgbaImage> {
+    let image = match ImageReader::open(&path).with_path(&path)?.decode() {
+        Ok(image) => image.into_rgba8(),
+        Err(source) => return Err(DrawError::Image {
+            source,
+            path,
+        }.into()),
+    };
+
+    let actual = image.dimensions();
+    if actual != expected {
+        return Err(DrawError::WrongDimensions {
+            path,
+            expected,
+            actual,
+        }.into());
+    }
+
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icon_is_generated_from_info() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+
+        let info = RgbaImage::from_fn(INFO_SIZE.0, INFO_SIZE.1, |x, _| {
+            image::Rgba([if x < 300 { 255 } else { 0 }, 0, 0, 255])
+        });
+        info.save(dir.join("Info.png")).unwrap();
+
+        assert!(generate_icon(dir).unwrap());
+        assert!(!generate_icon(dir).unwrap());
+        assert_eq!(icon(dir).unwrap().dimensions(), ICON_SIZE);
+        assert!(matches!(
+            load_checked(dir.join("Info.png"), ICON_SIZE),
+            Err(crate::KsError::Draw(DrawError::WrongDimensions { actual: (600, 240), .. })),
+        ));
+    }
+}
@@ -2,6 +2,21 @@ use std::{path::PathBuf, fs::{self, File}};
 
 use crate::Result;
 
+#[cfg(feature = "audio")]
+mod audio;
+#[cfg(feature = "audio")]
+pub use audio::{audit_audio, AudioAudit, AudioIssue, AudioTrack, ReplayGain, VorbisInfo, EXPECTED_SAMPLE_RATES};
+
+#[cfg(feature = "image")]
+mod images;
+#[cfg(feature = "image")]
+pub use images::{generate_icon, icon, info_image, ICON_SIZE, INFO_SIZE};
+
+#[cfg(feature = "image")]
+mod optimize;
+#[cfg(feature = "image")]
+pub use optimize::{optimize_images, OptimizeOptions, OptimizeReport, OptimizedImage};
+
 type AssetId = u8;
 
 pub struct AssetSource {
@@ -0,0 +1,166 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::{
+    codecs::png::{CompressionType, FilterType, PngEncoder},
+    ImageEncoder,
+};
+
+use crate::{error::ResultExt, world, DrawError, KsError, Result};
+
+/// Configures the behavior of [`optimize_images`].
+#[derive(Debug, Clone, Default)]
+pub struct OptimizeOptions {
+    /// If `true`, the savings are reported but no files are changed. Defaults to `false`.
+    pub dry_run: bool,
+}
+
+/// A PNG that [`optimize_images`] made smaller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptimizedImage {
+    /// The path of the image, relative to the level directory.
+    pub path: PathBuf,
+    /// The size of the file before optimizing, in bytes.
+    pub original_len: u64,
+    /// The size of the file after optimizing, in bytes.
+    pub optimized_len: u64,
+}
+
+/// The result of [`optimize_images`].
+#[derive(Debug, Default)]
+pub struct OptimizeReport {
+    /// The images that got smaller.
+    pub images: Vec<OptimizedImage>,
+    /// Images that couldn't be read or written. They're left alone.
+    pub failed: Vec<KsError>,
+}
+
+impl OptimizeReport {
+    /// Returns the total number of bytes saved.
+    pub fn saved(&self) -> u64 {
+        self.images.iter()
+            .map(|image| image.original_len - image.optimized_len)
+            .sum()
+    }
+}
+
+/// Re-encodes every PNG in the level in `world_dir` with the best compression available, making
+/// the level's .knytt.bin smaller.
+///
+/// Re-encoding drops ancillary chunks like text and color profiles, which KS is presumed to
+/// ignore. Pixel data is kept exactly, though palette images are expanded to RGB or RGBA. An
+/// image is only replaced if the result is smaller.
+///
+/// Unless it's a dry run, the level is locked while its images are replaced (see
+/// [`World::lock`](crate::world::World::lock)).
+pub fn optimize_images<P>(world_dir: P, options: OptimizeOptions) -> Result<OptimizeReport>
+where
+    P: AsRef<Path>
+{
+    let world_dir = world_dir.as_ref();
+    let _lock = if options.dry_run { None } else { world::lock_for_write(world_dir)? };
+    let mut paths = Vec::new();
+    find_pngs(world_dir, &mut paths)?;
+    paths.sort();
+
+    let mut report = OptimizeReport::default();
+    for path in paths {
+        match optimize_image(&path, &options) {
+            Ok(Some((original_len, optimized_len))) => report.images.push(OptimizedImage {
+                path: path.strip_prefix(world_dir).unwrap_or(&path).to_owned(),
+                original_len,
+                optimized_len,
+            }),
+            Ok(None) => {},
+            Err(err) => report.failed.push(err),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Re-encodes the PNG at `path`, returning its old and new sizes if it got smaller.
+fn optimize_image(path: &Path, options: &OptimizeOptions) -> Result<Option<(u64, u64)>> {
+    let original = fs::read(path).with_path(path)?;
+    let image = image::load_from_memory_with_format(&original, image::ImageFormat::Png)
+        .map_err(|source| DrawError::Image {
+            source,
+            path: path.to_owned(),
+        })?;
+
+    let mut optimized = Vec::new();
+    PngEncoder::new_with_quality(&mut optimized, CompressionType::Best, FilterType::Adaptive)
+        .write_image(image.as_bytes(), image.width(), image.height(), image.color())
+        .map_err(|source| DrawError::Image {
+            source,
+            path: path.to_owned(),
+        })?;
+
+    if optimized.len() >= original.len() {
+        return Ok(None);
+    }
+
+    if !options.dry_run {
+        fs::write(path, &optimized).with_path(path)?;
+    }
+
+    Ok(Some((original.len() as u64, optimized.len() as u64)))
+}
+
+/// Recursively finds the files in `dir` with a `.png` extension, in any case.
+fn find_pngs(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_path(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_pngs(&path, paths)?;
+        }
+        else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png")) {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::Crc;
+    use image::RgbaImage;
+
+    use super::*;
+
+    #[test]
+    fn ancillary_chunks_are_stripped() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("Tilesets")).unwrap();
+
+        let pixels = RgbaImage::from_fn(16, 16, |x, y| image::Rgba([x as u8 * 16, y as u8 * 16, 0, 255]));
+        let mut png = Vec::new();
+        PngEncoder::new_with_quality(&mut png, CompressionType::Fast, FilterType::NoFilter)
+            .write_image(&pixels, 16, 16, image::ColorType::Rgba8)
+            .unwrap();
+
+        // Insert a tEXt chunk before IEND
+        let text = [b"tEXt".as_slice(), b"Comment\0", &[b'x'; 200]].concat();
+        let mut crc = Crc::new();
+        crc.update(&text);
+        let iend = png.split_off(png.len() - 12);
+        png.extend_from_slice(&(text.len() as u32 - 4).to_be_bytes());
+        png.extend_from_slice(&text);
+        png.extend_from_slice(&crc.sum().to_be_bytes());
+        png.extend_from_slice(&iend);
+        fs::write(dir.join("Tilesets/Tileset1.PNG"), &png).unwrap();
+
+        let report = optimize_images(dir, OptimizeOptions::default()).unwrap();
+        assert!(report.failed.is_empty());
+        assert_eq!(report.images.len(), 1);
+        assert_eq!(report.images[0].path, Path::new("Tilesets/Tileset1.PNG"));
+        assert!(report.saved() > 200);
+
+        let optimized = image::open(dir.join("Tilesets/Tileset1.PNG")).unwrap().into_rgba8();
+        assert_eq!(optimized, pixels);
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{atomic::{AtomicUsize, Ordering}, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{KsError, Result};
+
+/// What happened when [`process`] ran the operation on one level.
+#[derive(Debug)]
+pub enum Outcome<T> {
+    Succeeded(T),
+    Failed(KsError),
+    /// The operation panicked. Holds the panic message, if it was a string.
+    Panicked(Option<String>),
+}
+
+impl<T> Outcome<T> {
+    pub fn is_success(&self) -> bool {
+        matches!(self, Outcome::Succeeded(_))
+    }
+}
+
+/// The outcome of [`process`] for one level.
+#[derive(Debug)]
+pub struct BatchItem<T> {
+    /// The level's directory or .knytt.bin, as given to [`process`].
+    pub path: PathBuf,
+    pub outcome: Outcome<T>,
+    /// How long the operation took on this level.
+    pub elapsed: Duration,
+}
+
+/// The results of [`process`], in the order the levels were given.
+#[derive(Debug)]
+pub struct BatchReport<T> {
+    pub items: Vec<BatchItem<T>>,
+    /// How long the whole batch took.
+    pub elapsed: Duration,
+}
+
+impl<T> BatchReport<T> {
+    /// Returns the number of levels the operation succeeded on.
+    pub fn succeeded(&self) -> usize {
+        self.items.iter().filter(|item| item.outcome.is_success()).count()
+    }
+
+    /// Returns the items the operation failed or panicked on.
+    pub fn failures(&self) -> impl Iterator<Item = &BatchItem<T>> {
+        self.items.iter().filter(|item| !item.outcome.is_success())
+    }
+}
+
+impl<T> std::fmt::Display for BatchReport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let failed = self.items.iter().filter(|item| matches!(item.outcome, Outcome::Failed(_))).count();
+        let panicked = self.items.iter().filter(|item| matches!(item.outcome, Outcome::Panicked(_))).count();
+        write!(f, "Processed {} levels in {:.1?}: {} succeeded, {failed} failed", self.items.len(), self.elapsed, self.succeeded())?;
+        if panicked > 0 {
+            write!(f, ", {panicked} panicked")?;
+        }
+        write!(f, ".")
+    }
+}
+
+/// Runs `op` on each of `worlds`, e.g. to repack, validate, or render every level in a
+/// directory, on up to `parallelism` threads at once (or one per CPU if it's 0).
+///
+/// Each level is isolated from the others: an error or panic is recorded in the report and
+/// the rest of the levels are still processed. `op` is given each path as-is, so it can be a
+/// level directory, a .knytt.bin, or anything else the operation expects.
+pub fn process<I, P, F, T>(worlds: I, op: F, parallelism: usize) -> BatchReport<T>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<PathBuf>,
+    F: Fn(&Path) -> Result<T> + Sync,
+    T: Send,
+{
+    let start = Instant::now();
+    let paths: Vec<PathBuf> = worlds.into_iter().map(Into::into).collect();
+    let parallelism = match parallelism {
+        0 => thread::available_parallelism().map_or(1, usize::from),
+        n => n,
+    }.min(paths.len()).max(1);
+
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(paths.len()));
+    thread::scope(|scope| {
+        for _ in 0..parallelism {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(i) else { break };
+
+                let item_start = Instant::now();
+                let outcome = match panic::catch_unwind(AssertUnwindSafe(|| op(path))) {
+                    Ok(Ok(value)) => Outcome::Succeeded(value),
+                    Ok(Err(err)) => Outcome::Failed(err),
+                    Err(payload) => Outcome::Panicked(panic_message(payload)),
+                };
+
+                outcomes.lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .push((i, outcome, item_start.elapsed()));
+            });
+        }
+    });
+
+    let mut outcomes = outcomes.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+    outcomes.sort_by_key(|(i, ..)| *i);
+    let items = paths.into_iter()
+        .zip(outcomes)
+        .map(|(path, (_, outcome, elapsed))| BatchItem { path, outcome, elapsed })
+        .collect();
+
+    BatchReport {
+        items,
+        elapsed: start.elapsed(),
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> Option<String> {
+    payload.downcast::<String>()
+        .map(|message| *message)
+        .or_else(|payload| payload.downcast::<&str>().map(|message| message.to_string()))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapBinError;
+
+    #[test]
+    fn failures_are_isolated() {
+        let worlds = ["A", "B", "C", "D"];
+        let report = process(worlds, |path| match path.to_str() {
+            Some("B") => Err(MapBinError::BadScreenPosition.into()),
+            Some("C") => panic!("oops"),
+            _ => Ok(path.to_owned()),
+        }, 2);
+
+        assert_eq!(report.items.len(), 4);
+        assert_eq!(report.succeeded(), 2);
+        assert!(matches!(&report.items[3].outcome, Outcome::Succeeded(path) if path == Path::new("D")));
+        assert!(matches!(report.items[1].outcome, Outcome::Failed(_)));
+        assert!(matches!(&report.items[2].outcome, Outcome::Panicked(Some(message)) if message == "oops"));
+        assert!(report.to_string().ends_with("2 succeeded, 1 failed, 1 panicked."));
+    }
+}
@@ -1,10 +1,22 @@
-pub fn parse_xy(s: &str) -> Option<(i64, i64)> {
-    let (x, y) =
-        s.strip_prefix('x')?
-        .split_once('y')?;
-    
-    let Ok(x) = str::parse::<i64>(x) else { return None };
-    let Ok(y) = str::parse::<i64>(y) else { return None };
+pub use libks_ini::parse_xy;
 
-    Some((x, y))
+/// Parses a boolean the way KS does: `True`/`False` (any case) or `1`/`0`.
+pub fn parse_bool(s: &str) -> Option<bool> {
+    if s.eq_ignore_ascii_case("true") || s == "1" {
+        Some(true)
+    }
+    else if s.eq_ignore_ascii_case("false") || s == "0" {
+        Some(false)
+    }
+    else {
+        None
+    }
+}
+
+/// Splits a slotted key like `ShiftXMap(A)` into its name and slot, e.g. `("ShiftXMap", "A")`.
+pub fn split_slot(key: &str) -> Option<(&str, &str)> {
+    let (name, slot) = key.strip_suffix(')')?
+        .split_once('(')?;
+
+    Some((name, slot))
 }
@@ -1,9 +1,14 @@
 #![allow(dead_code)]
 
+#[cfg(feature = "std")]
+pub mod objects;
+
 pub const SCREEN_WIDTH: usize = 25;
 pub const SCREEN_HEIGHT: usize = 10;
 pub const TILES_PER_LAYER: usize = SCREEN_WIDTH * SCREEN_HEIGHT;
 pub const LAYER_COUNT: usize = 8;
+/// The only tile layer the player collides with. Layers 0-2 are purely decorative.
+pub const COLLISION_LAYER: usize = 3;
 
 /// 1 kibibyte (2^10 bytes)
 pub(crate) const KB: usize = 1024;
@@ -0,0 +1,230 @@
+use crate::{editions::KsEdition, map_bin::Tile};
+
+/// The bank holding system objects (save points, powers, shifts, etc.).
+pub const SYSTEM_BANK: u8 = 0;
+/// The bank holding KS ACO objects.
+pub const ACO_BANK: u8 = 253;
+/// The bank holding KS Advanced objects, or KS Plus "B" custom objects.
+pub const ADVANCED_BANK: u8 = 254;
+/// The bank holding custom objects. Object `n` is defined by the `[Custom Object n]` section.
+pub const CUSTOM_OBJECT_BANK: u8 = 255;
+
+pub const SAVE_POINT: Tile = Tile(SYSTEM_BANK, 1);
+/// KS Plus map power.
+pub const MAP_POWER: Tile = Tile(SYSTEM_BANK, 33);
+/// KS Plus coin.
+pub const COIN: Tile = Tile(SYSTEM_BANK, 34);
+
+/// The names of powers by index, matching the `PowerN` keys of save files.
+pub const POWER_NAMES: [&str; 13] = [
+    "Run",
+    "Climb",
+    "Double Jump",
+    "High Jump",
+    "Eye",
+    "Enemy Detector",
+    "Umbrella",
+    "Hologram",
+    "Red Key",
+    "Yellow Key",
+    "Blue Key",
+    "Purple Key",
+    "Map",
+];
+
+/// The names of KS Plus artifacts, from artifact 1 to artifact 7.
+pub const ARTIFACT_NAMES: [&str; 7] = [
+    "Artifact 1",
+    "Artifact 2",
+    "Artifact 3",
+    "Artifact 4",
+    "Artifact 5",
+    "Artifact 6",
+    "Artifact 7",
+];
+
+/// What role an object plays in a level.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectCategory {
+    /// A save point.
+    Save,
+    /// A power pickup.
+    Power,
+    /// A KS Plus coin or artifact.
+    Collectible,
+    /// A shift, which moves the player to another location.
+    Warp,
+    /// A sign.
+    Sign,
+    /// Any other system object.
+    System,
+    /// A built-in creature or trap. Not every one is harmful.
+    Enemy,
+    /// A custom object.
+    Custom,
+    /// An object added by a mod that isn't otherwise classified.
+    Extension,
+}
+
+/// Information about an object.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectInfo {
+    pub tile: Tile,
+    /// The object's name, if it's known.
+    pub name: Option<&'static str>,
+    pub category: ObjectCategory,
+    /// The edition that introduced the object.
+    pub edition: KsEdition,
+}
+
+/// Looks up information about the object `tile`. Returns `None` for the empty object (index 0).
+///
+/// The system bank layout is presumed from the level editor: the save point, then the powers in
+/// save file order, then shifts A-C and signs A-C. Banks 1-19 hold creatures and traps, which
+/// aren't named individually.
+pub fn lookup(tile: Tile) -> Option<ObjectInfo> {
+    use ObjectCategory::*;
+
+    if tile.1 == 0 {
+        return None;
+    }
+
+    let (name, category) =
+        if let Some(power) = power_index(tile) {
+            (Some(POWER_NAMES[power]), Power)
+        }
+        else if let Some(artifact) = artifact_index(tile) {
+            (Some(ARTIFACT_NAMES[artifact - 1]), Collectible)
+        }
+        else {
+            match tile {
+                SAVE_POINT => (Some("Save Point"), Save),
+                COIN => (Some("Coin"), Collectible),
+                Tile(SYSTEM_BANK, 14) => (Some("Shift A"), Warp),
+                Tile(SYSTEM_BANK, 15) => (Some("Shift B"), Warp),
+                Tile(SYSTEM_BANK, 16) => (Some("Shift C"), Warp),
+                Tile(SYSTEM_BANK, 17) => (Some("Sign A"), Sign),
+                Tile(SYSTEM_BANK, 18) => (Some("Sign B"), Sign),
+                Tile(SYSTEM_BANK, 19) => (Some("Sign C"), Sign),
+                Tile(SYSTEM_BANK, _) => (None, System),
+                Tile(1..=19, _) => (None, Enemy),
+                Tile(CUSTOM_OBJECT_BANK, _) => (None, Custom),
+                _ => (None, Extension),
+            }
+        };
+
+    Some(ObjectInfo {
+        tile,
+        name,
+        category,
+        edition: introduced_in(tile),
+    })
+}
+
+/// Returns the category of the object `tile`, or `None` for the empty object.
+pub fn category(tile: Tile) -> Option<ObjectCategory> {
+    lookup(tile).map(|info| info.category)
+}
+
+/// Returns the edition that introduced the object `tile`.
+///
+/// Bank 254 is ambiguous: KS Plus uses it for `[Custom Object B#]` objects and KS Ex uses it
+/// with `[Templates]`, so World.ini has to be consulted to tell them apart from KS Advanced
+/// objects. This function assumes KS Advanced.
+pub fn introduced_in(tile: Tile) -> KsEdition {
+    match tile {
+        Tile(0, 32) => KsEdition::Extended,
+        Tile(0, 33..=49)
+        | Tile(0, 247..=255)
+        | Tile(1, 25..=27)
+        | Tile(6, 14..=17)
+        | Tile(7, 17)
+        | Tile(15, 31..=38)
+        | Tile(16, 17..=30)
+        | Tile(19, 1..=199) => KsEdition::Plus,
+        Tile(ADVANCED_BANK, 1..=22) => KsEdition::Advanced,
+        Tile(ACO_BANK, 1..=6) => KsEdition::AdvancedCustomObjects,
+        _ => KsEdition::Vanilla,
+    }
+}
+
+/// Returns the index of the power granted by the object `tile`, if it's a power pickup.
+///
+/// Vanilla powers occupy indices 2-13 of the system bank in save file order. The KS Plus map
+/// power is separate.
+pub fn power_index(tile: Tile) -> Option<usize> {
+    match tile {
+        Tile(SYSTEM_BANK, index @ 2..=13) => Some(usize::from(index - 2)),
+        MAP_POWER => Some(12),
+        _ => None,
+    }
+}
+
+/// Returns the number (1-7) of the KS Plus artifact `tile`, if it's an artifact.
+pub fn artifact_index(tile: Tile) -> Option<usize> {
+    match tile {
+        Tile(SYSTEM_BANK, index @ 35..=41) => Some(usize::from(index - 34)),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the object `tile` is a built-in creature or trap.
+pub fn is_enemy(tile: Tile) -> bool {
+    category(tile) == Some(ObjectCategory::Enemy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn objects_are_classified() {
+        assert_eq!(lookup(Tile(3, 0)), None);
+        assert_eq!(lookup(SAVE_POINT), Some(ObjectInfo {
+            tile: SAVE_POINT,
+            name: Some("Save Point"),
+            category: ObjectCategory::Save,
+            edition: KsEdition::Vanilla,
+        }));
+        assert_eq!(lookup(MAP_POWER), Some(ObjectInfo {
+            tile: MAP_POWER,
+            name: Some("Map"),
+            category: ObjectCategory::Power,
+            edition: KsEdition::Plus,
+        }));
+
+        let named = |tile| lookup(tile).and_then(|info| info.name);
+        assert_eq!(named(Tile(SYSTEM_BANK, 2)), Some("Run"));
+        assert_eq!(named(Tile(SYSTEM_BANK, 13)), Some("Purple Key"));
+        assert_eq!(named(Tile(SYSTEM_BANK, 35)), Some("Artifact 1"));
+        assert_eq!(named(Tile(SYSTEM_BANK, 41)), Some("Artifact 7"));
+        assert_eq!(named(Tile(SYSTEM_BANK, 16)), Some("Shift C"));
+        assert_eq!(named(Tile(SYSTEM_BANK, 17)), Some("Sign A"));
+
+        assert_eq!(category(Tile(SYSTEM_BANK, 20)), Some(ObjectCategory::System));
+        assert_eq!(category(COIN), Some(ObjectCategory::Collectible));
+        assert_eq!(category(Tile(CUSTOM_OBJECT_BANK, 5)), Some(ObjectCategory::Custom));
+        assert_eq!(category(Tile(ACO_BANK, 1)), Some(ObjectCategory::Extension));
+        assert!(is_enemy(Tile(1, 1)));
+        assert!(is_enemy(Tile(19, 199)));
+        assert!(!is_enemy(Tile(20, 1)));
+    }
+
+    #[test]
+    fn editions_and_indices() {
+        assert_eq!(introduced_in(Tile(0, 32)), KsEdition::Extended);
+        assert_eq!(introduced_in(Tile(16, 17)), KsEdition::Plus);
+        assert_eq!(introduced_in(Tile(16, 16)), KsEdition::Vanilla);
+        assert_eq!(introduced_in(Tile(ADVANCED_BANK, 22)), KsEdition::Advanced);
+        assert_eq!(introduced_in(Tile(ACO_BANK, 6)), KsEdition::AdvancedCustomObjects);
+        assert_eq!(introduced_in(Tile(ACO_BANK, 7)), KsEdition::Vanilla);
+
+        assert_eq!(power_index(Tile(SYSTEM_BANK, 1)), None);
+        assert_eq!(power_index(Tile(SYSTEM_BANK, 2)), Some(0));
+        assert_eq!(power_index(MAP_POWER), Some(12));
+        assert_eq!(artifact_index(COIN), None);
+        assert_eq!(artifact_index(Tile(SYSTEM_BANK, 41)), Some(7));
+    }
+}
@@ -1,8 +1,18 @@
-use std::collections::{HashMap, hash_map::Entry};
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 use image::{io::Reader as ImageReader, DynamicImage};
 
-use crate::{Result, map_bin::{AssetId, Tile, AssetIds}, assets::AssetSource};
+use crate::{
+    Result,
+    map_bin::{fnv1a, AssetId, Tile, AssetIds, FNV_OFFSET_BASIS},
+    assets::AssetSource,
+    perf,
+};
 use super::DrawError;
 
 pub struct AssetCache {
@@ -10,6 +20,46 @@ pub struct AssetCache {
     tilesets: HashMap<AssetId, Option<DynamicImage>>,
     gradients: HashMap<AssetId, Option<DynamicImage>>,
     objects: HashMap<Tile, Option<DynamicImage>>,
+    /// Custom object images by file name. See [`AssetCache::ensure_custom_object_loaded`].
+    custom_objects: HashMap<String, Option<DynamicImage>>,
+    /// Fingerprints of the loaded tilesets, gradients, and custom object images. See
+    /// [`AssetCache::fingerprint`].
+    fingerprints: HashMap<AssetKey, u64>,
+    /// Where the tilesets, gradients, and custom object images were loaded from. See
+    /// [`AssetCache::invalidate_changed`].
+    sources: HashMap<AssetKey, LoadedFrom>,
+}
+
+/// The file an asset was loaded from, or `None` if it wasn't found, when it was modified, and
+/// a hash of its contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LoadedFrom {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    hash: Option<u64>,
+}
+
+impl LoadedFrom {
+    fn new(path: Option<PathBuf>) -> LoadedFrom {
+        let modified = path.as_deref().and_then(modified_time);
+        let hash = path.as_deref().and_then(file_hash);
+        LoadedFrom { path, modified, hash }
+    }
+
+    /// Returns `true` if `path` is a different file than the one loaded, or the file has been
+    /// modified. The contents are only hashed if the modification time is the same.
+    fn has_changed(&self, path: Option<PathBuf>) -> bool {
+        path != self.path
+            || path.as_deref().and_then(modified_time) != self.modified
+            || path.as_deref().and_then(file_hash) != self.hash
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum AssetKey {
+    Tileset(AssetId),
+    Gradient(AssetId),
+    CustomObject(String),
 }
 
 impl AssetCache {
@@ -19,6 +69,9 @@ impl AssetCache {
             tilesets: HashMap::new(),
             gradients: HashMap::new(),
             objects: HashMap::new(),
+            custom_objects: HashMap::new(),
+            fingerprints: HashMap::new(),
+            sources: HashMap::new(),
         }
     }
 
@@ -40,6 +93,67 @@ impl AssetCache {
             .as_ref()
     }
 
+    pub fn get_custom_object(&self, image: &str) -> Option<&DynamicImage> {
+        self.custom_objects.get(image)
+            .unwrap_or(&None)
+            .as_ref()
+    }
+
+    /// Adds an already decoded tileset, replacing any that was loaded for `id`. This is useful
+    /// when the assets aren't on the filesystem, e.g. in a browser.
+    pub fn insert_tileset(&mut self, id: AssetId, image: DynamicImage) {
+        self.fingerprints.insert(AssetKey::Tileset(id), image_fingerprint(&image));
+        self.sources.remove(&AssetKey::Tileset(id));
+        self.tilesets.insert(id, Some(image));
+    }
+
+    /// Adds an already decoded gradient, replacing any that was loaded for `id`. See
+    /// [`insert_tileset`](Self::insert_tileset).
+    pub fn insert_gradient(&mut self, id: AssetId, image: DynamicImage) {
+        self.fingerprints.insert(AssetKey::Gradient(id), image_fingerprint(&image));
+        self.sources.remove(&AssetKey::Gradient(id));
+        self.gradients.insert(id, Some(image));
+    }
+
+    /// Returns a hash of the tilesets and gradient `assets` refers to, as currently loaded.
+    /// It changes when one of them is replaced with a different image, e.g. by
+    /// [`insert_tileset`](Self::insert_tileset). Assets that aren't loaded count as missing.
+    pub fn fingerprint(&self, assets: AssetIds) -> u64 {
+        self.combined_fingerprint(&[
+            AssetKey::Tileset(assets.tileset_a),
+            AssetKey::Tileset(assets.tileset_b),
+            AssetKey::Gradient(assets.gradient),
+        ])
+    }
+
+    /// Returns a hash of the custom object `images`, as currently loaded. See
+    /// [`fingerprint`](Self::fingerprint).
+    pub fn custom_object_fingerprint<'a, I>(&self, images: I) -> u64
+    where
+        I: IntoIterator<Item = &'a str>
+    {
+        let keys: Vec<_> = images.into_iter()
+            .map(|image| AssetKey::CustomObject(image.to_owned()))
+            .collect();
+        self.combined_fingerprint(&keys)
+    }
+
+    fn combined_fingerprint(&self, keys: &[AssetKey]) -> u64 {
+        keys.iter().fold(FNV_OFFSET_BASIS, |hash, key| {
+            let fingerprint = self.fingerprints.get(key).copied().unwrap_or(0);
+            fnv1a(hash, &fingerprint.to_le_bytes())
+        })
+    }
+
+    /// Adds an already decoded custom object image named `image`, replacing any that was
+    /// loaded. See [`insert_tileset`](Self::insert_tileset).
+    pub fn insert_custom_object(&mut self, image: &str, img: DynamicImage) {
+        let key = AssetKey::CustomObject(image.to_owned());
+        self.fingerprints.insert(key.clone(), image_fingerprint(&img));
+        self.sources.remove(&key);
+        self.custom_objects.insert(image.to_owned(), Some(img));
+    }
+
     pub fn ensure_assets_loaded(&mut self, assets: AssetIds) -> Result<()> {
         self.ensure_tileset_loaded(assets.tileset_a)?;
         self.ensure_tileset_loaded(assets.tileset_b)?;
@@ -49,40 +163,207 @@ impl AssetCache {
     }
 
     pub fn ensure_tileset_loaded(&mut self, id: AssetId) -> Result<()> {
-        if let Entry::Vacant(entry) = self.tilesets.entry(id) {
-            let Some(path) = self.source.tileset_path(id) else {
-                entry.insert(None);
-                return Ok(());
-            };
-
-            match ImageReader::open(&path)?.decode() {
-                Ok(img) => entry.insert(Some(img)),
-                Err(source) => return Err(DrawError::Image {
-                    source,
-                    path,
-                }.into()),
-            };
-        }
+        let Entry::Vacant(entry) = self.tilesets.entry(id) else {
+            perf::count(|c| c.cache_hits += 1);
+            return Ok(());
+        };
+        perf::count(|c| c.cache_misses += 1);
+
+        let Some(path) = self.source.tileset_path(id) else {
+            self.sources.insert(AssetKey::Tileset(id), LoadedFrom::new(None));
+            entry.insert(None);
+            return Ok(());
+        };
+
+        match ImageReader::open(&path)?.decode() {
+            Ok(img) => {
+                self.fingerprints.insert(AssetKey::Tileset(id), image_fingerprint(&img));
+                self.sources.insert(AssetKey::Tileset(id), LoadedFrom::new(Some(path)));
+                entry.insert(Some(img))
+            },
+            Err(source) => return Err(DrawError::Image {
+                source,
+                path,
+            }.into()),
+        };
 
         Ok(())
     }
 
     pub fn ensure_gradient_loaded(&mut self, id: AssetId) -> Result<()> {
-        if let Entry::Vacant(entry) = self.gradients.entry(id) {
-            let Some(path) = self.source.gradient_path(id) else {
-                entry.insert(None);
-                return Ok(());
-            };
-
-            match ImageReader::open(&path)?.decode() {
-                Ok(img) => entry.insert(Some(img)),
-                Err(source) => return Err(DrawError::Image {
-                    source,
-                    path,
-                }.into()),
-            };
+        let Entry::Vacant(entry) = self.gradients.entry(id) else {
+            perf::count(|c| c.cache_hits += 1);
+            return Ok(());
+        };
+        perf::count(|c| c.cache_misses += 1);
+
+        let Some(path) = self.source.gradient_path(id) else {
+            self.sources.insert(AssetKey::Gradient(id), LoadedFrom::new(None));
+            entry.insert(None);
+            return Ok(());
+        };
+
+        match ImageReader::open(&path)?.decode() {
+            Ok(img) => {
+                self.fingerprints.insert(AssetKey::Gradient(id), image_fingerprint(&img));
+                self.sources.insert(AssetKey::Gradient(id), LoadedFrom::new(Some(path)));
+                entry.insert(Some(img))
+            },
+            Err(source) => return Err(DrawError::Image {
+                source,
+                path,
+            }.into()),
+        };
+
+        Ok(())
+    }
+
+    /// Loads the custom object image `image`, the value of an `Image` key, from the world's
+    /// `Custom Objects` folder. A missing image isn't an error.
+    pub fn ensure_custom_object_loaded(&mut self, image: &str) -> Result<()> {
+        if self.custom_objects.contains_key(image) {
+            perf::count(|c| c.cache_hits += 1);
+            return Ok(());
         }
+        perf::count(|c| c.cache_misses += 1);
+
+        let key = AssetKey::CustomObject(image.to_owned());
+        let Some(path) = self.custom_object_path(image) else {
+            self.sources.insert(key, LoadedFrom::new(None));
+            self.custom_objects.insert(image.to_owned(), None);
+            return Ok(());
+        };
+
+        match ImageReader::open(&path)?.decode() {
+            Ok(img) => {
+                self.fingerprints.insert(key.clone(), image_fingerprint(&img));
+                self.sources.insert(key, LoadedFrom::new(Some(path)));
+                self.custom_objects.insert(image.to_owned(), Some(img))
+            },
+            Err(source) => return Err(DrawError::Image {
+                source,
+                path,
+            }.into()),
+        };
 
         Ok(())
     }
+
+    /// Forgets the tilesets, gradients, and custom object images whose files have changed since
+    /// they were loaded, so the next [`ensure_assets_loaded`](Self::ensure_assets_loaded) or
+    /// [`ensure_custom_object_loaded`](Self::ensure_custom_object_loaded) loads them again.
+    /// Returns the number forgotten.
+    ///
+    /// A file has changed if its modification time or contents are different, or if the asset
+    /// now resolves to a different file, e.g. because a world's own tileset was added or
+    /// removed. Comparing contents catches edits within the same second and copies that keep
+    /// the old modification time. This lets
+    /// a long-running editor pick up edits made in another program. Images added with
+    /// [`insert_tileset`](Self::insert_tileset) or
+    /// [`insert_gradient`](Self::insert_gradient) or
+    /// [`insert_custom_object`](Self::insert_custom_object) are never forgotten.
+    ///
+    /// An image that loads the same as before keeps its [`fingerprint`](Self::fingerprint), so
+    /// a [`RenderCache`](super::RenderCache) won't render its screens again.
+    pub fn invalidate_changed(&mut self) -> usize {
+        let changed: Vec<_> = self.sources.iter()
+            .filter(|&(key, loaded)| loaded.has_changed(self.asset_path(key)))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &changed {
+            match key {
+                AssetKey::Tileset(id) => { self.tilesets.remove(id); },
+                AssetKey::Gradient(id) => { self.gradients.remove(id); },
+                AssetKey::CustomObject(image) => { self.custom_objects.remove(image); },
+            }
+            self.fingerprints.remove(key);
+            self.sources.remove(key);
+        }
+
+        changed.len()
+    }
+
+    fn asset_path(&self, key: &AssetKey) -> Option<PathBuf> {
+        match key {
+            AssetKey::Tileset(id) => self.source.tileset_path(*id),
+            AssetKey::Gradient(id) => self.source.gradient_path(*id),
+            AssetKey::CustomObject(image) => self.custom_object_path(image),
+        }
+    }
+
+    fn custom_object_path(&self, image: &str) -> Option<PathBuf> {
+        let path = self.source.world_folder.join("Custom Objects").join(image);
+        path.is_file().then_some(path)
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn file_hash(path: &Path) -> Option<u64> {
+    fs::read(path).ok().map(|bytes| fnv1a(FNV_OFFSET_BASIS, &bytes))
+}
+
+/// Hashes the dimensions and pixels of `image`.
+fn image_fingerprint(image: &DynamicImage) -> u64 {
+    let hash = fnv1a(FNV_OFFSET_BASIS, &image.width().to_le_bytes());
+    let hash = fnv1a(hash, &image.height().to_le_bytes());
+    fnv1a(hash, image.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use image::{Rgba, RgbaImage};
+
+    use super::*;
+
+    #[test]
+    fn changed_files_are_reloaded() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let world = temp_dir.path();
+        fs::create_dir_all(world.join("Tilesets")).unwrap();
+        fs::create_dir_all(world.join("Custom Objects")).unwrap();
+        let path = world.join("Tilesets/Tileset1.png");
+        RgbaImage::from_pixel(384, 192, Rgba([255, 0, 0, 255])).save(&path).unwrap();
+        let sprite = world.join("Custom Objects/Sprite.png");
+        RgbaImage::from_pixel(24, 24, Rgba([255, 0, 0, 255])).save(&sprite).unwrap();
+
+        let mut assets = AssetCache::new(AssetSource {
+            data_folder: world.join("Data"),
+            world_folder: world.to_path_buf(),
+        });
+        let ids = AssetIds { tileset_a: 1, tileset_b: 2, ambiance_a: 0, ambiance_b: 0, music: 0, gradient: 1 };
+        assets.ensure_assets_loaded(ids).unwrap();
+        assets.ensure_custom_object_loaded("Sprite.png").unwrap();
+        let fingerprint = assets.fingerprint(ids);
+        let sprite_fingerprint = assets.custom_object_fingerprint(["Sprite.png"]);
+        assert_eq!(assets.invalidate_changed(), 0);
+
+        // Edit one file and add another that was missing
+        RgbaImage::from_pixel(384, 192, Rgba([0, 0, 255, 255])).save(&path).unwrap();
+        let later = SystemTime::now() + Duration::from_secs(10);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        RgbaImage::from_pixel(384, 192, Rgba([0, 255, 0, 255])).save(world.join("Tilesets/Tileset2.png")).unwrap();
+        RgbaImage::from_pixel(24, 24, Rgba([0, 0, 255, 255])).save(&sprite).unwrap();
+        fs::File::options().write(true).open(&sprite).unwrap().set_modified(later).unwrap();
+        assert_eq!(assets.invalidate_changed(), 3);
+        assert!(assets.get_custom_object("Sprite.png").is_none());
+
+        assets.ensure_assets_loaded(ids).unwrap();
+        assert_eq!(assets.get_tileset(1).unwrap().to_rgba8().get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+        assert!(assets.get_tileset(2).is_some());
+        assert_ne!(assets.fingerprint(ids), fingerprint);
+        assets.ensure_custom_object_loaded("Sprite.png").unwrap();
+        assert_ne!(assets.custom_object_fingerprint(["Sprite.png"]), sprite_fingerprint);
+
+        // Edit a file without changing its modification time
+        RgbaImage::from_pixel(384, 192, Rgba([255, 255, 255, 255])).save(&path).unwrap();
+        fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(assets.invalidate_changed(), 1);
+        assert!(assets.get_tileset(1).is_none());
+    }
 }
@@ -6,5 +6,23 @@ pub enum DrawError {
     Image {
         source: image::ImageError,
         path: PathBuf,
-    }
+    },
+    #[error("The image at {path:?} is {}x{}, but it should be {}x{}.", actual.0, actual.1, expected.0, expected.1)]
+    WrongDimensions {
+        path: PathBuf,
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    #[error("Failed to encode an image.")]
+    Encode(#[source] image::ImageError),
+    #[error("A {width}x{height} image is too large to draw.")]
+    TooLarge {
+        width: u128,
+        height: u128,
+    },
+    #[error("`{name}` can't be {value}.")]
+    InvalidOption {
+        name: &'static str,
+        value: u32,
+    },
 }
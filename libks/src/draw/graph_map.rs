@@ -0,0 +1,182 @@
+use image::{imageops::{self, FilterType}, Rgba, RgbaImage};
+
+use crate::{
+    Result,
+    analysis::{level_graph, ConnectionKind},
+    constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
+    world::World,
+};
+use super::{draw_screen, grid_dimensions, route::draw_line, AssetCache, DrawError};
+
+/// Configures [`draw_map_with_graph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphMapOptions {
+    /// How many times smaller than 600x240 each screen is drawn, from 1 to 240. Defaults to 4,
+    /// for 150x60.
+    pub downscale: u32,
+    /// The color of warps. Defaults to cyan.
+    pub warp_color: Rgba<u8>,
+    /// The color of shifts. Defaults to yellow.
+    pub shift_color: Rgba<u8>,
+    /// The color of flag-gated connections, which only apply when a flag condition holds.
+    /// Defaults to magenta.
+    pub flag_color: Rgba<u8>,
+}
+
+impl Default for GraphMapOptions {
+    fn default() -> Self {
+        Self {
+            downscale: 4,
+            warp_color: Rgba([0, 255, 255, 255]),
+            shift_color: Rgba([255, 255, 0, 255]),
+            flag_color: Rgba([255, 0, 255, 255]),
+        }
+    }
+}
+
+/// Draws the screens of `world` side by side, with arrows for the connections of its
+/// [`level_graph`] other than walks into adjacent screens.
+///
+/// The screen at the smallest position is in the top left corner, and positions without a
+/// screen are transparent. Arrows start at the center of the screen they leave and end at the
+/// destination tile if it's known, or else at the center of the destination screen.
+/// Flag-gated connections are drawn last, so they stand out where they overlap other arrows.
+///
+/// Returns [`DrawError::InvalidOption`] if `options.downscale` is outside 1 to 240, or
+/// [`DrawError::TooLarge`] if the screens are too far apart to fit in one image.
+pub fn draw_map_with_graph(world: &World, assets: &mut AssetCache, options: &GraphMapOptions) -> Result<RgbaImage> {
+    if !(1..=240).contains(&options.downscale) {
+        return Err(DrawError::InvalidOption { name: "downscale", value: options.downscale }.into());
+    }
+
+    let mut positions = world.screens.iter().map(|screen| screen.position);
+    let Some(first) = positions.next() else {
+        return Ok(RgbaImage::new(0, 0));
+    };
+    let (min, max) = positions.fold((first, first), |(min, max), (x, y)| {
+        ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+    });
+
+    let (cell_width, cell_height) = (600 / options.downscale, 240 / options.downscale);
+    let (width, height) = grid_dimensions(min, max, (cell_width, cell_height))?;
+    let mut img = RgbaImage::new(width, height);
+
+    for screen in &world.screens {
+        let rendered = draw_screen(screen, assets)?;
+        let rendered = imageops::resize(&rendered, cell_width, cell_height, FilterType::Triangle);
+        let left = (screen.position.0 - min.0) * i64::from(cell_width);
+        let top = (screen.position.1 - min.1) * i64::from(cell_height);
+        imageops::overlay(&mut img, &rendered, left, top);
+    }
+
+    // Pixel coordinates of a tile's center, or the screen's center if the tile isn't known
+    let pixel = |screen: (i64, i64), tile: Option<(i64, i64)>| {
+        let (tile_x, tile_y) = tile.map_or(
+            (SCREEN_WIDTH as f64 / 2.0, SCREEN_HEIGHT as f64 / 2.0),
+            |(x, y)| (x as f64 + 0.5, y as f64 + 0.5),
+        );
+        (
+            ((screen.0 - min.0) as f64 + tile_x / SCREEN_WIDTH as f64) * f64::from(cell_width),
+            ((screen.1 - min.1) as f64 + tile_y / SCREEN_HEIGHT as f64) * f64::from(cell_height),
+        )
+    };
+
+    let mut connections = level_graph(world);
+    connections.sort_by_key(|connection| connection.kind.is_flag_gated());
+    for connection in connections {
+        let color = match connection.kind {
+            ConnectionKind::Warp(_) => options.warp_color,
+            ConnectionKind::Shift(_) => options.shift_color,
+            ConnectionKind::FlagWarp(_) => options.flag_color,
+            ConnectionKind::Walk(_) => continue,
+        };
+        draw_arrow(&mut img, pixel(connection.from, None), pixel(connection.to, connection.position), color);
+    }
+
+    Ok(img)
+}
+
+/// Draws a line from `from` to `to` with an arrowhead at `to`. Nothing is drawn if the points
+/// are the same.
+fn draw_arrow(img: &mut RgbaImage, from: (f64, f64), to: (f64, f64), color: Rgba<u8>) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let len = dx.hypot(dy);
+    if len < 1.0 {
+        return;
+    }
+
+    draw_line(img, from, to, color);
+
+    let head = (len / 3.0).min(8.0);
+    let (ux, uy) = (dx / len, dy / len);
+    for side in [-1.0, 1.0] {
+        let wing = (
+            to.0 - head * ux + side * head * 0.5 * uy,
+            to.1 - head * uy - side * head * 0.5 * ux,
+        );
+        draw_line(img, wing, to, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::{assets::AssetSource, map_bin::{parse_screen_bytes, SCREEN_DATA_LEN}};
+
+    #[test]
+    fn arrows_connect_screens() {
+        let world = World {
+            dir: Default::default(),
+            ini: Ini::new("[x1000y1000]\nWarpRightX=1\nFlagWarpX(A)=1\nFlag(A)=3\n[x1001y1001]\nShiftAbsolute(A)=True\nShiftXMap(A)=1000\nShiftYMap(A)=1001\nShiftX(A)=0\nShiftY(A)=0\n"),
+            screens: [(1000, 1000), (1001, 1000), (1000, 1001), (1001, 1001)].into_iter()
+                .map(|position| parse_screen_bytes(&[0; SCREEN_DATA_LEN], position))
+                .collect(),
+        };
+        let mut assets = AssetCache::new(AssetSource {
+            data_folder: Default::default(),
+            world_folder: Default::default(),
+        });
+
+        let options = GraphMapOptions::default();
+        let img = draw_map_with_graph(&world, &mut assets, &options).unwrap();
+        assert_eq!(img.dimensions(), (300, 120));
+
+        // The warp and flag warp overlap, and the flag warp is drawn on top
+        assert_eq!(img.get_pixel(150, 30), &options.flag_color);
+        // The shift ends at the top left tile of the screen below the first
+        assert_eq!(img.get_pixel(3, 63), &options.shift_color);
+        assert_eq!(img.get_pixel(150, 10), &Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn bad_sizes_are_errors() {
+        let mut world = World {
+            dir: Default::default(),
+            ini: Ini::new(""),
+            screens: vec![parse_screen_bytes(&[0; SCREEN_DATA_LEN], (0, 0))],
+        };
+        let mut assets = AssetCache::new(AssetSource {
+            data_folder: Default::default(),
+            world_folder: Default::default(),
+        });
+
+        for downscale in [0, 241] {
+            let options = GraphMapOptions { downscale, ..Default::default() };
+            assert!(matches!(
+                draw_map_with_graph(&world, &mut assets, &options),
+                Err(crate::KsError::Draw(DrawError::InvalidOption { name: "downscale", .. })),
+            ));
+        }
+
+        let options = GraphMapOptions { downscale: 240, ..Default::default() };
+        assert_eq!(draw_map_with_graph(&world, &mut assets, &options).unwrap().dimensions(), (2, 1));
+
+        world.screens.push(parse_screen_bytes(&[0; SCREEN_DATA_LEN], (i64::MAX, i64::MIN)));
+        assert!(matches!(
+            draw_map_with_graph(&world, &mut assets, &options),
+            Err(crate::KsError::Draw(DrawError::TooLarge { .. })),
+        ));
+    }
+}
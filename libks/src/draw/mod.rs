@@ -1,13 +1,46 @@
-use image::{RgbaImage, imageops};
+use image::{GenericImageView, Rgba, RgbaImage, imageops::{self, FilterType}};
 
 use crate::{Result, map_bin::ScreenData, constants};
 
 mod error;
 pub use error::DrawError;
 
+mod animation;
+pub use animation::{encode_gif, render_animation, ObjectAnimation};
+
 mod cache;
 pub use cache::AssetCache;
 
+mod graph_map;
+pub use graph_map::{draw_map_with_graph, GraphMapOptions};
+
+mod render_cache;
+pub use render_cache::RenderCache;
+
+mod route;
+pub use route::draw_route;
+
+mod track_map;
+pub use track_map::{draw_track_map, track_color};
+
+/// The largest image, in pixels, that the map renderers will allocate. At 4 bytes per pixel,
+/// this is 1 GiB.
+pub const MAX_IMAGE_PIXELS: u128 = 1 << 28;
+
+/// Returns the size of an image with a `cell`-sized rectangle for every screen position from
+/// `min` to `max`, or [`DrawError::TooLarge`] if it would exceed [`MAX_IMAGE_PIXELS`].
+pub(crate) fn grid_dimensions(min: (i64, i64), max: (i64, i64), cell: (u32, u32)) -> Result<(u32, u32)> {
+    let columns = (i128::from(max.0) - i128::from(min.0) + 1) as u128;
+    let rows = (i128::from(max.1) - i128::from(min.1) + 1) as u128;
+    let width = columns * u128::from(cell.0);
+    let height = rows * u128::from(cell.1);
+
+    match width.checked_mul(height) {
+        Some(pixels) if pixels <= MAX_IMAGE_PIXELS => Ok((width as u32, height as u32)),
+        _ => Err(DrawError::TooLarge { width, height }.into()),
+    }
+}
+
 pub fn tileset_index_to_pixels(i: u32) -> (u32, u32) {
     (
         (i % 16) * 24,
@@ -22,7 +55,48 @@ pub fn screen_index_to_pixels(i: u32) -> (u32, u32) {
     )
 }
 
+/// Configures the behavior of [`draw_screen_with_options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrawOptions {
+    /// How many times larger than the screen's 600x240 pixels to draw it. Must be at least 1.
+    /// Defaults to 1.
+    pub scale: u32,
+    /// If `true`, the screen is drawn with hard edges, closer to how it looks in-game:
+    /// - Tile pixels are either drawn fully opaque or not at all, with an alpha of 128 or more
+    ///   being opaque. Magenta (`#FF00FF`) pixels are skipped.
+    /// - Scaling is nearest-neighbor.
+    ///
+    /// Otherwise, tiles are alpha blended and scaling is smoothed. This is an approximation; it
+    /// hasn't been compared with in-game screenshots. Defaults to `false`.
+    pub authentic: bool,
+}
+
+impl Default for DrawOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            authentic: false,
+        }
+    }
+}
+
+/// The color skipped by [`DrawOptions::authentic`].
+const TRANSPARENT_COLOR: [u8; 3] = [255, 0, 255];
+
 pub fn draw_screen(screen: &ScreenData, assets: &mut AssetCache) -> Result<RgbaImage> {
+    draw_screen_with_options(screen, assets, &DrawOptions::default())
+}
+
+/// Draws `screen` like [`draw_screen`], configured by `options`.
+///
+/// Returns [`DrawError::InvalidOption`] if `options.scale` is 0, or [`DrawError::TooLarge`] if
+/// the scaled image would exceed [`MAX_IMAGE_PIXELS`].
+pub fn draw_screen_with_options(screen: &ScreenData, assets: &mut AssetCache, options: &DrawOptions) -> Result<RgbaImage> {
+    if options.scale == 0 {
+        return Err(DrawError::InvalidOption { name: "scale", value: 0 }.into());
+    }
+    let scale = i64::from(options.scale);
+    let (width, height) = grid_dimensions((1, 1), (scale, scale), (600, 240))?;
     let mut img = RgbaImage::new(600, 240);
 
     assets.ensure_assets_loaded(screen.assets)?;
@@ -55,10 +129,78 @@ pub fn draw_screen(screen: &ScreenData, assets: &mut AssetCache) -> Result<RgbaI
 
                 let (screen_x, screen_y) = screen_index_to_pixels(i.try_into().unwrap());
 
-                imageops::overlay(&mut img, &*tile_img, screen_x.into(), screen_y.into());
+                if options.authentic {
+                    overlay_thresholded(&mut img, &*tile_img, screen_x, screen_y);
+                }
+                else {
+                    imageops::overlay(&mut img, &*tile_img, screen_x.into(), screen_y.into());
+                }
             }
         }
     }
 
+    if options.scale > 1 {
+        let filter = if options.authentic { FilterType::Nearest } else { FilterType::CatmullRom };
+        img = imageops::resize(&img, width, height, filter);
+    }
+
     Ok(img)
 }
+
+/// Draws `top` onto `bottom` with its top left corner at `(x, y)`, skipping pixels that are
+/// mostly transparent or the engine's transparent color and drawing the rest fully opaque.
+fn overlay_thresholded<I>(bottom: &mut RgbaImage, top: &I, x: u32, y: u32)
+where
+    I: GenericImageView<Pixel = Rgba<u8>>
+{
+    for (top_x, top_y, Rgba([r, g, b, a])) in top.pixels() {
+        let (bottom_x, bottom_y) = (x + top_x, y + top_y);
+        if a < 128 || [r, g, b] == TRANSPARENT_COLOR
+            || bottom_x >= bottom.width() || bottom_y >= bottom.height()
+        {
+            continue;
+        }
+        bottom.put_pixel(bottom_x, bottom_y, Rgba([r, g, b, 255]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::DynamicImage;
+
+    use super::*;
+    use crate::{assets::AssetSource, map_bin::{parse_screen_bytes, Tile, SCREEN_DATA_LEN}};
+
+    #[test]
+    fn authentic_mode_thresholds_alpha_and_scales_nearest() {
+        let mut assets = AssetCache::new(AssetSource {
+            data_folder: Default::default(),
+            world_folder: Default::default(),
+        });
+        let mut tileset = RgbaImage::new(384, 192);
+        tileset.put_pixel(24, 0, Rgba([0, 0, 255, 200]));
+        tileset.put_pixel(25, 0, Rgba([0, 0, 255, 100]));
+        tileset.put_pixel(26, 0, Rgba([255, 0, 255, 255]));
+        assets.insert_tileset(0, DynamicImage::ImageRgba8(tileset));
+
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.layers[0].0[0] = Tile(0, 1);
+
+        let blended = draw_screen(&screen, &mut assets).unwrap();
+        assert_eq!(blended.get_pixel(0, 0), &Rgba([0, 0, 255, 200]));
+        assert_eq!(blended.get_pixel(2, 0), &Rgba([255, 0, 255, 255]));
+
+        let options = DrawOptions { scale: 2, authentic: true };
+        let authentic = draw_screen_with_options(&screen, &mut assets, &options).unwrap();
+        assert_eq!(authentic.dimensions(), (1200, 480));
+        assert_eq!(authentic.get_pixel(1, 1), &Rgba([0, 0, 255, 255]));
+        assert_eq!(authentic.get_pixel(2, 0), &Rgba([0, 0, 0, 0]));
+        assert_eq!(authentic.get_pixel(4, 0), &Rgba([0, 0, 0, 0]));
+
+        for (scale, code) in [(0, 605), (u32::MAX, 604)] {
+            let options = DrawOptions { scale, authentic: false };
+            let err = draw_screen_with_options(&screen, &mut assets, &options).unwrap_err();
+            assert_eq!(err.code(), code);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use image::RgbaImage;
+
+use crate::{Result, map_bin::ScreenData, perf};
+use super::{draw_screen_with_options, AssetCache, DrawOptions};
+
+/// Remembers the images rendered by [`draw_screen_with_options`] so that screens are only
+/// rendered again when they change.
+///
+/// An image is reused while the screen's [`ScreenData::content_hash`], the
+/// [`AssetCache::fingerprint`] of its assets, and the [`DrawOptions`] stay the same. One image is kept per position, so
+/// the cache holds at most one image per screen of the map.
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    screens: HashMap<(i64, i64), CachedRender>,
+}
+
+#[derive(Debug)]
+struct CachedRender {
+    content_hash: u64,
+    fingerprint: u64,
+    options: DrawOptions,
+    image: RgbaImage,
+}
+
+impl RenderCache {
+    pub fn new() -> RenderCache {
+        Self::default()
+    }
+
+    /// Returns the rendered image of `screen`, drawing it only if it isn't cached or the screen
+    /// or its assets have changed since it was.
+    pub fn render(&mut self, screen: &ScreenData, assets: &mut AssetCache) -> Result<&RgbaImage> {
+        self.render_with_options(screen, assets, &DrawOptions::default())
+    }
+
+    /// Returns the image of `screen` rendered with `options`, like [`render`](Self::render). A
+    /// cached image drawn with different options is drawn again.
+    pub fn render_with_options(
+        &mut self,
+        screen: &ScreenData,
+        assets: &mut AssetCache,
+        options: &DrawOptions,
+    ) -> Result<&RgbaImage> {
+        assets.ensure_assets_loaded(screen.assets)?;
+        let content_hash = screen.content_hash();
+        let fingerprint = assets.fingerprint(screen.assets);
+
+        let is_current = self.screens.get(&screen.position).is_some_and(|cached| {
+            cached.content_hash == content_hash
+                && cached.fingerprint == fingerprint
+                && cached.options == *options
+        });
+        if is_current {
+            perf::count(|c| c.cache_hits += 1);
+        }
+        else {
+            perf::count(|c| c.cache_misses += 1);
+            let image = draw_screen_with_options(screen, assets, options)?;
+            let options = options.clone();
+            self.screens.insert(screen.position, CachedRender { content_hash, fingerprint, options, image });
+        }
+
+        Ok(&self.screens[&screen.position].image)
+    }
+
+    /// Returns `true` if the image cached for `screen` is up to date, whatever options it was
+    /// drawn with.
+    pub fn is_current(&self, screen: &ScreenData, assets: &AssetCache) -> bool {
+        self.screens.get(&screen.position).is_some_and(|cached| {
+            cached.content_hash == screen.content_hash()
+                && cached.fingerprint == assets.fingerprint(screen.assets)
+        })
+    }
+
+    /// Forgets the image cached for the screen at `position`, if any.
+    pub fn invalidate(&mut self, position: (i64, i64)) {
+        self.screens.remove(&position);
+    }
+
+    pub fn clear(&mut self) {
+        self.screens.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.screens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.screens.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, Rgba};
+
+    use super::*;
+    use crate::{assets::AssetSource, map_bin::{parse_screen_bytes, Tile, SCREEN_DATA_LEN}};
+
+    #[test]
+    fn screens_rerender_only_when_changed() {
+        let mut assets = AssetCache::new(AssetSource {
+            data_folder: Default::default(),
+            world_folder: Default::default(),
+        });
+        let tileset = |color| DynamicImage::ImageRgba8(RgbaImage::from_pixel(384, 192, Rgba(color)));
+        assets.insert_tileset(0, tileset([255, 0, 0, 255]));
+
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.layers[0].0[0] = Tile(0, 1);
+        let mut cache = RenderCache::new();
+
+        let first = cache.render(&screen, &mut assets).unwrap().clone();
+        assert_eq!(first.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert!(cache.is_current(&screen, &assets));
+
+        screen.layers[0].0[1] = Tile(0, 2);
+        assert!(!cache.is_current(&screen, &assets));
+        cache.render(&screen, &mut assets).unwrap();
+        assert!(cache.is_current(&screen, &assets));
+
+        assets.insert_tileset(0, tileset([0, 0, 255, 255]));
+        assert!(!cache.is_current(&screen, &assets));
+        let recolored = cache.render(&screen, &mut assets).unwrap();
+        assert_eq!(recolored.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+        assert_eq!(cache.len(), 1);
+
+        let options = DrawOptions { scale: 2, authentic: true };
+        let scaled = cache.render_with_options(&screen, &mut assets, &options).unwrap();
+        assert_eq!(scaled.dimensions(), (1200, 480));
+        assert_eq!(cache.render(&screen, &mut assets).unwrap().dimensions(), (600, 240));
+    }
+}
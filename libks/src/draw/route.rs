@@ -0,0 +1,50 @@
+use image::{Rgba, RgbaImage};
+
+use crate::{
+    analysis::route::{Route, Waypoint},
+    constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
+};
+
+/// Draws `route` onto an overview of the level in which each screen is a `cell_size` square and
+/// the screen at `origin` is in the top left corner, such as one from
+/// [`draw_track_map`](super::draw_track_map).
+///
+/// Consecutive waypoints are joined by straight lines. Waypoints without a tile position are
+/// placed at the center of their screen. Anything outside `img` is clipped.
+pub fn draw_route(img: &mut RgbaImage, route: &Route, origin: (i64, i64), cell_size: u32, color: Rgba<u8>) {
+    let points: Vec<(f64, f64)> = route.waypoints.iter()
+        .map(|waypoint| waypoint_pixel(waypoint, origin, cell_size))
+        .collect();
+
+    for pair in points.windows(2) {
+        draw_line(img, pair[0], pair[1], color);
+    }
+    if let [point] = points.as_slice() {
+        draw_line(img, *point, *point, color);
+    }
+}
+
+/// Returns the pixel at the center of the waypoint's tile.
+fn waypoint_pixel(waypoint: &Waypoint, origin: (i64, i64), cell_size: u32) -> (f64, f64) {
+    let (tile_x, tile_y) = waypoint.position
+        .map_or((SCREEN_WIDTH as f64 / 2.0, SCREEN_HEIGHT as f64 / 2.0), |(x, y)| (x as f64 + 0.5, y as f64 + 0.5));
+    let cell_size = cell_size as f64;
+
+    (
+        (waypoint.screen.0 - origin.0) as f64 * cell_size + tile_x / SCREEN_WIDTH as f64 * cell_size,
+        (waypoint.screen.1 - origin.1) as f64 * cell_size + tile_y / SCREEN_HEIGHT as f64 * cell_size,
+    )
+}
+
+pub(super) fn draw_line(img: &mut RgbaImage, from: (f64, f64), to: (f64, f64), color: Rgba<u8>) {
+    let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).ceil().max(1.0) as u32;
+
+    for step in 0..=steps {
+        let t = step as f64 / steps as f64;
+        let x = (from.0 + (to.0 - from.0) * t).floor();
+        let y = (from.1 + (to.1 - from.1) * t).floor();
+        if x >= 0.0 && y >= 0.0 && (x as u32) < img.width() && (y as u32) < img.height() {
+            img.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+
+use image::{Rgba, RgbaImage};
+
+use crate::{map_bin::AssetId, Result};
+use super::{grid_dimensions, DrawError};
+
+/// The color of ID 0, which is presumed to mean silence.
+const SILENCE_COLOR: Rgba<u8> = Rgba([64, 64, 64, 255]);
+
+/// Returns the color [`draw_track_map`] uses for track `id`. Hues are spread by the golden
+/// angle so that nearby IDs are easy to tell apart.
+pub fn track_color(id: AssetId) -> Rgba<u8> {
+    if id == 0 {
+        return SILENCE_COLOR;
+    }
+
+    let hue = (f32::from(id) * 137.508) % 360.0;
+    let (saturation, value) = (0.65, 0.9);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let channel = |c: f32| ((c + m) * 255.0).round() as u8;
+
+    Rgba([channel(r), channel(g), channel(b), 255])
+}
+
+/// Draws an overview of where each track plays, e.g. [`AudioMap::music`]. Each screen is a
+/// `cell_size` square colored by [`track_color`], and positions without a screen are
+/// transparent. A screen listed under several tracks gets the color of the last one.
+///
+/// Returns [`DrawError::InvalidOption`] if `cell_size` is 0, or [`DrawError::TooLarge`] if the
+/// screens are too far apart to fit in one image.
+///
+/// [`AudioMap::music`]: crate::analysis::AudioMap::music
+pub fn draw_track_map(tracks: &BTreeMap<AssetId, Vec<(i64, i64)>>, cell_size: u32) -> Result<RgbaImage> {
+    if cell_size == 0 {
+        return Err(DrawError::InvalidOption { name: "cell_size", value: cell_size }.into());
+    }
+
+    let mut positions = tracks.values().flatten();
+    let Some(&first) = positions.next() else {
+        return Ok(RgbaImage::new(0, 0));
+    };
+    let (min, max) = positions.fold((first, first), |(min, max), &(x, y)| {
+        ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+    });
+
+    // The image fits, so every offset within it does too
+    let (width, height) = grid_dimensions(min, max, (cell_size, cell_size))?;
+    let mut img = RgbaImage::new(width, height);
+
+    for (&id, screens) in tracks {
+        let color = track_color(id);
+        for &(x, y) in screens {
+            let left = x.abs_diff(min.0) as u32 * cell_size;
+            let top = y.abs_diff(min.1) as u32 * cell_size;
+            for dy in 0..cell_size {
+                for dx in 0..cell_size {
+                    img.put_pixel(left + dx, top + dy, color);
+                }
+            }
+        }
+    }
+
+    Ok(img)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_are_drawn_in_their_colors() {
+        let tracks = BTreeMap::from([
+            (0, vec![(1000, 1000)]),
+            (3, vec![(1002, 999)]),
+        ]);
+        let img = draw_track_map(&tracks, 2).unwrap();
+        assert_eq!(img.dimensions(), (6, 4));
+        assert_eq!(img.get_pixel(1, 3), &SILENCE_COLOR);
+        assert_eq!(img.get_pixel(5, 0), &track_color(3));
+        assert_eq!(img.get_pixel(0, 0), &Rgba([0, 0, 0, 0]));
+
+        assert!(matches!(
+            draw_track_map(&tracks, 0),
+            Err(crate::KsError::Draw(DrawError::InvalidOption { name: "cell_size", .. })),
+        ));
+        let far = BTreeMap::from([(1, vec![(i64::MIN, 0), (i64::MAX, 0)])]);
+        assert!(matches!(
+            draw_track_map(&far, 1),
+            Err(crate::KsError::Draw(DrawError::TooLarge { .. })),
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use crate::{
+    constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
+    map_bin::{LayerData, Tile},
+};
+
+/// The neighbor above.
+pub const N: u8 = 1 << 0;
+/// The neighbor to the right.
+pub const E: u8 = 1 << 1;
+/// The neighbor below.
+pub const S: u8 = 1 << 2;
+/// The neighbor to the left.
+pub const W: u8 = 1 << 3;
+/// The neighbor to the upper right. Only counted when [`N`] and [`E`] are also set.
+pub const NE: u8 = 1 << 4;
+/// The neighbor to the lower right. Only counted when [`S`] and [`E`] are also set.
+pub const SE: u8 = 1 << 5;
+/// The neighbor to the lower left. Only counted when [`S`] and [`W`] are also set.
+pub const SW: u8 = 1 << 6;
+/// The neighbor to the upper left. Only counted when [`N`] and [`W`] are also set.
+pub const NW: u8 = 1 << 7;
+
+/// Describes which tile of a tileset to use for a cell depending on which of its neighbors are
+/// part of the same terrain.
+///
+/// Neighbors are given as a mask of [`N`], [`E`], [`S`], [`W`], and the corner bits. A corner
+/// only matters when both of its sides are set, so `N | E | NE` and `N | E` are different but
+/// `N | NE` is the same as `N`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutotileTemplate {
+    /// The tile used when no other tile matches, usually the inside of the terrain.
+    pub fill: Tile,
+    tiles: BTreeMap<u8, Tile>,
+}
+
+impl AutotileTemplate {
+    /// Creates a template that uses `fill` everywhere until other tiles are set.
+    pub fn new(fill: Tile) -> Self {
+        Self {
+            fill,
+            tiles: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a template from a 3x3 block of tiles in a tileset, a common way to lay out
+    /// terrain: corners, edges, and the fill in the middle. `top_left` is the tile in the top
+    /// left corner of the block, which must fit within the tileset's 16 columns and 8 rows.
+    ///
+    /// Inner corners aren't part of the block, so they use the fill.
+    pub fn block(top_left: Tile) -> Self {
+        let Tile(tileset, index) = top_left;
+        let at = |x: u8, y: u8| Tile(tileset, index + x + 16 * y);
+
+        let mut template = Self::new(at(1, 1));
+        template.set(E | S, at(0, 0));
+        template.set(E | S | W, at(1, 0));
+        template.set(S | W, at(2, 0));
+        template.set(N | E | S, at(0, 1));
+        template.set(N | S | W, at(2, 1));
+        template.set(N | E, at(0, 2));
+        template.set(N | E | W, at(1, 2));
+        template.set(N | W, at(2, 2));
+        template
+    }
+
+    /// Uses `tile` for cells whose neighbors match `mask`.
+    pub fn set(&mut self, mask: u8, tile: Tile) {
+        self.tiles.insert(reduce(mask), tile);
+    }
+
+    /// Returns the tile for a cell whose neighbors match `mask`. If no tile was set for the
+    /// exact mask, the corners are ignored, and failing that, the fill is used.
+    pub fn tile_for(&self, mask: u8) -> Tile {
+        let mask = reduce(mask);
+        self.tiles.get(&mask)
+            .or_else(|| self.tiles.get(&(mask & (N | E | S | W))))
+            .copied()
+            .unwrap_or(self.fill)
+    }
+
+    /// Returns whether `tile` is one of the template's tiles.
+    pub fn contains(&self, tile: Tile) -> bool {
+        tile == self.fill || self.tiles.values().any(|&t| t == tile)
+    }
+}
+
+/// Clears the corner bits whose sides aren't both set.
+fn reduce(mask: u8) -> u8 {
+    let mut reduced = mask & (N | E | S | W);
+    for (corner, sides) in [(NE, N | E), (SE, S | E), (SW, S | W), (NW, N | W)] {
+        if mask & corner != 0 && mask & sides == sides {
+            reduced |= corner;
+        }
+    }
+    reduced
+}
+
+/// Fills the cells of `layer` in columns `x` and rows `y` with the terrain described by
+/// `template`, then picks the right tile for each of them and for the terrain around them so
+/// that edges and corners connect.
+///
+/// A cell is part of the terrain if it holds one of the template's tiles. Cells beyond the edge
+/// of the screen count as part of the terrain, so terrain that reaches the edge continues onto
+/// the next screen without a border. Ranges beyond the screen are clipped.
+pub fn autotile(layer: &mut LayerData, template: &AutotileTemplate, x: Range<usize>, y: Range<usize>) {
+    let x = x.start.min(SCREEN_WIDTH) .. x.end.min(SCREEN_WIDTH);
+    let y = y.start.min(SCREEN_HEIGHT) .. y.end.min(SCREEN_HEIGHT);
+
+    for row in y.clone() {
+        for col in x.clone() {
+            layer.0[col + row * SCREEN_WIDTH] = template.fill;
+        }
+    }
+
+    // The filled cells can change the tiles of their neighbors too
+    let cols = x.start.saturating_sub(1) .. (x.end + 1).min(SCREEN_WIDTH);
+    let rows = y.start.saturating_sub(1) .. (y.end + 1).min(SCREEN_HEIGHT);
+    let snapshot = layer.clone();
+    let member = |col: isize, row: isize| {
+        let outside = col < 0 || row < 0 || col >= SCREEN_WIDTH as isize || row >= SCREEN_HEIGHT as isize;
+        outside || template.contains(snapshot.0[col as usize + row as usize * SCREEN_WIDTH])
+    };
+
+    for row in rows {
+        for col in cols.clone() {
+            let (c, r) = (col as isize, row as isize);
+            if !member(c, r) {
+                continue;
+            }
+
+            let mask = [
+                (N, 0, -1), (E, 1, 0), (S, 0, 1), (W, -1, 0),
+                (NE, 1, -1), (SE, 1, 1), (SW, -1, 1), (NW, -1, -1),
+            ].into_iter()
+                .filter(|&(_, dc, dr)| member(c + dc, r + dr))
+                .fold(0, |mask, (bit, _, _)| mask | bit);
+
+            layer.0[col + row * SCREEN_WIDTH] = template.tile_for(mask);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions_get_edges_and_corners() {
+        fn at(layer: &LayerData, x: usize, y: usize) -> Tile {
+            layer.0[x + y * SCREEN_WIDTH]
+        }
+
+        let template = AutotileTemplate::block(Tile(0, 1));
+        let mut layer = LayerData([Tile(0, 0); SCREEN_WIDTH * SCREEN_HEIGHT]);
+
+        autotile(&mut layer, &template, 2..5, 2..5);
+        assert_eq!(at(&layer, 2, 2), Tile(0, 1));
+        assert_eq!(at(&layer, 3, 2), Tile(0, 2));
+        assert_eq!(at(&layer, 3, 3), Tile(0, 18));
+        assert_eq!(at(&layer, 4, 4), Tile(0, 35));
+        assert_eq!(at(&layer, 5, 5), Tile(0, 0));
+
+        // Extending the region turns the old edge into fill
+        autotile(&mut layer, &template, 5..7, 2..5);
+        assert_eq!(at(&layer, 4, 3), Tile(0, 18));
+        assert_eq!(at(&layer, 6, 3), Tile(0, 19));
+    }
+}
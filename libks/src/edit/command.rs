@@ -0,0 +1,271 @@
+use libks_ini::Ini;
+
+use crate::{
+    constants::{LAYER_COUNT, SCREEN_HEIGHT, SCREEN_WIDTH},
+    map_bin::{AssetIds, MapBinError, ScreenData, Tile},
+    world::World,
+    Result,
+};
+
+/// A reversible edit to a [`World`], for editors that need undo and redo.
+///
+/// [`Command::apply`] makes the change and returns the command that undoes it, which can be
+/// pushed onto an undo stack; applying that command returns one that redoes the change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Sets the tile or object at column `x` and row `y` of `layer` on `screen`.
+    SetTile {
+        screen: (i64, i64),
+        layer: usize,
+        x: usize,
+        y: usize,
+        tile: Tile,
+    },
+    /// Sets the tilesets, ambiance, music, and gradient of `screen`.
+    SetAssets {
+        screen: (i64, i64),
+        assets: AssetIds,
+    },
+    /// Sets a World.ini property, or removes it if `value` is `None`.
+    SetIniKey {
+        section: String,
+        key: String,
+        value: Option<String>,
+    },
+    /// Replaces all of World.ini with `text`. Used to undo the commands that edit it.
+    SetIni {
+        text: String,
+    },
+    /// Moves a screen. See [`World::move_screen`].
+    MoveScreen {
+        from: (i64, i64),
+        to: (i64, i64),
+    },
+    /// Adds a screen to Map.bin at `index`, or at the end if `index` is `None` or past the end.
+    /// Its World.ini section isn't touched.
+    AddScreen {
+        screen: Box<ScreenData>,
+        index: Option<usize>,
+    },
+    /// Removes the screen at `screen` from Map.bin. Its World.ini section isn't touched.
+    RemoveScreen {
+        screen: (i64, i64),
+    },
+    /// Applies several commands in order as one. If one fails, the ones before it are undone.
+    Batch(Vec<Command>),
+}
+
+impl Command {
+    /// Applies the command to `world` and returns the command that undoes it.
+    ///
+    /// If the command fails, `world` is left unchanged.
+    pub fn apply(&self, world: &mut World) -> Result<Command> {
+        if let Command::Batch(commands) = self {
+            return apply_batch(commands, world);
+        }
+
+        let inverse = self.invert(world)?;
+        match self {
+            Command::SetTile { screen, layer, x, y, tile } => {
+                let screen = screen_mut(world, *screen)?;
+                screen.layers[*layer].0[x + y * SCREEN_WIDTH] = *tile;
+            },
+            Command::SetAssets { screen, assets } => {
+                screen_mut(world, *screen)?.assets = *assets;
+            },
+            Command::SetIniKey { section, key, value: Some(value) } => {
+                world.ini.set_in(section, key, value.clone());
+            },
+            Command::SetIniKey { section, key, value: None } => {
+                world.ini.remove_in(section, key);
+            },
+            Command::SetIni { text } => {
+                world.ini = Ini::new(text);
+            },
+            Command::MoveScreen { from, to } => {
+                world.move_screen(*from, *to)?;
+            },
+            Command::AddScreen { screen, index } => {
+                let index = index.unwrap_or(usize::MAX).min(world.screens.len());
+                world.screens.insert(index, screen.as_ref().clone());
+            },
+            Command::RemoveScreen { screen } => {
+                world.screens.retain(|existing| existing.position != *screen);
+            },
+            Command::Batch(_) => unreachable!(),
+        }
+
+        Ok(inverse)
+    }
+
+    /// Returns the command that undoes this one, given the state of `world` before it's applied.
+    /// Fails if the command couldn't be applied to `world`.
+    ///
+    /// Undoing restores `world` exactly. Since [`Command::SetIniKey`] and
+    /// [`Command::MoveScreen`] can reorder or add World.ini lines, their inverses hold a copy of
+    /// the whole file, as [`Command::SetIni`].
+    pub fn invert(&self, world: &World) -> Result<Command> {
+        let inverse = match self {
+            Command::SetTile { screen, layer, x, y, .. } => {
+                if *layer >= LAYER_COUNT || *x >= SCREEN_WIDTH || *y >= SCREEN_HEIGHT {
+                    return Err(MapBinError::TileOutOfBounds { layer: *layer, x: *x, y: *y }.into());
+                }
+
+                Command::SetTile {
+                    screen: *screen,
+                    layer: *layer,
+                    x: *x,
+                    y: *y,
+                    tile: screen_ref(world, *screen)?.layers[*layer].0[x + y * SCREEN_WIDTH],
+                }
+            },
+            Command::SetAssets { screen, .. } => Command::SetAssets {
+                screen: *screen,
+                assets: screen_ref(world, *screen)?.assets,
+            },
+            Command::SetIniKey { .. } | Command::SetIni { .. } => Command::SetIni {
+                text: world.ini.to_string(),
+            },
+            Command::MoveScreen { from, to } => {
+                screen_ref(world, *from)?;
+                let (x, y) = *to;
+                if world.screen(*to).is_some() || world.ini.has_section(&format!("x{x}y{y}")) {
+                    return Err(MapBinError::ScreenExists { position: *to }.into());
+                }
+
+                // Moving back restores the screen's place in Map.bin, then the snapshot undoes
+                // the rewritten targets
+                Command::Batch(vec![
+                    Command::MoveScreen { from: *to, to: *from },
+                    Command::SetIni { text: world.ini.to_string() },
+                ])
+            },
+            Command::AddScreen { screen, .. } => {
+                if world.screen(screen.position).is_some() {
+                    return Err(MapBinError::ScreenExists { position: screen.position }.into());
+                }
+
+                Command::RemoveScreen { screen: screen.position }
+            },
+            Command::RemoveScreen { screen } => {
+                let index = world.screens.iter()
+                    .position(|existing| existing.position == *screen)
+                    .ok_or(MapBinError::NoScreen { position: *screen })?;
+
+                Command::AddScreen {
+                    screen: Box::new(world.screens[index].clone()),
+                    index: Some(index),
+                }
+            },
+            Command::Batch(commands) => {
+                // Later commands depend on the effects of earlier ones
+                let mut scratch = world.clone();
+                apply_batch(commands, &mut scratch)?
+            },
+        };
+
+        Ok(inverse)
+    }
+}
+
+fn apply_batch(commands: &[Command], world: &mut World) -> Result<Command> {
+    let mut inverses = Vec::with_capacity(commands.len());
+
+    for command in commands {
+        match command.apply(world) {
+            Ok(inverse) => inverses.push(inverse),
+            Err(err) => {
+                for inverse in inverses.iter().rev() {
+                    inverse.apply(world)?;
+                }
+                return Err(err);
+            },
+        }
+    }
+
+    inverses.reverse();
+    Ok(Command::Batch(inverses))
+}
+
+fn screen_ref(world: &World, position: (i64, i64)) -> Result<&ScreenData> {
+    world.screen(position)
+        .ok_or_else(|| MapBinError::NoScreen { position }.into())
+}
+
+fn screen_mut(world: &mut World, position: (i64, i64)) -> Result<&mut ScreenData> {
+    world.screen_mut(position)
+        .ok_or_else(|| MapBinError::NoScreen { position }.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, SCREEN_DATA_LEN};
+
+    #[test]
+    fn batches_undo_and_redo() {
+        let mut world = World {
+            dir: "Test - Level".into(),
+            ini: Ini::new("[x1000y1000]\nTint=Red\n"),
+            screens: vec![parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000))],
+        };
+        let batch = Command::Batch(vec![
+            Command::SetTile { screen: (1000, 1000), layer: 4, x: 3, y: 2, tile: Tile(0, 12) },
+            Command::SetIniKey { section: "x1000y1000".to_owned(), key: "Tint".to_owned(), value: None },
+            Command::MoveScreen { from: (1000, 1000), to: (1001, 1000) },
+        ]);
+
+        let undo = batch.apply(&mut world).unwrap();
+        assert_eq!(world.screens[0].position, (1001, 1000));
+        assert_eq!(world.screens[0].layers[4].0[3 + 2 * SCREEN_WIDTH], Tile(0, 12));
+        assert!(!world.ini.has_in("x1001y1000", "Tint"));
+
+        let redo = undo.apply(&mut world).unwrap();
+        assert_eq!(world.screens[0].position, (1000, 1000));
+        assert_eq!(world.screens[0].layers[4].0[3 + 2 * SCREEN_WIDTH], Tile(0, 0));
+        assert_eq!(world.ini.get_in("x1000y1000", "Tint"), Some("Red"));
+
+        redo.apply(&mut world).unwrap();
+        assert_eq!(world.screens[0].position, (1001, 1000));
+        assert_eq!(world.screens[0].layers[4].0[3 + 2 * SCREEN_WIDTH], Tile(0, 12));
+        assert!(!world.ini.has_in("x1001y1000", "Tint"));
+
+        // A failing batch leaves the world as it was
+        let failing = Command::Batch(vec![
+            Command::SetTile { screen: (1000, 1000), layer: 4, x: 0, y: 0, tile: Tile(0, 1) },
+            Command::RemoveScreen { screen: (5, 5) },
+        ]);
+        assert!(failing.apply(&mut world).is_err());
+        assert_eq!(world.screens[0].layers[4].0[0], Tile(0, 0));
+    }
+
+    #[test]
+    fn undo_is_exact() {
+        let source = "; Start\n[x1000y1000]\nTint=Red\nTint=Blue\nMusic=2\n[x1001y1000]\nWarpLeftX=-1\n";
+        let mut world = World {
+            dir: "Test - Level".into(),
+            ini: Ini::new(source),
+            screens: [(1000, 1000), (1001, 1000), (1002, 1000)].into_iter()
+                .map(|position| parse_screen_bytes(&[0; SCREEN_DATA_LEN], position))
+                .collect(),
+        };
+        let batch = Command::Batch(vec![
+            Command::SetIniKey { section: "x1000y1000".to_owned(), key: "Tint".to_owned(), value: None },
+            Command::SetIniKey { section: "x1000y1000".to_owned(), key: "Tint".to_owned(), value: Some("Green".to_owned()) },
+            Command::MoveScreen { from: (1000, 1000), to: (1000, 1001) },
+            Command::RemoveScreen { screen: (1001, 1000) },
+        ]);
+
+        let undo = batch.apply(&mut world).unwrap();
+        assert_ne!(world.ini.to_string(), source);
+        assert_eq!(world.ini.get_in("x1001y1000", "WarpLeftY"), Some("1"));
+
+        // Duplicate keys, key order, and the warp without a Y offset all come back
+        undo.apply(&mut world).unwrap();
+        assert_eq!(world.ini.to_string(), source);
+        let positions: Vec<_> = world.screens.iter().map(|screen| screen.position).collect();
+        assert_eq!(positions, [(1000, 1000), (1001, 1000), (1002, 1000)]);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod autotile;
+
+mod command;
+pub use command::Command;
@@ -0,0 +1,310 @@
+use crate::{
+    common::{parse_xy, split_slot},
+    constants::objects,
+    map_bin::Tile,
+    world::World,
+};
+use super::{
+    map_bin_heuristics::MapBinReason,
+    world_ini_heuristics::{
+        is_plus_artifact_warp,
+        is_plus_coin_flag,
+        is_plus_object_prop,
+        is_plus_object_section,
+        is_plus_screen_prop,
+        is_plus_world_prop,
+        IniReason,
+        PLUS_SECTIONS,
+    },
+    KsEdition,
+    Reason,
+};
+
+/// A change made by [`to_plus`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conversion {
+    /// `[World] Format` was set to 4. Holds the previous value, if there was one.
+    SetFormat(Option<String>),
+    /// A KS Ex property was removed from `[World]` so the level isn't detected as KS Ex.
+    RemovedWorldProp(String),
+}
+
+impl std::fmt::Display for Conversion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Conversion::SetFormat(Some(previous)) =>
+                write!(f, "Changed `Format` from `{previous}` to `4`."),
+            Conversion::SetFormat(None) =>
+                write!(f, "Set `Format` to `4`."),
+            Conversion::RemovedWorldProp(key) =>
+                write!(f, "Removed `{key}` from the [World] section."),
+        }
+    }
+}
+
+/// Something that [`to_plus`] can't convert automatically.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FollowUp {
+    /// Objects in bank 254 are placed on the screen. KS Plus reads bank 254 as
+    /// `[Custom Object B#]` objects rather than KS Advanced objects.
+    AdvancedObjects {
+        screen: (i64, i64),
+        tiles: Vec<Tile>,
+    },
+    /// An object from another mod is placed on the screen.
+    UnsupportedObjects {
+        screen: (i64, i64),
+        edition: KsEdition,
+        tiles: Vec<Tile>,
+    },
+    /// A section only another mod understands.
+    UnsupportedSection(String),
+}
+
+impl std::fmt::Display for FollowUp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tiles_to_string = |tiles: &[Tile]| {
+            let strings: Vec<_> = tiles.iter()
+                .map(|tile| format!("{}:{}", tile.0, tile.1))
+                .collect();
+            strings.join(", ")
+        };
+
+        match self {
+            FollowUp::AdvancedObjects { screen, tiles } =>
+                write!(f, "The screen x{}y{} uses KS Advanced objects, which KS Plus reads as [Custom Object B#] objects: {}", screen.0, screen.1, tiles_to_string(tiles)),
+            FollowUp::UnsupportedObjects { screen, edition, tiles } =>
+                write!(f, "The screen x{}y{} uses {edition:?} objects that KS Plus doesn't support: {}", screen.0, screen.1, tiles_to_string(tiles)),
+            FollowUp::UnsupportedSection(key) =>
+                write!(f, "The section [{key}] isn't supported by KS Plus."),
+        }
+    }
+}
+
+/// The result of [`to_plus`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    /// The changes that were made.
+    pub conversions: Vec<Conversion>,
+    /// Problems that need to be fixed by hand.
+    pub follow_ups: Vec<FollowUp>,
+}
+
+/// Converts `world` from a vanilla level into a KS Plus level. The changes are made in memory;
+/// call [`World::save`] to write them.
+///
+/// Setting `Format=4` opts the level into KS Plus behavior. KS Plus is otherwise compatible with
+/// vanilla levels, so the only other rewrite is removing KS Ex markers. Constructs from mods that
+/// KS Plus can't run are reported as follow-ups rather than removed.
+pub fn to_plus(world: &mut World) -> ConversionReport {
+    let mut report = ConversionReport::default();
+
+    let previous = world.ini.get_in("World", "Format").map(str::to_owned);
+    if previous.as_deref() != Some("4") {
+        world.ini.set_in("World", "Format", "4".to_owned());
+        report.conversions.push(Conversion::SetFormat(previous));
+    }
+
+    if world.ini.has_in("World", "FormatEx") {
+        world.ini.remove_in("World", "FormatEx");
+        report.conversions.push(Conversion::RemovedWorldProp("FormatEx".to_owned()));
+    }
+
+    for section_key in ["KS Ex", "Templates"] {
+        if world.ini.has_section(section_key) {
+            report.follow_ups.push(FollowUp::UnsupportedSection(section_key.to_owned()));
+        }
+    }
+
+    let has_plus_b_objects = world.ini.iter_sections()
+        .any(|section| is_plus_object_section(&section.key().to_ascii_lowercase()));
+
+    for screen in &world.screens {
+        let mut advanced = Vec::new();
+        let mut aco = Vec::new();
+
+        for object in screen.objects() {
+            let tile = object.tile;
+            let objects = match objects::introduced_in(tile) {
+                // Bank 254 is fine if the level defines [Custom Object B#] sections for it
+                KsEdition::Advanced if !has_plus_b_objects => &mut advanced,
+                KsEdition::AdvancedCustomObjects => &mut aco,
+                _ => continue,
+            };
+            if !objects.contains(&tile) {
+                objects.push(tile);
+            }
+        }
+
+        if !advanced.is_empty() {
+            report.follow_ups.push(FollowUp::AdvancedObjects {
+                screen: screen.position,
+                tiles: advanced,
+            });
+        }
+        if !aco.is_empty() {
+            report.follow_ups.push(FollowUp::UnsupportedObjects {
+                screen: screen.position,
+                edition: KsEdition::AdvancedCustomObjects,
+                tiles: aco,
+            });
+        }
+    }
+
+    report
+}
+
+/// Lists every KS Plus feature `world` uses. None of them have vanilla equivalents, so they
+/// would have to be removed or reworked to make the level playable in vanilla KS.
+pub fn downgrade_report(world: &World) -> Vec<Reason> {
+    let mut reasons = Vec::new();
+
+    if world.ini.get_in("World", "Format") == Some("4") {
+        reasons.push(IniReason::HasFormat("4".to_owned()).into());
+    }
+
+    for section_key in PLUS_SECTIONS {
+        if world.ini.has_section(section_key) {
+            reasons.push(IniReason::HasSection(section_key.to_owned()).into());
+        }
+    }
+
+    for section in world.ini.iter_sections() {
+        let section_key = section.key();
+        let section_key_lower = section_key.to_ascii_lowercase();
+
+        if section_key_lower == "world" {
+            for (key, _) in section.iter() {
+                if is_plus_world_prop(key) {
+                    reasons.push(IniReason::WorldSectionHasProp(key.to_owned()).into());
+                }
+            }
+        }
+        else if is_plus_object_section(&section_key_lower) {
+            reasons.push(IniReason::HasSection(section_key.to_owned()).into());
+        }
+        else if section_key_lower.starts_with("custom object") {
+            for (key, _) in section.iter() {
+                if is_plus_object_prop(key) {
+                    reasons.push(IniReason::ObjectSectionHasProp(section_key.to_owned(), key.to_owned()).into());
+                }
+            }
+        }
+        else if parse_xy(&section_key_lower).is_some() {
+            for (key, value) in section.iter() {
+                let name = split_slot(key).map_or(key, |(name, _)| name);
+
+                if is_plus_screen_prop(key) {
+                    reasons.push(IniReason::ScreenSectionHasProp(section_key.to_owned(), key.to_owned()).into());
+                }
+                else if name.eq_ignore_ascii_case("Flag") && is_plus_coin_flag(value) {
+                    reasons.push(IniReason::ScreenSectionHasCoinFlag(section_key.to_owned()).into());
+                }
+                else if (name.eq_ignore_ascii_case("FlagWarpX") || name.eq_ignore_ascii_case("FlagWarpY"))
+                    && is_plus_artifact_warp(value)
+                {
+                    reasons.push(IniReason::ScreenSectionHasArtifactWarp(section_key.to_owned()).into());
+                }
+            }
+        }
+    }
+
+    let mut seen = Vec::new();
+    for (_, object) in world.iter_objects() {
+        let tile = object.tile;
+        if objects::introduced_in(tile) == KsEdition::Plus && !seen.contains(&tile) {
+            seen.push(tile);
+            reasons.push(MapBinReason::HasKsPlusObject(tile).into());
+        }
+    }
+
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, SCREEN_DATA_LEN};
+
+    fn world_with_objects(ini: &str, tiles: &[Tile]) -> World {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        for (i, &tile) in tiles.iter().enumerate() {
+            screen.layers[4].0[i] = tile;
+        }
+
+        World {
+            dir: Default::default(),
+            ini: Ini::new(ini),
+            screens: vec![screen],
+        }
+    }
+
+    #[test]
+    fn to_plus_converts_and_reports() {
+        let tiles = [Tile(254, 3), Tile(254, 3), Tile(253, 1), Tile(0, 1)];
+        let mut world = world_with_objects("[World]\nFormat=2\nFormatEx=1\n[Templates]\nA=1\n", &tiles);
+
+        let report = to_plus(&mut world);
+        assert_eq!(report.conversions, [
+            Conversion::SetFormat(Some("2".to_owned())),
+            Conversion::RemovedWorldProp("FormatEx".to_owned()),
+        ]);
+        assert_eq!(report.follow_ups, [
+            FollowUp::UnsupportedSection("Templates".to_owned()),
+            FollowUp::AdvancedObjects { screen: (1000, 1000), tiles: vec![Tile(254, 3)] },
+            FollowUp::UnsupportedObjects {
+                screen: (1000, 1000),
+                edition: KsEdition::AdvancedCustomObjects,
+                tiles: vec![Tile(253, 1)],
+            },
+        ]);
+        assert_eq!(world.ini.get_in("World", "Format"), Some("4"));
+        assert!(!world.ini.has_in("World", "FormatEx"));
+
+        // Converting again changes nothing
+        assert_eq!(to_plus(&mut world).conversions, []);
+
+        // Bank 254 belongs to KS Plus when the level defines B objects
+        let mut world = world_with_objects("[Custom Object B3]\nImage=Lamp.png\n", &tiles[..2]);
+        assert_eq!(to_plus(&mut world).follow_ups, []);
+    }
+
+    #[test]
+    fn downgrade_report_lists_plus_features() {
+        let world = world_with_objects("\
+[World]
+Format=4
+Coin=Coin.png
+[Loop Music]
+[Custom Object B2]
+[Custom Object 1]
+Hurts=True
+[x1000y1000]
+TrigVisible(A)=True
+Flag(A)=Coin5
+FlagWarpX(A)=Artifact2
+", &[objects::COIN, objects::COIN, objects::SAVE_POINT]);
+
+        let reasons = downgrade_report(&world);
+        assert_eq!(reasons.len(), 9);
+        assert!(matches!(&reasons[0], Reason::Ini(IniReason::HasFormat(format)) if format == "4"));
+        assert!(matches!(&reasons[1], Reason::Ini(IniReason::HasSection(key)) if key == "Loop Music"));
+        assert!(matches!(&reasons[2], Reason::Ini(IniReason::WorldSectionHasProp(key)) if key == "Coin"));
+        assert!(matches!(&reasons[3], Reason::Ini(IniReason::HasSection(key)) if key == "Custom Object B2"));
+        assert!(matches!(
+            &reasons[4],
+            Reason::Ini(IniReason::ObjectSectionHasProp(section, key)) if section == "Custom Object 1" && key == "Hurts",
+        ));
+        assert!(matches!(
+            &reasons[5],
+            Reason::Ini(IniReason::ScreenSectionHasProp(section, key)) if section == "x1000y1000" && key == "TrigVisible(A)",
+        ));
+        assert!(matches!(&reasons[6], Reason::Ini(IniReason::ScreenSectionHasCoinFlag(key)) if key == "x1000y1000"));
+        assert!(matches!(&reasons[7], Reason::Ini(IniReason::ScreenSectionHasArtifactWarp(key)) if key == "x1000y1000"));
+        assert!(matches!(&reasons[8], Reason::MapBin(MapBinReason::HasKsPlusObject(objects::COIN))));
+
+        assert!(downgrade_report(&world_with_objects("[World]\nName=Vanilla\n", &[objects::SAVE_POINT])).is_empty());
+    }
+}
@@ -54,6 +54,7 @@ pub fn check_files_basic(world_dir: &Path) -> Result<Option<(KsEdition, FilesRea
 ///   - Custom Objects/ArtifactIcon.png
 ///   - Custom Objects/CreatureIcon.png
 ///   - Music/Intro#.ogg
+///
 /// KSA
 ///   - */Scene#.ini excluding vanilla directories
 pub fn check_files_thorough(world_dir: &Path) -> Result<Option<(KsEdition, FilesReason)>> {
@@ -168,7 +169,7 @@ where
 fn is_range_with_affixes<B, T>(s: &str, prefix: &str, suffix: &str, range: B) -> bool
 where
     B: RangeBounds<T>,
-    T: FromStr + PartialOrd<T> + ?Sized,
+    T: FromStr + PartialOrd<T>,
 {
     let Some(affixed) = s.strip_suffix(suffix)
         .and_then(|s| s.strip_prefix(prefix))
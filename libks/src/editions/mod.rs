@@ -15,7 +15,7 @@ use file_system_heuristics::{
 mod map_bin_heuristics;
 use map_bin_heuristics::{check_map_bin, MapBinReason};
 
-mod world_ini_heuristics;
+pub(crate) mod world_ini_heuristics;
 use world_ini_heuristics::{
     check_ini_basic,
     check_ini_format,
@@ -25,9 +25,12 @@ use world_ini_heuristics::{
 
 mod small_set;
 
+pub mod convert;
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
 pub enum KsEdition {
+    #[default]
     Vanilla,
     Plus,
     Extended,
@@ -42,12 +45,6 @@ pub struct KsExecutable {
     pub path: PathBuf,
 }
 
-impl Default for KsEdition {
-    fn default() -> Self {
-        Self::Vanilla
-    }
-}
-
 pub enum Reason {
     Ini(IniReason),
     Files(FilesReason),
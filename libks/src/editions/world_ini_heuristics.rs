@@ -88,7 +88,7 @@ pub fn check_ini_basic(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
     }
 
     // Check for KS Plus sections
-    for section_key in ["Loop Music", "Cutscene Color", "Custom Character"] {
+    for section_key in PLUS_SECTIONS {
         if world_ini.has_section(section_key) {
             let reason = HasSection(section_key.to_owned());
             return Some((Plus, reason));
@@ -97,28 +97,8 @@ pub fn check_ini_basic(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
     
     // Check for KS Plus world properties
     let world = world_ini.section("World")?;
-    let plus_world_props = static_set_lowercase![
-        "HoloFix",
-        "Character",
-        "Map",
-        "Font",
-        "Sign",
-        "Title",
-        "Subtitle",
-        "Powers",
-        "Coin",
-        "Artifact1",
-        "Artifact2",
-        "Artifact3",
-        "Artifact4",
-        "Artifact5",
-        "Artifact6",
-        "Artifact7",
-        "SinglePass",
-        "AltDie",
-    ];
 
-    if let Some((key, _)) = world.iter().find(|(key, _)| plus_world_props.has(key)) {
+    if let Some((key, _)) = world.iter().find(|(key, _)| is_plus_world_prop(key)) {
         let reason = WorldSectionHasProp(key.to_owned());
         return Some((Plus, reason));
     }
@@ -145,8 +125,10 @@ pub fn check_ini_basic(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
 ///     - Map properties
 ///     - Artifact warps
 ///     - Coin flags
+///
 /// KS Ex
 ///   - Screens: Signs can have custom labels besides A, B, and C. However, they only work with a Script.lua
+///
 /// KSA
 ///   - World: DeathByFalling
 ///   - Screen:
@@ -154,6 +136,7 @@ pub fn check_ini_basic(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
 ///     - Replace(R)
 ///     - Replace(G)
 ///     - Replace(B)
+///
 /// KS ACO
 ///   - COs:
 ///     - Does kill
@@ -168,8 +151,6 @@ pub fn check_ini_thorough(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
         "FlagWarpX(A)", "FlagWarpX(B)", "FlagWarpX(C)",
         "FlagWarpY(A)", "FlagWarpY(B)", "FlagWarpY(C)",
     ];
-    let plus_object_props = static_set_lowercase!["Bank", "Object", "Hurts", "Color"];
-    let plus_screen_props = static_set_lowercase_from_file!("data/plus_screen_props.txt");
     let adv_screen_props = static_set_lowercase![
         "ChangeToColor", "Replace(R)", "Replace(G)", "Replace(B)",
     ];
@@ -184,15 +165,6 @@ pub fn check_ini_thorough(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
         // Expects lowercase key
         parse_xy(key).is_some()
     };
-    let is_plus_b_bank_object_section = |key: &str| {
-        is_range_with_prefix(key, "custom object b", 1..=255)
-    };
-    let is_plus_coin_flag = |value: &str| {
-        is_range_with_prefix(&value.to_ascii_lowercase(), "coin", 1..=100)
-    };
-    let is_plus_artifact_warp = |value: &str| {
-        is_range_with_prefix(&value.to_ascii_lowercase(), "artifact", 1..=7)
-    };
 
     let mut adv_seen = HashSet::new();
     let mut adv_count = 0;
@@ -204,7 +176,7 @@ pub fn check_ini_thorough(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
         let section_key = section.key();
         let section_key_lower = section_key.to_ascii_lowercase();
 
-        if is_plus_b_bank_object_section(&section_key_lower) {
+        if is_plus_object_section(&section_key_lower) {
             let reason = HasSection(section_key.to_owned());
             return Some((Plus, reason));
         }
@@ -213,7 +185,7 @@ pub fn check_ini_thorough(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
                 let lower_key = key.to_ascii_lowercase();
                 let lower_key = lower_key.as_str();
 
-                if plus_object_props.has(&lower_key) {
+                if is_plus_object_prop(lower_key) {
                     let reason = ObjectSectionHasProp(section_key.to_owned(), key.to_owned());
                     return Some((Plus, reason));
                 }
@@ -228,7 +200,7 @@ pub fn check_ini_thorough(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
                 let lower_key = key.to_ascii_lowercase();
                 let lower_key = lower_key.as_str();
 
-                if plus_screen_props.has(&lower_key) {
+                if is_plus_screen_prop(lower_key) {
                     let reason = ScreenSectionHasProp(section_key.to_owned(), key.to_owned());
                     return Some((Plus, reason));
                 }
@@ -275,10 +247,66 @@ pub fn check_ini_thorough(world_ini: &Ini) -> Option<(KsEdition, IniReason)> {
     }
 }
 
+/// Sections introduced by KS Plus.
+pub(crate) const PLUS_SECTIONS: [&str; 3] = ["Loop Music", "Cutscene Color", "Custom Character"];
+
+/// Returns `true` if `key` is a `[World]` property introduced by KS Plus.
+pub(crate) fn is_plus_world_prop(key: &str) -> bool {
+    let props = static_set_lowercase![
+        "HoloFix",
+        "Character",
+        "Map",
+        "Font",
+        "Sign",
+        "Title",
+        "Subtitle",
+        "Powers",
+        "Coin",
+        "Artifact1",
+        "Artifact2",
+        "Artifact3",
+        "Artifact4",
+        "Artifact5",
+        "Artifact6",
+        "Artifact7",
+        "SinglePass",
+        "AltDie",
+    ];
+
+    props.has(&key.to_ascii_lowercase().as_str())
+}
+
+/// Returns `true` if `key` is a custom object property introduced by KS Plus.
+pub(crate) fn is_plus_object_prop(key: &str) -> bool {
+    let props = static_set_lowercase!["Bank", "Object", "Hurts", "Color"];
+    props.has(&key.to_ascii_lowercase().as_str())
+}
+
+/// Returns `true` if `key` is a screen property introduced by KS Plus.
+pub(crate) fn is_plus_screen_prop(key: &str) -> bool {
+    let props = static_set_lowercase_from_file!("data/plus_screen_props.txt");
+    props.has(&key.to_ascii_lowercase().as_str())
+}
+
+/// Returns `true` if the lowercase section key `key` names a KS Plus `[Custom Object B#]` section.
+pub(crate) fn is_plus_object_section(key: &str) -> bool {
+    is_range_with_prefix(key, "custom object b", 1..=255)
+}
+
+/// Returns `true` if `value` is a KS Plus coin flag, e.g. `Coin10`.
+pub(crate) fn is_plus_coin_flag(value: &str) -> bool {
+    is_range_with_prefix(&value.to_ascii_lowercase(), "coin", 1..=100)
+}
+
+/// Returns `true` if `value` is a KS Plus artifact warp, e.g. `Artifact3`.
+pub(crate) fn is_plus_artifact_warp(value: &str) -> bool {
+    is_range_with_prefix(&value.to_ascii_lowercase(), "artifact", 1..=7)
+}
+
 fn is_range_with_prefix<B, T>(s: &str, prefix: &str, range: B) -> bool
 where
     B: RangeBounds<T>,
-    T: FromStr + PartialOrd<T> + ?Sized,
+    T: FromStr + PartialOrd<T>,
 {
     let Some(suffix) = s.strip_prefix(prefix) else {
         return false;
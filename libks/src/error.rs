@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -28,11 +30,327 @@ pub enum KsError {
     MapBin(#[from] crate::MapBinError),
     #[error(transparent)]
     WorldIni(#[from] crate::WorldIniError),
+    #[error(transparent)]
+    World(#[from] crate::WorldError),
+    #[error(transparent)]
+    Install(#[from] crate::InstallError),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    Launch(#[from] crate::LaunchError),
     #[cfg(feature="image")]
     #[error(transparent)]
     Draw(#[from] crate::DrawError),
+    #[cfg(feature = "store")]
+    #[error(transparent)]
+    Store(#[from] crate::StoreError),
+    #[cfg(feature = "patch")]
+    #[error(transparent)]
+    Patch(#[from] crate::PatchError),
     #[error(transparent)]
     ReadString(#[from] crate::io_util::ReadStringError),
+    /// Another error with information about where it occurred. See [`ResultExt`].
+    #[error("{context}{source}")]
+    Context {
+        context: ErrorContext,
+        source: Box<KsError>,
+    },
+}
+
+impl KsError {
+    /// Returns a stable numeric code identifying the kind of error. Codes are grouped by
+    /// module: 1-99 general, 100-199 knytt_bin, 200-299 map_bin, 300-399 world_ini, 400-499
+    /// install, 500-599 launch, 600-699 draw, 700-799 world, 800-899 store, and 900-999 patch.
+    /// Codes are never reused.
+    pub fn code(&self) -> u16 {
+        use crate::{
+            io_util::ReadStringError,
+            InstallError,
+            KnyttBinError,
+            MapBinError,
+            WorldError,
+            WorldIniError,
+        };
+        #[cfg(not(target_family = "wasm"))]
+        use crate::LaunchError;
+
+        match self {
+            KsError::Io { .. } => 1,
+            KsError::FromUtf8 { .. } => 2,
+            KsError::Utf8 { .. } => 3,
+            KsError::ParseIntError { .. } => 4,
+            KsError::ReadString(err) => match err {
+                ReadStringError::TooLong => 5,
+                ReadStringError::Empty => 6,
+                ReadStringError::Io(_) => 1,
+            },
+            KsError::KnyttBin(err) => match err {
+                KnyttBinError::UnrecognizedSignature(_) => 101,
+                KnyttBinError::EmptyPath => 102,
+                KnyttBinError::IllegalPath(_) => 103,
+                KnyttBinError::BadFileName(_) => 104,
+                KnyttBinError::OversizedFile { .. } => 105,
+                KnyttBinError::MissingData { .. } => 106,
+                KnyttBinError::UnauthorizedOverwrite(_) => 107,
+                KnyttBinError::OutputPathExists(_) => 108,
+                KnyttBinError::MalformedFooter => 109,
+                KnyttBinError::FooterMismatch => 110,
+                KnyttBinError::BadSignature => 111,
+                KnyttBinError::ReservedName(_) => 112,
+                KnyttBinError::MalformedAttributes => 113,
+                KnyttBinError::ZeroPartSize => 114,
+            },
+            KsError::MapBin(err) => match err {
+                MapBinError::BadScreenPosition => 201,
+                MapBinError::MissingData { .. } => 202,
+                MapBinError::ScreenMissingData { .. } => 203,
+                MapBinError::NoScreen { .. } => 204,
+                MapBinError::ScreenExists { .. } => 205,
+                MapBinError::MoveOutOfRange { .. } => 206,
+                MapBinError::TileOutOfBounds { .. } => 207,
+                MapBinError::NotDecorativeLayer { .. } => 208,
+            },
+            KsError::WorldIni(err) => match err {
+                WorldIniError::BadEncoding { .. } => 301,
+                WorldIniError::Unencodable { .. } => 302,
+            },
+            KsError::World(err) => match err {
+                WorldError::Locked { .. } => 701,
+                WorldError::AlreadyExists(_) => 702,
+            },
+            KsError::Install(err) => match err {
+                InstallError::NotAWorld(_) => 401,
+                InstallError::AlreadyInstalled(_) => 402,
+                InstallError::NotInstalled(_) => 403,
+                InstallError::InvalidName(_) => 404,
+                #[cfg(feature = "watch")]
+                InstallError::Watch(_) => 405,
+            },
+            #[cfg(not(target_family = "wasm"))]
+            KsError::Launch(err) => match err {
+                LaunchError::NoRunner => 501,
+                LaunchError::MissingExecutable(_) => 502,
+                LaunchError::NoExecutable(_) => 503,
+                LaunchError::WorldNotFound(_) => 504,
+            },
+            #[cfg(feature="image")]
+            KsError::Draw(err) => match err {
+                crate::DrawError::Image { .. } => 601,
+                crate::DrawError::WrongDimensions { .. } => 602,
+                crate::DrawError::Encode(_) => 603,
+                crate::DrawError::TooLarge { .. } => 604,
+                crate::DrawError::InvalidOption { .. } => 605,
+            },
+            #[cfg(feature = "store")]
+            KsError::Store(err) => match err {
+                crate::StoreError::BadManifest { .. } => 801,
+                crate::StoreError::MissingBlob { .. } => 802,
+                crate::StoreError::CorruptBlob { .. } => 803,
+            },
+            #[cfg(feature = "patch")]
+            KsError::Patch(err) => match err {
+                crate::PatchError::Malformed => 901,
+                crate::PatchError::WrongBase => 902,
+                crate::PatchError::EntryMismatch { .. } => 903,
+                crate::PatchError::WrongResult => 904,
+            },
+            KsError::Context { source, .. } => source.code(),
+        }
+    }
+
+    /// Returns the innermost error, skipping any context.
+    pub fn root(&self) -> &KsError {
+        match self {
+            KsError::Context { source, .. } => source.root(),
+            err => err,
+        }
+    }
+
+    /// Returns the context attached to the error, merging nested contexts. The innermost
+    /// context wins when both specify the same field.
+    pub fn context(&self) -> ErrorContext {
+        match self {
+            KsError::Context { context, source } => context.clone().merge(source.context()),
+            _ => ErrorContext::default(),
+        }
+    }
+
+    /// Wraps the error with `context`.
+    pub fn with_context(self, context: ErrorContext) -> KsError {
+        KsError::Context {
+            context,
+            source: Box::new(self),
+        }
+    }
+}
+
+/// Information about where an error occurred.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// The file being read or written.
+    pub path: Option<PathBuf>,
+    /// The Map.bin entry, INI section or property, or .knytt.bin entry being processed.
+    pub key: Option<String>,
+    /// The screen being processed.
+    pub screen: Option<(i64, i64)>,
+    /// The byte offset into the file.
+    pub offset: Option<u64>,
+}
+
+impl ErrorContext {
+    /// Creates a context with only a path.
+    pub fn path<P>(path: P) -> ErrorContext
+    where
+        P: AsRef<Path>
+    {
+        ErrorContext {
+            path: Some(path.as_ref().to_owned()),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a context with only a key.
+    pub fn key<S>(key: S) -> ErrorContext
+    where
+        S: Into<String>
+    {
+        ErrorContext {
+            key: Some(key.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a context with only a byte offset.
+    pub fn offset(offset: u64) -> ErrorContext {
+        ErrorContext {
+            offset: Some(offset),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a context with only a screen.
+    pub fn screen(screen: (i64, i64)) -> ErrorContext {
+        ErrorContext {
+            screen: Some(screen),
+            ..Default::default()
+        }
+    }
+
+    /// Fills in the fields of `self` that `inner` specifies, preferring `inner`.
+    pub(crate) fn merge(self, inner: ErrorContext) -> ErrorContext {
+        ErrorContext {
+            path: inner.path.or(self.path),
+            key: inner.key.or(self.key),
+            screen: inner.screen.or(self.screen),
+            offset: inner.offset.or(self.offset),
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorContext {
+    /// Writes a prefix like `World.ini [x1000y1000] (offset 12): `, or nothing if the context is
+    /// empty.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(path) = &self.path {
+            parts.push(path.display().to_string());
+        }
+        if let Some(key) = &self.key {
+            parts.push(format!("[{key}]"));
+        }
+        if let Some((x, y)) = self.screen {
+            parts.push(format!("x{x}y{y}"));
+        }
+        if let Some(offset) = self.offset {
+            parts.push(format!("(offset {offset})"));
+        }
+
+        if parts.is_empty() {
+            Ok(())
+        }
+        else {
+            write!(f, "{}: ", parts.join(" "))
+        }
+    }
+}
+
+/// Adds context to the errors of results.
+pub trait ResultExt<T> {
+    /// Wraps the error, if any, with `context`.
+    fn context(self, context: ErrorContext) -> Result<T>;
+
+    /// Wraps the error, if any, with the file `path`.
+    fn with_path<P>(self, path: P) -> Result<T>
+    where
+        P: AsRef<Path>;
+}
+
+impl<T, E> ResultExt<T> for core::result::Result<T, E>
+where
+    E: Into<KsError>
+{
+    fn context(self, context: ErrorContext) -> Result<T> {
+        self.map_err(|err| err.into().with_context(context))
+    }
+
+    fn with_path<P>(self, path: P) -> Result<T>
+    where
+        P: AsRef<Path>
+    {
+        self.context(ErrorContext::path(path))
+    }
+}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for KsError {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(format!("libks::E{:04}", KsError::code(self))))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        use crate::{KnyttBinError, WorldIniError};
+
+        let help = match self.root() {
+            KsError::WorldIni(WorldIniError::BadEncoding { .. }) =>
+                "Re-save World.ini with Windows-1252 (ANSI) encoding.",
+            KsError::WorldIni(WorldIniError::Unencodable { .. }) =>
+                "Remove or replace characters that Windows-1252 can't represent.",
+            KsError::World(crate::WorldError::Locked { .. }) =>
+                "Wait for the other tool to finish. If it crashed, remove the lock with World::unlock.",
+            KsError::KnyttBin(KnyttBinError::UnauthorizedOverwrite(_)) =>
+                "Set UnpackOptions::allow_overwrite to replace the existing files.",
+            KsError::KnyttBin(KnyttBinError::ReservedName(_)) =>
+                "Rename the file in the archive, or set UnpackOptions::reserved_names to ReservedNamePolicy::Rename and fix the level's references to it.",
+            #[cfg(not(target_family = "wasm"))]
+            KsError::Launch(crate::LaunchError::NoRunner) =>
+                "Install Wine or configure a runner in LaunchOptions.",
+            _ => return None,
+        };
+
+        Some(Box::new(help))
+    }
 }
 
 pub type Result<T> = core::result::Result<T, KsError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_is_merged_and_displayed() {
+        let result: core::result::Result<(), _> = Err(crate::MapBinError::BadScreenPosition);
+        let err = result
+            .context(ErrorContext::screen((1000, 1000)))
+            .with_path("Map.bin")
+            .unwrap_err();
+
+        assert_eq!(err.code(), 201);
+        assert!(matches!(err.root(), KsError::MapBin(crate::MapBinError::BadScreenPosition)));
+        assert_eq!(err.context(), ErrorContext {
+            path: Some("Map.bin".into()),
+            screen: Some((1000, 1000)),
+            ..Default::default()
+        });
+        assert!(err.to_string().starts_with("Map.bin: x1000y1000: "));
+    }
+}
@@ -0,0 +1,16 @@
+use std::path::PathBuf;
+
+#[derive(thiserror::Error, Debug)]
+pub enum InstallError {
+    #[error("{0} is neither a .knytt.bin file nor a level directory.")]
+    NotAWorld(PathBuf),
+    #[error("A level is already installed at {0}.")]
+    AlreadyInstalled(PathBuf),
+    #[error("No level named `{0}` is installed.")]
+    NotInstalled(String),
+    #[error("`{0}` is not a valid level directory name.")]
+    InvalidName(String),
+    #[cfg(feature = "watch")]
+    #[error("Failed to watch the Worlds directory: {0}")]
+    Watch(#[from] notify_debouncer_mini::notify::Error),
+}
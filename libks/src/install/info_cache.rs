@@ -0,0 +1,210 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use libks_ini::Ini;
+
+use crate::{error::ResultExt, world, world_ini, Result};
+
+/// The name of the file in the `Worlds` directory that [`InfoCache`] is stored in.
+pub const INFO_CACHE_FILE_NAME: &str = ".libks-info-cache.ini";
+
+/// The `[World]` details of one installed level, as recorded by [`InfoCache`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InfoCacheEntry {
+    /// When the level's World.ini was last modified, in nanoseconds since the Unix epoch.
+    pub modified: u128,
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A cache of the `Name`, `Author`, and `Description` of every level installed in a KS
+/// installation, keyed by directory name.
+///
+/// Listing levels otherwise means parsing every World.ini. The cache is refreshed by
+/// [`InfoCache::refresh`], which only reparses World.ini files whose modification time has
+/// changed. It's stored in the `Worlds` directory under [`INFO_CACHE_FILE_NAME`], where KS
+/// ignores it because it isn't a directory. KS itself doesn't read or write this file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InfoCache {
+    entries: BTreeMap<String, InfoCacheEntry>,
+}
+
+impl InfoCache {
+    /// Reads the cache of the KS installation at `ks_dir`. A missing cache file results in an
+    /// empty cache, and entries without a `Folder` are skipped.
+    pub fn load<P>(ks_dir: P) -> Result<InfoCache>
+    where
+        P: AsRef<Path>
+    {
+        let path = info_cache_path(ks_dir);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(InfoCache::default()),
+            Err(err) => return Err(err).with_path(path),
+        };
+
+        let ini = Ini::new(&contents);
+        let entries = ini.iter_sections()
+            .filter_map(|section| {
+                let folder = section.get("Folder")?.to_owned();
+                let entry = InfoCacheEntry {
+                    modified: section.get("Modified").and_then(|value| value.parse().ok()).unwrap_or(0),
+                    name: section.get("Name").map(str::to_owned),
+                    author: section.get("Author").map(str::to_owned),
+                    description: section.get("Description").map(str::to_owned),
+                };
+                Some((folder, entry))
+            })
+            .collect();
+
+        Ok(InfoCache { entries })
+    }
+
+    /// Writes the cache into the KS installation at `ks_dir` as UTF-8.
+    pub fn save<P>(&self, ks_dir: P) -> Result<()>
+    where
+        P: AsRef<Path>
+    {
+        let mut ini = Ini::new("");
+        for (i, (folder, entry)) in self.entries.iter().enumerate() {
+            let mut section = ini.append_section(&format!("World{}", i + 1));
+            section.set("Folder", folder.clone());
+            section.set("Modified", entry.modified.to_string());
+            for (key, value) in [("Name", &entry.name), ("Author", &entry.author), ("Description", &entry.description)] {
+                if let Some(value) = value {
+                    section.set(key, value.clone());
+                }
+            }
+        }
+
+        let path = info_cache_path(ks_dir);
+        fs::write(&path, ini.to_string()).with_path(path)?;
+
+        Ok(())
+    }
+
+    /// Brings the cache up to date with the `Worlds` directory of the KS installation at
+    /// `ks_dir`. Levels that were removed are dropped, and new levels and levels whose World.ini
+    /// was modified are reparsed. Directories without a World.ini aren't levels and are skipped.
+    ///
+    /// Returns `true` if anything changed. The cache isn't saved automatically.
+    pub fn refresh<P>(&mut self, ks_dir: P) -> Result<bool>
+    where
+        P: AsRef<Path>
+    {
+        let worlds_dir = ks_dir.as_ref().join("Worlds");
+        let mut entries = BTreeMap::new();
+        let mut changed = false;
+
+        for dir_entry in fs::read_dir(&worlds_dir).with_path(&worlds_dir)? {
+            let dir_entry = dir_entry.with_path(&worlds_dir)?;
+            let Some(folder) = dir_entry.file_name().to_str().map(str::to_owned) else { continue };
+            if world::is_libks_file(&folder) || !dir_entry.file_type().with_path(dir_entry.path())?.is_dir() {
+                continue;
+            }
+
+            let ini_path = dir_entry.path().join("World.ini");
+            let Ok(modified) = fs::metadata(&ini_path).and_then(|metadata| metadata.modified()) else {
+                continue;
+            };
+            let modified = modified.duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_nanos())
+                .unwrap_or_default();
+
+            let entry = match self.entries.remove(&folder) {
+                Some(entry) if entry.modified == modified => entry,
+                _ => {
+                    changed = true;
+                    let ini = world_ini::load_ini(&ini_path)?;
+                    let get = |key: &str| ini.get_in("World", key).map(str::to_owned);
+                    InfoCacheEntry {
+                        modified,
+                        name: get("Name"),
+                        author: get("Author"),
+                        description: get("Description"),
+                    }
+                },
+            };
+            entries.insert(folder, entry);
+        }
+
+        changed |= !self.entries.is_empty();
+        self.entries = entries;
+
+        Ok(changed)
+    }
+
+    /// Returns the entry for the level whose directory is named `folder`.
+    pub fn get(&self, folder: &str) -> Option<&InfoCacheEntry> {
+        self.entries.get(folder)
+    }
+
+    /// Iterates over the directory names and entries of the cached levels, sorted by directory
+    /// name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &InfoCacheEntry)> {
+        self.entries.iter().map(|(folder, entry)| (folder.as_str(), entry))
+    }
+}
+
+/// Returns the path of the [`InfoCache`] file of the KS installation at `ks_dir`.
+pub fn info_cache_path<P>(ks_dir: P) -> PathBuf
+where
+    P: AsRef<Path>
+{
+    ks_dir.as_ref()
+        .join("Worlds")
+        .join(INFO_CACHE_FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_tracks_changes_and_round_trips() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ks_dir = temp_dir.path();
+        let worlds_dir = ks_dir.join("Worlds");
+        for (folder, ini) in [
+            ("Me - First", "[World]\nName=First\nAuthor=Me\nDescription=The first one\n"),
+            ("Me - Second", "[World]\nName=Second\n"),
+        ] {
+            fs::create_dir_all(worlds_dir.join(folder)).unwrap();
+            fs::write(worlds_dir.join(folder).join("World.ini"), ini).unwrap();
+        }
+        fs::create_dir_all(worlds_dir.join("Not a level")).unwrap();
+        fs::create_dir_all(worlds_dir.join(".libks-install-1/Me - Third")).unwrap();
+
+        let mut cache = InfoCache::load(ks_dir).unwrap();
+        assert_eq!(cache, InfoCache::default());
+        assert!(cache.refresh(ks_dir).unwrap());
+        assert!(!cache.refresh(ks_dir).unwrap());
+
+        let folders: Vec<_> = cache.iter().map(|(folder, _)| folder).collect();
+        assert_eq!(folders, ["Me - First", "Me - Second"]);
+        let first = cache.get("Me - First").unwrap();
+        assert_eq!(first.name.as_deref(), Some("First"));
+        assert_eq!(first.author.as_deref(), Some("Me"));
+        assert_eq!(first.description.as_deref(), Some("The first one"));
+        assert_eq!(cache.get("Me - Second").unwrap().author, None);
+
+        cache.save(ks_dir).unwrap();
+        assert_eq!(InfoCache::load(ks_dir).unwrap(), cache);
+
+        // Edits are picked up by modification time, and removed levels are dropped
+        let ini_path = worlds_dir.join("Me - Second/World.ini");
+        fs::write(&ini_path, "[World]\nName=Renamed\n").unwrap();
+        let later = fs::metadata(&ini_path).unwrap().modified().unwrap() + std::time::Duration::from_secs(1);
+        fs::File::options().write(true).open(&ini_path).unwrap().set_modified(later).unwrap();
+        fs::remove_dir_all(worlds_dir.join("Me - First")).unwrap();
+
+        assert!(cache.refresh(ks_dir).unwrap());
+        assert_eq!(cache.get("Me - First"), None);
+        assert_eq!(cache.get("Me - Second").unwrap().name.as_deref(), Some("Renamed"));
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    io_util,
+    knytt_bin::{self, UnpackOptions},
+    world,
+    Result,
+};
+
+mod error;
+pub use error::InstallError;
+
+mod info_cache;
+pub use info_cache::{info_cache_path, InfoCache, InfoCacheEntry, INFO_CACHE_FILE_NAME};
+
+mod naming;
+pub use naming::{canonical_folder_name, check_folder_name, FolderNameMismatch};
+
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "watch")]
+pub use watch::{watch_worlds, WorldChange, WorldsWatcher, WATCH_DEBOUNCE};
+
+/// What [`install_world`] does when a level with the same directory name is already installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameCollision {
+    /// Return [`InstallError::AlreadyInstalled`]. This is the default.
+    #[default]
+    Fail,
+    /// Delete the installed level and install the new one in its place.
+    Replace,
+    /// Install the new level alongside the old one with a numbered suffix, e.g. `Name (2)`.
+    Rename,
+}
+
+/// Configures the behavior of [`install_world`].
+#[derive(Debug, Clone)]
+pub struct InstallOptions {
+    /// What to do if the level is already installed. Defaults to [`NameCollision::Fail`].
+    pub on_collision: NameCollision,
+    /// If `true`, a level replaced due to [`NameCollision::Replace`] is first packed into a
+    /// timestamped .knytt.bin beside it. Defaults to `true`.
+    pub backup_previous: bool,
+}
+
+impl Default for InstallOptions {
+    fn default() -> Self {
+        Self {
+            on_collision: NameCollision::default(),
+            backup_previous: true,
+        }
+    }
+}
+
+/// Installs the level at `source`, which may be a .knytt.bin file or a level directory, into the
+/// `Worlds` directory of the KS installation at `ks_dir`.
+///
+/// On success, it returns the directory the level was installed into.
+pub fn install_world<P1, P2>(source: P1, ks_dir: P2, options: InstallOptions) -> Result<PathBuf>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>,
+{
+    let source = source.as_ref();
+    let worlds_dir = ks_dir.as_ref().join("Worlds");
+    fs::create_dir_all(&worlds_dir)?;
+
+    if source.is_dir() {
+        return install_dir(source, &worlds_dir, &options, false);
+    }
+    if !source.is_file() {
+        return Err(InstallError::NotAWorld(source.to_owned()).into());
+    }
+
+    // Unpack next to the destination so the level can be moved into place with a rename
+    let staging_dir = worlds_dir.join(format!(".libks-install-{}", std::process::id()));
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+
+    let result = knytt_bin::unpack_with_options(source, &staging_dir, UnpackOptions::default())
+        .and_then(|unpacked_dir| install_dir(&unpacked_dir, &worlds_dir, &options, true));
+    let _ = fs::remove_dir_all(&staging_dir);
+
+    result
+}
+
+/// Uninstalls the level whose directory in the `Worlds` directory of `ks_dir` is named `name`.
+///
+/// KS doesn't cache anything about installed levels outside of their directories, so deleting
+/// the directory removes the level completely. Save files are left alone, and an [`InfoCache`]
+/// drops the level the next time it's refreshed.
+pub fn uninstall_world<P>(ks_dir: P, name: &str) -> Result<()>
+where
+    P: AsRef<Path>
+{
+    if !is_valid_world_name(name) {
+        return Err(InstallError::InvalidName(name.to_owned()).into());
+    }
+
+    let world_dir = ks_dir.as_ref().join("Worlds").join(name);
+    if !world_dir.is_dir() {
+        return Err(InstallError::NotInstalled(name.to_owned()).into());
+    }
+
+    world::ensure_unlocked(&world_dir)?;
+    fs::remove_dir_all(world_dir)?;
+
+    Ok(())
+}
+
+/// Packs the level in `world_dir` into a .knytt.bin beside it, named after the directory and the
+/// current Unix time, e.g. `Author - Level.1700000000.knytt.bin`. KS only lists directories, so
+/// the backup doesn't show up in game.
+///
+/// On success, it returns the path of the backup.
+pub fn backup_world<P>(world_dir: P) -> Result<PathBuf>
+where
+    P: AsRef<Path>
+{
+    let world_dir = world_dir.as_ref();
+    let Some(name) = world_dir.file_name().and_then(|name| name.to_str()) else {
+        return Err(InstallError::NotAWorld(world_dir.to_owned()).into());
+    };
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let bin_path = world_dir.with_file_name(format!("{name}.{timestamp}.knytt.bin"));
+
+    knytt_bin::pack(world_dir, &bin_path)?;
+
+    Ok(bin_path)
+}
+
+/// Lists the backups of the level in `world_dir` made by [`backup_world`], oldest first.
+pub fn list_backups<P>(world_dir: P) -> Result<Vec<PathBuf>>
+where
+    P: AsRef<Path>
+{
+    let world_dir = world_dir.as_ref();
+    let Some(name) = world_dir.file_name().and_then(|name| name.to_str()) else {
+        return Ok(Vec::new());
+    };
+    let Some(parent) = world_dir.parent() else {
+        return Ok(Vec::new());
+    };
+
+    let prefix = format!("{name}.");
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(parent)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(timestamp) = file_name.to_str()
+            .and_then(|file_name| file_name.strip_prefix(&prefix))
+            .and_then(|rest| rest.strip_suffix(".knytt.bin"))
+            .and_then(|timestamp| timestamp.parse::<u64>().ok())
+        else {
+            continue;
+        };
+
+        backups.push((timestamp, entry.path()));
+    }
+
+    backups.sort();
+    Ok(backups.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Restores the backup at `backup_path`, replacing the level it was made from. The backup is
+/// unpacked beside itself, so it must not have been moved.
+///
+/// The backup is fully unpacked before the current version of the level is touched, so a
+/// corrupt backup leaves the level untouched. The current version is then renamed aside and the
+/// backup renamed into its place; if that fails, the current version is moved back. It's only
+/// deleted once the backup is in place. On success, it returns the level's directory.
+pub fn restore_backup<P>(backup_path: P) -> Result<PathBuf>
+where
+    P: AsRef<Path>
+{
+    let backup_path = backup_path.as_ref();
+    let Some(parent) = backup_path.parent() else {
+        return Err(InstallError::NotAWorld(backup_path.to_owned()).into());
+    };
+
+    let staging_dir = parent.join(format!(".libks-restore-{}", std::process::id()));
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+
+    let result = knytt_bin::unpack_with_options(backup_path, staging_dir.join("backup"), UnpackOptions::default())
+        .and_then(|unpacked_dir| {
+            let name = unpacked_dir.file_name()
+                .expect("unpacked directory should have a name");
+            let world_dir = parent.join(name);
+            if !world_dir.exists() {
+                fs::rename(&unpacked_dir, &world_dir)?;
+                return Ok(world_dir);
+            }
+
+            world::ensure_unlocked(&world_dir)?;
+            let previous_dir = staging_dir.join("previous");
+            fs::rename(&world_dir, &previous_dir)?;
+            if let Err(err) = fs::rename(&unpacked_dir, &world_dir) {
+                let _ = fs::rename(&previous_dir, &world_dir);
+                return Err(err.into());
+            }
+
+            Ok(world_dir)
+        });
+    let _ = fs::remove_dir_all(&staging_dir);
+
+    result
+}
+
+/// Installs the level directory `world_dir` into `worlds_dir`. If `is_staged`, the directory is
+/// moved rather than copied.
+fn install_dir(world_dir: &Path, worlds_dir: &Path, options: &InstallOptions, is_staged: bool) -> Result<PathBuf> {
+    let Some(name) = world_dir.file_name().and_then(|name| name.to_str()) else {
+        return Err(InstallError::NotAWorld(world_dir.to_owned()).into());
+    };
+    if !world_dir.join("World.ini").is_file() {
+        return Err(InstallError::NotAWorld(world_dir.to_owned()).into());
+    }
+
+    let mut dest = worlds_dir.join(name);
+    if dest.exists() {
+        match options.on_collision {
+            NameCollision::Fail => {
+                return Err(InstallError::AlreadyInstalled(dest).into());
+            },
+            NameCollision::Replace => {
+                world::ensure_unlocked(&dest)?;
+                if options.backup_previous {
+                    backup_world(&dest)?;
+                }
+                fs::remove_dir_all(&dest)?;
+            },
+            NameCollision::Rename => {
+                dest = (2..)
+                    .map(|n| worlds_dir.join(format!("{name} ({n})")))
+                    .find(|path| !path.exists())
+                    .expect("there should be an unused name");
+            },
+        }
+    }
+
+    if is_staged {
+        fs::rename(world_dir, &dest)?;
+    }
+    else {
+        io_util::copy_dir_all(world_dir, &dest)?;
+    }
+
+    Ok(dest)
+}
+
+/// Returns `true` if `name` can safely be joined to the `Worlds` directory.
+fn is_valid_world_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collisions_follow_the_options() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ks_dir = temp_dir.path().join("KS");
+        let source_dir = temp_dir.path().join("Me - Level");
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::write(source_dir.join("World.ini"), "[World]\nName=First\n").unwrap();
+        let installed_dir = ks_dir.join("Worlds/Me - Level");
+
+        assert_eq!(install_world(&source_dir, &ks_dir, InstallOptions::default()).unwrap(), installed_dir);
+        assert!(source_dir.join("World.ini").is_file());
+        assert!(matches!(
+            install_world(&source_dir, &ks_dir, InstallOptions::default()),
+            Err(crate::KsError::Install(InstallError::AlreadyInstalled(path))) if path == installed_dir,
+        ));
+
+        let options = InstallOptions { on_collision: NameCollision::Rename, ..Default::default() };
+        assert_eq!(install_world(&source_dir, &ks_dir, options).unwrap(), ks_dir.join("Worlds/Me - Level (2)"));
+
+        // Replacing from a .knytt.bin backs up the installed level
+        fs::write(source_dir.join("World.ini"), "[World]\nName=Second\n").unwrap();
+        let bin_path = temp_dir.path().join("Me - Level.knytt.bin");
+        knytt_bin::pack(&source_dir, &bin_path).unwrap();
+        let options = InstallOptions { on_collision: NameCollision::Replace, ..Default::default() };
+        assert_eq!(install_world(&bin_path, &ks_dir, options).unwrap(), installed_dir);
+        assert_eq!(fs::read_to_string(installed_dir.join("World.ini")).unwrap(), "[World]\nName=Second\n");
+        assert_eq!(list_backups(&installed_dir).unwrap().len(), 1);
+        assert!(!fs::read_dir(ks_dir.join("Worlds")).unwrap()
+            .any(|entry| world::is_libks_file(entry.unwrap().file_name().to_str().unwrap())));
+
+        assert!(matches!(
+            install_world(temp_dir.path().join("Missing"), &ks_dir, InstallOptions::default()),
+            Err(crate::KsError::Install(InstallError::NotAWorld(_))),
+        ));
+    }
+
+    #[test]
+    fn uninstall_checks_the_name() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ks_dir = temp_dir.path();
+        fs::create_dir_all(ks_dir.join("Worlds/Me - Level")).unwrap();
+
+        for name in ["", "..", "Me/Level"] {
+            assert!(matches!(
+                uninstall_world(ks_dir, name),
+                Err(crate::KsError::Install(InstallError::InvalidName(_))),
+            ));
+        }
+        assert!(matches!(
+            uninstall_world(ks_dir, "Me - Missing"),
+            Err(crate::KsError::Install(InstallError::NotInstalled(_))),
+        ));
+
+        uninstall_world(ks_dir, "Me - Level").unwrap();
+        assert!(!ks_dir.join("Worlds/Me - Level").exists());
+        assert!(ks_dir.join("Worlds").is_dir());
+    }
+
+    #[test]
+    fn backups_replace_the_level() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let worlds_dir = temp_dir.path().join("Worlds");
+        let world_dir = worlds_dir.join("Me - Level");
+        fs::create_dir_all(&world_dir).unwrap();
+        fs::write(world_dir.join("World.ini"), "[World]\nName=Original\n").unwrap();
+
+        let backup_path = backup_world(&world_dir).unwrap();
+        assert_eq!(list_backups(&world_dir).unwrap(), std::slice::from_ref(&backup_path));
+
+        fs::write(world_dir.join("World.ini"), "[World]\nName=Edited\n").unwrap();
+        fs::write(world_dir.join("Extra.txt"), "").unwrap();
+        assert_eq!(restore_backup(&backup_path).unwrap(), world_dir);
+        assert_eq!(fs::read_to_string(world_dir.join("World.ini")).unwrap(), "[World]\nName=Original\n");
+        assert!(!world_dir.join("Extra.txt").exists());
+
+        // A corrupt backup leaves the level alone, and nothing is left behind
+        fs::write(&backup_path, "not a backup").unwrap();
+        assert!(restore_backup(&backup_path).is_err());
+        assert_eq!(fs::read_to_string(world_dir.join("World.ini")).unwrap(), "[World]\nName=Original\n");
+        let mut names: Vec<_> = fs::read_dir(&worlds_dir).unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, [world_dir.file_name().unwrap(), backup_path.file_name().unwrap()]);
+    }
+}
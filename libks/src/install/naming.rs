@@ -0,0 +1,90 @@
+use crate::world::World;
+
+/// Characters that Windows doesn't allow in file names.
+const FORBIDDEN_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Names that Windows reserves for devices, regardless of extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A level directory whose name doesn't follow the `Author - Title` convention for its World.ini.
+/// See [`check_folder_name`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FolderNameMismatch {
+    /// The name of the directory.
+    pub actual: String,
+    /// The name given by [`canonical_folder_name`].
+    pub expected: String,
+}
+
+/// Returns the conventional directory name for a level, `Author - Title`, or just `Title` if
+/// `author` is empty.
+///
+/// Characters that Windows doesn't allow in file names or that can't be encoded in Windows-1252
+/// (as .knytt.bin requires) are removed, as are leading and trailing spaces and trailing dots.
+/// Returns `None` if nothing usable is left or the result is a reserved name such as `CON`.
+pub fn canonical_folder_name(author: &str, title: &str) -> Option<String> {
+    let author = sanitize(author);
+    let title = sanitize(title);
+
+    let name = match (author.is_empty(), title.is_empty()) {
+        (_, true) => return None,
+        (true, false) => title,
+        (false, false) => format!("{author} - {title}"),
+    };
+
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+    let is_reserved = RESERVED_NAMES.iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved));
+    (!is_reserved).then_some(name)
+}
+
+/// Checks whether the directory of `world` is named after the `Author` and `Name` in its
+/// World.ini. Names are compared without regard to case, as they are on Windows.
+///
+/// Returns `None` if the names match or the World.ini doesn't give a usable name.
+pub fn check_folder_name(world: &World) -> Option<FolderNameMismatch> {
+    let author = world.ini.get_in("World", "Author").unwrap_or_default();
+    let title = world.ini.get_in("World", "Name").unwrap_or_default();
+    let expected = canonical_folder_name(author, title)?;
+    let actual = world.dir_name()?;
+
+    (!actual.eq_ignore_ascii_case(&expected))
+        .then(|| FolderNameMismatch {
+            actual: actual.to_owned(),
+            expected,
+        })
+}
+
+fn sanitize(s: &str) -> String {
+    let s: String = s.chars()
+        .filter(|&c| !c.is_control() && !FORBIDDEN_CHARS.contains(&c) && is_windows_1252(c))
+        .collect();
+
+    s.trim()
+        .trim_end_matches(['.', ' '])
+        .to_owned()
+}
+
+fn is_windows_1252(c: char) -> bool {
+    let mut buf = [0; 4];
+    let (_, _, had_errors) = encoding_rs::WINDOWS_1252.encode(c.encode_utf8(&mut buf));
+    !had_errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_sanitized() {
+        assert_eq!(canonical_folder_name("Nifflas", "The Machine").as_deref(), Some("Nifflas - The Machine"));
+        assert_eq!(canonical_folder_name(" ", "What? No: 2...").as_deref(), Some("What No 2"));
+        assert_eq!(canonical_folder_name("A/B", "Café ☃").as_deref(), Some("AB - Café"));
+        assert_eq!(canonical_folder_name("Me", "???"), None);
+        assert_eq!(canonical_folder_name("", "con"), None);
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+    time::Duration,
+};
+
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult,
+    Debouncer,
+};
+
+use crate::{world, Result};
+use super::InstallError;
+
+/// How long the filesystem has to be quiet before changes are reported.
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// A change to the `Worlds` directory reported by [`watch_worlds`]. Each holds the name of the
+/// level's directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WorldChange {
+    Added(String),
+    Removed(String),
+    Modified(String),
+}
+
+/// Watches the `Worlds` directory of a KS installation. Watching stops when this is dropped.
+pub struct WorldsWatcher {
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+/// Watches the `Worlds` directory of the KS installation at `ks_dir` and calls `callback` with
+/// the levels that were added, removed, or modified.
+///
+/// Changes are debounced by [`WATCH_DEBOUNCE`], so unpacking a level produces a single
+/// [`WorldChange::Added`] rather than one event per file. `callback` runs on a background
+/// thread.
+pub fn watch_worlds<P, F>(ks_dir: P, mut callback: F) -> Result<WorldsWatcher>
+where
+    P: AsRef<Path>,
+    F: FnMut(Vec<WorldChange>) + Send + 'static,
+{
+    let worlds_dir = ks_dir.as_ref().join("Worlds");
+    let mut known = list_worlds(&worlds_dir)?;
+
+    let handler_worlds_dir = worlds_dir.clone();
+    let mut debouncer = new_debouncer(WATCH_DEBOUNCE, move |result: DebounceEventResult| {
+        let Ok(events) = result else { return };
+
+        let touched: HashSet<String> = events.iter()
+            .filter_map(|event| world_name(&handler_worlds_dir, &event.path))
+            .collect();
+
+        // Sorted for deterministic output
+        let mut changes = BTreeMap::new();
+        for name in touched {
+            let exists = handler_worlds_dir.join(&name).is_dir();
+            let change = match (known.contains(&name), exists) {
+                (false, true) => WorldChange::Added(name.clone()),
+                (true, false) => WorldChange::Removed(name.clone()),
+                (true, true) => WorldChange::Modified(name.clone()),
+                (false, false) => continue,
+            };
+
+            if exists {
+                known.insert(name.clone());
+            }
+            else {
+                known.remove(&name);
+            }
+            changes.insert(name, change);
+        }
+
+        if !changes.is_empty() {
+            callback(changes.into_values().collect());
+        }
+    }).map_err(InstallError::from)?;
+
+    debouncer.watcher()
+        .watch(&worlds_dir, RecursiveMode::Recursive)
+        .map_err(InstallError::from)?;
+
+    Ok(WorldsWatcher { _debouncer: debouncer })
+}
+
+/// Lists the names of the level directories in `worlds_dir`, leaving out the ones libks stages
+/// into.
+fn list_worlds(worlds_dir: &Path) -> Result<HashSet<String>> {
+    let mut worlds = HashSet::new();
+    for entry in fs::read_dir(worlds_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            if let Some(name) = entry.file_name().to_str().filter(|name| !world::is_libks_file(name)) {
+                worlds.insert(name.to_owned());
+            }
+        }
+    }
+
+    Ok(worlds)
+}
+
+/// Returns the name of the level directory in `worlds_dir` that contains `path`. The files and
+/// directories libks creates there, like the ones [`install_world`](super::install_world)
+/// stages into, are ignored.
+fn world_name(worlds_dir: &Path, path: &Path) -> Option<String> {
+    let name = path.strip_prefix(worlds_dir).ok()?
+        .iter().next()?
+        .to_str()?;
+
+    (!world::is_libks_file(name)).then(|| name.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn staging_dirs_are_not_worlds() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let worlds_dir = temp_dir.path();
+        for name in ["Me - Level", ".libks-install-1", ".libks-restore-1", ".Hidden"] {
+            fs::create_dir_all(worlds_dir.join(name)).unwrap();
+        }
+        fs::write(worlds_dir.join(crate::install::INFO_CACHE_FILE_NAME), "").unwrap();
+
+        let mut worlds: Vec<_> = list_worlds(worlds_dir).unwrap().into_iter().collect();
+        worlds.sort();
+        assert_eq!(worlds, [".Hidden", "Me - Level"]);
+
+        assert_eq!(world_name(worlds_dir, &worlds_dir.join("Me - Level/World.ini")).as_deref(), Some("Me - Level"));
+        assert_eq!(world_name(worlds_dir, &worlds_dir.join(".libks-install-1/Me - Level/World.ini")), None);
+        assert_eq!(world_name(worlds_dir, &worlds_dir.join(".libks-info-cache.ini")), None);
+        assert_eq!(world_name(worlds_dir, Path::new("/elsewhere/World.ini")), None);
+    }
+}
@@ -1,7 +1,9 @@
 use std::{
     cmp::min,
+    ffi::OsString,
+    fs,
     io::{self, Read, BufRead},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use thiserror::Error;
@@ -150,3 +152,55 @@ where
         Err(err) => Err(err),
     }
 }
+
+/// Recursively copies the directory at `from` to `to`, creating `to` if necessary.
+pub fn copy_dir_all<P1, P2>(from: P1, to: P2) -> Result<(), io::Error>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>,
+{
+    let to = to.as_ref();
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let dest = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(entry.path(), dest)?;
+        }
+        else {
+            fs::copy(entry.path(), dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns a form of the existing directory `dir` that long relative paths can be joined to
+/// with [`join_relative`].
+///
+/// On Windows, this is a `\\?\`-prefixed path, which isn't limited to `MAX_PATH` (260)
+/// characters. Elsewhere, it's just the canonical path.
+pub fn long_path_root<P>(dir: P) -> Result<PathBuf, io::Error>
+where
+    P: AsRef<Path>
+{
+    fs::canonicalize(dir)
+}
+
+/// Joins `relative` to `root` one component at a time. Paths with the `\\?\` prefix aren't
+/// normalized by Windows, so this makes sure `/` in `relative` becomes the native separator.
+pub fn join_relative(root: &Path, relative: &Path) -> PathBuf {
+    let mut path = root.to_owned();
+    for component in relative.iter() {
+        path.push(component);
+    }
+    path
+}
+
+/// Appends `suffix` to the file name of `path`, e.g. `Level.knytt.bin.001`.
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    path.into()
+}
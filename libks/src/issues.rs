@@ -0,0 +1,136 @@
+use crate::{
+    error::ErrorContext,
+    map_bin::ParseWarning,
+    KsError,
+    Result,
+};
+
+/// A problem that was tolerated while loading data instead of aborting the load.
+#[derive(Debug)]
+pub enum Issue {
+    /// An abnormality in Map.bin data. The affected entry was skipped or truncated.
+    MapBin {
+        context: ErrorContext,
+        warning: ParseWarning,
+    },
+    /// An error that was recovered from. The data it affected was skipped or replaced with a
+    /// default.
+    Error(KsError),
+}
+
+impl Issue {
+    /// Returns information about where the issue occurred.
+    pub fn context(&self) -> ErrorContext {
+        match self {
+            Issue::MapBin { context, .. } => context.clone(),
+            Issue::Error(err) => err.context(),
+        }
+    }
+
+    /// Adds `context` to the issue. Fields the issue already specifies are kept.
+    pub fn with_context(self, context: ErrorContext) -> Issue {
+        match self {
+            Issue::MapBin { context: inner, warning } => Issue::MapBin {
+                context: context.merge(inner),
+                warning,
+            },
+            Issue::Error(err) => Issue::Error(err.with_context(context)),
+        }
+    }
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::MapBin { context, warning } => write!(f, "{context}{warning}"),
+            Issue::Error(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<KsError> for Issue {
+    fn from(err: KsError) -> Self {
+        Issue::Error(err)
+    }
+}
+
+/// Collects the issues found by the `*_with_issues` loaders, such as
+/// [`World::load_with_issues`](crate::World::load_with_issues).
+///
+/// Those loaders tolerate junk data the way KS does, so a single load of a broken level gathers
+/// every problem rather than failing at the first.
+#[derive(Debug, Default)]
+pub struct Issues {
+    issues: Vec<Issue>,
+}
+
+impl Issues {
+    /// Creates an empty collector.
+    pub fn new() -> Issues {
+        Issues { issues: Vec::new() }
+    }
+
+    /// Records `issue`.
+    pub fn push<I>(&mut self, issue: I)
+    where
+        I: Into<Issue>
+    {
+        self.issues.push(issue.into());
+    }
+
+    /// Returns the value of `result`, or records its error and returns `None`.
+    pub fn report<T>(&mut self, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.push(err);
+                None
+            },
+        }
+    }
+
+    /// Moves the issues in `other` into `self`, adding `context` to each.
+    pub fn append_with_context(&mut self, other: Issues, context: ErrorContext) {
+        self.issues.extend(other.issues.into_iter()
+            .map(|issue| issue.with_context(context.clone())));
+    }
+
+    /// Returns the number of issues.
+    pub fn len(&self) -> usize {
+        self.issues.len()
+    }
+
+    /// Returns `true` if no issues have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns `true` if any recorded issue is an error.
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter()
+            .any(|issue| matches!(issue, Issue::Error(_)))
+    }
+
+    /// Returns an iterator over the issues in the order they were found.
+    pub fn iter(&self) -> std::slice::Iter<'_, Issue> {
+        self.issues.iter()
+    }
+}
+
+impl IntoIterator for Issues {
+    type Item = Issue;
+    type IntoIter = std::vec::IntoIter<Issue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.issues.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Issues {
+    type Item = &'a Issue;
+    type IntoIter = std::slice::Iter<'a, Issue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.issues.iter()
+    }
+}
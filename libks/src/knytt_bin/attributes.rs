@@ -0,0 +1,191 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{error::ResultExt, io_util, Result};
+use super::KnyttBinError;
+
+/// The bytes that begin an attributes file, a libks extension that preserves the modification
+/// times and read-only flags of packed files. See
+/// [`PackOptions::preserve_attributes`](super::PackOptions::preserve_attributes).
+///
+/// The attributes are kept out of the .knytt.bin, which stays readable by anything, in a file
+/// next to it named like `Level.knytt.bin.attributes` (see [`attributes_path`]):
+/// - Signature `"KSATTRIB"` (8 bytes)
+/// - Version (1 byte, currently `1`)
+/// - Number of files (unsigned 32-bit little endian integer)
+/// - For each file:
+///   - Path length (unsigned 16-bit little endian integer), then the path as UTF-8. This is
+///     the path from the file's entry header, which is stored there as Windows-1252, after
+///     decoding.
+///   - Modification time in seconds since the Unix epoch (signed 64-bit little endian
+///     integer), then nanoseconds (unsigned 32-bit little endian integer)
+///   - Flags (1 byte): bit 0 is set if the file is read-only
+pub const ATTRIBUTES_SIGNATURE: [u8; 8] = *b"KSATTRIB";
+
+const ATTRIBUTES_VERSION: u8 = 1;
+
+const READONLY_FLAG: u8 = 1;
+
+/// The preserved attributes of a packed file. See [`ATTRIBUTES_SIGNATURE`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryAttributes {
+    /// The path from the file's entry header.
+    pub path: String,
+    pub modified: SystemTime,
+    pub readonly: bool,
+}
+
+impl EntryAttributes {
+    /// Reads the attributes of the file at `path`, to be recorded under `entry_path`.
+    pub(crate) fn of_file(path: &Path, entry_path: &str) -> Result<EntryAttributes> {
+        let metadata = fs::metadata(path)?;
+        Ok(EntryAttributes {
+            path: entry_path.to_owned(),
+            modified: metadata.modified()?,
+            readonly: metadata.permissions().readonly(),
+        })
+    }
+
+    /// Sets the modification time and read-only flag of the file at `path`. The file must be
+    /// writable beforehand.
+    pub(crate) fn apply(&self, path: &Path) -> Result<()> {
+        File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(self.modified)?;
+
+        if self.readonly {
+            let mut permissions = fs::metadata(path)?.permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(path, permissions)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the path of the attributes file for the .knytt.bin at `bin_path`. See
+/// [`ATTRIBUTES_SIGNATURE`].
+pub fn attributes_path<P>(bin_path: P) -> PathBuf
+where
+    P: AsRef<Path>
+{
+    io_util::with_suffix(bin_path.as_ref(), ".attributes")
+}
+
+/// Serializes an attributes file.
+pub(crate) fn attributes_to_bytes(attributes: &[EntryAttributes]) -> Result<Vec<u8>> {
+    let mut bytes = ATTRIBUTES_SIGNATURE.to_vec();
+    bytes.push(ATTRIBUTES_VERSION);
+    let count = u32::try_from(attributes.len()).expect("file count should not exceed u32::MAX");
+    bytes.write_u32::<LittleEndian>(count)?;
+
+    for entry in attributes {
+        let path_len = u16::try_from(entry.path.len())
+            .map_err(|_| KnyttBinError::MalformedAttributes)?;
+        let (secs, nanos) = match entry.modified.duration_since(UNIX_EPOCH) {
+            Ok(after) => (after.as_secs() as i64, after.subsec_nanos()),
+            // Before the epoch, the seconds are rounded down and the nanoseconds count forward
+            Err(err) => {
+                let before = err.duration();
+                match before.subsec_nanos() {
+                    0 => (-(before.as_secs() as i64), 0),
+                    nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+                }
+            },
+        };
+
+        bytes.write_u16::<LittleEndian>(path_len)?;
+        bytes.extend_from_slice(entry.path.as_bytes());
+        bytes.write_i64::<LittleEndian>(secs)?;
+        bytes.write_u32::<LittleEndian>(nanos)?;
+        bytes.push(if entry.readonly { READONLY_FLAG } else { 0 });
+    }
+
+    Ok(bytes)
+}
+
+/// Parses an attributes file serialized by [`attributes_to_bytes`].
+fn attributes_from_bytes(bytes: &[u8]) -> Option<Vec<EntryAttributes>> {
+    let mut body = bytes.strip_prefix(&ATTRIBUTES_SIGNATURE)?;
+    if body.read_u8().ok()? != ATTRIBUTES_VERSION {
+        return None;
+    }
+
+    let count = body.read_u32::<LittleEndian>().ok()?;
+    let mut attributes = Vec::new();
+    for _ in 0..count {
+        let path_len = body.read_u16::<LittleEndian>().ok()?.into();
+        let (path, rest) = body.split_at_checked(path_len)?;
+        body = rest;
+        let path = String::from_utf8(path.to_vec()).ok()?;
+
+        let secs = body.read_i64::<LittleEndian>().ok()?;
+        let nanos = body.read_u32::<LittleEndian>().ok()?;
+        let modified = match secs {
+            0.. => UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos))?,
+            _ => UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))?
+                .checked_add(Duration::from_nanos(nanos.into()))?,
+        };
+        let flags = body.read_u8().ok()?;
+
+        attributes.push(EntryAttributes {
+            path,
+            modified,
+            readonly: flags & READONLY_FLAG != 0,
+        });
+    }
+
+    body.is_empty().then_some(attributes)
+}
+
+/// Reads the attributes file of the .knytt.bin at `bin_path`. Returns `None` if it doesn't
+/// have one, which is the case unless it was packed with
+/// [`PackOptions::preserve_attributes`](super::PackOptions::preserve_attributes).
+pub fn read_attributes<P>(bin_path: P) -> Result<Option<Vec<EntryAttributes>>>
+where
+    P: AsRef<Path>
+{
+    let path = attributes_path(bin_path);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).with_path(&path),
+    };
+
+    attributes_from_bytes(&bytes)
+        .map(Some)
+        .ok_or_else(|| KnyttBinError::MalformedAttributes.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_round_trip() {
+        let attributes = vec![
+            EntryAttributes {
+                path: "World.ini".to_owned(),
+                modified: UNIX_EPOCH + Duration::new(1_234_567_890, 5),
+                readonly: false,
+            },
+            EntryAttributes {
+                path: "Music/Song1é.ogg".to_owned(),
+                modified: UNIX_EPOCH - Duration::new(60, 250),
+                readonly: true,
+            },
+        ];
+
+        let bytes = attributes_to_bytes(&attributes).unwrap();
+        assert!(bytes.starts_with(&ATTRIBUTES_SIGNATURE));
+        assert_eq!(attributes_from_bytes(&bytes), Some(attributes));
+        assert_eq!(attributes_from_bytes(&bytes[..bytes.len() - 1]), None);
+    }
+}
@@ -0,0 +1,169 @@
+use std::{io::BufRead, path::Path};
+
+use crate::{
+    io_util,
+    perf,
+    Result,
+    constants::MB,
+};
+use super::{raw, unpack::open_archive, KnyttBinError, UnpackOptions};
+
+/// The longest path read while auditing. Longer paths are an error, since the data is
+/// presumably corrupt rather than a level with very long names.
+const AUDIT_MAX_PATH_LEN: usize = 4096;
+
+/// Device names Windows reserves in every directory, with or without an extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A problem with an entry path found by [`audit_names`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameProblem {
+    /// The path has characters outside ASCII. These are fine in Windows-1252, but tools that
+    /// assume ASCII may mangle them.
+    NonAscii,
+    /// A component ends with a dot or space, which Windows strips.
+    TrailingDotOrSpace(String),
+    /// A component is a device name reserved by Windows, like `CON` or `COM1.png`.
+    ReservedName(String),
+    /// The path contains a character Windows doesn't allow in names.
+    InvalidChar(char),
+    /// The path is longer than [`unpack`](super::unpack) allows by default, in bytes.
+    TooLong {
+        len: usize,
+        max: usize,
+    },
+}
+
+/// An entry path with a problem. See [`audit_names`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameIssue {
+    /// The path as stored in the .knytt.bin. For the first entry, this is the level's directory.
+    pub path: String,
+    pub problem: NameProblem,
+}
+
+/// Checks the entry paths of the .knytt.bin at `bin_path` for names that may not unpack
+/// correctly, without unpacking anything. Issues are listed in entry order.
+///
+/// Errors are only returned if the file can't be read as a .knytt.bin.
+pub fn audit_names<P>(bin_path: P) -> Result<Vec<NameIssue>>
+where
+    P: AsRef<Path>
+{
+    let mut reader = open_archive(bin_path.as_ref())?;
+
+    let max_len = UnpackOptions::default().max_path_len;
+    let mut issues = Vec::new();
+    let mut buf = vec![0; MB];
+
+    // The first entry has no data
+    let level = raw::read_header(&mut reader, AUDIT_MAX_PATH_LEN)?;
+    audit_name(&level.path, max_len, &mut issues);
+
+    while !reader.fill_buf()?.is_empty() {
+        let header = raw::read_header(&mut reader, AUDIT_MAX_PATH_LEN)?;
+        audit_name(&header.path, max_len, &mut issues);
+
+        let file_size = header.data_len();
+        let bytes_read = io_util::skip_at_most(&mut reader, &mut buf, file_size)?;
+        perf::count(|c| c.bytes_read += bytes_read as u64);
+        if bytes_read < file_size {
+            return Err(KnyttBinError::MissingData {
+                path: header.path.into(),
+                file_size,
+                bytes_read,
+            }.into());
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Appends the problems with `path` to `issues`.
+fn audit_name(path: &str, max_len: usize, issues: &mut Vec<NameIssue>) {
+    let mut push = |problem| issues.push(NameIssue {
+        path: path.to_owned(),
+        problem,
+    });
+
+    if !path.is_ascii() {
+        push(NameProblem::NonAscii);
+    }
+
+    if let Some(ch) = path.chars().find(|&c| c.is_control() || "<>:\"|?*".contains(c)) {
+        push(NameProblem::InvalidChar(ch));
+    }
+
+    for component in path.split(['/', '\\']).filter(|component| !component.is_empty()) {
+        if has_trailing_dot_or_space(component) {
+            push(NameProblem::TrailingDotOrSpace(component.to_owned()));
+        }
+        if is_reserved_name(component) {
+            push(NameProblem::ReservedName(component.to_owned()));
+        }
+    }
+
+    // Paths are stored as Windows-1252, which has one byte per character
+    let len = path.chars().count();
+    if len > max_len {
+        push(NameProblem::TooLong { len, max: max_len });
+    }
+}
+
+/// Returns `true` if Windows strips the end of the path component `name`. `.` and `..` are
+/// left to [`EntryHeader::safe_path`](super::raw::EntryHeader::safe_path).
+pub(crate) fn has_trailing_dot_or_space(name: &str) -> bool {
+    name.ends_with(['.', ' ']) && name != "." && name != ".."
+}
+
+/// Returns `true` if the path component `name` is a device name reserved by Windows, with or
+/// without an extension.
+pub(crate) fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::knytt_bin::raw::{write_header, EntryHeader};
+
+    #[test]
+    fn problem_names_are_reported() {
+        let long = "a".repeat(300);
+        let mut data = Vec::new();
+        for (path, contents) in [
+            ("Me - Level", &b""[..]),
+            ("World.ini", b"[World]"),
+            ("Custom Objects/Café.png", b"x"),
+            ("Music/con.ogg", b""),
+            ("Tilesets./Tileset1.png", b"xy"),
+            (long.as_str(), b""),
+        ] {
+            let len = if path == "Me - Level" { 5 } else { contents.len() as u32 };
+            write_header(&mut data, &EntryHeader { path: path.to_owned(), len }).unwrap();
+            data.extend_from_slice(contents);
+        }
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let bin_path = temp_dir.path().join("Level.knytt.bin");
+        fs::write(&bin_path, &data).unwrap();
+        let issues = audit_names(&bin_path).unwrap();
+
+        let problems: Vec<_> = issues.into_iter().map(|issue| issue.problem).collect();
+        assert_eq!(problems, [
+            NameProblem::NonAscii,
+            NameProblem::ReservedName("con.ogg".to_owned()),
+            NameProblem::TrailingDotOrSpace("Tilesets.".to_owned()),
+            NameProblem::TooLong { len: 300, max: 256 },
+        ]);
+    }
+}
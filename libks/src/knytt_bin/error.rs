@@ -25,4 +25,16 @@ pub enum KnyttBinError {
     UnauthorizedOverwrite(PathBuf),
     #[error("Something other than a directory already exists at {0}.")]
     OutputPathExists(PathBuf),
+    #[error("The footer is malformed.")]
+    MalformedFooter,
+    #[error("The archive doesn't match the hash in its footer.")]
+    FooterMismatch,
+    #[error("The footer's signature is invalid.")]
+    BadSignature,
+    #[error("The path {0} contains a name Windows doesn't allow.")]
+    ReservedName(PathBuf),
+    #[error("The attributes file is malformed.")]
+    MalformedAttributes,
+    #[error("The maximum part size for a split archive must be greater than 0.")]
+    ZeroPartSize,
 }
@@ -0,0 +1,380 @@
+use std::io::{self, Read, Seek};
+#[cfg(feature = "footer")]
+use std::{
+    fs,
+    io::SeekFrom,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "footer")]
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+#[cfg(feature = "footer")]
+pub use ed25519_dalek::SigningKey;
+#[cfg(feature = "footer")]
+use ed25519_dalek::{Signature, Signer, VerifyingKey};
+#[cfg(feature = "footer")]
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "footer")]
+use crate::{error::ResultExt, Result};
+#[cfg(feature = "footer")]
+use super::KnyttBinError;
+
+/// The bytes that end a footer.
+///
+/// A footer is appended to a .knytt.bin after its last entry:
+/// - Version (1 byte, currently `1`)
+/// - Tool name length (unsigned 16-bit little endian integer), then the name as UTF-8
+/// - Timestamp in seconds since the Unix epoch (unsigned 64-bit little endian integer)
+/// - SHA-256 hash of everything before the footer (32 bytes)
+/// - `0` if unsigned, or `1`, the Ed25519 public key (32 bytes), and the signature (64 bytes)
+///   of all of the above
+/// - Length of all of the above (unsigned 32-bit little endian integer)
+/// - Signature `"KSFOOTER"` (8 bytes)
+///
+/// Footers are an experimental libks extension, only recognized with the `footer` feature.
+/// Whether KS accepts an archive with data after its last entry hasn't been confirmed, and older
+/// versions of libks refuse to unpack one, so archives with footers shouldn't be distributed
+/// until that's settled.
+pub const FOOTER_SIGNATURE: [u8; 8] = *b"KSFOOTER";
+
+#[cfg(feature = "footer")]
+const FOOTER_VERSION: u8 = 1;
+
+/// The length of the body length and signature at the very end of the footer.
+#[cfg(feature = "footer")]
+const TRAILER_LEN: u64 = 4 + FOOTER_SIGNATURE.len() as u64;
+
+/// The length of the longest possible footer, with a signature and a 65535-byte tool name.
+#[cfg(feature = "footer")]
+const MAX_FOOTER_LEN: u64 = 1 + 2 + u16::MAX as u64 + 8 + 32 + 1 + 32 + 64 + TRAILER_LEN;
+
+/// Returns the length of the footer at the end of `reader`, or 0 if there is none. The reader's
+/// position is left wherever checking happened to leave it.
+///
+/// The footer is only counted if it parses and its hash matches everything before it, so an
+/// archive whose last file happens to end like a footer keeps its data. Without the `footer`
+/// feature, footers aren't recognized at all.
+#[cfg(feature = "footer")]
+pub(crate) fn footer_len<R>(reader: &mut R) -> io::Result<u64>
+where
+    R: Read + Seek
+{
+    let footer_len = unchecked_footer_len(reader)?;
+    if footer_len == 0 {
+        return Ok(0);
+    }
+
+    let archive_len = reader.seek(SeekFrom::End(-(footer_len as i64)))?;
+    let mut bytes = Vec::new();
+    reader.by_ref().take(footer_len).read_to_end(&mut bytes)?;
+    let Some(footer) = Footer::from_bytes_impl(&bytes) else {
+        return Ok(0);
+    };
+
+    reader.rewind()?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut reader.by_ref().take(archive_len), &mut hasher)?;
+    if <[u8; 32]>::from(hasher.finalize()) != footer.sha256 {
+        return Ok(0);
+    }
+
+    Ok(footer_len)
+}
+
+/// Returns the length of the footer at the end of `reader`. Without the `footer` feature,
+/// footers aren't recognized, so this is always 0.
+#[cfg(not(feature = "footer"))]
+pub(crate) fn footer_len<R>(_reader: &mut R) -> io::Result<u64>
+where
+    R: Read + Seek
+{
+    Ok(0)
+}
+
+/// Returns the length the trailer at the end of `reader` claims for the footer, or 0 if it
+/// doesn't end with [`FOOTER_SIGNATURE`]. Nothing else about the footer is checked.
+#[cfg(feature = "footer")]
+fn unchecked_footer_len<R>(reader: &mut R) -> io::Result<u64>
+where
+    R: Read + Seek
+{
+    let total_len = reader.seek(SeekFrom::End(0))?;
+    if total_len < TRAILER_LEN {
+        return Ok(0);
+    }
+
+    reader.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+    let body_len = reader.read_u32::<LittleEndian>()?;
+    let mut signature = [0u8; FOOTER_SIGNATURE.len()];
+    reader.read_exact(&mut signature)?;
+
+    let footer_len = u64::from(body_len) + TRAILER_LEN;
+    if signature != FOOTER_SIGNATURE || footer_len > total_len || footer_len > MAX_FOOTER_LEN {
+        return Ok(0);
+    }
+
+    Ok(footer_len)
+}
+
+/// Provenance information appended to a .knytt.bin. See [`FOOTER_SIGNATURE`] for the format.
+#[cfg(feature = "footer")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footer {
+    /// The name (and ideally version) of the program that made the archive.
+    pub tool: String,
+    /// When the footer was made, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The SHA-256 hash of the archive without the footer.
+    pub sha256: [u8; 32],
+    pub signature: Option<FooterSignature>,
+}
+
+/// An Ed25519 signature of a [`Footer`], which covers the archive through its hash.
+#[cfg(feature = "footer")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FooterSignature {
+    pub public_key: [u8; 32],
+    pub signature: [u8; 64],
+}
+
+#[cfg(feature = "footer")]
+impl Footer {
+    /// Makes an unsigned footer for `archive` (without any existing footer) timestamped now.
+    pub fn new(archive: &[u8], tool: impl Into<String>) -> Footer {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        Footer {
+            tool: tool.into(),
+            timestamp,
+            sha256: Sha256::digest(archive).into(),
+            signature: None,
+        }
+    }
+
+    /// Signs the footer with `key`, replacing any existing signature.
+    pub fn sign(&mut self, key: &SigningKey) -> Result<()> {
+        let signature = key.sign(&self.signed_bytes()?);
+        self.signature = Some(FooterSignature {
+            public_key: key.verifying_key().to_bytes(),
+            signature: signature.to_bytes(),
+        });
+
+        Ok(())
+    }
+
+    /// Checks that `archive` (without the footer) matches the footer's hash and that the
+    /// signature, if any, is valid.
+    ///
+    /// A valid signature only shows that the holder of the public key signed the archive. It's
+    /// up to the caller to decide whether to trust that key.
+    pub fn verify(&self, archive: &[u8]) -> Result<()> {
+        if <[u8; 32]>::from(Sha256::digest(archive)) != self.sha256 {
+            return Err(KnyttBinError::FooterMismatch.into());
+        }
+
+        if let Some(FooterSignature { public_key, signature }) = &self.signature {
+            let signed_bytes = self.signed_bytes()?;
+            VerifyingKey::from_bytes(public_key)
+                .and_then(|key| key.verify_strict(&signed_bytes, &Signature::from_bytes(signature)))
+                .map_err(|_| KnyttBinError::BadSignature)?;
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the footer, ready to be appended to its archive.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = self.signed_bytes()?;
+        match &self.signature {
+            Some(FooterSignature { public_key, signature }) => {
+                bytes.push(1);
+                bytes.extend_from_slice(public_key);
+                bytes.extend_from_slice(signature);
+            },
+            None => bytes.push(0),
+        }
+
+        let body_len = u32::try_from(bytes.len()).expect("footer should be far smaller than 4 GiB");
+        bytes.write_u32::<LittleEndian>(body_len)?;
+        bytes.extend_from_slice(&FOOTER_SIGNATURE);
+
+        Ok(bytes)
+    }
+
+    /// Parses a footer serialized by [`Footer::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Footer> {
+        Self::from_bytes_impl(bytes).ok_or_else(|| KnyttBinError::MalformedFooter.into())
+    }
+
+    fn from_bytes_impl(bytes: &[u8]) -> Option<Footer> {
+        let body = bytes.strip_suffix(&FOOTER_SIGNATURE)?;
+        let (mut body, mut body_len) = body.split_at_checked(body.len().checked_sub(4)?)?;
+        if body_len.read_u32::<LittleEndian>().ok()? as usize != body.len() {
+            return None;
+        }
+
+        fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+            let (taken, rest) = bytes.split_at_checked(len)?;
+            *bytes = rest;
+            Some(taken)
+        }
+
+        if body.read_u8().ok()? != FOOTER_VERSION {
+            return None;
+        }
+        let tool_len = body.read_u16::<LittleEndian>().ok()?;
+        let tool = String::from_utf8(take(&mut body, tool_len.into())?.to_vec()).ok()?;
+        let timestamp = body.read_u64::<LittleEndian>().ok()?;
+        let sha256 = take(&mut body, 32)?.try_into().ok()?;
+        let signature = match body.read_u8().ok()? {
+            0 => None,
+            1 => Some(FooterSignature {
+                public_key: take(&mut body, 32)?.try_into().ok()?,
+                signature: take(&mut body, 64)?.try_into().ok()?,
+            }),
+            _ => return None,
+        };
+
+        body.is_empty().then_some(Footer { tool, timestamp, sha256, signature })
+    }
+
+    /// Returns the part of the footer covered by the signature.
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        let tool_len = u16::try_from(self.tool.len())
+            .map_err(|_| KnyttBinError::MalformedFooter)?;
+
+        let mut bytes = vec![FOOTER_VERSION];
+        bytes.write_u16::<LittleEndian>(tool_len)?;
+        bytes.extend_from_slice(self.tool.as_bytes());
+        bytes.write_u64::<LittleEndian>(self.timestamp)?;
+        bytes.extend_from_slice(&self.sha256);
+
+        Ok(bytes)
+    }
+}
+
+/// Splits the .knytt.bin `data` into the archive and its footer, if it has one.
+///
+/// Trailing bytes that don't parse as a footer or whose hash doesn't match the rest of `data` are
+/// treated as part of the archive, as when unpacking. The signature isn't checked; see
+/// [`Footer::verify`].
+#[cfg(feature = "footer")]
+pub fn split_footer(data: &[u8]) -> Result<(&[u8], Option<Footer>)> {
+    let footer_len = footer_len(&mut io::Cursor::new(data))? as usize;
+    if footer_len == 0 {
+        return Ok((data, None));
+    }
+
+    let (archive, footer) = data.split_at(data.len() - footer_len);
+    Ok((archive, Some(Footer::from_bytes(footer)?)))
+}
+
+/// Adds a footer to the .knytt.bin at `bin_path`, replacing any existing one, and returns it.
+/// The footer is signed if `key` is given.
+#[cfg(feature = "footer")]
+pub fn write_footer<P>(bin_path: P, tool: &str, key: Option<&SigningKey>) -> Result<Footer>
+where
+    P: AsRef<Path>
+{
+    let bin_path = bin_path.as_ref();
+    let mut data = fs::read(bin_path).with_path(bin_path)?;
+    let archive_len = footer_len(&mut io::Cursor::new(&data))
+        .map(|footer_len| data.len() - footer_len as usize)?;
+    data.truncate(archive_len);
+
+    let mut footer = Footer::new(&data, tool);
+    if let Some(key) = key {
+        footer.sign(key)?;
+    }
+    data.extend_from_slice(&footer.to_bytes()?);
+    fs::write(bin_path, data).with_path(bin_path)?;
+
+    Ok(footer)
+}
+
+/// Reads and verifies the footer of the .knytt.bin at `bin_path`. Returns `None` if it doesn't
+/// have one, including when the archive was changed after the footer was added, since the hash no
+/// longer matches. See [`split_footer`].
+#[cfg(feature = "footer")]
+pub fn verify_footer<P>(bin_path: P) -> Result<Option<Footer>>
+where
+    P: AsRef<Path>
+{
+    let bin_path = bin_path.as_ref();
+    let data = fs::read(bin_path).with_path(bin_path)?;
+    let (archive, footer) = split_footer(&data)?;
+    if let Some(footer) = &footer {
+        footer.verify(archive)?;
+    }
+
+    Ok(footer)
+}
+
+#[cfg(all(test, feature = "footer"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_footer_round_trip() {
+        let archive = b"NFMe - Level\0\x01\0\0\0NFWorld.ini\0\x03\0\0\0abc".to_vec();
+        let key = SigningKey::from_bytes(&[7; 32]);
+
+        let mut footer = Footer::new(&archive, "libks test");
+        footer.sign(&key).unwrap();
+        let mut data = archive.clone();
+        data.extend_from_slice(&footer.to_bytes().unwrap());
+
+        let (split_archive, parsed) = split_footer(&data).unwrap();
+        assert_eq!(split_archive, archive);
+        assert_eq!(parsed.as_ref(), Some(&footer));
+        footer.verify(&archive).unwrap();
+
+        let mut tampered = archive.clone();
+        *tampered.last_mut().unwrap() = b'x';
+        assert!(footer.verify(&tampered).is_err());
+
+        footer.timestamp += 1;
+        assert!(footer.verify(&archive).is_err());
+    }
+
+    #[test]
+    fn only_valid_footers_are_stripped() {
+        let archive = b"NFMe - Level\0\x01\0\0\0NFWorld.ini\0\x03\0\0\0abc".to_vec();
+        let mut data = archive.clone();
+        data.extend_from_slice(&Footer::new(&archive, "libks test").to_bytes().unwrap());
+        assert_eq!(footer_len(&mut io::Cursor::new(&data)).unwrap() as usize, data.len() - archive.len());
+
+        // A file that happens to end like a footer
+        let mut lookalike = b"NFMe - Level\0\x01\0\0\0NFData.bin\0\x0d\0\0\0x\x00\0\0\0KSFOOTER".to_vec();
+        assert_eq!(footer_len(&mut io::Cursor::new(&lookalike)).unwrap(), 0);
+        let body_len_at = lookalike.len() - 12;
+        lookalike[body_len_at] = 5;
+        assert_eq!(footer_len(&mut io::Cursor::new(&lookalike)).unwrap(), 0);
+
+        assert_eq!(split_footer(&lookalike).unwrap(), (&lookalike[..], None));
+
+        // A footer whose hash doesn't match
+        data[archive.len() - 1] = b'x';
+        assert_eq!(footer_len(&mut io::Cursor::new(&data)).unwrap(), 0);
+        assert!(split_footer(&data).unwrap().1.is_none());
+    }
+
+    #[test]
+    fn lookalikes_are_kept_when_writing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let bin_path = temp_dir.path().join("Me - Level.knytt.bin");
+        let lookalike = b"NFMe - Level\0\x01\0\0\0NFData.bin\0\x0d\0\0\0x\x05\0\0\0KSFOOTER".to_vec();
+        fs::write(&bin_path, &lookalike).unwrap();
+
+        assert!(verify_footer(&bin_path).unwrap().is_none());
+        let footer = write_footer(&bin_path, "libks test", None).unwrap();
+        assert_eq!(verify_footer(&bin_path).unwrap(), Some(footer));
+        let data = fs::read(&bin_path).unwrap();
+        assert_eq!(split_footer(&data).unwrap().0, lookalike);
+    }
+}
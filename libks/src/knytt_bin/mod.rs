@@ -1,14 +1,46 @@
 mod error;
 pub use error::KnyttBinError;
 
+pub mod raw;
+
+mod audit;
+pub use audit::{audit_names, NameIssue, NameProblem};
+
+mod attributes;
+pub use attributes::{attributes_path, read_attributes, EntryAttributes, ATTRIBUTES_SIGNATURE};
+
+mod footer;
+pub use footer::FOOTER_SIGNATURE;
+#[cfg(feature = "footer")]
+pub use footer::{
+    split_footer,
+    verify_footer,
+    write_footer,
+    Footer,
+    FooterSignature,
+    SigningKey,
+};
+
 mod pack;
-pub use pack::pack;
+pub use pack::{pack, pack_with_options, PackOptions};
+
+#[cfg(feature = "store")]
+mod repack;
+#[cfg(feature = "store")]
+pub use repack::pack_if_changed;
+
+mod split;
+pub use split::{pack_split, unpack_parts};
 
 mod unpack;
 pub use unpack::{
     unpack,
+    unpack_resume,
     unpack_with_options,
+    unpack_with_issues,
+    PlannedFile,
+    ReservedNamePolicy,
+    ResumeReport,
     UnpackOptions,
+    UnpackPlan,
 };
-
-const ENTRY_SIGNATURE: [u8; 2] = [b'N', b'F'];
@@ -1,63 +1,113 @@
 use std::{
-    env,
     fs::{self, File, OpenOptions},
     path::Path,
-    io::{BufWriter, Write, SeekFrom, Seek},
+    io::{self, BufWriter, Write},
 };
 
-use byteorder::{LittleEndian, WriteBytesExt};
+use crate::{error::ResultExt, io_util, world, Result};
+use super::{
+    attributes::{attributes_path, attributes_to_bytes, EntryAttributes},
+    raw::{self, EntryHeader},
+    KnyttBinError,
+};
 
-use crate::Result;
-use super::{KnyttBinError, ENTRY_SIGNATURE};
+/// Configures the behavior of [`pack_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct PackOptions {
+    /// If `true`, the modification times and read-only flags of the files are recorded in a
+    /// file next to the .knytt.bin, so they can be restored by
+    /// [`UnpackOptions::preserve_attributes`](super::UnpackOptions::preserve_attributes). The
+    /// .knytt.bin itself is the same either way. See
+    /// [`ATTRIBUTES_SIGNATURE`](super::ATTRIBUTES_SIGNATURE). Defaults to `false`.
+    pub preserve_attributes: bool,
+}
 
 /// Packs the files in the directory at `input_dir` into a .knytt.bin and writes it to `bin_path`.
 /// 
 /// The .knytt.bin's "enclosing directory" will be the name of `input_dir`.
+/// 
+/// The default packing options will be used. See [`PackOptions`] for more information.
 pub fn pack<P1, P2>(input_dir: P1, bin_path: P2) -> Result<usize>
 where
     P1: AsRef<Path>,
     P2: AsRef<Path>
 {
+    pack_with_options(input_dir, bin_path, PackOptions::default())
+}
+
+/// Packs the files in the directory at `input_dir` into a .knytt.bin and writes it to
+/// `bin_path`, returning the number of files packed.
+pub fn pack_with_options<P1, P2>(input_dir: P1, bin_path: P2, options: PackOptions) -> Result<usize>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>
+{
+    let bin_path = bin_path.as_ref();
     let mut writer = {
         let file = OpenOptions::new()
             .write(true)
             .create_new(true)
-            .open(bin_path)?;
+            .open(bin_path)
+            .with_path(bin_path)?;
         BufWriter::new(file)
     };
 
-    // Temporarily cd into the directory to be packed
-    let prev_wd = env::current_dir()?;
-    env::set_current_dir(input_dir)?;
-    
+    // Files are read through the long form of the directory, so deeply nested files can be
+    // packed on Windows
+    let root = io_util::long_path_root(input_dir.as_ref()).with_path(input_dir.as_ref())?;
+
     // First header gives the name of the enclosing directory and the number of files packed
-    // We don't know how many files are going to be packed, so write a placeholder for now
-    let enclosing_dir = name_of_current_dir()?;
-    write_entry_header(&mut writer, &enclosing_dir, 0)?;
+    let enclosing_dir = name_of_dir(input_dir.as_ref(), &root)?;
+    let paths = file_paths(&root)?;
+    write_entry_header(&mut writer, &enclosing_dir, paths.len())?;
 
     // Pack it up!
-    let packed_count = pack_dir_recursive("".to_owned(), &mut writer)?;
+    let mut attributes = Vec::new();
+    for path in &paths {
+        if options.preserve_attributes {
+            let file_path = io_util::join_relative(&root, path.as_ref());
+            attributes.push(EntryAttributes::of_file(&file_path, path)?);
+        }
+        pack_file(&root, path, &mut writer)?;
+    }
+    writer.flush()?;
 
-    // Go back and update the number of packed files
-    writer.seek(SeekFrom::Start(0))?;
-    write_entry_header(&mut writer, &enclosing_dir, packed_count)?;
+    // An attributes file from an earlier pack would no longer match
+    let attributes_path = attributes_path(bin_path);
+    if options.preserve_attributes {
+        fs::write(&attributes_path, attributes_to_bytes(&attributes)?).with_path(&attributes_path)?;
+    }
+    else {
+        match fs::remove_file(&attributes_path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err).with_path(&attributes_path),
+            _ => (),
+        }
+    }
 
-    // Restore working directory
-    env::set_current_dir(prev_wd)?;
-    
-    Ok(packed_count)
+    Ok(paths.len())
 }
 
-fn pack_dir_recursive(path: String, writer: &mut BufWriter<File>) -> Result<usize> {
-    let path_ref: &Path = path.as_ref();
-    let mut packed_count = 0;
+/// Returns the paths of the files in the directory at `root`, relative to it and separated by
+/// `/`, in the order they're packed. Files libks creates in level directories, like the lock
+/// file from [`World::lock`](crate::world::World::lock), are left out.
+pub(super) fn file_paths(root: &Path) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    add_file_paths(root, "".to_owned(), &mut paths)?;
+    Ok(paths)
+}
 
-    for entry in path_ref.read_dir()? {
+/// Adds the paths of the files in the directory at `path`, relative to `root`, to `paths`. An
+/// empty path is the root.
+fn add_file_paths(root: &Path, path: String, paths: &mut Vec<String>) -> Result<()> {
+    for entry in io_util::join_relative(root, path.as_ref()).read_dir()? {
         let entry = entry?;
         let entry_path = {
             let name = entry.file_name()
                 .into_string()
                 .map_err(|_| KnyttBinError::BadFileName(entry.path()))?;
+            if world::is_libks_file(&name) {
+                continue;
+            }
 
             if path.is_empty() {
                 name
@@ -66,21 +116,18 @@ fn pack_dir_recursive(path: String, writer: &mut BufWriter<File>) -> Result<usiz
                 format!("{path}/{name}")
             }
         };
-        let entry_path_ref: &Path = entry_path.as_ref();
-
-        if entry_path_ref.is_dir() {
-            packed_count += pack_dir_recursive(entry_path, writer)?;
+        if entry.path().is_dir() {
+            add_file_paths(root, entry_path, paths)?;
         }
         else {
-            pack_file(entry_path, writer)?;
-            packed_count += 1;
+            paths.push(entry_path);
         }
     }
 
-    Ok(packed_count)
+    Ok(())
 }
 
-fn pack_file(path: String, writer: &mut BufWriter<File>) -> Result<()>
+fn pack_file(root: &Path, path: &str, writer: &mut BufWriter<File>) -> Result<()>
 {
     // Read file and determine size
     // I would like to use fs::metadata() to determine size and then io::copy to copy
@@ -89,11 +136,11 @@ fn pack_file(path: String, writer: &mut BufWriter<File>) -> Result<()>
     // seek back to the file size offset, write the size returned by io::copy, and then
     // seek to the end, but that is probably not worth it. Most files being packed
     // are not going to be very large.
-    let contents = fs::read(&path)?;
+    let contents = fs::read(io_util::join_relative(root, path.as_ref()))?;
     let file_size = contents.len();
 
     // Write header and contents
-    write_entry_header(writer, &path, file_size)?;
+    write_entry_header(writer, path, file_size)?;
     writer.write_all(&contents)?;
 
     Ok(())
@@ -105,21 +152,20 @@ fn write_entry_header(writer: &mut BufWriter<File>, name: &str, len: usize) -> R
         .try_into()
         .expect("Entry length should not exceed u32::MAX bytes");
 
-    writer.write_all(&ENTRY_SIGNATURE)?;
-    writer.write_all(name.as_bytes())?;
-    writer.write_all(&[0u8])?; // null terminator
-    writer.write_u32::<LittleEndian>(len)?;
-
-    Ok(())
+    raw::write_header(writer, &EntryHeader {
+        path: name.to_owned(),
+        len,
+    })
 }
 
-/// Converts the name of the current working directory to a `String`.
-fn name_of_current_dir() -> Result<String> {
-    let current_dir = env::current_dir()?;
-    if let Some(name) = current_dir.file_name().and_then(|s| s.to_str()) {
+/// Converts the name of the directory at `dir` to a `String`. The name is taken from `dir` as
+/// given, so a symlinked directory keeps the name of the link. Paths like `.` that don't end in
+/// a name fall back to `root`, the canonical form of `dir`.
+pub(super) fn name_of_dir(dir: &Path, root: &Path) -> Result<String> {
+    if let Some(name) = dir.file_name().or_else(|| root.file_name()).and_then(|s| s.to_str()) {
         Ok(name.to_owned())
     }
     else {
-        Err(KnyttBinError::BadFileName(current_dir).into())
+        Err(KnyttBinError::BadFileName(dir.to_owned()).into())
     }
 }
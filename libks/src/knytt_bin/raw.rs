@@ -0,0 +1,150 @@
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{io_util, perf, Result};
+use super::KnyttBinError;
+
+/// The two bytes that begin every .knytt.bin entry.
+pub const ENTRY_SIGNATURE: [u8; 2] = [b'N', b'F'];
+
+/// The header of a .knytt.bin entry.
+///
+/// A .knytt.bin is a series of entries, each a header followed by `len` bytes of data. The
+/// header format is:
+/// - Signature `"NF"` (2 bytes)
+/// - Null-terminated path, relative to the root directory. The encoding is presumed to be
+///   Windows-1252, but this hasn't been confirmed.
+/// - Length of the data (unsigned 32-bit little endian integer)
+///
+/// The first entry is special: its path is the name of the level's directory and its length is
+/// a count related to the number of files, which isn't reliable. It has no data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryHeader {
+    pub path: String,
+    pub len: u32,
+}
+
+impl EntryHeader {
+    /// Returns the length of the data as a `usize`.
+    pub fn data_len(&self) -> usize {
+        self.len
+            .try_into()
+            .expect("u32::MAX should be less than or equal to usize::MAX")
+    }
+
+    /// Returns the path if it's safe to unpack to: nonempty, relative, and without `..`.
+    pub fn safe_path(&self) -> Result<PathBuf> {
+        if self.path.is_empty() {
+            return Err(KnyttBinError::EmptyPath.into());
+        }
+
+        let path = PathBuf::from(&self.path);
+
+        if path.is_absolute()
+            || path.iter().any(|part| part == "..")
+        {
+            return Err(KnyttBinError::IllegalPath(path).into());
+        }
+
+        Ok(path)
+    }
+}
+
+/// Reads an entry header from `reader`, leaving it positioned at the start of the entry's
+/// data. Paths longer than `max_path_len` bytes are rejected.
+///
+/// Only the signature is checked. Use [`EntryHeader::safe_path`] before writing to the path.
+pub fn read_header<R>(reader: &mut R, max_path_len: usize) -> Result<EntryHeader>
+where
+    R: BufRead
+{
+    let mut signature = [0u8; 2];
+    reader.read_exact(&mut signature)?;
+    if signature != ENTRY_SIGNATURE {
+        return Err(KnyttBinError::UnrecognizedSignature(signature).into());
+    }
+
+    let mut buf = Vec::new();
+    let path = io_util::read_windows_1252_null_term(reader, &mut buf, max_path_len)?;
+    let len = reader.read_u32::<LittleEndian>()?;
+
+    // Signature, path, null terminator, and length
+    let header_len = 2 + buf.len() + 1 + 4;
+    perf::count(|c| c.bytes_read += header_len as u64);
+
+    Ok(EntryHeader { path, len })
+}
+
+/// Writes `header` to `writer`. The path is encoded as Windows-1252.
+pub fn write_header<W>(writer: &mut W, header: &EntryHeader) -> Result<()>
+where
+    W: Write
+{
+    let (path, _, had_errors) = encoding_rs::WINDOWS_1252.encode(&header.path);
+    if had_errors || path.contains(&0) {
+        return Err(KnyttBinError::BadFileName(PathBuf::from(&header.path)).into());
+    }
+
+    writer.write_all(&ENTRY_SIGNATURE)?;
+    writer.write_all(&path)?;
+    writer.write_all(&[0u8])?; // null terminator
+    writer.write_u32::<LittleEndian>(header.len)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_round_trip() {
+        let header = EntryHeader {
+            path: "Tilesets/Tileset1é.png".to_owned(),
+            len: 1234,
+        };
+
+        let mut data = Vec::new();
+        write_header(&mut data, &header).unwrap();
+        assert_eq!(data.len(), 2 + 22 + 1 + 4);
+        assert_eq!(read_header(&mut &data[..], 256).unwrap(), header);
+    }
+
+    #[test]
+    fn bad_headers_are_rejected() {
+        assert!(matches!(
+            read_header(&mut &b"NG\0\0\0\0\0"[..], 256),
+            Err(crate::KsError::KnyttBin(KnyttBinError::UnrecognizedSignature([b'N', b'G']))),
+        ));
+        assert!(read_header(&mut &b"NFWorld.ini\0\0\0\0\0"[..], 4).is_err());
+        assert!(read_header(&mut &b"NFWorld.ini\0\0\0"[..], 256).is_err());
+
+        for path in ["Tileset\u{3042}.png", "World\0.ini"] {
+            let header = EntryHeader { path: path.to_owned(), len: 0 };
+            let mut data = Vec::new();
+            assert!(matches!(
+                write_header(&mut data, &header),
+                Err(crate::KsError::KnyttBin(KnyttBinError::BadFileName(_))),
+            ));
+            assert!(data.is_empty());
+        }
+    }
+
+    #[test]
+    fn unsafe_paths_are_rejected() {
+        for (path, safe) in [
+            ("Music/Song1.ogg", true),
+            ("", false),
+            ("../World.ini", false),
+            ("Music/../../World.ini", false),
+            ("/World.ini", false),
+        ] {
+            let header = EntryHeader { path: path.to_owned(), len: 0 };
+            assert_eq!(header.safe_path().is_ok(), safe, "{path}");
+        }
+    }
+}
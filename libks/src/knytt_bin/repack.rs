@@ -0,0 +1,106 @@
+use std::{fs, io, path::Path};
+
+use crate::{
+    error::ResultExt,
+    io_util,
+    store::{blob_hash, Manifest, ManifestEntry},
+    Result,
+};
+use super::{
+    pack::{self, file_paths, name_of_dir},
+    raw::EntryHeader,
+};
+
+/// Packs the files in the directory at `input_dir` into a .knytt.bin at `bin_path`, unless it
+/// was already packed from the same files. Returns `true` if it was packed and `false` if it
+/// was skipped.
+///
+/// After packing, a [`Manifest`] of the entries is written next to the archive at
+/// `<bin_path>.manifest`. Later calls hash the files in `input_dir` and compare them with it, so
+/// a skip is only as good as the manifest: changes to the .knytt.bin itself aren't noticed. A
+/// missing or unreadable manifest causes a repack. The old archive is replaced when repacking.
+pub fn pack_if_changed<P1, P2>(input_dir: P1, bin_path: P2) -> Result<bool>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>
+{
+    let input_dir = input_dir.as_ref();
+    let bin_path = bin_path.as_ref();
+    let manifest_path = io_util::with_suffix(bin_path, ".manifest");
+
+    let manifest = dir_manifest(input_dir)?;
+    let is_unchanged = bin_path.is_file()
+        && fs::read_to_string(&manifest_path).ok()
+            .and_then(|text| Manifest::parse(&text).ok())
+            .is_some_and(|stored| stored == manifest);
+    if is_unchanged {
+        return Ok(false);
+    }
+
+    // The manifest is removed first so a failed pack can't be mistaken for a finished one
+    for path in [&manifest_path, bin_path] {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err).with_path(path),
+            _ => (),
+        }
+    }
+    pack::pack(input_dir, bin_path)?;
+    fs::write(&manifest_path, manifest.to_string()).with_path(&manifest_path)?;
+
+    Ok(true)
+}
+
+/// Returns the manifest of the .knytt.bin that [`pack`](super::pack) would make from the
+/// directory at `input_dir`.
+fn dir_manifest(input_dir: &Path) -> Result<Manifest> {
+    let root = io_util::long_path_root(input_dir).with_path(input_dir)?;
+    let paths = file_paths(&root)?;
+
+    let mut manifest = Manifest::default();
+    manifest.entries.push(ManifestEntry {
+        header: EntryHeader {
+            path: name_of_dir(input_dir, &root)?,
+            len: entry_len(paths.len()),
+        },
+        blob: None,
+    });
+
+    for path in paths {
+        let data = fs::read(io_util::join_relative(&root, path.as_ref()))?;
+        manifest.entries.push(ManifestEntry {
+            header: EntryHeader { path, len: entry_len(data.len()) },
+            blob: Some(blob_hash(&data)),
+        });
+    }
+
+    Ok(manifest)
+}
+
+fn entry_len(len: usize) -> u32 {
+    len.try_into().expect("Entry length should not exceed u32::MAX bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_dirs_are_skipped() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp = temp_dir.path();
+        let level = temp.join("Me - Level");
+        fs::create_dir_all(&level).unwrap();
+        fs::write(level.join("World.ini"), "[World]").unwrap();
+        let bin_path = temp.join("Level.knytt.bin");
+
+        assert!(pack_if_changed(&level, &bin_path).unwrap());
+        assert!(!pack_if_changed(&level, &bin_path).unwrap());
+
+        fs::write(level.join("World.ini"), "[World]\nName=Changed").unwrap();
+        assert!(pack_if_changed(&level, &bin_path).unwrap());
+        assert!(!pack_if_changed(&level, &bin_path).unwrap());
+
+        fs::remove_file(&bin_path).unwrap();
+        assert!(pack_if_changed(&level, &bin_path).unwrap());
+    }
+}
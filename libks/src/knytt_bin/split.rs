@@ -0,0 +1,177 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{error::ResultExt, io_util::with_suffix, Result};
+use super::{pack, unpack, KnyttBinError};
+
+/// Packs the files in the directory at `input_dir` into a .knytt.bin split into parts of at
+/// most `max_part_size` bytes, and returns the paths of the parts in order.
+///
+/// The parts are named after `base_path` with a three-digit number appended, starting from 1,
+/// e.g. `Level.knytt.bin.001`. They're plain slices of the archive, so besides
+/// [`unpack_parts`], they can be joined with tools like `cat` or `copy /b`. An archive that fits
+/// in one part is still written as `.001`.
+///
+/// Existing parts with the same names are replaced, and higher-numbered parts left over from a
+/// previous, larger split are deleted. Each part is written to a temporary file first, so if
+/// packing or splitting fails, the existing parts are left as they were. A `max_part_size` of 0
+/// is rejected with [`KnyttBinError::ZeroPartSize`].
+pub fn pack_split<P1, P2>(input_dir: P1, base_path: P2, max_part_size: u64) -> Result<Vec<PathBuf>>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>
+{
+    if max_part_size == 0 {
+        return Err(KnyttBinError::ZeroPartSize.into());
+    }
+
+    let base_path = base_path.as_ref();
+    let temp_path = with_suffix(base_path, ".partial");
+    if let Err(err) = pack(input_dir, &temp_path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err);
+    }
+
+    let result = split_file(&temp_path, base_path, max_part_size);
+    fs::remove_file(&temp_path).with_path(&temp_path)?;
+    result
+}
+
+/// Unpacks a .knytt.bin split by [`pack_split`] or another tool into a subdirectory of
+/// `output_dir`, like [`unpack`]. `parts` must be in order.
+///
+/// The parts are joined into a temporary file first, which is deleted afterward.
+pub fn unpack_parts<P1, P2>(parts: &[P1], output_dir: P2) -> Result<PathBuf>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>
+{
+    // Calls on other threads get their own file
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let temp_path = std::env::temp_dir().join(format!("libks_parts_{}_{id}.knytt.bin", process::id()));
+
+    let result = join_parts(parts, &temp_path)
+        .and_then(|_| unpack(&temp_path, output_dir));
+    let _ = fs::remove_file(&temp_path);
+    result
+}
+
+/// Copies the file at `path` into numbered parts of at most `max_part_size` bytes named after
+/// `base_path`, replacing any existing parts.
+fn split_file(path: &Path, base_path: &Path, max_part_size: u64) -> Result<Vec<PathBuf>> {
+    let mut temp_parts = Vec::new();
+    let result = write_temp_parts(path, base_path, max_part_size, &mut temp_parts);
+    if let Err(err) = result {
+        for temp_path in &temp_parts {
+            let _ = fs::remove_file(temp_path);
+        }
+        return Err(err);
+    }
+
+    let mut parts = Vec::new();
+    for temp_path in temp_parts {
+        let part_path = part_path(base_path, parts.len() + 1);
+        fs::rename(&temp_path, &part_path).with_path(&part_path)?;
+        parts.push(part_path);
+    }
+
+    // Remove the parts of a previous split that had more of them
+    for number in parts.len() + 1.. {
+        let stale = part_path(base_path, number);
+        if !stale.is_file() {
+            break;
+        }
+        fs::remove_file(&stale).with_path(&stale)?;
+    }
+
+    Ok(parts)
+}
+
+/// Writes the parts [`split_file`] makes to temporary files, adding their paths to
+/// `temp_parts` as they're created.
+fn write_temp_parts(path: &Path, base_path: &Path, max_part_size: u64, temp_parts: &mut Vec<PathBuf>) -> Result<()> {
+    let len = fs::metadata(path).with_path(path)?.len();
+    let mut reader = BufReader::new(File::open(path).with_path(path)?);
+
+    loop {
+        let temp_path = with_suffix(&part_path(base_path, temp_parts.len() + 1), ".partial");
+        let file = File::create(&temp_path).with_path(&temp_path)?;
+        temp_parts.push(temp_path);
+
+        let mut writer = BufWriter::new(file);
+        io::copy(&mut (&mut reader).take(max_part_size), &mut writer)?;
+        writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+
+        if temp_parts.len() as u64 * max_part_size >= len {
+            return Ok(());
+        }
+    }
+}
+
+/// Returns the path of part `number`, counting from 1.
+fn part_path(base_path: &Path, number: usize) -> PathBuf {
+    with_suffix(base_path, &format!(".{number:03}"))
+}
+
+/// Concatenates `parts` into a new file at `joined_path`.
+fn join_parts<P>(parts: &[P], joined_path: &Path) -> Result<()>
+where
+    P: AsRef<Path>
+{
+    let mut writer = BufWriter::new(File::create(joined_path).with_path(joined_path)?);
+    for part in parts {
+        let part = part.as_ref();
+        io::copy(&mut File::open(part).with_path(part)?, &mut writer)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_archives_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp = temp_dir.path();
+        let level = temp.join("Me - Level");
+        fs::create_dir_all(&level).unwrap();
+        fs::write(level.join("World.ini"), "[World]\nName=Split").unwrap();
+        fs::write(level.join("Map.bin"), vec![7; 100]).unwrap();
+
+        let parts = pack_split(&level, temp.join("Level.knytt.bin"), 40).unwrap();
+        let sizes: Vec<_> = parts.iter().map(|part| fs::metadata(part).unwrap().len()).collect();
+        assert!(sizes.len() > 1 && sizes.iter().all(|&size| size > 0 && size <= 40));
+        assert_eq!(parts[1], temp.join("Level.knytt.bin.002"));
+        assert!(!temp.join("Level.knytt.bin.partial").exists());
+
+        let output_dir = unpack_parts(&parts, temp.join("out")).unwrap();
+        assert_eq!(fs::read(output_dir.join("Map.bin")).unwrap(), vec![7; 100]);
+        assert_eq!(fs::read_to_string(output_dir.join("World.ini")).unwrap(), "[World]\nName=Split");
+
+        // Splitting again into fewer parts replaces the old ones
+        let fewer = pack_split(&level, temp.join("Level.knytt.bin"), 1000).unwrap();
+        assert_eq!(fewer, [temp.join("Level.knytt.bin.001")]);
+        assert!(!temp.join("Level.knytt.bin.002").exists());
+        let output_dir = unpack_parts(&fewer, temp.join("again")).unwrap();
+        assert_eq!(fs::read(output_dir.join("Map.bin")).unwrap(), vec![7; 100]);
+
+        // A failed split leaves the existing parts alone
+        assert!(pack_split(temp.join("Missing"), temp.join("Level.knytt.bin"), 40).is_err());
+        assert_eq!(fs::read_dir(temp).unwrap().count(), 4);
+
+        assert!(matches!(
+            pack_split(&level, temp.join("Level.knytt.bin"), 0),
+            Err(crate::KsError::KnyttBin(KnyttBinError::ZeroPartSize)),
+        ));
+        assert_eq!(fs::read_dir(temp).unwrap().count(), 4);
+    }
+}
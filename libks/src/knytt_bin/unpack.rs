@@ -1,14 +1,25 @@
 use std::{
-    env,
+    cmp::min,
     fs::{self, File, OpenOptions},
     path::{Path, PathBuf},
-    io::{BufReader, BufRead, BufWriter, Write, Read},
+    io::{BufReader, BufRead, BufWriter, Read, Seek, Take, Write},
 };
 
-use byteorder::{LittleEndian, ReadBytesExt};
-
-use crate::{io_util, Result, constants::MB};
-use super::{KnyttBinError, ENTRY_SIGNATURE};
+use crate::{
+    error::{ErrorContext, ResultExt},
+    issues::Issues,
+    io_util,
+    perf,
+    Result,
+    constants::MB,
+};
+use super::{
+    attributes,
+    audit::{has_trailing_dot_or_space, is_reserved_name},
+    footer,
+    raw,
+    KnyttBinError,
+};
 
 /// Configures the behavior of [`unpack_with_options`].
 #[derive(Debug)]
@@ -24,6 +35,168 @@ pub struct UnpackOptions {
     pub max_file_size: usize,
     /// The maximum length in bytes allow for a single file path. Defaults to 256.
     pub max_path_len: usize,
+    /// What to do with paths Windows can't create, like `CON` or `Music.`. Defaults to
+    /// [`ReservedNamePolicy::Reject`] on Windows and [`ReservedNamePolicy::Allow`] elsewhere.
+    pub reserved_names: ReservedNamePolicy,
+    /// If `true`, the modification times and read-only flags recorded by
+    /// [`PackOptions::preserve_attributes`](super::PackOptions::preserve_attributes) are
+    /// applied to the unpacked files. Archives without them unpack as usual. Defaults to
+    /// `false`.
+    pub preserve_attributes: bool,
+}
+
+/// How [`UnpackOptions`] treats path components that Windows reserves, like `CON`, `NUL`, or
+/// `COM1.png`, or that end with a dot or space.
+///
+/// On Windows, these fail with confusing IO errors or land somewhere else, and KS can't load
+/// them either. Elsewhere they unpack fine, but the level won't work on Windows. The default is
+/// [`ReservedNamePolicy::Reject`] on Windows and [`ReservedNamePolicy::Allow`] elsewhere, so
+/// archives that unpack on a platform keep unpacking there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReservedNamePolicy {
+    /// Fail with [`KnyttBinError::ReservedName`].
+    Reject,
+    /// Strip trailing dots and spaces, and add `_` to the end of reserved names, e.g.
+    /// `con_.ogg`. A renamed file can collide with another entry, which fails like any other
+    /// existing file.
+    ///
+    /// Nothing else is changed, so World.ini and Map.bin still refer to the file by its
+    /// original name. Unless those references are fixed by hand, the level won't find a
+    /// renamed tileset, sound, or image.
+    Rename,
+    /// Unpack the paths as written.
+    Allow,
+}
+
+impl Default for ReservedNamePolicy {
+    fn default() -> Self {
+        if cfg!(windows) {
+            ReservedNamePolicy::Reject
+        }
+        else {
+            ReservedNamePolicy::Allow
+        }
+    }
+}
+
+impl ReservedNamePolicy {
+    /// Applies the policy to `path`, returning the path to unpack to.
+    fn apply(self, path: PathBuf) -> Result<PathBuf> {
+        let is_bad = |name: &str| is_reserved_name(name) || has_trailing_dot_or_space(name);
+        if self == ReservedNamePolicy::Allow
+            || !path.iter().any(|component| component.to_str().is_some_and(is_bad))
+        {
+            return Ok(path);
+        }
+
+        match self {
+            ReservedNamePolicy::Reject => Err(KnyttBinError::ReservedName(path).into()),
+            _ => Ok(path.iter()
+                .map(|component| match component.to_str() {
+                    Some(name) if is_bad(name) => rename_component(name).into(),
+                    _ => component.to_owned(),
+                })
+                .collect()),
+        }
+    }
+}
+
+/// Renames a path component for [`ReservedNamePolicy::Rename`].
+fn rename_component(name: &str) -> String {
+    let name = match name.trim_end_matches(['.', ' ']) {
+        "" => "_",
+        trimmed => trimmed,
+    };
+
+    if is_reserved_name(name) {
+        let stem = name.split('.').next().unwrap_or(name);
+        format!("{}_{}", stem.trim_end(), &name[stem.len()..])
+    }
+    else {
+        name.to_owned()
+    }
+}
+
+/// A file that [`UnpackOptions::dry_run`] found would be unpacked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFile {
+    /// The path the file would be written to, relative to the output directory.
+    pub path: PathBuf,
+    pub size: usize,
+}
+
+/// The result of [`UnpackOptions::dry_run`].
+#[derive(Debug, Default)]
+pub struct UnpackPlan {
+    /// The directory the files would be unpacked into.
+    pub output_dir: PathBuf,
+    /// The files that would be unpacked, in order.
+    pub files: Vec<PlannedFile>,
+    /// The problems that would stop unpacking, or that would make
+    /// [`unpack_with_issues`] skip an entry.
+    pub issues: Issues,
+}
+
+impl UnpackPlan {
+    /// Returns the total size of the files that would be unpacked.
+    pub fn total_size(&self) -> u64 {
+        self.files.iter()
+            .map(|file| file.size as u64)
+            .sum()
+    }
+}
+
+impl UnpackOptions {
+    /// Reads the .knytt.bin at `bin_path` and validates it as [`unpack_with_options`] would
+    /// with these options, without touching the file system.
+    ///
+    /// Problems are collected rather than returned, so one dry run can report everything
+    /// wrong with an archive. Reading stops if the data itself is unreadable. Errors are only
+    /// returned if the file can't be opened or its first entry can't be read.
+    pub fn dry_run<P1, P2>(&self, bin_path: P1, output_dir: P2) -> Result<UnpackPlan>
+    where
+        P1: AsRef<Path>,
+        P2: AsRef<Path>
+    {
+        let bin_path = bin_path.as_ref();
+        let mut reader = open_archive(bin_path)?;
+        let mut buf = Vec::new();
+        let mut plan = UnpackPlan::default();
+
+        let level_name = raw::read_header(&mut reader, self.max_path_len)?.safe_path()
+            .and_then(|level_name| self.reserved_names.apply(level_name));
+        plan.output_dir = match plan.issues.report(level_name) {
+            Some(level_name) if self.create_top_level_dir => output_dir.as_ref().join(level_name),
+            _ => output_dir.as_ref().to_owned(),
+        };
+        plan.issues.report(prepare_output_dir(&plan.output_dir, self, true));
+
+        if let Err(err) = plan_entries(&mut reader, &mut buf, self, &mut plan) {
+            plan.issues.push(err.with_context(ErrorContext::path(bin_path)));
+        }
+
+        Ok(plan)
+    }
+}
+
+/// Validates the remaining .knytt.bin entries from `reader`, adding them to `plan`.
+fn plan_entries(
+    reader: &mut BufReader<Take<File>>,
+    buf: &mut Vec<u8>,
+    options: &UnpackOptions,
+    plan: &mut UnpackPlan,
+) -> Result<()> {
+    while !reader.fill_buf()?.is_empty() {
+        let header = raw::read_header(reader, options.max_path_len)?;
+        let size = header.data_len();
+        let path = validate_entry(&header, options).context(ErrorContext::key(header.path.clone()));
+        if let Some(path) = plan.issues.report(path) {
+            plan.files.push(PlannedFile { path, size });
+        }
+        skip_entry_data(reader, buf, header.path, size)?;
+    }
+
+    Ok(())
 }
 
 impl Default for UnpackOptions {
@@ -33,6 +206,8 @@ impl Default for UnpackOptions {
             create_top_level_dir: true,
             max_file_size: 256 * MB,
             max_path_len: 256,
+            reserved_names: ReservedNamePolicy::default(),
+            preserve_attributes: false,
         }
     }
 }
@@ -61,129 +236,251 @@ where
     P1: AsRef<Path>,
     P2: AsRef<Path>
 {
-    let mut reader = {
-        let file = File::open(bin_path)?;
-        BufReader::new(file)
+    unpack_impl(bin_path.as_ref(), output_dir.as_ref(), options, None, None)
+}
+
+/// Like [`unpack_with_options`], but tolerates bad entries the way KS does.
+/// 
+/// Entries with an empty or illegal path, or that are larger than
+/// [`UnpackOptions::max_file_size`], are skipped and recorded in `issues`. If the data becomes
+/// unreadable (e.g. because it's truncated), the error is recorded and the files unpacked so far
+/// are kept. Problems that prevent unpacking from starting, such as an unreadable file or an
+/// unauthorized overwrite, are still returned as errors.
+pub fn unpack_with_issues<P1, P2>(
+    bin_path: P1,
+    output_dir: P2,
+    options: UnpackOptions,
+    issues: &mut Issues,
+) -> Result<PathBuf>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>
+{
+    unpack_impl(bin_path.as_ref(), output_dir.as_ref(), options, Some(issues), None)
+}
+
+/// The outcome of [`unpack_resume`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumeReport {
+    /// The directory the files were unpacked into.
+    pub output_dir: PathBuf,
+    /// The number of files written.
+    pub unpacked: usize,
+    /// The number of files that were already unpacked and left alone.
+    pub already_unpacked: usize,
+}
+
+/// Continues an unpack of the .knytt.bin at `bin_path` into a subdirectory of `output_dir`
+/// that was interrupted, e.g. by a crash or a full disk.
+///
+/// Files that already exist with the same contents as their entries are left alone. Any
+/// others, such as a file that was only partly written, are replaced. Files that aren't in the
+/// archive are kept. The default [`UnpackOptions`] are used, except that the subdirectory may
+/// already have files in it.
+pub fn unpack_resume<P1, P2>(bin_path: P1, output_dir: P2) -> Result<ResumeReport>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>
+{
+    let mut report = ResumeReport {
+        output_dir: PathBuf::new(),
+        unpacked: 0,
+        already_unpacked: 0,
     };
+    report.output_dir = unpack_impl(
+        bin_path.as_ref(),
+        output_dir.as_ref(),
+        UnpackOptions::default(),
+        None,
+        Some(&mut report),
+    )?;
+
+    Ok(report)
+}
+
+/// Unpacks the .knytt.bin at `bin_path`. If `issues` is `Some`, bad entries are recorded there
+/// instead of causing an error. If `resume` is `Some`, existing files are kept if they match
+/// and counted there.
+fn unpack_impl(
+    bin_path: &Path,
+    output_dir: &Path,
+    options: UnpackOptions,
+    mut issues: Option<&mut Issues>,
+    resume: Option<&mut ResumeReport>,
+) -> Result<PathBuf> {
+    let mut reader = open_archive(bin_path)?;
     let mut buf = Vec::<u8>::with_capacity(4 * MB);
 
     // First header gives the name of the enclosing directory
     // It also gives a number related to the number of packed files, but which may be higher or lower
     // depending on some arcane rules in the original packer implementation, rendering it useless.
-    let (level_name, _) = read_entry_header(&mut reader, &mut buf, options.max_path_len)?;
+    let level_name = raw::read_header(&mut reader, options.max_path_len)?.safe_path()?;
+    let level_name = options.reserved_names.apply(level_name)?;
 
     // Determine the final output directory
     let output_dir =
         if options.create_top_level_dir {
-            output_dir.as_ref().join(level_name)
+            output_dir.join(level_name)
         }
         else {
-            output_dir.as_ref().to_owned()
+            output_dir.to_owned()
         };
 
     // Check if the output path exists and create if necessary
-    {
-        use io_util::PathInfo::*;
+    if resume.is_some() {
         match io_util::path_info(&output_dir)? {
-            NonemptyDirectory if options.allow_overwrite => {
-                fs::remove_dir_all(&output_dir)?;
-                fs::create_dir_all(&output_dir)?;
-            },
-            NonemptyDirectory => {
-                return Err(KnyttBinError::UnauthorizedOverwrite(output_dir).into());
-            },
-            EmptyDirectory => (),
-            Nonexistent => {
-                fs::create_dir_all(&output_dir)?;
-            },
-            _ => {
-                return Err(KnyttBinError::OutputPathExists(output_dir).into());
-            },
-        };
+            io_util::PathInfo::NonemptyDirectory | io_util::PathInfo::EmptyDirectory => (),
+            io_util::PathInfo::Nonexistent => fs::create_dir_all(&output_dir)?,
+            _ => return Err(KnyttBinError::OutputPathExists(output_dir).into()),
+        }
+    }
+    else {
+        prepare_output_dir(&output_dir, &options, false)?;
     }
 
-    // cd into the world directory temporarily
-    let prev_working_dir = env::current_dir()?;
-    env::set_current_dir(&output_dir)?;
+    // Entries are written through the long form of the directory, so deeply nested files can
+    // be unpacked on Windows
+    let root = io_util::long_path_root(&output_dir)?;
 
     // Unpack the contents
-    while !reader.fill_buf()?.is_empty() {
-        unpack_next_entry(&mut reader, &mut buf, options.max_path_len, options.max_file_size)?;
+    let result = unpack_entries(
+        &mut reader, &mut buf, &root, &options,
+        issues.as_deref_mut(), resume,
+    );
+
+    match (result, issues) {
+        (Err(err), Some(issues)) => issues.push(err.with_context(ErrorContext::path(bin_path))),
+        (result, _) => result?,
     }
 
-    // Restore working directory
-    env::set_current_dir(prev_working_dir)?;
+    if options.preserve_attributes {
+        apply_attributes(bin_path, &root, &options)?;
+    }
 
     Ok(output_dir)
 }
 
-/// Parses a .knytt.bin entry header from `reader`.
-/// 
-/// The header format is:
-/// - Signature `"NF"` (2 bytes)
-/// - Null-terminated file path (relative to root directory)
-/// - File size (unsigned 32-bit integer)
-fn read_entry_header(
-    reader: &mut BufReader<File>, 
-    buf: &mut Vec<u8>,
-    max_path_len: usize,
-) -> Result<(PathBuf, usize)> {
-    // Validate entry signature
-    {
-        let mut buf = [0u8; 2];
-        reader.read_exact(&mut buf)?;
-        if buf != ENTRY_SIGNATURE {
-            return Err(KnyttBinError::UnrecognizedSignature(buf).into());
-        }
-    }
+/// Applies the attributes file of the .knytt.bin at `bin_path`, if it has one, to the files
+/// unpacked into the directory at `root`. Files that weren't unpacked are skipped.
+fn apply_attributes(bin_path: &Path, root: &Path, options: &UnpackOptions) -> Result<()> {
+    let Some(attributes) = attributes::read_attributes(bin_path)? else {
+        return Ok(());
+    };
 
-    // Read and validate path
-    let path: PathBuf = {
-        let path = io_util::read_windows_1252_null_term(reader, buf, max_path_len)?;
+    for entry in attributes {
+        let header = raw::EntryHeader { path: entry.path.clone(), len: 0 };
+        let Ok(path) = validate_entry(&header, options) else {
+            continue;
+        };
 
-        if path.is_empty() {
-            return Err(KnyttBinError::EmptyPath.into());
+        let path = io_util::join_relative(root, &path);
+        if path.is_file() {
+            entry.apply(&path)?;
         }
+    }
 
-        let path = PathBuf::from(path);
+    Ok(())
+}
 
-        if path.is_absolute()
-            || path.iter().any(|part| part == "..")
-        {
-            return Err(KnyttBinError::IllegalPath(path).into());
-        }
+/// Opens the .knytt.bin at `bin_path` for reading entries, stopping before a valid footer if
+/// there is one.
+pub(super) fn open_archive(bin_path: &Path) -> Result<BufReader<Take<File>>> {
+    let mut file = File::open(bin_path).with_path(bin_path)?;
+    let archive_len = file.metadata()?.len() - footer::footer_len(&mut file)?;
+    file.rewind()?;
+    Ok(BufReader::new(file.take(archive_len)))
+}
 
-        path
+/// Checks that unpacking into `output_dir` is allowed by `options`, and creates or empties it
+/// unless `dry_run` is `true`.
+fn prepare_output_dir(output_dir: &Path, options: &UnpackOptions, dry_run: bool) -> Result<()> {
+    use io_util::PathInfo::*;
+    match io_util::path_info(output_dir)? {
+        NonemptyDirectory if options.allow_overwrite => {
+            if !dry_run {
+                fs::remove_dir_all(output_dir)?;
+                fs::create_dir_all(output_dir)?;
+            }
+        },
+        NonemptyDirectory => {
+            return Err(KnyttBinError::UnauthorizedOverwrite(output_dir.to_owned()).into());
+        },
+        EmptyDirectory => (),
+        Nonexistent => {
+            if !dry_run {
+                fs::create_dir_all(output_dir)?;
+            }
+        },
+        _ => {
+            return Err(KnyttBinError::OutputPathExists(output_dir.to_owned()).into());
+        },
     };
 
-    // Read and validate size
-    let size: usize = reader.read_u32::<LittleEndian>()?
-        .try_into()
-        .expect("u32::MAX should be less than or equal to usize::MAX");
+    Ok(())
+}
+
+/// Unpacks the remaining .knytt.bin entries from `reader` into the directory at `root`.
+fn unpack_entries(
+    reader: &mut BufReader<Take<File>>,
+    buf: &mut Vec<u8>,
+    root: &Path,
+    options: &UnpackOptions,
+    mut issues: Option<&mut Issues>,
+    mut resume: Option<&mut ResumeReport>,
+) -> Result<()> {
+    while !reader.fill_buf()?.is_empty() {
+        unpack_next_entry(reader, buf, root, options, issues.as_deref_mut(), resume.as_deref_mut())?;
+    }
 
-    Ok((path, size))
+    Ok(())
 }
 
-/// Unpacks the next .knytt.bin entry from `reader` into the current working directory.
+/// Unpacks the next .knytt.bin entry from `reader` into the directory at `root`.
+/// 
+/// If `issues` is `Some`, an entry with a bad path or size is recorded there and skipped. If
+/// `resume` is `Some`, an existing file is left alone if it matches the entry and replaced
+/// otherwise. Errors are given the entry's path as their key.
 fn unpack_next_entry(
-    reader: &mut BufReader<File>,
+    reader: &mut BufReader<Take<File>>,
     buf: &mut Vec<u8>,
-    max_path_len: usize,
-    max_file_size: usize,
+    root: &Path,
+    options: &UnpackOptions,
+    issues: Option<&mut Issues>,
+    resume: Option<&mut ResumeReport>,
 ) -> Result<()> {
-    let (path, file_size) = read_entry_header(reader, buf, max_path_len)?;
+    let header = raw::read_header(reader, options.max_path_len)?;
+    let context = ErrorContext::key(header.path.clone());
+    unpack_entry_data(reader, buf, root, options, issues, resume, header)
+        .context(context)
+}
 
-    if file_size > max_file_size {
-        return Err(KnyttBinError::OversizedFile {
-            path,
-            size: file_size,
-        }.into());
-    }
+/// Unpacks the data of the entry with `header`. See [`unpack_next_entry`].
+fn unpack_entry_data(
+    reader: &mut BufReader<Take<File>>,
+    buf: &mut Vec<u8>,
+    root: &Path,
+    options: &UnpackOptions,
+    issues: Option<&mut Issues>,
+    mut resume: Option<&mut ResumeReport>,
+    header: raw::EntryHeader,
+) -> Result<()> {
+    let file_size = header.data_len();
+    let path = validate_entry(&header, options);
+
+    let path = match (path, issues) {
+        (Ok(path), _) => path,
+        (Err(err), Some(issues)) => {
+            issues.push(err.with_context(ErrorContext::key(header.path.clone())));
+            return skip_entry_data(reader, buf, header.path, file_size);
+        },
+        (Err(err), None) => return Err(err),
+    };
 
     // Read contents
     {
         io_util::resize_buffer(buf, file_size);
         let bytes_read = io_util::read_at_most(reader, buf.as_mut_slice())?;
+        perf::count(|c| c.bytes_read += bytes_read as u64);
         if bytes_read < file_size {
             return Err(KnyttBinError::MissingData {
                 path,
@@ -195,21 +492,218 @@ fn unpack_next_entry(
 
     // Write the contents to disk
     {
+        let path = io_util::join_relative(root, &path);
         if let Some(parent) = path.parent() {
-            if parent.iter().next().is_some() {
-                fs::create_dir_all(parent)?;
+            fs::create_dir_all(parent)?;
+        }
+
+        if let Some(report) = resume.as_deref_mut() {
+            if is_already_unpacked(&path, buf)? {
+                report.already_unpacked += 1;
+                return Ok(());
             }
         }
 
         let mut writer = {
             let file = OpenOptions::new()
                 .write(true)
-                .create_new(true)
+                .create_new(resume.is_none())
+                .create(true)
+                .truncate(true)
                 .open(path)?;
             BufWriter::new(file)
         };
         writer.write_all(buf)?;
     }
 
+    if let Some(report) = resume {
+        report.unpacked += 1;
+    }
+
+    Ok(())
+}
+
+/// Returns `true` if the file at `path` exists with exactly the contents `data`. The size is
+/// compared first, so most mismatches are found without reading the file.
+fn is_already_unpacked(path: &Path, data: &[u8]) -> Result<bool> {
+    match fs::metadata(path) {
+        Ok(meta) if meta.is_file() && meta.len() == data.len() as u64 => Ok(fs::read(path)? == data),
+        Ok(_) => Ok(false),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Returns the path to unpack the entry with `header` to, relative to the output directory, or
+/// an error if it isn't allowed by `options`.
+fn validate_entry(header: &raw::EntryHeader, options: &UnpackOptions) -> Result<PathBuf> {
+    let path = options.reserved_names.apply(header.safe_path()?)?;
+    let file_size = header.data_len();
+    if file_size > options.max_file_size {
+        return Err(KnyttBinError::OversizedFile {
+            path,
+            size: file_size,
+        }.into());
+    }
+
+    Ok(path)
+}
+
+/// Skips the `file_size` bytes of data belonging to the entry at `path`.
+fn skip_entry_data(
+    reader: &mut BufReader<Take<File>>,
+    buf: &mut Vec<u8>,
+    path: String,
+    file_size: usize,
+) -> Result<()> {
+    io_util::resize_buffer(buf, min(file_size, MB));
+    let bytes_read = io_util::skip_at_most(reader, buf, file_size)?;
+    perf::count(|c| c.bytes_read += bytes_read as u64);
+    if bytes_read < file_size {
+        return Err(KnyttBinError::MissingData {
+            path: path.into(),
+            file_size,
+            bytes_read,
+        }.into());
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_names_are_rejected_or_renamed() {
+        let path = PathBuf::from("Music./con.ogg");
+        assert!(ReservedNamePolicy::Reject.apply(path.clone()).is_err());
+        assert_eq!(ReservedNamePolicy::Rename.apply(path.clone()).unwrap(), PathBuf::from("Music/con_.ogg"));
+        assert_eq!(ReservedNamePolicy::Allow.apply(path.clone()).unwrap(), path);
+
+        let fine = PathBuf::from("Custom Objects/Console.png");
+        assert_eq!(ReservedNamePolicy::Reject.apply(fine.clone()).unwrap(), fine);
+        assert_eq!(rename_component("LPT1 .txt."), "LPT1_.txt");
+
+        let expected = if cfg!(windows) { ReservedNamePolicy::Reject } else { ReservedNamePolicy::Allow };
+        assert_eq!(UnpackOptions::default().reserved_names, expected);
+    }
+
+    #[test]
+    fn long_paths_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp = temp_dir.path();
+        let level = temp.join("Me - Level");
+        let nested: PathBuf = (0..6).map(|i| format!("{i}{}", "d".repeat(60))).collect();
+        fs::create_dir_all(level.join(&nested)).unwrap();
+        fs::write(level.join(&nested).join("World.ini"), "[World]").unwrap();
+
+        let bin_path = temp.join("Level.knytt.bin");
+        let working_dir = std::env::current_dir().unwrap();
+        assert_eq!(crate::knytt_bin::pack(&level, &bin_path).unwrap(), 1);
+
+        let options = UnpackOptions { max_path_len: 1024, ..Default::default() };
+        let output_dir = unpack_with_options(&bin_path, temp.join("out"), options).unwrap();
+        assert_eq!(std::env::current_dir().unwrap(), working_dir);
+        assert!(output_dir.join(&nested).join("World.ini").as_os_str().len() > 400);
+        assert_eq!(fs::read_to_string(output_dir.join(&nested).join("World.ini")).unwrap(), "[World]");
+
+        // A symlinked directory is packed under the name of the link
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&level, temp.join("Me - Link")).unwrap();
+            crate::knytt_bin::pack(temp.join("Me - Link"), temp.join("Link.knytt.bin")).unwrap();
+            let options = UnpackOptions { max_path_len: 1024, ..Default::default() };
+            let output_dir = unpack_with_options(temp.join("Link.knytt.bin"), temp.join("out"), options).unwrap();
+            assert_eq!(output_dir, temp.join("out/Me - Link"));
+        }
+    }
+
+    #[test]
+    fn dry_run_plans_without_writing() {
+        let mut data = Vec::new();
+        for (path, contents) in [
+            ("Me - Level", &b""[..]),
+            ("World.ini", b"[World]"),
+            ("Music/Song1.ogg", b"0123456789"),
+            ("../Evil", b""),
+        ] {
+            let header = raw::EntryHeader { path: path.to_owned(), len: contents.len() as u32 };
+            raw::write_header(&mut data, &header).unwrap();
+            data.extend_from_slice(contents);
+        }
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp = temp_dir.path();
+        let bin_path = temp.join("Level.knytt.bin");
+        fs::write(&bin_path, &data).unwrap();
+
+        let options = UnpackOptions { max_file_size: 8, ..Default::default() };
+        let plan = options.dry_run(&bin_path, temp.join("out")).unwrap();
+        assert_eq!(plan.output_dir, temp.join("out/Me - Level"));
+        assert_eq!(plan.files, [PlannedFile { path: "World.ini".into(), size: 7 }]);
+        assert_eq!(plan.total_size(), 7);
+        assert_eq!(plan.issues.len(), 2);
+        assert!(!temp.join("out").exists());
+
+        let mut issues = Issues::new();
+        unpack_with_issues(&bin_path, temp.join("out"), options, &mut issues).unwrap();
+        let keys: Vec<_> = issues.iter().map(|issue| issue.context().key).collect();
+        assert_eq!(keys, [Some("Music/Song1.ogg".to_owned()), Some("../Evil".to_owned())]);
+        assert_eq!(plan.issues.iter().next().unwrap().context().key.as_deref(), Some("Music/Song1.ogg"));
+    }
+
+    #[test]
+    fn interrupted_unpack_resumes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp = temp_dir.path();
+        let level = temp.join("Me - Level");
+        fs::create_dir_all(level.join("Music")).unwrap();
+        fs::write(level.join("World.ini"), "[World]").unwrap();
+        fs::write(level.join("Music/Song1.ogg"), "0123456789").unwrap();
+        let bin_path = temp.join("Level.knytt.bin");
+        crate::knytt_bin::pack(&level, &bin_path).unwrap();
+
+        // Simulate a crash partway through writing the second file
+        let output_dir = temp.join("out/Me - Level");
+        fs::create_dir_all(output_dir.join("Music")).unwrap();
+        fs::write(output_dir.join("World.ini"), "[World]").unwrap();
+        fs::write(output_dir.join("Music/Song1.ogg"), "01234").unwrap();
+        assert!(unpack(&bin_path, temp.join("out")).is_err());
+
+        let report = unpack_resume(&bin_path, temp.join("out")).unwrap();
+        assert_eq!((report.unpacked, report.already_unpacked), (1, 1));
+        assert_eq!(report.output_dir, output_dir);
+        assert_eq!(fs::read_to_string(output_dir.join("Music/Song1.ogg")).unwrap(), "0123456789");
+    }
+
+    #[test]
+    fn attributes_are_preserved() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp = temp_dir.path();
+        let level = temp.join("Me - Level");
+        fs::create_dir_all(&level).unwrap();
+        let modified = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        fs::write(level.join("World.ini"), "[World]").unwrap();
+        File::options().write(true).open(level.join("World.ini")).unwrap().set_modified(modified).unwrap();
+
+        let bin_path = temp.join("Level.knytt.bin");
+        let options = crate::knytt_bin::PackOptions { preserve_attributes: true };
+        crate::knytt_bin::pack_with_options(&level, &bin_path, options).unwrap();
+
+        let attributes_path = crate::knytt_bin::attributes_path(&bin_path);
+        assert!(attributes_path.is_file());
+        let plain_bin_path = temp.join("Plain.knytt.bin");
+        crate::knytt_bin::pack(&level, &plain_bin_path).unwrap();
+        assert_eq!(fs::read(&bin_path).unwrap(), fs::read(&plain_bin_path).unwrap());
+
+        let plain = unpack(&bin_path, temp.join("plain")).unwrap();
+        assert_eq!(fs::read_to_string(plain.join("World.ini")).unwrap(), "[World]");
+        assert_ne!(fs::metadata(plain.join("World.ini")).unwrap().modified().unwrap(), modified);
+
+        let options = UnpackOptions { preserve_attributes: true, ..Default::default() };
+        let preserved = unpack_with_options(&bin_path, temp.join("preserved"), options).unwrap();
+        assert_eq!(fs::metadata(preserved.join("World.ini")).unwrap().modified().unwrap(), modified);
+    }
+}
@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+
+use crate::editions::KsEdition;
+
+#[derive(thiserror::Error, Debug)]
+pub enum LaunchError {
+    #[error("No way to run Windows programs was found. (hint: is Wine installed?)")]
+    NoRunner,
+    #[error("The executable {0} does not exist.")]
+    MissingExecutable(PathBuf),
+    #[error("No executable for {0:?} was found.")]
+    NoExecutable(KsEdition),
+    #[error("No level named `{0}` is installed.")]
+    WorldNotFound(String),
+}
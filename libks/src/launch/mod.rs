@@ -0,0 +1,281 @@
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    editions::{self, KsEdition, KsExecutable},
+    saves::{self, SaveGame},
+    Result,
+};
+
+mod error;
+pub use error::LaunchError;
+
+mod process;
+pub use process::KsProcess;
+
+/// How to run a KS executable.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Runner {
+    /// Run natively on Windows, or through the first runner [`detect_runner`] finds elsewhere.
+    /// This is the default.
+    #[default]
+    Auto,
+    /// Run the executable directly.
+    Native,
+    /// Run the executable with the given `wine` binary.
+    Wine(PathBuf),
+    /// Run the executable with the given Proton script, as `proton run <exe>`. Proton also needs
+    /// `STEAM_COMPAT_DATA_PATH` to be set, e.g. through [`LaunchOptions::env`].
+    Proton(PathBuf),
+}
+
+/// Configures the behavior of [`launch_ks`].
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    /// How to run the executable. Defaults to [`Runner::Auto`].
+    pub runner: Runner,
+    /// The working directory. Defaults to the directory containing the executable, which KS
+    /// needs in order to find its Data and Worlds directories.
+    pub working_dir: Option<PathBuf>,
+    /// Extra environment variables for the process.
+    pub env: Vec<(OsString, OsString)>,
+}
+
+/// Starts `exe` according to `options` and returns a handle to the running process.
+///
+/// On platforms other than Windows, the executable is run through Wine or Proton. With
+/// [`Runner::Auto`], [`detect_runner`] decides which; if it finds nothing,
+/// [`LaunchError::NoRunner`] is returned.
+pub fn launch_ks(exe: &KsExecutable, options: LaunchOptions) -> Result<KsProcess> {
+    launch_ks_with_args(exe, &[], options)
+}
+
+/// Like [`launch_ks`], but passes `args` to the executable.
+pub fn launch_ks_with_args(exe: &KsExecutable, args: &[OsString], options: LaunchOptions) -> Result<KsProcess> {
+    if !exe.path.is_file() {
+        return Err(LaunchError::MissingExecutable(exe.path.clone()).into());
+    }
+
+    let runner = match options.runner {
+        Runner::Auto if cfg!(windows) => Runner::Native,
+        Runner::Auto => detect_runner().ok_or(LaunchError::NoRunner)?,
+        runner => runner,
+    };
+
+    let mut command = match &runner {
+        Runner::Auto | Runner::Native => Command::new(&exe.path),
+        Runner::Wine(wine) => {
+            let mut command = Command::new(wine);
+            command.arg(&exe.path);
+            command
+        },
+        Runner::Proton(proton) => {
+            let mut command = Command::new(proton);
+            command.arg("run").arg(&exe.path);
+            command
+        },
+    };
+
+    let working_dir = options.working_dir
+        .or_else(|| exe.path.parent().map(Path::to_owned));
+    if let Some(working_dir) = working_dir {
+        command.current_dir(working_dir);
+    }
+
+    command.args(args);
+    command.envs(options.env);
+
+    Ok(KsProcess::new(command.spawn()?))
+}
+
+/// Prepares save slot `slot` (1-3) of the KS installation at `ks_dir` for the level in
+/// `Worlds/{world_name}` and returns the save in the slot.
+///
+/// If the slot already holds a game in that level, it's kept so the player can continue.
+/// Otherwise, a new game is started in the slot as if the level had been picked from the menu.
+///
+/// KS has no confirmed way to skip its menus, so this doesn't boot into the level: after
+/// starting KS with [`launch_ks`], the player still has to choose the slot.
+/// [`KsProcess::watch_saves`] reports their progress from there.
+pub fn prepare_slot<P>(ks_dir: P, world_name: &str, slot: u8) -> Result<SaveGame>
+where
+    P: AsRef<Path>
+{
+    let ks_dir = ks_dir.as_ref();
+    let world_dir = ks_dir.join("Worlds").join(world_name);
+    if world_name.is_empty() || !world_dir.is_dir() {
+        return Err(LaunchError::WorldNotFound(world_name.to_owned()).into());
+    }
+
+    let slot_path = saves::slot_path(ks_dir, slot);
+    if slot_path.is_file() {
+        let save = SaveGame::load(&slot_path)?;
+        if save.world().is_some_and(|world| world.eq_ignore_ascii_case(world_name)) {
+            return Ok(save);
+        }
+    }
+
+    saves::start_new_game(ks_dir, slot, &world_dir)
+}
+
+/// Prepares save slot `slot` (1-3) for the level in `Worlds/{world_name}` with [`prepare_slot`],
+/// then launches the `edition` executable in `ks_dir` with [`launch_ks`].
+///
+/// This doesn't boot into the level: the player still has to choose the slot from the menu.
+/// The returned process is already watching the Saves directory. See [`KsProcess::watch_saves`].
+///
+/// The default launch options will be used. If you need to override them, use
+/// [`launch_world_with_options`].
+pub fn launch_world<P>(ks_dir: P, world_name: &str, slot: u8, edition: KsEdition) -> Result<KsProcess>
+where
+    P: AsRef<Path>
+{
+    launch_world_with_options(ks_dir, world_name, slot, edition, LaunchOptions::default())
+}
+
+/// Like [`launch_world`], but with custom launch options.
+pub fn launch_world_with_options<P>(
+    ks_dir: P,
+    world_name: &str,
+    slot: u8,
+    edition: KsEdition,
+    options: LaunchOptions,
+) -> Result<KsProcess>
+where
+    P: AsRef<Path>
+{
+    let ks_dir = ks_dir.as_ref();
+    let exe = editions::list_executables(ks_dir)
+        .into_iter()
+        .find(|exe| exe.edition == edition)
+        .ok_or(LaunchError::NoExecutable(edition))?;
+
+    prepare_slot(ks_dir, world_name, slot)?;
+
+    let mut process = launch_ks(&exe, options)?;
+    process.watch_saves(ks_dir)?;
+
+    Ok(process)
+}
+
+/// Finds a way to run Windows programs on this platform. Returns [`Runner::Native`] on Windows.
+///
+/// Elsewhere, the `WINE` environment variable is checked first, then `wine` and `wine64` are
+/// searched for on the `PATH`. Proton is never detected automatically because it needs a
+/// prefix to be chosen.
+pub fn detect_runner() -> Option<Runner> {
+    if cfg!(windows) {
+        return Some(Runner::Native);
+    }
+
+    if let Some(wine) = env::var_os("WINE") {
+        let wine = PathBuf::from(wine);
+        if wine.is_file() {
+            return Some(Runner::Wine(wine));
+        }
+    }
+
+    ["wine", "wine64"].into_iter()
+        .find_map(find_on_path)
+        .map(Runner::Wine)
+}
+
+/// Searches the directories in the `PATH` environment variable for a file named `name`.
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn runners_wrap_the_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ks_dir = temp_dir.path().canonicalize().unwrap();
+        let exe = KsExecutable {
+            edition: KsEdition::Vanilla,
+            path: ks_dir.join("Knytt Stories.exe"),
+        };
+        let options = |runner| LaunchOptions {
+            runner,
+            env: vec![("LIBKS_TEST".into(), "set".into())],
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            launch_ks(&exe, options(Runner::Native)),
+            Err(crate::KsError::Launch(LaunchError::MissingExecutable(_))),
+        ));
+        fs::write(&exe.path, "").unwrap();
+
+        // A stand-in for Wine and Proton that records how it was run
+        let runner = ks_dir.join("runner.sh");
+        fs::write(&runner, "#!/bin/sh\necho \"$(pwd -P)|$*|$LIBKS_TEST\" > runner.txt\n").unwrap();
+        fs::set_permissions(&runner, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let exe_path = exe.path.display();
+        let mut process = launch_ks_with_args(&exe, &["-x".into()], options(Runner::Wine(runner.clone()))).unwrap();
+        assert!(process.wait().unwrap().success());
+        assert_eq!(
+            fs::read_to_string(ks_dir.join("runner.txt")).unwrap(),
+            format!("{}|{exe_path} -x|set\n", ks_dir.display()),
+        );
+
+        let mut process = launch_ks(&exe, options(Runner::Proton(runner))).unwrap();
+        assert!(process.wait().unwrap().success());
+        assert_eq!(
+            fs::read_to_string(ks_dir.join("runner.txt")).unwrap(),
+            format!("{}|run {exe_path}|set\n", ks_dir.display()),
+        );
+    }
+
+    #[test]
+    fn slots_are_kept_or_restarted() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ks_dir = temp_dir.path();
+        fs::create_dir_all(ks_dir.join("Worlds/Me - Level")).unwrap();
+        fs::create_dir_all(ks_dir.join("Saves")).unwrap();
+
+        assert!(matches!(
+            prepare_slot(ks_dir, "Me - Missing", 1),
+            Err(crate::KsError::Launch(LaunchError::WorldNotFound(_))),
+        ));
+
+        // A game in the same level continues
+        SaveGame::new("me - level", (1005, 1000), (3, 4)).write(saves::slot_path(ks_dir, 1)).unwrap();
+        let save = prepare_slot(ks_dir, "Me - Level", 1).unwrap();
+        assert_eq!(save.screen(), Some((1005, 1000)));
+
+        // A game in another level is replaced with a new game
+        SaveGame::new("Me - Other", (1005, 1000), (3, 4)).write(saves::slot_path(ks_dir, 2)).unwrap();
+        let save = prepare_slot(ks_dir, "Me - Level", 2).unwrap();
+        assert_eq!(save.world(), Some("Me - Level"));
+        assert_eq!(save.screen(), Some((1000, 1000)));
+        assert_eq!(SaveGame::load(saves::slot_path(ks_dir, 2)).unwrap().world(), Some("Me - Level"));
+    }
+
+    #[test]
+    fn launch_world_needs_the_executable() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ks_dir = temp_dir.path();
+        fs::create_dir_all(ks_dir.join("Worlds/Me - Level")).unwrap();
+
+        assert!(matches!(
+            launch_world(ks_dir, "Me - Level", 1, KsEdition::Vanilla),
+            Err(crate::KsError::Launch(LaunchError::NoExecutable(KsEdition::Vanilla))),
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    process::{Child, ExitStatus},
+    time::SystemTime,
+};
+
+use crate::Result;
+
+/// A running KS process started by [`launch_ks`](super::launch_ks).
+///
+/// The handle can also watch a Saves directory so that tools can tell when the player has saved,
+/// e.g. to detect that a tester reached the end of a run. Watching works by comparing
+/// modification times whenever [`KsProcess::changed_saves`] is called.
+pub struct KsProcess {
+    child: Child,
+    saves_dir: Option<PathBuf>,
+    save_times: HashMap<PathBuf, SystemTime>,
+}
+
+impl KsProcess {
+    pub(super) fn new(child: Child) -> KsProcess {
+        KsProcess {
+            child,
+            saves_dir: None,
+            save_times: HashMap::new(),
+        }
+    }
+
+    /// Returns the OS process ID.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Returns `true` if the process hasn't exited yet.
+    pub fn is_running(&mut self) -> Result<bool> {
+        Ok(self.child.try_wait()?.is_none())
+    }
+
+    /// Blocks until the process exits and returns its exit status.
+    pub fn wait(&mut self) -> Result<ExitStatus> {
+        Ok(self.child.wait()?)
+    }
+
+    /// Forcibly stops the process. Unsaved progress is lost.
+    pub fn kill(&mut self) -> Result<()> {
+        match self.child.kill() {
+            // The process already exited
+            Err(err) if err.kind() == io::ErrorKind::InvalidInput => Ok(()),
+            result => Ok(result?),
+        }
+    }
+
+    /// Starts watching the Saves directory of the KS installation at `ks_dir`. Saves that exist
+    /// now are considered unchanged.
+    pub fn watch_saves<P>(&mut self, ks_dir: P) -> Result<()>
+    where
+        P: AsRef<Path>
+    {
+        let saves_dir = ks_dir.as_ref().join("Saves");
+        self.save_times = save_times(&saves_dir)?;
+        self.saves_dir = Some(saves_dir);
+
+        Ok(())
+    }
+
+    /// Returns the save files that were created or modified since watching started or this was
+    /// last called. Returns nothing if [`KsProcess::watch_saves`] hasn't been called.
+    pub fn changed_saves(&mut self) -> Result<Vec<PathBuf>> {
+        let Some(saves_dir) = &self.saves_dir else {
+            return Ok(Vec::new());
+        };
+
+        let current = save_times(saves_dir)?;
+        let mut changed: Vec<PathBuf> = current.iter()
+            .filter(|(path, time)| self.save_times.get(*path) != Some(time))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.sort();
+        self.save_times = current;
+
+        Ok(changed)
+    }
+
+    /// Returns the underlying process.
+    pub fn child(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
+
+/// Reads the modification times of the .ini files in `saves_dir`.
+fn save_times(saves_dir: &Path) -> Result<HashMap<PathBuf, SystemTime>> {
+    let mut times = HashMap::new();
+    if !saves_dir.is_dir() {
+        return Ok(times);
+    }
+
+    for entry in fs::read_dir(saves_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let is_ini = path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("ini"));
+        if is_ini {
+            times.insert(path, entry.metadata()?.modified()?);
+        }
+    }
+
+    Ok(times)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{process::Command, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn changed_saves_are_reported() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ks_dir = temp_dir.path();
+        let saves_dir = ks_dir.join("Saves");
+        fs::create_dir_all(&saves_dir).unwrap();
+        fs::write(saves_dir.join("slot1.ini"), "").unwrap();
+
+        let mut process = KsProcess::new(Command::new("sh").args(["-c", "exit 3"]).spawn().unwrap());
+        assert_eq!(process.changed_saves().unwrap(), Vec::<PathBuf>::new());
+        process.watch_saves(ks_dir).unwrap();
+        assert_eq!(process.changed_saves().unwrap(), Vec::<PathBuf>::new());
+
+        let slot1 = fs::File::options().write(true).open(saves_dir.join("slot1.ini")).unwrap();
+        let later = slot1.metadata().unwrap().modified().unwrap() + Duration::from_secs(1);
+        slot1.set_modified(later).unwrap();
+        fs::write(saves_dir.join("slot2.ini"), "").unwrap();
+        fs::write(saves_dir.join("notes.txt"), "").unwrap();
+        assert_eq!(process.changed_saves().unwrap(), [saves_dir.join("slot1.ini"), saves_dir.join("slot2.ini")]);
+        assert_eq!(process.changed_saves().unwrap(), Vec::<PathBuf>::new());
+
+        assert_eq!(process.wait().unwrap().code(), Some(3));
+        assert!(!process.is_running().unwrap());
+        process.kill().unwrap();
+    }
+
+    #[test]
+    fn running_processes_can_be_killed() {
+        let mut process = KsProcess::new(Command::new("sleep").arg("10").spawn().unwrap());
+        assert!(process.is_running().unwrap());
+        process.kill().unwrap();
+        assert!(!process.wait().unwrap().success());
+    }
+}
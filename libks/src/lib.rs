@@ -1,16 +1,27 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
 mod common;
+#[cfg(feature = "std")]
 mod io_util;
 
 pub mod constants;
 
+#[cfg(feature = "std")]
 pub mod knytt_bin;
+#[cfg(feature = "std")]
 pub use knytt_bin::KnyttBinError;
 
 pub mod map_bin;
+#[cfg(feature = "std")]
 pub use map_bin::MapBinError;
 
+#[cfg(feature = "std")]
 pub mod assets;
 
+#[cfg(feature = "std")]
 pub mod editions;
 
 #[cfg(feature="image")]
@@ -18,9 +29,67 @@ pub mod draw;
 #[cfg(feature="image")]
 pub use draw::DrawError;
 
+#[cfg(feature = "std")]
 pub mod world_ini;
+#[cfg(feature = "std")]
 pub use world_ini::WorldIniError;
 
+#[cfg(feature = "std")]
+pub mod world;
+#[cfg(feature = "std")]
+pub use world::{Bounds, RenameReport, World, WorldError, WorldMetadata};
+
+#[cfg(feature = "std")]
+pub mod edit;
+
+// Threads can't be spawned in the browser
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+pub mod batch;
+
+#[cfg(feature = "store")]
+pub mod store;
+#[cfg(feature = "store")]
+pub use store::StoreError;
+
+#[cfg(feature = "patch")]
+pub mod patch;
+#[cfg(feature = "patch")]
+pub use patch::PatchError;
+
+#[cfg(feature = "std")]
+pub mod saves;
+
+// Processes can't be spawned in the browser
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+pub mod launch;
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+pub use launch::LaunchError;
+
+#[cfg(feature = "std")]
+pub mod install;
+#[cfg(feature = "std")]
+pub use install::InstallError;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+
+#[cfg(feature = "std")]
+pub mod perf;
+
+#[cfg(feature = "std")]
+pub mod issues;
+#[cfg(feature = "std")]
+pub use issues::{Issue, Issues};
+
+#[cfg(feature = "std")]
 pub mod error;
-pub use error::KsError;
+#[cfg(feature = "std")]
+pub use error::{ErrorContext, KsError, ResultExt};
+#[cfg(feature = "std")]
 pub use error::Result;
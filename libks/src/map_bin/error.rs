@@ -11,5 +11,28 @@ pub enum MapBinError {
     #[error("The screen at x{}y{} is missing data.", position.0, position.1)]
     ScreenMissingData {
         position: (i64, i64),
-    }
+    },
+    #[error("There is no screen at x{}y{}.", position.0, position.1)]
+    NoScreen {
+        position: (i64, i64),
+    },
+    #[error("A screen or World.ini section already exists at x{}y{}.", position.0, position.1)]
+    ScreenExists {
+        position: (i64, i64),
+    },
+    #[error("Moving x{}y{} by ({}, {}) would put it out of range.", position.0, position.1, offset.0, offset.1)]
+    MoveOutOfRange {
+        position: (i64, i64),
+        offset: (i64, i64),
+    },
+    #[error("Layer {layer} has no tile at ({x}, {y}).")]
+    TileOutOfBounds {
+        layer: usize,
+        x: usize,
+        y: usize,
+    },
+    #[error("Layer {layer} is not a decorative tile layer (0-2).")]
+    NotDecorativeLayer {
+        layer: usize,
+    },
 }
@@ -0,0 +1,410 @@
+use std::{
+    cmp::min,
+    fs::OpenOptions,
+    io::{self, prelude::*, BufReader, BufWriter},
+    path::Path,
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+use crate::{
+    common::parse_xy,
+    constants::*,
+    error::{ErrorContext, KsError, ResultExt},
+    issues::{Issue, Issues},
+    io_util,
+    perf,
+    Result,
+};
+use super::{
+    encode_map,
+    parse_screen_bytes,
+    recover_bytes,
+    MapBinError,
+    ParseWarning,
+    RecoveredMap,
+    ScreenData,
+    SCREEN_DATA_LEN,
+};
+
+/// Parses all screens from the Map.bin data stored at `path`. The data is assumed to be gzipped.
+/// 
+/// This variant ignores abnormalities in the data. Use [`parse_map_file_with_warnings`] if you
+/// want information about abnormalities.
+pub fn parse_map_file<P>(path: P) -> Result<Vec<ScreenData>>
+where
+    P: AsRef<Path>
+{
+    Ok(parse_map_file_with_warnings(path)?.0)
+}
+
+/// Parses all screens from the Map.bin data stored at `path`. The data is assumed to be gzipped.
+/// 
+/// This variant provides warnings if there are abnormalities in the data such as non-screen entries
+/// or screens with extra data. If you don't care about these warnings, use [`parse_map_file`].
+pub fn parse_map_file_with_warnings<P>(path: P) -> Result<(Vec<ScreenData>, Vec<ParseWarning>)>
+where
+    P: AsRef<Path>
+{
+    let path = path.as_ref();
+    let file = std::fs::File::open(path).with_path(path)?;
+    let mut reader = BufReader::new(file);
+    parse_map_gzipped(&mut reader).with_path(path)
+}
+
+/// Parses as many screens as possible from the Map.bin data stored at `path`. The data is
+/// assumed to be gzipped.
+/// 
+/// This variant never fails. Abnormalities and errors are recorded in `issues` instead. If the
+/// file can't be read, no screens are returned. See [`parse_map_uncompressed_with_issues`].
+pub fn parse_map_file_with_issues<P>(path: P, issues: &mut Issues) -> Vec<ScreenData>
+where
+    P: AsRef<Path>
+{
+    let path = path.as_ref();
+    let mut file_issues = Issues::new();
+    let screens = match std::fs::File::open(path) {
+        Ok(file) => parse_map_gzipped_with_issues(&mut BufReader::new(file), &mut file_issues),
+        Err(err) => {
+            file_issues.push(KsError::from(err));
+            Vec::new()
+        },
+    };
+
+    issues.append_with_context(file_issues, ErrorContext::path(path));
+    screens
+}
+
+/// The first two bytes of gzipped data.
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// Parses all screens from `reader`, which may yield either gzipped or uncompressed Map.bin
+/// data. The data is treated as gzipped if it starts with the gzip magic bytes, which
+/// uncompressed Map.bin data never does.
+pub fn parse_map_auto<R>(reader: &mut R) -> Result<(Vec<ScreenData>, Vec<ParseWarning>)>
+where
+    R: BufRead
+{
+    if is_gzipped(reader)? {
+        parse_map_gzipped(reader)
+    }
+    else {
+        parse_map_uncompressed(reader)
+    }
+}
+
+/// Parses as many screens as possible from `reader`, which may yield either gzipped or
+/// uncompressed Map.bin data. See [`parse_map_auto`] and [`parse_map_uncompressed_with_issues`].
+pub fn parse_map_auto_with_issues<R>(reader: &mut R, issues: &mut Issues) -> Vec<ScreenData>
+where
+    R: BufRead
+{
+    match is_gzipped(reader) {
+        Ok(true) => parse_map_gzipped_with_issues(reader, issues),
+        Ok(false) => parse_map_uncompressed_with_issues(reader, issues),
+        Err(err) => {
+            issues.push(err);
+            Vec::new()
+        },
+    }
+}
+
+/// Salvages as many screens as possible from `reader`, which may yield gzipped or uncompressed
+/// Map.bin data that's truncated or partially corrupted. See [`recover_bytes`].
+///
+/// If the data can't be fully read or decompressed, whatever was read before the error is
+/// recovered. An error is only returned if nothing could be read at all.
+pub fn recover<R>(reader: &mut R) -> Result<RecoveredMap>
+where
+    R: BufRead
+{
+    let mut data = Vec::new();
+    let result = if is_gzipped(reader)? {
+        GzDecoder::new(reader).read_to_end(&mut data)
+    }
+    else {
+        reader.read_to_end(&mut data)
+    };
+
+    match result {
+        Err(err) if data.is_empty() => Err(err.into()),
+        _ => Ok(recover_bytes(&data)),
+    }
+}
+
+/// Checks whether the data in `reader` starts with the gzip magic bytes without consuming it.
+/// See [`is_gzip`].
+fn is_gzipped<R>(reader: &mut R) -> Result<bool>
+where
+    R: BufRead
+{
+    Ok(is_gzip(reader.fill_buf()?))
+}
+
+/// Returns `true` if `data` starts with the gzip magic bytes. If `data` is only one byte long,
+/// it's enough for it to match.
+pub(crate) fn is_gzip(data: &[u8]) -> bool {
+    data.first() == Some(&GZIP_MAGIC[0])
+        && data.get(1).is_none_or(|&byte| byte == GZIP_MAGIC[1])
+}
+
+/// Parses all screens from `reader`, which must yield gzipped Map.bin data.
+/// If the data is uncompressed, call [`parse_map_uncompressed`] instead.
+pub fn parse_map_gzipped<R>(reader: &mut R) -> Result<(Vec<ScreenData>, Vec<ParseWarning>)>
+where
+    R: Read
+{
+    let decoder = GzDecoder::new(reader);
+    let mut reader = BufReader::new(decoder);
+    parse_map_uncompressed(&mut reader)
+}
+
+/// Parses as many screens as possible from `reader`, which must yield gzipped Map.bin data.
+/// See [`parse_map_uncompressed_with_issues`].
+pub fn parse_map_gzipped_with_issues<R>(reader: &mut R, issues: &mut Issues) -> Vec<ScreenData>
+where
+    R: Read
+{
+    let decoder = GzDecoder::new(reader);
+    let mut reader = BufReader::new(decoder);
+    parse_map_uncompressed_with_issues(&mut reader, issues)
+}
+
+/// Parses all screens from `reader`, which must yield uncompressed Map.bin data.
+/// If the data is compressed, call [`parse_map_gzipped`] instead.
+/// 
+/// Map.bin consists solely of a series of named binary chunks called workspaces. Each
+/// workspace consists of:
+/// - A name, such as `x1000y1000`. Null-terminated string. The encoding is presumed
+///   to be Windows-1252, but this hasn't been confirmed.
+/// - Length in bytes. Little endian 32-byte integer. Presumed to be unsigned, but
+///   this hasn't been confirmed.
+/// - Data
+pub fn parse_map_uncompressed<R>(reader: &mut R) -> Result<(Vec<ScreenData>, Vec<ParseWarning>)>
+where
+    R: BufRead
+{
+    let mut issues = Issues::new();
+    let screens = parse_map_uncompressed_with_issues(reader, &mut issues);
+
+    let mut warnings = Vec::new();
+    for issue in issues {
+        match issue {
+            Issue::MapBin { warning, .. } => warnings.push(warning),
+            Issue::Error(err) => return Err(err),
+        }
+    }
+
+    Ok((screens, warnings))
+}
+
+/// Parses as many screens as possible from `reader`, which must yield uncompressed Map.bin
+/// data. See [`parse_map_uncompressed`] for the format.
+/// 
+/// This variant never fails. Abnormalities are recorded in `issues` as [`Issue::MapBin`]. Like
+/// KS, it keeps the screens that were read before the data became unreadable (e.g. because
+/// it's truncated); the error that stopped parsing is recorded as [`Issue::Error`].
+pub fn parse_map_uncompressed_with_issues<R>(reader: &mut R, issues: &mut Issues) -> Vec<ScreenData>
+where
+    R: BufRead
+{
+    let mut screens = Vec::new();
+    let result = parse_entries(reader, &mut screens, issues);
+    issues.report(result);
+
+    screens
+}
+
+/// Parses Map.bin entries from `reader` into `screens` until the data runs out or an error
+/// occurs.
+fn parse_entries<R>(reader: &mut R, screens: &mut Vec<ScreenData>, issues: &mut Issues) -> Result<()>
+where
+    R: BufRead
+{
+    let mut buf = Vec::with_capacity(256);
+    let mut offset = 0;
+
+    // Parse screens
+    while !reader.fill_buf()?.is_empty() {
+        let (entry_key, entry_len, header_len) = read_entry_header(reader, &mut buf, 256)
+            .context(ErrorContext::offset(offset))?;
+        let screen = parse_xy(&entry_key);
+        let context = ErrorContext {
+            key: screen.is_none().then(|| entry_key.clone()),
+            screen,
+            offset: Some(offset),
+            ..Default::default()
+        };
+        offset += (header_len + entry_len) as u64;
+
+        let mut warn = |warning| issues.push(Issue::MapBin {
+            context: context.clone(),
+            warning,
+        });
+
+        let bytes_read = match screen {
+            // Incomplete screen data
+            Some(_) if entry_len < SCREEN_DATA_LEN => {
+                warn(ParseWarning::IncompleteScreenData(entry_key.clone(), entry_len));
+                0
+            },
+            // Screen data
+            Some(position) => {
+                if entry_len > SCREEN_DATA_LEN {
+                    warn(ParseWarning::ExtraScreenData(entry_key.clone(), entry_len));
+                }
+
+                let screen = parse_screen(reader, position).context(context.clone())?;
+                screens.push(screen);
+
+                SCREEN_DATA_LEN
+            },
+            // Unknown entry
+            // This is most likely level editor garbage under the empty key
+            None => {
+                warn(ParseWarning::UnrecognizedEntry(entry_key.clone(), entry_len));
+                0
+            }
+        };
+
+        let bytes_to_skip = entry_len - bytes_read;
+        if bytes_to_skip > 0 {
+            // Generally, this won't happen, but when it does, we may need to
+            // skip a lot of bytes. We'll enlarge the buffer as needed (up to 1 MB)
+            // to speed things up.
+            io_util::resize_buffer(&mut buf, min(bytes_to_skip, MB));
+
+            let bytes_skipped = io_util::skip_at_most(reader, &mut buf, bytes_to_skip).context(context.clone())?;
+            perf::count(|c| c.bytes_read += bytes_skipped as u64);
+            if bytes_skipped < bytes_to_skip {
+                return Err(KsError::from(MapBinError::MissingData {
+                    entry_key,
+                    entry_len,
+                    bytes_read: bytes_read + bytes_skipped,
+                }).with_context(context));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads an entry's key and data length, and returns them with the length of the header.
+fn read_entry_header<R>(reader: &mut R, buf: &mut Vec<u8>, max_len: usize) -> Result<(String, usize, usize)>
+where
+    R: BufRead
+{
+    let key = io_util::read_windows_1252_null_term(reader, buf, max_len)?;
+    let len = reader.read_u32::<LittleEndian>()?
+        .try_into()
+        .expect("u32::MAX should be less than or equal to usize::MAX");
+
+    // Key, null terminator, and length
+    let header_len = buf.len() + 1 + 4;
+    perf::count(|c| c.bytes_read += header_len as u64);
+
+    Ok((key, len, header_len))
+}
+
+/// Parses a single screen from `reader`. See [`parse_screen_bytes`] for the format.
+fn parse_screen<R>(reader: &mut R, position: (i64, i64)) -> Result<ScreenData>
+where
+    R: BufRead
+{
+    let mut data = [0; SCREEN_DATA_LEN];
+    reader.read_exact(&mut data)
+        .map_err(|err| make_missing_data_error(err.into(), position))?;
+    perf::count(|c| c.bytes_read += SCREEN_DATA_LEN as u64);
+
+    Ok(parse_screen_bytes(&data, position))
+}
+
+/// Converts an `UnexpectedEof` error to `MapBinError::MissingData`.
+fn make_missing_data_error(err: KsError, position: (i64, i64)) -> KsError {
+    if let KsError::Io { source, .. } = &err {
+        if source.kind() == io::ErrorKind::UnexpectedEof {
+            return MapBinError::ScreenMissingData { position }.into();
+        }
+    }
+
+    err
+}
+
+/// Compresses and writes the data in `screens` to the file at `path`.
+pub fn write_map_file<P>(path: P, screens: &[ScreenData]) -> Result<()>
+where
+    P: AsRef<Path>
+{
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(&path)
+        .with_path(&path)?;
+    let writer = BufWriter::new(file);
+    let mut encoder = GzEncoder::new(writer, Compression::default());
+
+    encoder.write_all(&encode_map(screens))?;
+    encoder.finish()?.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gzip_is_sniffed() {
+        assert!(is_gzip(&[0x1F, 0x8B, 8]));
+        assert!(is_gzip(&[0x1F]));
+        assert!(!is_gzip(&[0x1F, 0x00]));
+        assert!(!is_gzip(b"x1000y1000"));
+        assert!(!is_gzip(&[]));
+    }
+
+    #[test]
+    fn issues_are_collected_from_truncated_data() {
+        let mut data = Vec::new();
+        for (key, len) in [("junk", 2), ("x1000y1000", SCREEN_DATA_LEN)] {
+            data.extend_from_slice(key.as_bytes());
+            data.push(0);
+            data.extend_from_slice(&(len as u32).to_le_bytes());
+        }
+        data.splice(9..9, [1, 2]);
+
+        let mut issues = Issues::new();
+        let screens = parse_map_uncompressed_with_issues(&mut &data[..], &mut issues);
+
+        assert!(screens.is_empty());
+        assert_eq!(issues.len(), 2);
+        assert!(matches!(issues.iter().next(), Some(Issue::MapBin {
+            warning: ParseWarning::UnrecognizedEntry(..),
+            ..
+        })));
+        assert!(issues.has_errors());
+        let contexts: Vec<_> = issues.iter().map(Issue::context).collect();
+        assert_eq!(contexts, [
+            ErrorContext { key: Some("junk".to_owned()), offset: Some(0), ..Default::default() },
+            ErrorContext { screen: Some((1000, 1000)), offset: Some(11), ..Default::default() },
+        ]);
+        assert!(parse_map_uncompressed(&mut &data[..]).is_err());
+    }
+
+    #[test]
+    fn compression_is_detected() {
+        let screen = super::super::parse_screen_bytes(&[1; SCREEN_DATA_LEN], (1000, 1000));
+        let expected = std::slice::from_ref(&screen);
+        let data = encode_map(expected);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        for data in [&data, &gzipped] {
+            let (screens, warnings) = parse_map_auto(&mut &data[..]).unwrap();
+            assert_eq!(screens, expected);
+            assert!(warnings.is_empty());
+        }
+    }
+}
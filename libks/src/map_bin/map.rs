@@ -0,0 +1,173 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, prelude::*, BufWriter},
+    path::Path,
+};
+
+use flate2::{Compress, Compression, Crc, FlushCompress};
+
+use crate::{
+    error::ResultExt,
+    Result,
+};
+use super::{
+    encode_map,
+    parse::{fnv1a, FNV_OFFSET_BASIS},
+    parse_map_file,
+    ScreenData,
+};
+
+/// The number of consecutive screens compressed together by [`Map::save_incremental`].
+const SCREENS_PER_CHUNK: usize = 64;
+
+/// The gzip header written by [`Map::save_incremental`]: deflate, no flags, no timestamp,
+/// unknown OS.
+const GZIP_HEADER: [u8; 10] = [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+
+/// An empty, final deflate block with fixed Huffman codes. Ends the deflate stream after the
+/// last chunk.
+const FINAL_BLOCK: [u8; 2] = [0x03, 0x00];
+
+/// Map.bin screens that remember how they were last saved, so that saving again only has to
+/// compress the parts that changed.
+pub struct Map {
+    /// The screens, in the order they'll be written.
+    pub screens: Vec<ScreenData>,
+    /// The chunks written by the last call to [`Map::save_incremental`].
+    chunks: Vec<Chunk>,
+}
+
+/// A run of up to [`SCREENS_PER_CHUNK`] screens, compressed independently of the others.
+struct Chunk {
+    /// The length of the uncompressed data.
+    len: usize,
+    /// A hash of the uncompressed data.
+    hash: u64,
+    /// The CRC-32 of the uncompressed data.
+    crc: Crc,
+    /// The compressed data: deflate blocks ending on a byte boundary, none of them final.
+    deflated: Vec<u8>,
+}
+
+impl Map {
+    /// Creates a map that hasn't been saved yet.
+    pub fn new(screens: Vec<ScreenData>) -> Map {
+        Map {
+            screens,
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Parses the Map.bin at `path`. See [`parse_map_file`].
+    ///
+    /// Nothing from the file's compressed data can be reused, so the first call to
+    /// [`Map::save_incremental`] compresses every screen.
+    pub fn load<P>(path: P) -> Result<Map>
+    where
+        P: AsRef<Path>
+    {
+        Ok(Map::new(parse_map_file(path)?))
+    }
+
+    /// Compresses and writes the screens to the file at `path`, reusing the compressed data from
+    /// the previous call wherever the screens haven't changed.
+    ///
+    /// The output is a single gzip stream like the one written by
+    /// [`write_map_file`](super::write_map_file), but the screens are compressed in independent
+    /// runs of 64 so that each run can be reused. Runs are matched up by index, so editing,
+    /// adding, or removing screens at the end of the list is cheap, while inserting or removing
+    /// one near the start means most of the map is compressed again.
+    pub fn save_incremental<P>(&mut self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>
+    {
+        let mut old_chunks = std::mem::take(&mut self.chunks).into_iter();
+        for screens in self.screens.chunks(SCREENS_PER_CHUNK) {
+            let data = encode_map(screens);
+            let hash = fnv1a(FNV_OFFSET_BASIS, &data);
+            let mut crc = Crc::new();
+            crc.update(&data);
+
+            let unchanged = |old: &Chunk| {
+                old.len == data.len() && old.hash == hash && old.crc.sum() == crc.sum()
+            };
+            let chunk = match old_chunks.next() {
+                Some(old) if unchanged(&old) => old,
+                _ => Chunk {
+                    len: data.len(),
+                    hash,
+                    crc,
+                    deflated: deflate_chunk(&data)?,
+                },
+            };
+            self.chunks.push(chunk);
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .with_path(&path)?;
+        let mut writer = BufWriter::new(file);
+
+        let mut total_crc = Crc::new();
+        writer.write_all(&GZIP_HEADER)?;
+        for chunk in &self.chunks {
+            writer.write_all(&chunk.deflated)?;
+            total_crc.combine(&chunk.crc);
+        }
+        writer.write_all(&FINAL_BLOCK)?;
+        writer.write_all(&total_crc.sum().to_le_bytes())?;
+        writer.write_all(&total_crc.amount().to_le_bytes())?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Compresses `data` on its own with a full flush, so the result can be placed anywhere in a
+/// deflate stream.
+fn deflate_chunk(data: &[u8]) -> Result<Vec<u8>> {
+    let mut compress = Compress::new(Compression::default(), false);
+    let mut deflated = Vec::with_capacity(data.len() / 4);
+
+    loop {
+        deflated.reserve(4096);
+        let consumed = compress.total_in() as usize;
+        compress.compress_vec(&data[consumed..], &mut deflated, FlushCompress::Full)
+            .map_err(io::Error::other)?;
+
+        // The flush is complete once everything is consumed and there's room to spare
+        if compress.total_in() as usize == data.len() && deflated.len() < deflated.capacity() {
+            break;
+        }
+    }
+
+    Ok(deflated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, SCREEN_DATA_LEN};
+
+    #[test]
+    fn unchanged_chunks_are_reused() {
+        let screens = (0..100)
+            .map(|i| parse_screen_bytes(&[i as u8; SCREEN_DATA_LEN], (1000 + i, 1000)))
+            .collect();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("Map.bin");
+
+        let mut map = Map::new(screens);
+        map.save_incremental(&path).unwrap();
+        assert_eq!(parse_map_file(&path).unwrap(), map.screens);
+
+        let first_chunk = map.chunks[0].deflated.as_ptr();
+        map.screens[80].assets.music = 9;
+        map.save_incremental(&path).unwrap();
+        assert_eq!(map.chunks[0].deflated.as_ptr(), first_chunk);
+        assert_eq!(parse_map_file(&path).unwrap(), map.screens);
+    }
+}
@@ -0,0 +1,561 @@
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{
+    common::parse_xy,
+    constants::*,
+};
+
+/// The length in bytes of a screen entry's data.
+pub const SCREEN_DATA_LEN: usize = 3006;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenData {
+    pub position: (i64, i64),
+    pub layers: [LayerData; LAYER_COUNT],
+    pub assets: AssetIds,
+}
+
+impl ScreenData {
+    /// Returns a hash of the screen's position and Map.bin data.
+    ///
+    /// The hash is 64-bit FNV-1a over the position (x then y, as little endian 64-bit integers)
+    /// followed by the output of [`encode_screen`]. It won't change between libks versions, so
+    /// it can be stored and compared later.
+    pub fn content_hash(&self) -> u64 {
+        let (x, y) = self.position;
+        let hash = fnv1a(FNV_OFFSET_BASIS, &x.to_le_bytes());
+        let hash = fnv1a(hash, &y.to_le_bytes());
+        fnv1a(hash, &encode_screen(self))
+    }
+
+    /// Iterates over the objects placed on the screen's object layers, skipping empty cells.
+    /// Objects are visited layer by layer, then row by row from the top left.
+    pub fn objects(&self) -> impl Iterator<Item = PlacedObject> + '_ {
+        self.layers.iter()
+            .enumerate()
+            .skip(4)
+            .flat_map(|(layer, data)| {
+                data.0.iter()
+                    .enumerate()
+                    .filter(|(_, tile)| tile.1 != 0)
+                    .map(move |(i, &tile)| PlacedObject {
+                        layer,
+                        x: i % SCREEN_WIDTH,
+                        y: i / SCREEN_WIDTH,
+                        tile,
+                    })
+            })
+    }
+
+    /// Returns the topmost non-empty tile of each cell across the tile layers (0-3), or an empty
+    /// tile where all four are empty.
+    ///
+    /// Tile index 0 is treated as empty, as the renderer does. Tiles may be partly transparent,
+    /// so lower tiles can still show through the one returned.
+    pub fn flatten_tiles(&self) -> LayerData {
+        LayerData(core::array::from_fn(|i| {
+            self.layers[..4].iter()
+                .rev()
+                .map(|layer| layer.0[i])
+                .find(|tile| tile.1 != 0)
+                .unwrap_or(Tile(0, 0))
+        }))
+    }
+
+    /// Moves the tiles of decorative tile layer `src` into decorative tile layer `dst` wherever
+    /// that doesn't change how the screen is drawn: the cell must be empty in `dst` and in every
+    /// layer between the two. Returns the number of tiles that had to stay in `src`; if it's 0,
+    /// `src` is now empty.
+    ///
+    /// Only layers 0-2 can be merged. Moving tiles into or out of [`COLLISION_LAYER`] would
+    /// change what the player collides with, so any other layer is rejected with
+    /// [`MapBinError::NotDecorativeLayer`](crate::MapBinError::NotDecorativeLayer).
+    #[cfg(feature = "std")]
+    pub fn merge_layer(&mut self, src: usize, dst: usize) -> crate::Result<usize> {
+        if let Some(layer) = [src, dst].into_iter().find(|&layer| layer >= COLLISION_LAYER) {
+            return Err(crate::MapBinError::NotDecorativeLayer { layer }.into());
+        }
+        if src == dst {
+            return Ok(0);
+        }
+
+        let between = src.min(dst) + 1 .. src.max(dst);
+        let mut remaining = 0;
+
+        for i in 0..TILES_PER_LAYER {
+            let tile = self.layers[src].0[i];
+            if tile.1 == 0 {
+                continue;
+            }
+
+            let blocked = self.layers[dst].0[i].1 != 0
+                || self.layers[between.clone()].iter().any(|layer| layer.0[i].1 != 0);
+            if blocked {
+                remaining += 1;
+            }
+            else {
+                self.layers[dst].0[i] = tile;
+                self.layers[src].0[i] = Tile(0, 0);
+            }
+        }
+
+        Ok(remaining)
+    }
+}
+
+/// An object placed on a screen. See [`ScreenData::objects`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlacedObject {
+    /// The index of the object layer in [`ScreenData::layers`], from 4 to 7.
+    pub layer: usize,
+    /// The column, from 0 at the left.
+    pub x: usize,
+    /// The row, from 0 at the top.
+    pub y: usize,
+    /// The object's bank and index.
+    pub tile: Tile,
+}
+
+pub type AssetId = u8;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssetIds {
+    pub tileset_a: AssetId,
+    pub tileset_b: AssetId,
+    pub ambiance_a: AssetId,
+    pub ambiance_b: AssetId,
+    pub music: AssetId,
+    pub gradient: AssetId,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tile(pub u8, pub u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerData(pub [Tile; TILES_PER_LAYER]);
+
+#[derive(Debug)]
+pub enum ParseWarning {
+    UnrecognizedEntry(String, usize),
+    IncompleteScreenData(String, usize),
+    ExtraScreenData(String, usize),
+}
+
+impl core::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use ParseWarning::*;
+        match self {
+            UnrecognizedEntry(key, len) =>
+                write!(f, "Found an unrecognized entry `{key}` with {len} bytes."),
+            IncompleteScreenData(key, len) =>
+                write!(f, "The screen entry `{key}` was skipped because it was only {len}/3006 bytes."),
+            ExtraScreenData(key, len) =>
+                write!(f, "The screen entry `{key}` had {} extra bytes.", len - 3006),
+        }
+    }
+}
+
+/// An error that stopped [`parse_map_bytes`] or [`MapEntries`] from reading any further.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The data ended in the middle of an entry's name or length.
+    IncompleteHeader,
+    /// An entry's name was longer than 256 bytes.
+    KeyTooLong,
+    /// An entry's data was shorter than its length.
+    MissingData {
+        entry_key: String,
+        entry_len: usize,
+        bytes_read: usize,
+    },
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::IncompleteHeader =>
+                write!(f, "The data ended in the middle of an entry header."),
+            ParseError::KeyTooLong =>
+                write!(f, "An entry name was too long."),
+            ParseError::MissingData { entry_key, entry_len, bytes_read } =>
+                write!(f, "An entry called `{entry_key}` is missing data: found {bytes_read}/{entry_len} bytes."),
+        }
+    }
+}
+
+/// A single entry of uncompressed Map.bin data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapEntry<'a> {
+    /// The raw name of the entry, such as `x1000y1000`, without the null terminator.
+    pub key: &'a [u8],
+    /// The entry's data.
+    pub data: &'a [u8],
+}
+
+impl MapEntry<'_> {
+    /// Decodes the name of the entry. The encoding is presumed to be Windows-1252, but this
+    /// hasn't been confirmed.
+    pub fn key(&self) -> String {
+        let (key, _) = encoding_rs::WINDOWS_1252.decode_without_bom_handling(self.key);
+        key.into_owned()
+    }
+}
+
+/// An iterator over the entries of uncompressed Map.bin data. See [`map_entries`].
+#[derive(Debug, Clone)]
+pub struct MapEntries<'a> {
+    data: &'a [u8],
+}
+
+/// Returns an iterator over the entries in `data`, which must be uncompressed Map.bin data.
+///
+/// Map.bin consists solely of a series of named binary chunks called workspaces. Each
+/// workspace consists of:
+/// - A name, such as `x1000y1000`. Null-terminated string. The encoding is presumed
+///   to be Windows-1252, but this hasn't been confirmed.
+/// - Length in bytes. Little endian 32-byte integer. Presumed to be unsigned, but
+///   this hasn't been confirmed.
+/// - Data
+///
+/// If an entry can't be read, the iterator yields an error and then ends.
+pub fn map_entries(data: &[u8]) -> MapEntries<'_> {
+    MapEntries { data }
+}
+
+impl<'a> Iterator for MapEntries<'a> {
+    type Item = Result<MapEntry<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let result = self.next_entry();
+        if result.is_err() {
+            self.data = &[];
+        }
+
+        Some(result)
+    }
+}
+
+impl<'a> MapEntries<'a> {
+    fn next_entry(&mut self) -> Result<MapEntry<'a>, ParseError> {
+        const MAX_KEY_LEN: usize = 256;
+
+        let key_len = match self.data.iter().position(|&byte| byte == 0) {
+            Some(key_len) if key_len < MAX_KEY_LEN => key_len,
+            Some(_) => return Err(ParseError::KeyTooLong),
+            None if self.data.len() >= MAX_KEY_LEN => return Err(ParseError::KeyTooLong),
+            None => return Err(ParseError::IncompleteHeader),
+        };
+        let key = &self.data[..key_len];
+
+        let len_bytes = self.data.get(key_len + 1..key_len + 5)
+            .ok_or(ParseError::IncompleteHeader)?;
+        let entry_len: usize = u32::from_le_bytes(len_bytes.try_into().unwrap())
+            .try_into()
+            .expect("u32::MAX should be less than or equal to usize::MAX");
+
+        let rest = &self.data[key_len + 5..];
+        if rest.len() < entry_len {
+            return Err(ParseError::MissingData {
+                entry_key: MapEntry { key, data: rest }.key(),
+                entry_len,
+                bytes_read: rest.len(),
+            });
+        }
+
+        let (data, rest) = rest.split_at(entry_len);
+        self.data = rest;
+
+        Ok(MapEntry { key, data })
+    }
+}
+
+/// Parses all screens from `data`, which must be uncompressed Map.bin data. See
+/// [`map_entries`] for the format.
+///
+/// Unlike the functions that read from files, this only needs `core` and `alloc`. If the data
+/// can't be fully read, the screens and warnings found so far are returned with the error.
+pub fn parse_map_bytes(data: &[u8]) -> (Vec<ScreenData>, Vec<ParseWarning>, Option<ParseError>) {
+    let mut screens = Vec::new();
+    let mut warnings = Vec::new();
+
+    #[cfg(feature = "std")]
+    crate::perf::count(|c| c.bytes_read += data.len() as u64);
+
+    for entry in map_entries(data) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => return (screens, warnings, Some(err)),
+        };
+
+        let key = entry.key();
+        let len = entry.data.len();
+        match parse_xy(&key) {
+            // Incomplete screen data
+            Some(_) if len < SCREEN_DATA_LEN => {
+                warnings.push(ParseWarning::IncompleteScreenData(key, len));
+            },
+            // Screen data
+            Some(position) => {
+                if len > SCREEN_DATA_LEN {
+                    warnings.push(ParseWarning::ExtraScreenData(key, len));
+                }
+
+                let data = entry.data[..SCREEN_DATA_LEN].try_into().unwrap();
+                screens.push(parse_screen_bytes(data, position));
+            },
+            // Unknown entry
+            // This is most likely level editor garbage under the empty key
+            None => {
+                warnings.push(ParseWarning::UnrecognizedEntry(key, len));
+            },
+        }
+    }
+
+    (screens, warnings, None)
+}
+
+/// Parses a single screen from `data`.
+///
+/// The screen format is:
+/// - 4 tile layers (0-3, 250 bytes each) - see [`parse_tile_layer`]
+/// - 4 object layers (4-7, 500 bytes each) - see [`parse_object_layer`]
+/// - Asset IDs (6 bytes) - see [`parse_asset_ids`]
+pub fn parse_screen_bytes(data: &[u8; SCREEN_DATA_LEN], position: (i64, i64)) -> ScreenData {
+    #[cfg(feature = "std")]
+    crate::perf::count(|c| c.screens_parsed += 1);
+
+    const TILE_LAYERS_LEN: usize = 4 * TILES_PER_LAYER;
+    const OBJECT_LAYERS_LEN: usize = 4 * 2 * TILES_PER_LAYER;
+
+    let (tile_data, rest) = data.split_at(TILE_LAYERS_LEN);
+    let (object_data, asset_data) = rest.split_at(OBJECT_LAYERS_LEN);
+
+    let mut tile_layers = tile_data.chunks_exact(TILES_PER_LAYER)
+        .map(|chunk| parse_tile_layer(chunk.try_into().unwrap()));
+    let mut object_layers = object_data.chunks_exact(2 * TILES_PER_LAYER)
+        .map(|chunk| parse_object_layer(chunk.try_into().unwrap()));
+    let layers = core::array::from_fn(|i| {
+        if is_object_layer(i) {
+            object_layers.next().unwrap()
+        }
+        else {
+            tile_layers.next().unwrap()
+        }
+    });
+
+    ScreenData {
+        position,
+        layers,
+        assets: parse_asset_ids(asset_data.try_into().unwrap()),
+    }
+}
+
+/// Encodes `screen` in the format read by [`parse_screen_bytes`].
+pub fn encode_screen(screen: &ScreenData) -> [u8; SCREEN_DATA_LEN] {
+    let mut screen_buffer = [0; SCREEN_DATA_LEN];
+    let mut i = 0;
+
+    for layer_index in 0..4 {
+        for tile in &screen.layers[layer_index].0 {
+            screen_buffer[i] = tile.1 | (tile.0 * 0x80);
+            i += 1;
+        }
+    }
+
+    for layer_index in 4..8 {
+        for tile in &screen.layers[layer_index].0 {
+            screen_buffer[i] = tile.1;
+            screen_buffer[i + 250] = tile.0;
+            i += 1;
+        }
+        i += 250;
+    }
+
+    screen_buffer[i]     = screen.assets.tileset_a;
+    screen_buffer[i + 1] = screen.assets.tileset_b;
+    screen_buffer[i + 2] = screen.assets.ambiance_a;
+    screen_buffer[i + 3] = screen.assets.ambiance_b;
+    screen_buffer[i + 4] = screen.assets.music;
+    screen_buffer[i + 5] = screen.assets.gradient;
+
+    screen_buffer
+}
+
+/// Encodes `screens` as uncompressed Map.bin data, the format read by [`parse_map_bytes`].
+pub fn encode_map(screens: &[ScreenData]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(screens.len() * (SCREEN_DATA_LEN + 20));
+    for screen in screens {
+        let (x, y) = screen.position;
+        data.extend_from_slice(format!("x{x}y{y}\0").as_bytes());
+        data.extend_from_slice(&(SCREEN_DATA_LEN as u32).to_le_bytes());
+        data.extend_from_slice(&encode_screen(screen));
+    }
+
+    data
+}
+
+/// Returns a hash of `screens` that depends only on their positions and contents, not on their
+/// order or how the Map.bin was compressed. Like [`ScreenData::content_hash`], it won't change
+/// between libks versions.
+///
+/// If more than one screen has the same position, all of them contribute to the hash.
+pub fn map_hash(screens: &[ScreenData]) -> u64 {
+    let mut hashes: Vec<_> = screens.iter()
+        .map(|screen| (screen.position, screen.content_hash()))
+        .collect();
+    hashes.sort_unstable();
+
+    hashes.iter().fold(FNV_OFFSET_BASIS, |hash, (_, screen_hash)| {
+        fnv1a(hash, &screen_hash.to_le_bytes())
+    })
+}
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Continues a 64-bit FNV-1a hash over `bytes`.
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME))
+}
+
+/// Returns true if the layer at index `i` is an object layer.
+fn is_object_layer(i: usize) -> bool {
+    i >= 4
+}
+
+/// Parses an asset ID block from `data`.
+///
+/// Each asset ID is a single unsigned byte. The order is:
+/// tileset A, tileset B, music, ambiance A, ambiance B, gradient.
+fn parse_asset_ids(data: &[u8; 6]) -> AssetIds {
+    AssetIds {
+        tileset_a: data[0],
+        tileset_b: data[1],
+        ambiance_a: data[2],
+        ambiance_b: data[3],
+        music: data[4],
+        gradient: data[5],
+    }
+}
+
+/// Parses a single tile layer from `data`.
+///
+/// A tile layer consists of 250 bytes. Each byte represents one tile, starting from the top left
+/// and proceeding row by row. The highest order bit is 0 for tileset A or 1 for tileset B.
+/// The remaining 7 bits are the tile index, again starting from the top left and proceeding row by row.
+///
+/// For example, `0x00` is the top left tile of tileset A. `0x01` is the tile to its right.
+/// `0x7F` is the bottom right tile of tileset A. `0x80` is the top left tile of tileset B.
+/// `0x81` is the tile to its right. `0xFF` is the bottom right tile of tileset B.
+fn parse_tile_layer(data: &[u8; TILES_PER_LAYER]) -> LayerData {
+    LayerData(data.map(|byte| {
+        if byte < 128 {
+            Tile(0, byte)
+        }
+        else {
+            Tile(1, byte - 128)
+        }
+    }))
+}
+
+/// Parses a single object layer from `data`.
+///
+/// An object layer consists of 500 bytes: 250 bytes of object indices followed by 250 bytes
+/// of bank indices. In each 250 byte block, each byte represents one tile, starting from the
+/// top left and proceeding row by row.
+fn parse_object_layer(data: &[u8; 2 * TILES_PER_LAYER]) -> LayerData {
+    let (indices, banks) = data.split_at(TILES_PER_LAYER);
+    LayerData(core::array::from_fn(|i| Tile(banks[i], indices[i])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screens_round_trip_through_bytes() {
+        let mut screen_data = [0; SCREEN_DATA_LEN];
+        for (i, byte) in screen_data.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+
+        let mut data = Vec::new();
+        data.extend_from_slice(b"x1000y1001\0");
+        data.extend_from_slice(&(SCREEN_DATA_LEN as u32).to_le_bytes());
+        data.extend_from_slice(&screen_data);
+        data.extend_from_slice(b"\0");
+
+        let (screens, warnings, error) = parse_map_bytes(&data);
+        assert_eq!(screens.len(), 1);
+        assert!(warnings.is_empty());
+        assert_eq!(error, Some(ParseError::IncompleteHeader));
+
+        let screen = &screens[0];
+        assert_eq!(screen.position, (1000, 1001));
+        assert_eq!(screen.layers[0].0[1], Tile(0, 1));
+        assert_eq!(screen.layers[4].0[0], Tile(screen_data[1250], screen_data[1000]));
+        assert_eq!(encode_screen(screen), screen_data);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn merging_layers_keeps_appearance() {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.layers[2].0[0] = Tile(0, 5);
+        screen.layers[2].0[1] = Tile(0, 6);
+        screen.layers[1].0[1] = Tile(1, 7);
+        screen.layers[0].0[0] = Tile(0, 8);
+        let flattened = screen.flatten_tiles();
+        assert_eq!(flattened.0[..3], [Tile(0, 5), Tile(0, 6), Tile(0, 0)]);
+
+        assert_eq!(screen.merge_layer(2, 0).unwrap(), 2);
+        assert_eq!(screen.merge_layer(1, 0).unwrap(), 0);
+        assert_eq!(screen.layers[0].0[1], Tile(1, 7));
+        assert_eq!(screen.flatten_tiles(), flattened);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn merging_into_collision_layer_fails() {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.layers[2].0[0] = Tile(0, 5);
+        assert!(matches!(
+            screen.merge_layer(2, COLLISION_LAYER),
+            Err(crate::KsError::MapBin(crate::MapBinError::NotDecorativeLayer { layer: COLLISION_LAYER })),
+        ));
+        assert_eq!(screen.layers[2].0[0], Tile(0, 5));
+    }
+
+    #[test]
+    fn objects_skip_empty_cells() {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.layers[0].0[0] = Tile(0, 1);
+        screen.layers[6].0[SCREEN_WIDTH + 2] = Tile(0, 1);
+
+        let objects: Vec<_> = screen.objects().collect();
+        assert_eq!(objects, [PlacedObject { layer: 6, x: 2, y: 1, tile: Tile(0, 1) }]);
+    }
+
+    #[test]
+    fn map_hash_ignores_order() {
+        let screen = parse_screen_bytes(&[7; SCREEN_DATA_LEN], (1000, 1000));
+        let mut moved = screen.clone();
+        moved.position = (1001, 1000);
+        let mut changed = screen.clone();
+        changed.assets.music = 3;
+
+        assert_eq!(screen.content_hash(), screen.clone().content_hash());
+        assert_ne!(screen.content_hash(), moved.content_hash());
+        assert_ne!(screen.content_hash(), changed.content_hash());
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"a"), 0xaf63_dc4c_8601_ec8c);
+
+        let forward = [screen.clone(), moved.clone()];
+        let backward = [moved, screen];
+        assert_eq!(map_hash(&forward), map_hash(&backward));
+        assert_ne!(map_hash(&forward), map_hash(&forward[..1]));
+    }
+}
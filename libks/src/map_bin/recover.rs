@@ -0,0 +1,147 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::common::parse_xy;
+use super::{
+    map_entries,
+    parse_screen_bytes,
+    ParseWarning,
+    ScreenData,
+    SCREEN_DATA_LEN,
+};
+
+/// The screens salvaged from damaged Map.bin data by [`recover_bytes`].
+#[derive(Debug)]
+pub struct RecoveredMap {
+    /// The screens that could be read, in the order they were found.
+    pub screens: Vec<ScreenData>,
+    /// Abnormalities in the entries that could be read, as reported by
+    /// [`parse_map_bytes`](super::parse_map_bytes).
+    pub warnings: Vec<ParseWarning>,
+    /// The byte ranges of the uncompressed data that couldn't be read and were skipped.
+    pub skipped: Vec<Range<usize>>,
+}
+
+/// Salvages as many screens as possible from `data`, which must be uncompressed Map.bin data
+/// that may be truncated or partially corrupted.
+///
+/// Whenever an entry can't be read, the data is scanned for the next plausible screen header:
+/// a name of the form `x1000y1000`, then a length of at least 3006, then at least 3006 bytes of
+/// data. Entries are only trusted if they end at the end of the data or at another plausible
+/// screen header. If a screen header looks right but its length doesn't, the screen is kept
+/// and the data after its first 3006 bytes is scanned instead.
+pub fn recover_bytes(data: &[u8]) -> RecoveredMap {
+    let mut recovered = RecoveredMap {
+        screens: Vec::new(),
+        warnings: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    #[cfg(feature = "std")]
+    crate::perf::count(|c| c.bytes_read += data.len() as u64);
+
+    let is_boundary = |pos: usize| pos == data.len() || screen_header(&data[pos..]).is_some();
+
+    let mut pos = 0;
+    while pos < data.len() {
+        if let Some((position, header_len, entry_len)) = screen_header(&data[pos..]) {
+            let start = pos + header_len;
+            let screen_data = data[start..start + SCREEN_DATA_LEN].try_into().unwrap();
+            recovered.screens.push(parse_screen_bytes(screen_data, position));
+
+            let end = start.saturating_add(entry_len);
+            if entry_len > SCREEN_DATA_LEN && end <= data.len() && is_boundary(end) {
+                let key = map_entries(&data[pos..]).next()
+                    .and_then(Result::ok)
+                    .map(|entry| entry.key())
+                    .unwrap_or_default();
+                recovered.warnings.push(ParseWarning::ExtraScreenData(key, entry_len));
+                pos = end;
+            }
+            else {
+                pos = start + SCREEN_DATA_LEN;
+            }
+
+            continue;
+        }
+
+        // Some other entry, e.g. level editor garbage under the empty key
+        if let Some(Ok(entry)) = map_entries(&data[pos..]).next() {
+            let end = pos + entry.key.len() + 5 + entry.data.len();
+            if is_boundary(end) {
+                let (key, len) = (entry.key(), entry.data.len());
+                recovered.warnings.push(match parse_xy(&key) {
+                    Some(_) => ParseWarning::IncompleteScreenData(key, len),
+                    None => ParseWarning::UnrecognizedEntry(key, len),
+                });
+                pos = end;
+                continue;
+            }
+        }
+
+        let next = (pos + 1..data.len())
+            .find(|&next| is_boundary(next))
+            .unwrap_or(data.len());
+        recovered.skipped.push(pos..next);
+        pos = next;
+    }
+
+    recovered
+}
+
+/// Checks whether `data` starts with a plausible screen entry. If so, returns the screen's
+/// position, the length of the header, and the length given in the header.
+fn screen_header(data: &[u8]) -> Option<((i64, i64), usize, usize)> {
+    const MAX_KEY_LEN: usize = 256;
+
+    if data.first() != Some(&b'x') {
+        return None;
+    }
+
+    let key_len = data.iter()
+        .take(MAX_KEY_LEN)
+        .position(|&byte| byte == 0)?;
+    let key = core::str::from_utf8(&data[..key_len]).ok()?;
+    let position = parse_xy(key)?;
+
+    let len_bytes = data.get(key_len + 1..key_len + 5)?;
+    let entry_len = u32::from_le_bytes(len_bytes.try_into().unwrap())
+        .try_into()
+        .expect("u32::MAX should be less than or equal to usize::MAX");
+
+    let header_len = key_len + 5;
+    (entry_len >= SCREEN_DATA_LEN && data.len() - header_len >= SCREEN_DATA_LEN)
+        .then_some((position, header_len, entry_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_bin::encode_map;
+
+    #[test]
+    fn screens_after_corruption_are_recovered() {
+        let screens: Vec<_> = (0..3)
+            .map(|i| parse_screen_bytes(&[i as u8; SCREEN_DATA_LEN], (1000 + i, 1000)))
+            .collect();
+        let mut data = encode_map(&screens);
+        let second = data.len() / 3;
+
+        // Corrupt the second screen's header and truncate the third screen
+        data[second + 2] = b'?';
+        data.truncate(data.len() - 10);
+
+        let recovered = recover_bytes(&data);
+        assert_eq!(recovered.screens, screens[..1]);
+        assert_eq!(recovered.skipped.len(), 1);
+        assert_eq!(recovered.skipped[0], second..data.len());
+
+        // Only corrupt the length of the second screen
+        let mut data = encode_map(&screens);
+        data[second + 11..second + 15].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let recovered = recover_bytes(&data);
+        assert_eq!(recovered.screens, screens);
+        assert!(recovered.skipped.is_empty());
+    }
+}
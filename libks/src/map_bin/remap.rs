@@ -0,0 +1,112 @@
+use alloc::vec::Vec;
+
+use super::{AssetId, ScreenData};
+
+/// The number of tiles in a tileset.
+const TILESET_LEN: usize = 128;
+
+/// A new arrangement of the tiles in one tileset. See [`remap_tiles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRemap {
+    /// The tileset whose tiles are moved.
+    pub tileset: AssetId,
+    /// If `true`, [`remap_tiles`] only reports the screens that would change. Defaults to
+    /// `false`.
+    pub dry_run: bool,
+    indices: [u8; TILESET_LEN],
+}
+
+impl TileRemap {
+    /// Creates a remapping for `tileset` that leaves every tile where it is.
+    pub fn new(tileset: AssetId) -> TileRemap {
+        TileRemap {
+            tileset,
+            dry_run: false,
+            indices: core::array::from_fn(|i| i as u8),
+        }
+    }
+
+    /// Moves tile `from` to index `to`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is 128 or greater.
+    pub fn set(&mut self, from: u8, to: u8) -> &mut TileRemap {
+        assert!(usize::from(to) < TILESET_LEN, "tile index {to} is out of range");
+        self.indices[usize::from(from)] = to;
+        self
+    }
+
+    /// Returns the new index of tile `index`.
+    pub fn get(&self, index: u8) -> u8 {
+        self.indices.get(usize::from(index))
+            .copied()
+            .unwrap_or(index)
+    }
+}
+
+/// Rewrites the tile layers of `screens` so that tiles from `remap.tileset` refer to their new
+/// indices, e.g. after the tiles in the tileset's image have been rearranged. Screens using the
+/// tileset in both slots are handled.
+///
+/// Returns the positions of the screens that were changed, or would be changed in a dry run.
+pub fn remap_tiles(screens: &mut [ScreenData], remap: TileRemap) -> Vec<(i64, i64)> {
+    let mut affected = Vec::new();
+
+    for screen in screens {
+        let slots = [
+            screen.assets.tileset_a == remap.tileset,
+            screen.assets.tileset_b == remap.tileset,
+        ];
+        let mut changed = false;
+
+        for layer in &mut screen.layers[0..4] {
+            for tile in &mut layer.0 {
+                if !slots.get(usize::from(tile.0)).copied().unwrap_or(false) {
+                    continue;
+                }
+
+                let index = remap.get(tile.1);
+                if index != tile.1 {
+                    changed = true;
+                    if !remap.dry_run {
+                        tile.1 = index;
+                    }
+                }
+            }
+        }
+
+        if changed {
+            affected.push(screen.position);
+        }
+    }
+
+    affected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_bin::{parse_screen_bytes, Tile, SCREEN_DATA_LEN};
+
+    #[test]
+    fn only_tiles_from_the_tileset_are_moved() {
+        let mut screen = parse_screen_bytes(&[0; SCREEN_DATA_LEN], (1000, 1000));
+        screen.assets.tileset_b = 2;
+        screen.layers[0].0[0] = Tile(0, 5);
+        screen.layers[0].0[1] = Tile(1, 5);
+        screen.layers[4].0[0] = Tile(1, 5);
+        let mut screens = [screen];
+
+        let mut remap = TileRemap::new(2);
+        remap.set(5, 6).dry_run = true;
+        assert_eq!(remap_tiles(&mut screens, remap), [(1000, 1000)]);
+        assert_eq!(screens[0].layers[0].0[1], Tile(1, 5));
+
+        remap.dry_run = false;
+        assert_eq!(remap_tiles(&mut screens, remap), [(1000, 1000)]);
+        assert_eq!(screens[0].layers[0].0[0], Tile(0, 5));
+        assert_eq!(screens[0].layers[0].0[1], Tile(1, 6));
+        assert_eq!(screens[0].layers[4].0[0], Tile(1, 5));
+    }
+}
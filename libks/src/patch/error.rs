@@ -0,0 +1,13 @@
+#[derive(thiserror::Error, Debug)]
+pub enum PatchError {
+    #[error("The patch data is malformed.")]
+    Malformed,
+    #[error("The patch is for a different version of the level.")]
+    WrongBase,
+    #[error("Entry {index} of the original archive is missing or doesn't match the patch.")]
+    EntryMismatch {
+        index: usize,
+    },
+    #[error("The patched archive doesn't match the version the patch was made for.")]
+    WrongResult,
+}
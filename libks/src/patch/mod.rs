@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{
+    knytt_bin::raw::{self, EntryHeader},
+    store::{blob_hash, BlobHash, MAX_PATH_LEN},
+    Result,
+};
+
+mod error;
+pub use error::PatchError;
+
+/// The bytes that begin a serialized [`KsPatch`].
+pub const PATCH_SIGNATURE: [u8; 8] = *b"KSPATCH1";
+
+/// How to build one entry of the new archive. See [`KsPatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchOp {
+    /// Copy the entry at `index` of the old archive, header and all. `hash` is the hash of its
+    /// data (or of nothing, for the first entry), which is checked when the patch is applied.
+    Copy {
+        index: usize,
+        hash: BlobHash,
+    },
+    /// Write a new or changed entry. `data` is `None` for the first entry, which has none.
+    Insert {
+        header: EntryHeader,
+        data: Option<Vec<u8>>,
+    },
+}
+
+/// An update from one version of a .knytt.bin to another that only contains the entries that
+/// changed. Made by [`diff`] and used by [`apply`].
+///
+/// The hashes of both whole archives are recorded, so a patch can only be applied to the
+/// version it was made from and the result is checked against the version it was made for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KsPatch {
+    pub old_hash: BlobHash,
+    pub new_hash: BlobHash,
+    /// One operation per entry of the new archive, in order.
+    pub ops: Vec<PatchOp>,
+    /// Bytes after the last complete entry of the new archive, if any.
+    pub trailing: Vec<u8>,
+}
+
+impl KsPatch {
+    /// Serializes the patch:
+    /// - Signature `"KSPATCH1"` (8 bytes)
+    /// - Old and new archive hashes (32 bytes each)
+    /// - Operation count (unsigned 32-bit little endian integer), then each operation:
+    ///   - `0`, the entry index (u32), and the data hash (32 bytes) to copy an entry
+    ///   - `1` and a .knytt.bin entry header to insert an entry without data
+    ///   - `2`, a .knytt.bin entry header, and the data to insert an entry
+    /// - Trailing data length (u32) and the data
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&PATCH_SIGNATURE);
+        bytes.extend_from_slice(&self.old_hash);
+        bytes.extend_from_slice(&self.new_hash);
+        bytes.write_u32::<LittleEndian>(to_u32(self.ops.len())?)?;
+
+        for op in &self.ops {
+            match op {
+                PatchOp::Copy { index, hash } => {
+                    bytes.push(0);
+                    bytes.write_u32::<LittleEndian>(to_u32(*index)?)?;
+                    bytes.extend_from_slice(hash);
+                },
+                PatchOp::Insert { header, data: None } => {
+                    bytes.push(1);
+                    raw::write_header(&mut bytes, header)?;
+                },
+                PatchOp::Insert { header, data: Some(data) } => {
+                    if data.len() != header.data_len() {
+                        return Err(PatchError::Malformed.into());
+                    }
+                    bytes.push(2);
+                    raw::write_header(&mut bytes, header)?;
+                    bytes.extend_from_slice(data);
+                },
+            }
+        }
+
+        bytes.write_u32::<LittleEndian>(to_u32(self.trailing.len())?)?;
+        bytes.extend_from_slice(&self.trailing);
+
+        Ok(bytes)
+    }
+
+    /// Parses a patch serialized by [`KsPatch::to_bytes`].
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<KsPatch> {
+        let malformed = |_| PatchError::Malformed;
+        let take = |bytes: &mut &[u8], len: usize| -> Result<Vec<u8>> {
+            let taken = bytes.get(..len).ok_or(PatchError::Malformed)?.to_vec();
+            *bytes = &bytes[len..];
+            Ok(taken)
+        };
+        let hash = |bytes: &[u8]| -> BlobHash { bytes.try_into().expect("hash should be 32 bytes") };
+
+        if take(&mut bytes, 8)? != PATCH_SIGNATURE {
+            return Err(PatchError::Malformed.into());
+        }
+        let old_hash = hash(&take(&mut bytes, 32)?);
+        let new_hash = hash(&take(&mut bytes, 32)?);
+
+        let op_count = bytes.read_u32::<LittleEndian>().map_err(malformed)?;
+        let mut ops = Vec::new();
+        for _ in 0..op_count {
+            let op = match bytes.read_u8().map_err(malformed)? {
+                0 => PatchOp::Copy {
+                    index: bytes.read_u32::<LittleEndian>().map_err(malformed)? as usize,
+                    hash: hash(&take(&mut bytes, 32)?),
+                },
+                tag @ (1 | 2) => {
+                    let header = raw::read_header(&mut bytes, MAX_PATH_LEN)
+                        .map_err(|_| PatchError::Malformed)?;
+                    let data = match tag {
+                        2 => Some(take(&mut bytes, header.data_len())?),
+                        _ => None,
+                    };
+                    PatchOp::Insert { header, data }
+                },
+                _ => return Err(PatchError::Malformed.into()),
+            };
+            ops.push(op);
+        }
+
+        let trailing_len = bytes.read_u32::<LittleEndian>().map_err(malformed)? as usize;
+        let trailing = take(&mut bytes, trailing_len)?;
+        if !bytes.is_empty() {
+            return Err(PatchError::Malformed.into());
+        }
+
+        Ok(KsPatch { old_hash, new_hash, ops, trailing })
+    }
+
+    /// Returns the number of bytes of entry data the patch carries.
+    pub fn payload_len(&self) -> usize {
+        self.ops.iter()
+            .map(|op| match op {
+                PatchOp::Insert { data: Some(data), .. } => data.len(),
+                _ => 0,
+            })
+            .sum::<usize>() + self.trailing.len()
+    }
+}
+
+fn to_u32(n: usize) -> Result<u32> {
+    u32::try_from(n).map_err(|_| PatchError::Malformed.into())
+}
+
+/// Makes a patch that turns the .knytt.bin `old_bin` into `new_bin`.
+///
+/// Entries of `new_bin` whose header and data both appear in `old_bin` are copied from it, even
+/// if they moved; the rest are included in the patch.
+pub fn diff(old_bin: &[u8], new_bin: &[u8]) -> KsPatch {
+    let old = split_archive(old_bin);
+    let new = split_archive(new_bin);
+
+    let mut old_entries = HashMap::new();
+    for (index, (header, data)) in old.entries.iter().enumerate() {
+        let hash = blob_hash(data.unwrap_or_default());
+        old_entries.entry((header.path.as_str(), header.len, data.is_some(), hash)).or_insert(index);
+    }
+
+    let ops = new.entries.iter()
+        .map(|(header, data)| {
+            let hash = blob_hash(data.unwrap_or_default());
+            match old_entries.get(&(header.path.as_str(), header.len, data.is_some(), hash)) {
+                Some(&index) => PatchOp::Copy { index, hash },
+                None => PatchOp::Insert {
+                    header: header.clone(),
+                    data: data.map(<[u8]>::to_vec),
+                },
+            }
+        })
+        .collect();
+
+    KsPatch {
+        old_hash: blob_hash(old_bin),
+        new_hash: blob_hash(new_bin),
+        ops,
+        trailing: new.trailing.to_vec(),
+    }
+}
+
+/// Applies `patch` to the .knytt.bin `old_bin` and returns the new version.
+///
+/// Fails if `old_bin` isn't the version the patch was made from or the result isn't the version
+/// it was made for.
+pub fn apply(old_bin: &[u8], patch: &KsPatch) -> Result<Vec<u8>> {
+    if blob_hash(old_bin) != patch.old_hash {
+        return Err(PatchError::WrongBase.into());
+    }
+
+    let old = split_archive(old_bin);
+    let mut new_bin = Vec::new();
+
+    for op in &patch.ops {
+        match op {
+            PatchOp::Copy { index, hash } => {
+                let Some((header, data)) = old.entries.get(*index) else {
+                    return Err(PatchError::EntryMismatch { index: *index }.into());
+                };
+                if blob_hash(data.unwrap_or_default()) != *hash {
+                    return Err(PatchError::EntryMismatch { index: *index }.into());
+                }
+
+                raw::write_header(&mut new_bin, header)?;
+                new_bin.extend_from_slice(data.unwrap_or_default());
+            },
+            PatchOp::Insert { header, data } => {
+                raw::write_header(&mut new_bin, header)?;
+                new_bin.extend_from_slice(data.as_deref().unwrap_or_default());
+            },
+        }
+    }
+    new_bin.extend_from_slice(&patch.trailing);
+
+    if blob_hash(&new_bin) != patch.new_hash {
+        return Err(PatchError::WrongResult.into());
+    }
+
+    Ok(new_bin)
+}
+
+/// The entries of a .knytt.bin held in memory. See [`split_archive`].
+struct SplitArchive<'a> {
+    /// Each entry's header and data. The first entry has no data.
+    entries: Vec<(EntryHeader, Option<&'a [u8]>)>,
+    /// Whatever couldn't be read as a complete entry.
+    trailing: &'a [u8],
+}
+
+/// Splits a .knytt.bin into its entries the same way
+/// [`BlobStore::ingest`](crate::store::BlobStore::ingest) does.
+fn split_archive(data: &[u8]) -> SplitArchive<'_> {
+    let mut entries = Vec::new();
+    let mut rest = data;
+
+    while !rest.is_empty() {
+        let mut reader = rest;
+        let Ok(header) = raw::read_header(&mut reader, MAX_PATH_LEN) else { break };
+        if entries.is_empty() {
+            entries.push((header, None));
+            rest = reader;
+            continue;
+        }
+
+        let Some(entry_data) = reader.get(..header.data_len()) else { break };
+        rest = &reader[header.data_len()..];
+        entries.push((header, Some(entry_data)));
+    }
+
+    SplitArchive { entries, trailing: rest }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        raw::write_header(&mut data, &EntryHeader { path: "Me - Level".to_owned(), len: files.len() as u32 }).unwrap();
+        for (path, contents) in files {
+            raw::write_header(&mut data, &EntryHeader { path: path.to_string(), len: contents.len() as u32 }).unwrap();
+            data.extend_from_slice(contents);
+        }
+        data
+    }
+
+    #[test]
+    fn patches_carry_only_changes() {
+        let big = [7u8; 1000];
+        let old_bin = archive(&[("World.ini", b"Name=Old"), ("Music\\Song1.ogg", &big), ("Map.bin", b"map")]);
+        let new_bin = archive(&[("Music\\Song1.ogg", &big), ("World.ini", b"Name=New"), ("Map.bin", b"map")]);
+
+        let patch = diff(&old_bin, &new_bin);
+        assert_eq!(patch.payload_len(), 8);
+        assert!(matches!(patch.ops[1], PatchOp::Copy { index: 2, .. }));
+
+        let patch = KsPatch::from_bytes(&patch.to_bytes().unwrap()).unwrap();
+        assert_eq!(apply(&old_bin, &patch).unwrap(), new_bin);
+        assert!(apply(&new_bin, &patch).is_err());
+    }
+}
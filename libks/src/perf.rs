@@ -0,0 +1,103 @@
+use std::{
+    cell::RefCell,
+    ops::AddAssign,
+};
+
+/// Counts of the work done by libks while [recording](Counters::record).
+///
+/// The parsers populate these as they go, so recording adds almost no overhead and nothing is
+/// counted unless a recording is in progress on the current thread.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counters {
+    /// Bytes read from Map.bin (after decompression), World.ini, and .knytt.bin data.
+    pub bytes_read: u64,
+    /// Screens parsed from Map.bin data.
+    pub screens_parsed: u64,
+    /// INI files parsed.
+    pub inis_parsed: u64,
+    /// Asset lookups answered by an [`AssetCache`](crate::draw::AssetCache) without loading.
+    pub cache_hits: u64,
+    /// Asset lookups that required an [`AssetCache`](crate::draw::AssetCache) to load a file.
+    pub cache_misses: u64,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Counters>> = const { RefCell::new(None) };
+}
+
+impl Counters {
+    /// Creates a set of zeroed counters.
+    pub fn new() -> Counters {
+        Counters::default()
+    }
+
+    /// Calls `f` and adds the work it does on the current thread to `self`.
+    ///
+    /// Recordings may be nested, in which case the inner work is counted by every recording.
+    pub fn record<T, F>(&mut self, f: F) -> T
+    where
+        F: FnOnce() -> T
+    {
+        /// Restores the outer recording even if `f` panics.
+        struct Restore<'a> {
+            outer: Option<Counters>,
+            target: &'a mut Counters,
+        }
+
+        impl Drop for Restore<'_> {
+            fn drop(&mut self) {
+                let inner = CURRENT.with(|current| current.replace(self.outer.take()))
+                    .unwrap_or_default();
+                *self.target += inner;
+                count(|outer| *outer += inner);
+            }
+        }
+
+        let outer = CURRENT.with(|current| current.replace(Some(Counters::new())));
+        let _restore = Restore { outer, target: self };
+
+        f()
+    }
+}
+
+impl AddAssign for Counters {
+    fn add_assign(&mut self, rhs: Counters) {
+        self.bytes_read += rhs.bytes_read;
+        self.screens_parsed += rhs.screens_parsed;
+        self.inis_parsed += rhs.inis_parsed;
+        self.cache_hits += rhs.cache_hits;
+        self.cache_misses += rhs.cache_misses;
+    }
+}
+
+/// Updates the counters being recorded on the current thread, if any.
+pub(crate) fn count<F>(f: F)
+where
+    F: FnOnce(&mut Counters)
+{
+    CURRENT.with(|current| {
+        if let Some(counters) = current.borrow_mut().as_mut() {
+            f(counters);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_recordings_are_counted_by_both() {
+        let mut outer = Counters::new();
+        let mut inner = Counters::new();
+
+        outer.record(|| {
+            count(|c| c.screens_parsed += 1);
+            inner.record(|| count(|c| c.bytes_read += 10));
+        });
+        count(|c| c.cache_hits += 1);
+
+        assert_eq!(inner, Counters { bytes_read: 10, ..Default::default() });
+        assert_eq!(outer, Counters { bytes_read: 10, screens_parsed: 1, ..Default::default() });
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use libks_ini::Ini;
+
+use crate::{common::parse_bool, world_ini, Result};
+
+/// The number of powers tracked by a save file. Vanilla KS has 12 (0-11). KS Plus adds the
+/// map (12).
+pub const POWER_COUNT: usize = 13;
+
+/// The number of flags tracked by a save file.
+pub const FLAG_COUNT: usize = 10;
+
+/// The number of artifacts in KS Plus.
+pub const ARTIFACT_COUNT: usize = 7;
+
+/// A Knytt Stories save file.
+///
+/// Save files are INI files with the following sections:
+/// - `[Positions]`: `World` (the name of the level's directory), `X Map` and `Y Map` (the current
+///   screen), and `X Pos` and `Y Pos` (the player's tile position within the screen).
+/// - `[Powers]`: `Power0` through `Power12`. `1` if the power has been collected.
+/// - `[Flags]`: `Flag0` through `Flag9`. `1` if the flag is set.
+/// - `[Coins]` (KS Plus): one property per coin collected.
+/// - `[Artifacts]` (KS Plus): `Artifact1` through `Artifact7`, the number of each artifact collected.
+///
+/// The underlying [`Ini`] is retained so that unrecognized properties survive a round trip.
+pub struct SaveGame {
+    ini: Ini,
+}
+
+impl SaveGame {
+    /// Attempts to read and parse the save file at `path`.
+    pub fn load<P>(path: P) -> Result<SaveGame>
+    where
+        P: AsRef<Path>
+    {
+        let ini = world_ini::load_ini(path)?;
+        Ok(Self::from_ini(ini))
+    }
+
+    /// Attempts to read and parse the DefaultSavegame.ini for the level in `world_dir`.
+    ///
+    /// This is the save that KS copies into a slot when a new game is started, so it determines
+    /// where the player starts and which powers and flags they start with.
+    pub fn load_default<P>(world_dir: P) -> Result<SaveGame>
+    where
+        P: AsRef<Path>
+    {
+        Self::load(default_savegame_path(world_dir))
+    }
+
+    /// Wraps an already parsed save file.
+    pub fn from_ini(ini: Ini) -> SaveGame {
+        SaveGame { ini }
+    }
+
+    /// Creates a save file for the level whose directory is named `world`. The player starts on
+    /// `screen` at the tile position `position` with no powers or flags.
+    ///
+    /// Every property is written explicitly, matching the saves KS produces.
+    pub fn new(world: &str, screen: (i64, i64), position: (i64, i64)) -> SaveGame {
+        let mut save = SaveGame { ini: Ini::new("") };
+
+        save.set_world(world);
+        save.set_screen(screen);
+        save.set_position(position);
+        for power in 0..POWER_COUNT {
+            save.set_power(power, false);
+        }
+        for flag in 0..FLAG_COUNT {
+            save.set_flag(flag, false);
+        }
+
+        save
+    }
+
+    /// Writes the save file to `path`.
+    pub fn write<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>
+    {
+        world_ini::write_ini(path, &self.ini)
+    }
+
+    /// Returns the underlying INI data.
+    pub fn ini(&self) -> &Ini {
+        &self.ini
+    }
+
+    /// Returns the name of the directory of the level this save belongs to.
+    pub fn world(&self) -> Option<&str> {
+        self.ini.get_in("Positions", "World")
+    }
+
+    /// Sets the name of the directory of the level this save belongs to.
+    pub fn set_world(&mut self, world: &str) {
+        self.ini.set_in("Positions", "World", world.to_owned());
+    }
+
+    /// Returns the screen the player saved on.
+    pub fn screen(&self) -> Option<(i64, i64)> {
+        let x = self.get_number("Positions", "X Map")?;
+        let y = self.get_number("Positions", "Y Map")?;
+        Some((x, y))
+    }
+
+    /// Sets the screen the player saved on.
+    pub fn set_screen(&mut self, (x, y): (i64, i64)) {
+        self.ini.set_in("Positions", "X Map", x.to_string());
+        self.ini.set_in("Positions", "Y Map", y.to_string());
+    }
+
+    /// Returns the player's tile position within [`SaveGame::screen`].
+    pub fn position(&self) -> Option<(i64, i64)> {
+        let x = self.get_number("Positions", "X Pos")?;
+        let y = self.get_number("Positions", "Y Pos")?;
+        Some((x, y))
+    }
+
+    /// Sets the player's tile position within [`SaveGame::screen`].
+    pub fn set_position(&mut self, (x, y): (i64, i64)) {
+        self.ini.set_in("Positions", "X Pos", x.to_string());
+        self.ini.set_in("Positions", "Y Pos", y.to_string());
+    }
+
+    /// Returns `true` if the power with index `power` has been collected.
+    pub fn has_power(&self, power: usize) -> bool {
+        self.get_bool("Powers", &format!("Power{power}"))
+    }
+
+    /// Sets whether the power with index `power` has been collected.
+    pub fn set_power(&mut self, power: usize, collected: bool) {
+        self.set_bool("Powers", &format!("Power{power}"), collected);
+    }
+
+    /// Returns `true` if the flag with index `flag` is set.
+    pub fn has_flag(&self, flag: usize) -> bool {
+        self.get_bool("Flags", &format!("Flag{flag}"))
+    }
+
+    /// Sets whether the flag with index `flag` is set.
+    pub fn set_flag(&mut self, flag: usize, value: bool) {
+        self.set_bool("Flags", &format!("Flag{flag}"), value);
+    }
+
+    /// Returns the number of coins collected (KS Plus).
+    pub fn coins(&self) -> usize {
+        let Some(section) = self.ini.section("Coins") else {
+            return 0;
+        };
+
+        section.iter()
+            .filter(|(_, value)| parse_bool(value).unwrap_or(false))
+            .count()
+    }
+
+    /// Returns the number of distinct artifacts collected (KS Plus).
+    pub fn artifacts(&self) -> usize {
+        (1..=ARTIFACT_COUNT)
+            .filter(|i| {
+                self.get_number("Artifacts", &format!("Artifact{i}"))
+                    .is_some_and(|count| count > 0)
+            })
+            .count()
+    }
+
+    fn get_number(&self, section_key: &str, prop_key: &str) -> Option<i64> {
+        self.ini.get_in(section_key, prop_key)
+            .and_then(|value| value.parse().ok())
+    }
+
+    fn get_bool(&self, section_key: &str, prop_key: &str) -> bool {
+        self.ini.get_in(section_key, prop_key)
+            .and_then(parse_bool)
+            .unwrap_or(false)
+    }
+
+    fn set_bool(&mut self, section_key: &str, prop_key: &str, value: bool) {
+        let value = if value { "1" } else { "0" };
+        self.ini.set_in(section_key, prop_key, value.to_owned());
+    }
+}
+
+/// Returns the path of the save file for `slot` (1-3) in the KS installation at `ks_dir`.
+pub fn slot_path<P>(ks_dir: P, slot: u8) -> PathBuf
+where
+    P: AsRef<Path>
+{
+    ks_dir.as_ref()
+        .join("Saves")
+        .join(format!("{slot}.ini"))
+}
+
+/// Returns the path of the DefaultSavegame.ini for the level in `world_dir`.
+pub fn default_savegame_path<P>(world_dir: P) -> PathBuf
+where
+    P: AsRef<Path>
+{
+    world_dir.as_ref().join("DefaultSavegame.ini")
+}
+
+/// Starts a new game in `slot` (1-3) of the KS installation at `ks_dir`, as KS does when a level
+/// is selected from the menu. The level's DefaultSavegame.ini is copied into the slot and its
+/// `World` property is pointed at the level's directory.
+///
+/// If `world_dir` has no DefaultSavegame.ini, one is generated that starts the player at
+/// x1000y1000.
+pub fn start_new_game<P1, P2>(ks_dir: P1, slot: u8, world_dir: P2) -> Result<SaveGame>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>,
+{
+    let world_dir = world_dir.as_ref();
+    let world_name = world_dir.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+
+    let mut save =
+        if default_savegame_path(world_dir).is_file() {
+            SaveGame::load_default(world_dir)?
+        }
+        else {
+            SaveGame::new(world_name, (1000, 1000), (0, 0))
+        };
+    save.set_world(world_name);
+
+    let path = slot_path(ks_dir, slot);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    save.write(path)?;
+
+    Ok(save)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_games_copy_the_default_save() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ks_dir = temp_dir.path();
+        let world_dir = ks_dir.join("Worlds/Me - Level");
+        fs::create_dir_all(&world_dir).unwrap();
+
+        // Without a DefaultSavegame.ini, the player starts at x1000y1000 with nothing
+        let save = start_new_game(ks_dir, 1, &world_dir).unwrap();
+        assert_eq!(save.world(), Some("Me - Level"));
+        assert_eq!(save.screen(), Some((1000, 1000)));
+        assert_eq!(save.position(), Some((0, 0)));
+        assert_eq!(save.ini().get_in("Powers", "Power12"), Some("0"));
+        assert_eq!(save.ini().get_in("Flags", "Flag9"), Some("0"));
+
+        let mut default = SaveGame::new("Old Name", (1002, 998), (5, 7));
+        default.set_power(3, true);
+        default.set_flag(0, true);
+        default.write(default_savegame_path(&world_dir)).unwrap();
+
+        let save = start_new_game(ks_dir, 2, &world_dir).unwrap();
+        let written = SaveGame::load(slot_path(ks_dir, 2)).unwrap();
+        for save in [save, written] {
+            assert_eq!(save.world(), Some("Me - Level"));
+            assert_eq!(save.screen(), Some((1002, 998)));
+            assert_eq!(save.position(), Some((5, 7)));
+            assert!(save.has_power(3));
+            assert!(!save.has_power(4));
+            assert!(save.has_flag(0));
+        }
+        assert_eq!(SaveGame::load_default(&world_dir).unwrap().world(), Some("Old Name"));
+    }
+}
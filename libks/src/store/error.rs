@@ -0,0 +1,15 @@
+#[derive(thiserror::Error, Debug)]
+pub enum StoreError {
+    #[error("Line {line} of the manifest is malformed.")]
+    BadManifest {
+        line: usize,
+    },
+    #[error("The blob {hash} is missing from the store.")]
+    MissingBlob {
+        hash: String,
+    },
+    #[error("The blob {hash} is corrupt: its contents don't match its hash.")]
+    CorruptBlob {
+        hash: String,
+    },
+}
@@ -0,0 +1,347 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::ResultExt,
+    knytt_bin::raw::{self, EntryHeader},
+    Result,
+};
+
+mod error;
+pub use error::StoreError;
+
+/// The longest entry path [`BlobStore::ingest`] reads, the same as the default for unpacking.
+/// Anything from an entry with a longer path onward is kept as trailing data.
+pub(crate) const MAX_PATH_LEN: usize = 256;
+
+/// The SHA-256 hash of a blob's contents, which is also its name in the store.
+pub type BlobHash = [u8; 32];
+
+/// Returns the hash of `data` as it's named in a [`BlobStore`].
+pub fn blob_hash(data: &[u8]) -> BlobHash {
+    Sha256::digest(data).into()
+}
+
+/// Formats `hash` as 64 lowercase hex digits.
+pub fn hash_to_hex(hash: &BlobHash) -> String {
+    hash.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Parses 64 hex digits as a hash.
+pub fn hash_from_hex(hex: &str) -> Option<BlobHash> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut hash = [0; 32];
+    for (byte, digits) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+    Some(hash)
+}
+
+/// An entry of a .knytt.bin as recorded in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub header: EntryHeader,
+    /// The blob holding the entry's data, or `None` for the first entry, which has none.
+    pub blob: Option<BlobHash>,
+}
+
+/// Describes how to rebuild a .knytt.bin from the blobs in a [`BlobStore`].
+///
+/// As text, a manifest has one line per entry: the blob's hash (or `-` for no data), the length
+/// from the entry's header, and the entry's path, separated by tabs. Backslashes and line breaks
+/// in paths are escaped with a backslash. If the archive ended with bytes that aren't a
+/// complete entry, a last line `trailing` followed by a tab and their hash records them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+    /// The blob holding any bytes after the last complete entry.
+    pub trailing: Option<BlobHash>,
+}
+
+impl Manifest {
+    /// Parses a manifest written by its [`Display`](std::fmt::Display) implementation.
+    pub fn parse(text: &str) -> Result<Manifest> {
+        let mut manifest = Manifest::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let bad_line = || StoreError::BadManifest { line: i + 1 };
+            if let Some(hex) = line.strip_prefix("trailing\t") {
+                manifest.trailing = Some(hash_from_hex(hex).ok_or_else(bad_line)?);
+                continue;
+            }
+
+            let mut fields = line.splitn(3, '\t');
+            let (Some(blob), Some(len), Some(path)) = (fields.next(), fields.next(), fields.next()) else {
+                return Err(bad_line().into());
+            };
+            let blob = match blob {
+                "-" => None,
+                hex => Some(hash_from_hex(hex).ok_or_else(bad_line)?),
+            };
+            let len = len.parse().map_err(|_| bad_line())?;
+            let path = unescape_path(path).ok_or_else(bad_line)?;
+
+            manifest.entries.push(ManifestEntry {
+                header: EntryHeader { path, len },
+                blob,
+            });
+        }
+
+        Ok(manifest)
+    }
+
+    /// Returns the distinct blobs the manifest refers to.
+    pub fn blobs(&self) -> Vec<BlobHash> {
+        let mut blobs: Vec<BlobHash> = self.entries.iter()
+            .filter_map(|entry| entry.blob)
+            .chain(self.trailing)
+            .collect();
+        blobs.sort();
+        blobs.dedup();
+        blobs
+    }
+}
+
+impl std::fmt::Display for Manifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            let blob = entry.blob.as_ref().map_or("-".to_owned(), hash_to_hex);
+            writeln!(f, "{blob}\t{}\t{}", entry.header.len, escape_path(&entry.header.path))?;
+        }
+        if let Some(trailing) = &self.trailing {
+            writeln!(f, "trailing\t{}", hash_to_hex(trailing))?;
+        }
+        Ok(())
+    }
+}
+
+fn escape_path(path: &str) -> String {
+    path.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape_path(escaped: &str) -> Option<String> {
+    let mut path = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        path.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(path)
+}
+
+/// A directory of blobs named by the hashes of their contents, for hosting many .knytt.bins
+/// that share files: each distinct file is stored once, and each archive is described by a
+/// [`Manifest`] from which it can be rebuilt byte for byte.
+///
+/// Blobs are stored at `<root>/<first 2 hex digits>/<all 64 hex digits>`.
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    /// Opens the store in `root`, creating the directory if necessary.
+    pub fn open<P>(root: P) -> Result<BlobStore>
+    where
+        P: AsRef<Path>
+    {
+        let root = root.as_ref().to_owned();
+        fs::create_dir_all(&root).with_path(&root)?;
+        Ok(BlobStore { root })
+    }
+
+    /// Returns the path of the blob with `hash`, whether or not it exists.
+    pub fn blob_path(&self, hash: &BlobHash) -> PathBuf {
+        let hex = hash_to_hex(hash);
+        self.root.join(&hex[..2]).join(hex)
+    }
+
+    pub fn contains(&self, hash: &BlobHash) -> bool {
+        self.blob_path(hash).is_file()
+    }
+
+    /// Adds `data` to the store if it isn't there already and returns its hash.
+    pub fn insert(&self, data: &[u8]) -> Result<BlobHash> {
+        let hash = blob_hash(data);
+        let path = self.blob_path(&hash);
+        if path.is_file() {
+            return Ok(hash);
+        }
+
+        // Write to a temporary file first so a crash can't leave a truncated blob behind
+        let dir = path.parent().expect("blob path should have a parent");
+        fs::create_dir_all(dir).with_path(dir)?;
+        let temp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&temp, data).with_path(&temp)?;
+        fs::rename(&temp, &path).with_path(&path)?;
+
+        Ok(hash)
+    }
+
+    /// Reads the blob with `hash`, checking that its contents still match.
+    pub fn read(&self, hash: &BlobHash) -> Result<Vec<u8>> {
+        let path = self.blob_path(hash);
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound =>
+                return Err(StoreError::MissingBlob { hash: hash_to_hex(hash) }.into()),
+            Err(err) => return Err(err).with_path(&path),
+        };
+
+        if blob_hash(&data) != *hash {
+            return Err(StoreError::CorruptBlob { hash: hash_to_hex(hash) }.into());
+        }
+
+        Ok(data)
+    }
+
+    /// Splits the .knytt.bin at `bin_path` into blobs, adds the ones the store doesn't have yet,
+    /// and returns the manifest for rebuilding it.
+    pub fn ingest<P>(&self, bin_path: P) -> Result<Manifest>
+    where
+        P: AsRef<Path>
+    {
+        let bin_path = bin_path.as_ref();
+        let file = fs::File::open(bin_path).with_path(bin_path)?;
+        self.ingest_reader(BufReader::new(file)).with_path(bin_path)
+    }
+
+    /// Like [`BlobStore::ingest`], but reads the .knytt.bin from `reader`.
+    ///
+    /// Data that can't be read as entries, such as a truncated last entry, is kept as a single
+    /// trailing blob so that the archive can still be rebuilt exactly.
+    pub fn ingest_reader<R>(&self, mut reader: R) -> Result<Manifest>
+    where
+        R: BufRead
+    {
+        let mut manifest = Manifest::default();
+        let mut consumed = Vec::new();
+
+        while !reader.fill_buf()?.is_empty() {
+            consumed.clear();
+            let mut tee = Tee { reader: &mut reader, copy: &mut consumed };
+            let Ok(header) = raw::read_header(&mut tee, MAX_PATH_LEN) else { break };
+
+            // The first entry has no data
+            if manifest.entries.is_empty() {
+                manifest.entries.push(ManifestEntry { header, blob: None });
+                continue;
+            }
+
+            let mut data = Vec::new();
+            tee.reader.take(header.len.into()).read_to_end(&mut data)?;
+            if data.len() != header.data_len() {
+                consumed.extend_from_slice(&data);
+                break;
+            }
+
+            let blob = self.insert(&data)?;
+            manifest.entries.push(ManifestEntry { header, blob: Some(blob) });
+            consumed.clear();
+        }
+
+        // Whatever couldn't be read as a complete entry
+        reader.read_to_end(&mut consumed)?;
+        if !consumed.is_empty() {
+            manifest.trailing = Some(self.insert(&consumed)?);
+        }
+
+        Ok(manifest)
+    }
+
+    /// Rebuilds the .knytt.bin described by `manifest` and writes it to `writer`.
+    pub fn reconstruct<W>(&self, manifest: &Manifest, writer: &mut W) -> Result<()>
+    where
+        W: Write
+    {
+        for entry in &manifest.entries {
+            raw::write_header(writer, &entry.header)?;
+            if let Some(blob) = &entry.blob {
+                writer.write_all(&self.read(blob)?)?;
+            }
+        }
+        if let Some(trailing) = &manifest.trailing {
+            writer.write_all(&self.read(trailing)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Copies everything read through it, so that bytes read by a failed parse can be recovered.
+struct Tee<'a, R> {
+    reader: &'a mut R,
+    copy: &'a mut Vec<u8>,
+}
+
+impl<R: BufRead> Read for Tee<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.copy.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Tee<'_, R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buf) = self.reader.fill_buf() {
+            self.copy.extend_from_slice(&buf[..amt.min(buf.len())]);
+        }
+        self.reader.consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(data: &mut Vec<u8>, path: &str, contents: &[u8]) {
+        raw::write_header(data, &EntryHeader { path: path.to_owned(), len: contents.len() as u32 }).unwrap();
+        data.extend_from_slice(contents);
+    }
+
+    #[test]
+    fn archives_share_blobs_and_rebuild_exactly() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let store = BlobStore::open(root).unwrap();
+
+        let mut a = Vec::new();
+        raw::write_header(&mut a, &EntryHeader { path: "Me - A".to_owned(), len: 7 }).unwrap();
+        entry(&mut a, "World.ini", b"[World]\r\nName=A\r\n");
+        entry(&mut a, "Tilesets\\Tileset1.png", b"shared");
+        let mut b = a.clone();
+        entry(&mut b, "Music\\Song1.ogg", b"shared");
+        b.extend_from_slice(b"NF trunc");
+
+        let manifest_a = store.ingest_reader(&a[..]).unwrap();
+        let manifest_b = store.ingest_reader(&b[..]).unwrap();
+        assert_eq!(manifest_a.blobs().len(), 2);
+        assert!(manifest_b.trailing.is_some());
+
+        let manifest_b = Manifest::parse(&manifest_b.to_string()).unwrap();
+        assert_eq!(manifest_b.entries[2].header.path, "Tilesets\\Tileset1.png");
+        let mut rebuilt = Vec::new();
+        store.reconstruct(&manifest_b, &mut rebuilt).unwrap();
+        assert_eq!(rebuilt, b);
+    }
+}
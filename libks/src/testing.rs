@@ -0,0 +1,163 @@
+use libks_ini::Ini;
+use proptest::{collection::vec, prelude::*};
+
+use crate::{
+    constants::*,
+    map_bin::{self, AssetIds, LayerData, ScreenData, Tile},
+};
+
+/// Generates a screen position. Positions cluster around the usual `x1000y1000` so that
+/// generated screens are likely to neighbor each other.
+pub fn arbitrary_position() -> impl Strategy<Value = (i64, i64)> {
+    (990i64..1010, 990i64..1010)
+}
+
+/// Generates a tile for a tile layer: tileset 0 or 1 and an index from 0 to 127.
+pub fn arbitrary_tile() -> impl Strategy<Value = Tile> {
+    (0u8..2, 0u8..128).prop_map(|(tileset, index)| Tile(tileset, index))
+}
+
+/// Generates an object: any bank and index.
+pub fn arbitrary_object() -> impl Strategy<Value = Tile> {
+    any::<(u8, u8)>().prop_map(|(bank, index)| Tile(bank, index))
+}
+
+/// Generates a layer of [`TILES_PER_LAYER`] tiles from `tile`.
+fn arbitrary_layer<S>(tile: S) -> impl Strategy<Value = LayerData>
+where
+    S: Strategy<Value = Tile>
+{
+    vec(tile, TILES_PER_LAYER)
+        .prop_map(|tiles| LayerData(tiles.try_into().unwrap()))
+}
+
+/// Generates a set of asset IDs.
+pub fn arbitrary_assets() -> impl Strategy<Value = AssetIds> {
+    any::<[u8; 6]>().prop_map(|ids| AssetIds {
+        tileset_a: ids[0],
+        tileset_b: ids[1],
+        ambiance_a: ids[2],
+        ambiance_b: ids[3],
+        music: ids[4],
+        gradient: ids[5],
+    })
+}
+
+/// Generates a screen that can be represented in Map.bin, i.e. that survives
+/// [`assert_screen_round_trip`].
+pub fn arbitrary_screen() -> impl Strategy<Value = ScreenData> {
+    (
+        arbitrary_position(),
+        vec(arbitrary_layer(arbitrary_tile()), 4),
+        vec(arbitrary_layer(arbitrary_object()), 4),
+        arbitrary_assets(),
+    ).prop_map(|(position, tile_layers, object_layers, assets)| {
+        let mut layers = tile_layers.into_iter().chain(object_layers);
+        ScreenData {
+            position,
+            layers: core::array::from_fn(|_| layers.next().unwrap()),
+            assets,
+        }
+    })
+}
+
+/// Generates up to `max_len` screens with distinct positions.
+pub fn arbitrary_screens(max_len: usize) -> impl Strategy<Value = Vec<ScreenData>> {
+    vec(arbitrary_screen(), 0..=max_len)
+        .prop_map(|mut screens| {
+            let mut seen = std::collections::HashSet::new();
+            screens.retain(|screen| seen.insert(screen.position));
+            screens
+        })
+}
+
+/// Generates text that can appear in a World.ini value: Windows-1252 characters other than
+/// line breaks.
+fn arbitrary_value() -> impl Strategy<Value = String> {
+    "[ -~À-ÿ]{0,40}".prop_map(|value| value.trim().to_owned())
+}
+
+/// Generates a World.ini with a `[World]` section and screen sections holding signs, shifts,
+/// and warps.
+pub fn arbitrary_world_ini() -> impl Strategy<Value = Ini> {
+    let world = (arbitrary_value(), arbitrary_value(), arbitrary_value());
+    let screen = (
+        arbitrary_position(),
+        proptest::option::of(arbitrary_value()),
+        proptest::option::of((0u8..3, arbitrary_position(), 0i64..25, 0i64..10)),
+        proptest::option::of((0usize..4, -3i64..=3)),
+    );
+
+    (world, vec(screen, 0..8)).prop_map(|((name, author, description), screens)| {
+        let mut ini = Ini::new("");
+        ini.set_in("World", "Name", name);
+        ini.set_in("World", "Author", author);
+        ini.set_in("World", "Description", description);
+
+        for ((x, y), sign, shift, warp) in screens {
+            let section = format!("x{x}y{y}");
+            if let Some(sign) = sign {
+                ini.set_in(&section, "Sign(A)", sign);
+            }
+            if let Some((slot, (to_x, to_y), tile_x, tile_y)) = shift {
+                let slot = ["A", "B", "C"][usize::from(slot)];
+                ini.set_in(&section, &format!("ShiftXMap({slot})"), to_x.to_string());
+                ini.set_in(&section, &format!("ShiftYMap({slot})"), to_y.to_string());
+                ini.set_in(&section, &format!("ShiftXPos({slot})"), tile_x.to_string());
+                ini.set_in(&section, &format!("ShiftYPos({slot})"), tile_y.to_string());
+            }
+            if let Some((edge, offset)) = warp {
+                let edge = ["Up", "Down", "Left", "Right"][edge];
+                ini.set_in(&section, &format!("Warp{edge}X"), offset.to_string());
+            }
+        }
+
+        ini
+    })
+}
+
+/// Asserts that `screen` is unchanged after being encoded and parsed.
+pub fn assert_screen_round_trip(screen: &ScreenData) {
+    let data = map_bin::encode_screen(screen);
+    let parsed = map_bin::parse_screen_bytes(&data, screen.position);
+    assert_eq!(&parsed, screen, "screen changed after a round trip through Map.bin");
+}
+
+/// Asserts that `screens` are unchanged after being encoded as Map.bin and parsed without any
+/// warnings.
+pub fn assert_map_round_trip(screens: &[ScreenData]) {
+    let data = map_bin::encode_map(screens);
+    let (parsed, warnings, error) = map_bin::parse_map_bytes(&data);
+    assert!(error.is_none(), "failed to parse encoded Map.bin: {error:?}");
+    assert!(warnings.is_empty(), "parsing encoded Map.bin produced warnings: {warnings:?}");
+    assert_eq!(parsed, screens, "screens changed after a round trip through Map.bin");
+}
+
+/// Asserts that `ini` serializes to the same text after being encoded as Windows-1252, decoded,
+/// and parsed again, as happens when World.ini is written and loaded.
+pub fn assert_ini_round_trip(ini: &Ini) {
+    let text = ini.to_string();
+    let (bytes, _, had_errors) = encoding_rs::WINDOWS_1252.encode(&text);
+    assert!(!had_errors, "INI contains characters that Windows-1252 can't encode");
+
+    let (decoded, _) = encoding_rs::WINDOWS_1252.decode_without_bom_handling(&bytes);
+    assert_eq!(Ini::new(&decoded).to_string(), text, "INI changed after a round trip");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn serializers_round_trip(screens in arbitrary_screens(4), ini in arbitrary_world_ini()) {
+            for screen in &screens {
+                assert_screen_round_trip(screen);
+            }
+            assert_map_round_trip(&screens);
+            assert_ini_round_trip(&ini);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use libks_ini::Ini;
+use wasm_bindgen::prelude::*;
+
+use crate::map_bin::{self, ScreenData};
+
+/// Map.bin data parsed by [`parse_map_bytes`].
+#[wasm_bindgen]
+pub struct MapData {
+    screens: Vec<ScreenData>,
+    warnings: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl MapData {
+    /// The number of screens.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.screens.len()
+    }
+
+    /// Descriptions of any abnormalities in the data.
+    #[wasm_bindgen(getter)]
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+
+    /// Returns the position of the screen at `index` as `[x, y]`.
+    pub fn position(&self, index: usize) -> Option<Vec<i64>> {
+        let (x, y) = self.screens.get(index)?.position;
+        Some(vec![x, y])
+    }
+
+    /// Returns the index of the screen at `(x, y)`, if it exists.
+    #[wasm_bindgen(js_name = indexOf)]
+    pub fn index_of(&self, x: i64, y: i64) -> Option<usize> {
+        self.screens.iter()
+            .position(|screen| screen.position == (x, y))
+    }
+
+    /// Returns the raw data of the screen at `index`. See
+    /// [`parse_screen_bytes`](map_bin::parse_screen_bytes) for the layout.
+    #[wasm_bindgen(js_name = screenBytes)]
+    pub fn screen_bytes(&self, index: usize) -> Option<Vec<u8>> {
+        let screen = self.screens.get(index)?;
+        Some(map_bin::encode_screen(screen).to_vec())
+    }
+}
+
+/// Parses Map.bin data. The data may be gzipped, as it is on disk, or uncompressed.
+#[wasm_bindgen(js_name = parseMapBytes)]
+pub fn parse_map_bytes(data: &[u8]) -> Result<MapData, JsError> {
+    let mut decompressed = Vec::new();
+    let data = if map_bin::is_gzip(data) {
+        GzDecoder::new(data).read_to_end(&mut decompressed)?;
+        &decompressed[..]
+    }
+    else {
+        data
+    };
+
+    let (screens, warnings, error) = map_bin::parse_map_bytes(data);
+    if let Some(error) = error {
+        return Err(JsError::new(&error.to_string()));
+    }
+
+    Ok(MapData {
+        screens,
+        warnings: warnings.iter().map(ToString::to_string).collect(),
+    })
+}
+
+/// INI data parsed by [`parse_ini`].
+#[wasm_bindgen]
+pub struct IniData {
+    ini: Ini,
+}
+
+#[wasm_bindgen]
+impl IniData {
+    /// Returns the value of `key` in `section`, if it exists.
+    pub fn get(&self, section: &str, key: &str) -> Option<String> {
+        self.ini.get_in(section, key)
+            .map(str::to_owned)
+    }
+
+    /// Sets the value of `key` in `section`, creating either if necessary.
+    pub fn set(&mut self, section: &str, key: &str, value: String) {
+        self.ini.set_in(section, key, value);
+    }
+
+    /// Returns the names of the sections in file order.
+    #[wasm_bindgen(js_name = sectionNames)]
+    pub fn section_names(&self) -> Vec<String> {
+        self.ini.iter_sections()
+            .map(|section| section.key().to_owned())
+            .collect()
+    }
+
+    /// Serializes the INI data, preserving the original formatting.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.ini.to_string()
+    }
+}
+
+/// Parses INI data from a string.
+#[wasm_bindgen(js_name = parseIni)]
+pub fn parse_ini(source: &str) -> IniData {
+    IniData { ini: Ini::new(source) }
+}
+
+/// Parses INI data from bytes encoded as Windows-1252, like World.ini on disk.
+#[wasm_bindgen(js_name = parseIniBytes)]
+pub fn parse_ini_bytes(data: &[u8]) -> IniData {
+    let (source, _) = encoding_rs::WINDOWS_1252.decode_without_bom_handling(data);
+    parse_ini(&source)
+}
+
+/// Draws screens from [`MapData`]. Since there's no filesystem to load assets from, the
+/// tilesets and gradients have to be added first.
+#[cfg(feature = "image")]
+#[wasm_bindgen]
+pub struct ScreenRenderer {
+    assets: crate::draw::AssetCache,
+}
+
+#[cfg(feature = "image")]
+#[wasm_bindgen]
+impl ScreenRenderer {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ScreenRenderer {
+        let source = crate::assets::AssetSource {
+            data_folder: Default::default(),
+            world_folder: Default::default(),
+        };
+
+        ScreenRenderer {
+            assets: crate::draw::AssetCache::new(source),
+        }
+    }
+
+    /// Adds tileset `id` from the contents of a PNG file.
+    #[wasm_bindgen(js_name = addTileset)]
+    pub fn add_tileset(&mut self, id: u8, png: &[u8]) -> Result<(), JsError> {
+        self.assets.insert_tileset(id, image::load_from_memory(png)?);
+        Ok(())
+    }
+
+    /// Adds gradient `id` from the contents of a PNG file.
+    #[wasm_bindgen(js_name = addGradient)]
+    pub fn add_gradient(&mut self, id: u8, png: &[u8]) -> Result<(), JsError> {
+        self.assets.insert_gradient(id, image::load_from_memory(png)?);
+        Ok(())
+    }
+
+    /// Draws the screen at `index` in `map` and returns its pixels as 600x240 RGBA. Assets
+    /// that haven't been added are left out.
+    pub fn render(&mut self, map: &MapData, index: usize) -> Result<Vec<u8>, JsError> {
+        let screen = map.screens.get(index)
+            .ok_or_else(|| JsError::new("screen index out of bounds"))?;
+        let img = crate::draw::draw_screen(screen, &mut self.assets)?;
+
+        Ok(img.into_raw())
+    }
+}
+
+#[cfg(feature = "image")]
+impl Default for ScreenRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
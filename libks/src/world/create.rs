@@ -0,0 +1,231 @@
+use std::{fs, path::PathBuf};
+
+use libks_ini::{Ini, SectionKind};
+
+use crate::{
+    error::ResultExt,
+    install::{canonical_folder_name, InstallError},
+    io_util,
+    map_bin::{self, SCREEN_DATA_LEN},
+    saves::{default_savegame_path, SaveGame},
+    Result,
+};
+use super::{World, WorldError};
+
+/// The asset directories created by [`World::create_new`].
+pub const STANDARD_DIRS: [&str; 5] = ["Ambiance", "Custom Objects", "Gradients", "Music", "Tilesets"];
+
+/// Options for [`World::create_new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewWorldOptions {
+    /// The directory to create the level's directory in, e.g. the `Worlds` directory of a KS
+    /// installation. Defaults to the current directory.
+    pub parent_dir: PathBuf,
+    /// `Description` in the `[World]` section. Left out if `None`, which is the default.
+    pub description: Option<String>,
+    /// `Size` in the `[World]` section. Defaults to `Small`.
+    pub size: Option<String>,
+    /// The screen the player starts on. Defaults to x1000y1000.
+    pub start_screen: (i64, i64),
+    /// The tile position the player starts at. Defaults to the top left corner.
+    pub start_position: (i64, i64),
+}
+
+impl Default for NewWorldOptions {
+    fn default() -> Self {
+        Self {
+            parent_dir: PathBuf::new(),
+            description: None,
+            size: Some("Small".to_owned()),
+            start_screen: (1000, 1000),
+            start_position: (0, 0),
+        }
+    }
+}
+
+/// Options for [`World::templated_from`]. By default, everything that can be copied is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateOptions {
+    /// The name of the new level.
+    pub name: String,
+    /// The author of the new level.
+    pub author: String,
+    /// How to create the new level. See [`World::create_new`].
+    pub new_world: NewWorldOptions,
+    /// Copy the Tilesets directory.
+    pub tilesets: bool,
+    /// Copy the Gradients directory.
+    pub gradients: bool,
+    /// Copy the Music directory.
+    pub music: bool,
+    /// Copy the Ambiance directory.
+    pub ambiance: bool,
+    /// Copy the Custom Objects directory.
+    pub custom_objects: bool,
+    /// Copy every World.ini section that doesn't belong to a screen, such as custom objects and
+    /// cutscene settings, and the `[World]` properties the new level doesn't set itself.
+    pub ini: bool,
+}
+
+impl Default for TemplateOptions {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            author: String::new(),
+            new_world: NewWorldOptions::default(),
+            tilesets: true,
+            gradients: true,
+            music: true,
+            ambiance: true,
+            custom_objects: true,
+            ini: true,
+        }
+    }
+}
+
+impl World {
+    /// Creates a new level called `name` by `author` and saves it.
+    ///
+    /// The level's directory is named `Author - Name` (see [`canonical_folder_name`]) and
+    /// contains a World.ini with the level's details, a Map.bin with a single empty start screen,
+    /// a DefaultSavegame.ini that starts the player there, and the empty
+    /// [asset directories](STANDARD_DIRS).
+    ///
+    /// Fails if the names don't make a usable directory name or the directory already exists.
+    pub fn create_new(name: &str, author: &str, options: NewWorldOptions) -> Result<World> {
+        let Some(dir_name) = canonical_folder_name(author, name) else {
+            return Err(InstallError::InvalidName(format!("{author} - {name}")).into());
+        };
+        let dir = options.parent_dir.join(&dir_name);
+        if dir.exists() {
+            return Err(WorldError::AlreadyExists(dir).into());
+        }
+
+        let mut ini = Ini::new("");
+        ini.set_in("World", "Name", name.to_owned());
+        ini.set_in("World", "Author", author.to_owned());
+        if let Some(description) = options.description {
+            ini.set_in("World", "Description", description);
+        }
+        if let Some(size) = options.size {
+            ini.set_in("World", "Size", size);
+        }
+
+        let world = World {
+            dir,
+            ini,
+            screens: vec![map_bin::parse_screen_bytes(&[0; SCREEN_DATA_LEN], options.start_screen)],
+        };
+
+        for asset_dir in STANDARD_DIRS {
+            let path = world.dir.join(asset_dir);
+            fs::create_dir_all(&path).with_path(&path)?;
+        }
+        world.save()?;
+        SaveGame::new(&dir_name, options.start_screen, options.start_position)
+            .write(default_savegame_path(&world.dir))?;
+
+        Ok(world)
+    }
+
+    /// Creates a new level with [`World::create_new`] and copies the assets and World.ini
+    /// settings selected in `options` from `source`, but none of its screens. This suits
+    /// starting levels from a personal template level.
+    ///
+    /// Asset directories are found without regard to case. Selected ones that `source` doesn't
+    /// have are skipped.
+    pub fn templated_from(source: &World, options: TemplateOptions) -> Result<World> {
+        let mut world = World::create_new(&options.name, &options.author, options.new_world)?;
+
+        let selected = [
+            ("Tilesets", options.tilesets),
+            ("Gradients", options.gradients),
+            ("Music", options.music),
+            ("Ambiance", options.ambiance),
+            ("Custom Objects", options.custom_objects),
+        ];
+        for entry in fs::read_dir(&source.dir).with_path(&source.dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let Some(name) = file_name.to_str() else { continue };
+            let Some((dir_name, _)) = selected.iter()
+                .find(|(dir_name, copy)| *copy && dir_name.eq_ignore_ascii_case(name))
+            else {
+                continue;
+            };
+
+            if entry.file_type()?.is_dir() {
+                io_util::copy_dir_all(entry.path(), world.dir.join(dir_name)).with_path(entry.path())?;
+            }
+        }
+
+        if options.ini {
+            for section in source.ini.iter_sections() {
+                let key = section.key();
+                let kind = section.kind();
+                if matches!(kind, SectionKind::Screen(_)) {
+                    continue;
+                }
+
+                let is_world = kind == SectionKind::World;
+                for (prop, value) in section.iter() {
+                    if is_world && world.ini.has_in("World", prop) {
+                        continue;
+                    }
+                    world.ini.set_in(key, prop, value.to_owned());
+                }
+            }
+            world.save()?;
+        }
+
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_worlds_load() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let parent_dir = temp_dir.path();
+        let options = NewWorldOptions {
+            parent_dir: parent_dir.to_path_buf(),
+            ..Default::default()
+        };
+
+        let created = World::create_new("First", "Me", options.clone()).unwrap();
+        assert_eq!(created.dir, parent_dir.join("Me - First"));
+        assert!(World::create_new("First", "Me", options).is_err());
+
+        let loaded = World::load(&created.dir).unwrap();
+        assert_eq!(loaded.ini.get_in("World", "Name"), Some("First"));
+        assert_eq!(loaded.screens.len(), 1);
+        assert!(loaded.dir.join("Custom Objects").is_dir());
+        let save = SaveGame::load_default(&loaded.dir).unwrap();
+        assert_eq!(save.screen(), Some((1000, 1000)));
+        assert_eq!(save.world(), Some("Me - First"));
+
+        let source = World {
+            ini: Ini::new("[World]\nName=Template\nFormat=4\n[Custom Object 1]\nImage=Spike.png\n[x1000y1000]\nTint=Red\n"),
+            ..loaded
+        };
+        fs::create_dir_all(source.dir.join("tilesets")).unwrap();
+        fs::write(source.dir.join("tilesets/Tileset128.png"), b"").unwrap();
+        let templated = World::templated_from(&source, TemplateOptions {
+            name: "Second".to_owned(),
+            author: "Me".to_owned(),
+            new_world: NewWorldOptions {
+                parent_dir: parent_dir.to_path_buf(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }).unwrap();
+        assert!(templated.dir.join("Tilesets/Tileset128.png").is_file());
+        assert_eq!(templated.ini.get_in("World", "Name"), Some("Second"));
+        assert_eq!(templated.ini.get_in("World", "Format"), Some("4"));
+        assert_eq!(templated.ini.get_in("Custom Object 1", "Image"), Some("Spike.png"));
+        assert!(!templated.ini.has_section("x1000y1000"));
+    }
+}
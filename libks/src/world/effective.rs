@@ -0,0 +1,154 @@
+use crate::{
+    common::parse_bool,
+    map_bin::AssetIds,
+    world_ini::{screen_targets, DefaultScope, Defaults, Edge, TargetKind, SLOTS},
+};
+use super::World;
+
+/// A shift as the engine would use it. See [`EffectiveScreen`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveShift {
+    /// The slot, e.g. `A`.
+    pub slot: String,
+    /// `ShiftType`.
+    pub kind: u8,
+    /// The destination screen.
+    pub screen: (i64, i64),
+    /// The destination tile position, or `None` if it's relative to the player.
+    pub position: Option<(i64, i64)>,
+    pub visible: bool,
+    pub effect: bool,
+    pub quantize: bool,
+    pub save: bool,
+    pub hide: bool,
+    pub deny_hologram: bool,
+}
+
+/// Everything the engine uses to set up a screen, with defaults filled in. See
+/// [`World::effective_screen`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveScreen {
+    pub position: (i64, i64),
+    /// The tilesets, ambiance, music, and gradient from Map.bin.
+    pub assets: AssetIds,
+    /// Where leaving through each edge leads, in the order of [`Edge::ALL`].
+    pub exits: [(Edge, (i64, i64)); 4],
+    /// The shifts in slots A, B, and C. Every slot is listed, even if the screen doesn't have a
+    /// shift object for it.
+    pub shifts: Vec<EffectiveShift>,
+}
+
+impl EffectiveScreen {
+    /// Returns where leaving through `edge` leads.
+    pub fn exit(&self, edge: Edge) -> (i64, i64) {
+        self.exits.iter()
+            .find(|(exit_edge, _)| *exit_edge == edge)
+            .map(|&(_, screen)| screen)
+            .expect("every edge should have an exit")
+    }
+}
+
+impl World {
+    /// Combines the Map.bin data for the screen at `position` with its World.ini section and
+    /// `defaults`, or returns `None` if Map.bin has no such screen.
+    ///
+    /// Without a warp, leaving through an edge is presumed to lead to the adjacent screen.
+    /// Values that can't be parsed are replaced by their defaults; see [`targets`] for how
+    /// shift and warp destinations are read.
+    ///
+    /// [`targets`]: crate::world_ini::targets
+    pub fn effective_screen(&self, position: (i64, i64), defaults: &Defaults) -> Option<EffectiveScreen> {
+        let screen = self.screen(position)?;
+        let section = self.ini.section(&format!("x{}y{}", position.0, position.1));
+        let targets = screen_targets(&self.ini, position);
+
+        let exits = Edge::ALL.map(|edge| {
+            let warp = targets.iter()
+                .find(|target| target.kind == TargetKind::Warp(edge))
+                .map(|target| target.screen);
+            let adjacent = match edge {
+                Edge::Up => (position.0, position.1 - 1),
+                Edge::Down => (position.0, position.1 + 1),
+                Edge::Left => (position.0 - 1, position.1),
+                Edge::Right => (position.0 + 1, position.1),
+            };
+            (edge, warp.unwrap_or(adjacent))
+        });
+
+        let shifts = SLOTS.into_iter()
+            .map(|slot| {
+                let get = |name: &str| {
+                    let key = format!("{name}({slot})");
+                    (section.as_ref().and_then(|section| section.get(&key)), key)
+                };
+                let flag = |name: &str| {
+                    let (value, key) = get(name);
+                    defaults.resolve_bool(DefaultScope::Screen, &key, value.and_then(parse_bool))
+                        .unwrap_or(false)
+                };
+                let (kind_value, kind_key) = get("ShiftType");
+
+                let target = targets.iter()
+                    .find(|target| target.kind == TargetKind::Shift(slot.to_owned()));
+
+                EffectiveShift {
+                    slot: slot.to_owned(),
+                    kind: defaults.resolve(DefaultScope::Screen, &kind_key, kind_value.and_then(|value| value.trim().parse().ok()))
+                        .unwrap_or(0),
+                    screen: target.map_or(position, |target| target.screen),
+                    position: target.and_then(|target| target.position),
+                    visible: flag("ShiftVisible"),
+                    effect: flag("ShiftEffect"),
+                    quantize: flag("ShiftQuantize"),
+                    save: flag("ShiftSave"),
+                    hide: flag("ShiftHide"),
+                    deny_hologram: flag("ShiftDenyHologram"),
+                }
+            })
+            .collect();
+
+        Some(EffectiveScreen {
+            position,
+            assets: screen.assets,
+            exits,
+            shifts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+    use crate::{
+        constants::{LAYER_COUNT, TILES_PER_LAYER},
+        editions::KsEdition,
+        map_bin::{LayerData, ScreenData, Tile},
+        world_ini::defaults,
+    };
+
+    #[test]
+    fn screen_settings_fall_back_to_defaults() {
+        let screen = ScreenData {
+            position: (1000, 1000),
+            layers: std::array::from_fn::<_, LAYER_COUNT, _>(|_| LayerData([Tile(0, 0); TILES_PER_LAYER])),
+            assets: AssetIds { tileset_a: 1, tileset_b: 2, ambiance_a: 0, ambiance_b: 0, music: 7, gradient: 1 },
+        };
+        let world = World {
+            dir: std::env::temp_dir(),
+            ini: Ini::new("[x1000y1000]\nShiftVisible(A)=False\nShiftType(A)=2\nShiftXMap(A)=1\nWarpUpY=-3\nShiftEffect(B)=maybe\n"),
+            screens: vec![screen],
+        };
+
+        let effective = world.effective_screen((1000, 1000), &defaults(&KsEdition::Vanilla)).unwrap();
+        assert_eq!(effective.assets.music, 7);
+        assert_eq!(effective.exit(Edge::Up), (1000, 997));
+        assert_eq!(effective.exit(Edge::Left), (999, 1000));
+
+        let [a, b, _] = &effective.shifts[..] else { panic!("expected three shifts") };
+        assert_eq!((a.kind, a.visible, a.effect, a.screen), (2, false, true, (1001, 1000)));
+        assert_eq!((b.kind, b.visible, b.effect, b.screen), (0, true, true, (1000, 1000)));
+        assert!(world.effective_screen((0, 0), &defaults(&KsEdition::Vanilla)).is_none());
+    }
+}
@@ -0,0 +1,13 @@
+use std::path::PathBuf;
+
+#[derive(thiserror::Error, Debug)]
+pub enum WorldError {
+    #[error("The level at {} is locked by another tool{}.", dir.display(), holder.as_ref().map(|holder| format!(" ({holder})")).unwrap_or_default())]
+    Locked {
+        dir: PathBuf,
+        /// The contents of the lock file, which identify the process holding it, if readable.
+        holder: Option<String>,
+    },
+    #[error("A level already exists at {}.", .0.display())]
+    AlreadyExists(PathBuf),
+}
@@ -0,0 +1,190 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::ResultExt,
+    KsError,
+    Result,
+};
+use super::{World, WorldError};
+
+/// The name of the lock file created by [`World::lock`].
+pub const LOCK_FILE_NAME: &str = ".libks-lock";
+
+/// An advisory lock on a level's directory. See [`World::lock`].
+///
+/// The lock is released when this is dropped.
+#[derive(Debug)]
+pub struct WorldLock {
+    path: PathBuf,
+}
+
+impl WorldLock {
+    /// Returns the path of the lock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Follows the lock file after its level's directory is renamed to `world_dir`.
+    pub(crate) fn moved_to(&mut self, world_dir: &Path) {
+        self.path = world_dir.join(LOCK_FILE_NAME);
+    }
+}
+
+impl Drop for WorldLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl World {
+    /// Locks the level in `world_dir` so that other libks-based tools know not to write to it,
+    /// e.g. so an installer doesn't replace a level an editor is saving. The lock is held until
+    /// the returned [`WorldLock`] is dropped.
+    ///
+    /// The lock is advisory: it's a file named [`LOCK_FILE_NAME`] in the level's directory that
+    /// records the process ID of its holder. Nothing stops a tool that doesn't check for it, and
+    /// KS itself ignores it. Fails with [`WorldError::Locked`] if the level is already locked.
+    ///
+    /// libks won't replace or uninstall a locked level with [`install`](crate::install), and
+    /// [`World::save`], [`World::save_atomic`], and [`World::rename`] take the lock while they
+    /// write. Saving a level this process has locked is allowed. The lock file is never packed
+    /// into a .knytt.bin. If a tool crashes while holding the lock, the file is left behind and
+    /// the level stays locked until [`World::unlock`] is called.
+    pub fn lock<P>(world_dir: P) -> Result<WorldLock>
+    where
+        P: AsRef<Path>
+    {
+        let dir = world_dir.as_ref();
+        let path = dir.join(LOCK_FILE_NAME);
+
+        let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                return Err(locked_error(dir));
+            },
+            Err(err) => return Err(err).with_path(&path),
+        };
+
+        let lock = WorldLock { path };
+        writeln!(file, "{}", own_holder()).with_path(&lock.path)?;
+
+        Ok(lock)
+    }
+
+    /// Returns whether the level in `world_dir` is locked. See [`World::lock`].
+    pub fn is_locked<P>(world_dir: P) -> bool
+    where
+        P: AsRef<Path>
+    {
+        world_dir.as_ref().join(LOCK_FILE_NAME).exists()
+    }
+
+    /// Removes the lock on the level in `world_dir`, whoever holds it. This is meant for clearing
+    /// a lock left behind by a tool that crashed.
+    pub fn unlock<P>(world_dir: P) -> Result<()>
+    where
+        P: AsRef<Path>
+    {
+        let path = world_dir.as_ref().join(LOCK_FILE_NAME);
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err).with_path(&path),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Locks the level in `world_dir` while libks writes to it. If this process already holds the
+/// lock, e.g. because an editor locked the level before saving it, that lock is used and `None`
+/// is returned. Fails with [`WorldError::Locked`] if another process holds it.
+pub(crate) fn lock_for_write(world_dir: &Path) -> Result<Option<WorldLock>> {
+    if holder(world_dir).is_some_and(|holder| holder == own_holder()) {
+        return Ok(None);
+    }
+
+    World::lock(world_dir).map(Some)
+}
+
+/// Fails with [`WorldError::Locked`] if the level in `world_dir` is locked. Used by the parts of
+/// libks that replace or delete levels.
+pub(crate) fn ensure_unlocked(world_dir: &Path) -> Result<()> {
+    if World::is_locked(world_dir) {
+        return Err(locked_error(world_dir));
+    }
+
+    Ok(())
+}
+
+/// Returns what the lock file in `world_dir` says about its holder, if it exists and isn't empty.
+fn holder(world_dir: &Path) -> Option<String> {
+    fs::read_to_string(world_dir.join(LOCK_FILE_NAME)).ok()
+        .map(|holder| holder.trim().to_owned())
+        .filter(|holder| !holder.is_empty())
+}
+
+/// Returns what [`World::lock`] writes to the lock file for this process.
+fn own_holder() -> String {
+    format!("pid={}", std::process::id())
+}
+
+fn locked_error(world_dir: &Path) -> KsError {
+    WorldError::Locked { dir: world_dir.to_owned(), holder: holder(world_dir) }.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_are_exclusive_until_dropped() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+
+        let lock = World::lock(dir).unwrap();
+        assert!(World::is_locked(dir));
+        let err = World::lock(dir).unwrap_err();
+        assert_eq!(err.code(), 701);
+        assert!(err.to_string().contains(&format!("pid={}", std::process::id())));
+
+        drop(lock);
+        assert!(!World::is_locked(dir));
+        let _lock = World::lock(dir).unwrap();
+    }
+
+    #[test]
+    fn writes_respect_locks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let temp = temp_dir.path();
+        let dir = temp.join("Me - Level");
+        let mut world = World {
+            dir: dir.clone(),
+            ini: libks_ini::Ini::new("[World]\nName=Level\nAuthor=Me\n"),
+            screens: Vec::new(),
+        };
+        world.save().unwrap();
+
+        let lock = World::lock(&dir).unwrap();
+        world.save().unwrap();
+        world.save_atomic().unwrap();
+        assert!(World::is_locked(&dir));
+        assert_eq!(world.rename("Other").unwrap_err().code(), 701);
+
+        let bin_path = temp.join("Level.knytt.bin");
+        crate::knytt_bin::pack(&dir, &bin_path).unwrap();
+        let unpacked = crate::knytt_bin::unpack(&bin_path, temp.join("out")).unwrap();
+        assert!(!unpacked.join(LOCK_FILE_NAME).exists());
+        assert!(unpacked.join("World.ini").exists());
+
+        drop(lock);
+        fs::write(dir.join(LOCK_FILE_NAME), "pid=0").unwrap();
+        assert_eq!(world.save().unwrap_err().code(), 701);
+        assert_eq!(world.save_atomic().unwrap_err().code(), 701);
+
+        World::unlock(&dir).unwrap();
+        world.rename("Other").unwrap();
+        assert!(!World::is_locked(&world.dir));
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+
+use crate::analysis::Difficulty;
+use super::World;
+
+/// The details of a level that a level listing shows. See [`World::metadata`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldMetadata {
+    /// `Name` from the `[World]` section.
+    pub name: Option<String>,
+    /// `Author` from the `[World]` section.
+    pub author: Option<String>,
+    /// `Description` from the `[World]` section.
+    pub description: Option<String>,
+    /// `Size` from the `[World]` section, e.g. `Small`, `Medium`, or `Large`.
+    pub size: Option<String>,
+    /// The recognized tags in `Difficulty A`-`Difficulty C`, in order.
+    pub difficulties: Vec<Difficulty>,
+    /// The nonempty values of `Category A` and `Category B`, e.g. `Tutorial` or `Puzzle`.
+    pub categories: Vec<String>,
+    /// `Format` from the `[World]` section. See [`crate::editions`].
+    pub format: Option<u32>,
+    /// The contents of Icon.png, or `None` if it's missing or can't be read.
+    pub icon: Option<Vec<u8>>,
+}
+
+impl World {
+    /// Gathers the details of the level that a level listing needs from World.ini and
+    /// Icon.png. Empty World.ini values are treated as missing.
+    pub fn metadata(&self) -> WorldMetadata {
+        let get = |key: &str| {
+            self.ini.get_in("World", key)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+
+        let difficulties = ["Difficulty A", "Difficulty B", "Difficulty C"].into_iter()
+            .filter_map(|key| get(key).and_then(Difficulty::parse))
+            .collect();
+        let categories = ["Category A", "Category B"].into_iter()
+            .filter_map(|key| get(key).map(str::to_owned))
+            .collect();
+
+        WorldMetadata {
+            name: get("Name").map(str::to_owned),
+            author: get("Author").map(str::to_owned),
+            description: get("Description").map(str::to_owned),
+            size: get("Size").map(str::to_owned),
+            difficulties,
+            categories,
+            format: get("Format").and_then(|format| format.parse().ok()),
+            icon: fs::read(self.dir.join("Icon.png")).ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libks_ini::Ini;
+
+    use super::*;
+
+    #[test]
+    fn metadata_comes_from_world_section() {
+        let world = World {
+            dir: "Nonexistent - Level".into(),
+            ini: Ini::new("[World]\nName=Level\nAuthor= \nDifficulty A=very hard\nDifficulty B=Nope\nCategory B=Puzzle\nFormat=4\n"),
+            screens: Vec::new(),
+        };
+
+        let metadata = world.metadata();
+        assert_eq!(metadata.name.as_deref(), Some("Level"));
+        assert_eq!(metadata.author, None);
+        assert_eq!(metadata.difficulties, [Difficulty::VeryHard]);
+        assert_eq!(metadata.categories, ["Puzzle"]);
+        assert_eq!(metadata.format, Some(4));
+        assert_eq!(metadata.icon, None);
+    }
+}
@@ -0,0 +1,710 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use libks_ini::Ini;
+
+use crate::{
+    analysis::screen_dependencies,
+    common::parse_xy,
+    error::ResultExt,
+    install::{self, InstallError},
+    issues::Issues,
+    map_bin::{self, MapBinError, PlacedObject, ScreenData, Tile},
+    saves::{default_savegame_path, slot_path, SaveGame},
+    world_ini::{self, Target, TargetKind},
+    Result,
+};
+
+mod create;
+pub use create::{NewWorldOptions, TemplateOptions, STANDARD_DIRS};
+
+mod effective;
+pub use effective::{EffectiveScreen, EffectiveShift};
+
+mod error;
+pub use error::WorldError;
+
+mod lock;
+pub use lock::{WorldLock, LOCK_FILE_NAME};
+pub(crate) use lock::{ensure_unlocked, lock_for_write};
+
+mod metadata;
+pub use metadata::WorldMetadata;
+
+/// An inclusive rectangle of screen positions.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    pub min: (i64, i64),
+    pub max: (i64, i64),
+}
+
+impl Bounds {
+    /// Creates bounds spanning the corners `a` and `b`, in any order.
+    pub fn new(a: (i64, i64), b: (i64, i64)) -> Bounds {
+        Bounds {
+            min: (a.0.min(b.0), a.1.min(b.1)),
+            max: (a.0.max(b.0), a.1.max(b.1)),
+        }
+    }
+
+    /// Returns `true` if `position` lies within the bounds.
+    pub fn contains(&self, position: (i64, i64)) -> bool {
+        (self.min.0..=self.max.0).contains(&position.0)
+            && (self.min.1..=self.max.1).contains(&position.1)
+    }
+}
+
+/// The outcome of [`World::rename`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameReport {
+    /// The level's previous directory.
+    pub old_dir: PathBuf,
+    /// The save slots of the KS installation that still refer to the previous directory name.
+    /// KS can't load these until their `World` property is updated.
+    pub stale_saves: Vec<PathBuf>,
+}
+
+/// The extension of the temporary files written by [`World::save_atomic`].
+const SAVE_TEMP_EXTENSION: &str = "libks-tmp";
+/// The extension given to the previous files by [`World::save_atomic`] until it's done.
+const SAVE_BACKUP_EXTENSION: &str = "libks-bak";
+
+/// Returns `true` if `file_name` is one of the files libks creates inside level directories and
+/// the Worlds directory: locks, [`World::save_atomic`]'s temporary and backup files, and
+/// [`install`]'s staging directories. These aren't part of the level.
+pub(crate) fn is_libks_file(file_name: &str) -> bool {
+    file_name.starts_with(".libks-")
+        || Path::new(file_name).extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| [SAVE_TEMP_EXTENSION, SAVE_BACKUP_EXTENSION].contains(&extension))
+}
+
+/// Makes `backup` a copy of the file at `path` without touching `path`, replacing any previous
+/// backup. A hard link is used if possible.
+fn back_up(path: &Path, backup: &Path) -> Result<()> {
+    match fs::remove_file(backup) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err).with_path(backup),
+        _ => (),
+    }
+    if fs::hard_link(path, backup).is_err() {
+        fs::copy(path, backup).with_path(path)?;
+    }
+
+    Ok(())
+}
+
+/// A level loaded into memory: its directory, World.ini, and Map.bin screens.
+#[derive(Clone)]
+pub struct World {
+    /// The directory containing the level's files.
+    pub dir: PathBuf,
+    /// The parsed contents of World.ini.
+    pub ini: Ini,
+    /// The screens parsed from Map.bin.
+    pub screens: Vec<ScreenData>,
+}
+
+impl World {
+    /// Loads World.ini and Map.bin from the level in `world_dir`.
+    pub fn load<P>(world_dir: P) -> Result<World>
+    where
+        P: AsRef<Path>
+    {
+        let dir = world_dir.as_ref().to_owned();
+        let ini = world_ini::load_ini_from_dir(&dir)?;
+        let screens = map_bin::parse_map_file(dir.join("Map.bin"))?;
+
+        Ok(World {
+            dir,
+            ini,
+            screens,
+        })
+    }
+
+    /// Loads World.ini and Map.bin from the level in `world_dir`, tolerating problems the way KS
+    /// does.
+    /// 
+    /// This variant never fails. Problems are recorded in `issues` instead, and whatever could
+    /// be read is kept. See [`world_ini::load_ini_with_issues`] and
+    /// [`map_bin::parse_map_file_with_issues`].
+    pub fn load_with_issues<P>(world_dir: P, issues: &mut Issues) -> World
+    where
+        P: AsRef<Path>
+    {
+        let dir = world_dir.as_ref().to_owned();
+        let ini = world_ini::load_ini_with_issues(dir.join("World.ini"), issues);
+        let screens = map_bin::parse_map_file_with_issues(dir.join("Map.bin"), issues);
+
+        World {
+            dir,
+            ini,
+            screens,
+        }
+    }
+
+    /// Returns the name of the level's directory. KS uses this to identify the level,
+    /// e.g. in save files. By convention, it has the form `Author - Level Name`.
+    pub fn dir_name(&self) -> Option<&str> {
+        self.dir.file_name()
+            .and_then(|name| name.to_str())
+    }
+
+    /// Returns the screen at `position`, if it exists.
+    pub fn screen(&self, position: (i64, i64)) -> Option<&ScreenData> {
+        self.screens.iter()
+            .find(|screen| screen.position == position)
+    }
+
+    /// Returns the screen at `position`, if it exists.
+    pub fn screen_mut(&mut self, position: (i64, i64)) -> Option<&mut ScreenData> {
+        self.screens.iter_mut()
+            .find(|screen| screen.position == position)
+    }
+
+    /// Iterates over every object placed in the level, along with the position of its screen.
+    /// See [`ScreenData::objects`].
+    pub fn iter_objects(&self) -> impl Iterator<Item = ((i64, i64), PlacedObject)> + '_ {
+        self.screens.iter()
+            .flat_map(|screen| screen.objects().map(|object| (screen.position, object)))
+    }
+
+    /// Lists the screens that contain the object `tile`, with the number of instances on each.
+    /// These are the screens [`World::replace_objects`] would change, so this can be used as a
+    /// dry run.
+    pub fn find_objects(&self, tile: Tile) -> Vec<((i64, i64), usize)> {
+        self.screens.iter()
+            .map(|screen| (screen.position, screen.objects().filter(|object| object.tile == tile).count()))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    /// Replaces every instance of the object `from` with `to` on every object layer of every
+    /// screen. Returns the number of objects replaced.
+    ///
+    /// Empty cells (object index 0) are never replaced, so `from` can't be used to fill a level
+    /// with objects. Replacing with an empty object removes the instances.
+    pub fn replace_objects(&mut self, from: Tile, to: Tile) -> usize {
+        if from.1 == 0 {
+            return 0;
+        }
+
+        let mut count = 0;
+        for screen in &mut self.screens {
+            for layer in &mut screen.layers[4..] {
+                for tile in layer.0.iter_mut().filter(|tile| **tile == from) {
+                    *tile = to;
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Renames the level to `new_name`: `Name` is set in World.ini and the directory is renamed
+    /// according to [`install::canonical_folder_name`]. World.ini is written, but Map.bin is
+    /// left alone.
+    ///
+    /// DefaultSavegame.ini is updated to refer to the new directory. Save slots can't be updated
+    /// safely, since KS might be using them, so those that refer to the old directory are
+    /// reported instead. They're found by assuming the level is installed in the `Worlds`
+    /// directory of a KS installation. Packing the level afterward uses the new directory name.
+    ///
+    /// The level is locked while it's renamed (see [`World::lock`]). Unlike [`World::save`],
+    /// this fails with [`WorldError::Locked`] even if this process holds the lock, since the lock
+    /// file would move with the directory.
+    pub fn rename(&mut self, new_name: &str) -> Result<RenameReport> {
+        let author = self.ini.get_in("World", "Author").unwrap_or_default();
+        let Some(dir_name) = install::canonical_folder_name(author, new_name) else {
+            return Err(InstallError::InvalidName(new_name.to_owned()).into());
+        };
+
+        let mut lock = World::lock(&self.dir)?;
+        let new_dir = self.dir.with_file_name(&dir_name);
+        if new_dir != self.dir {
+            if new_dir.exists() {
+                return Err(InstallError::AlreadyInstalled(new_dir).into());
+            }
+            fs::rename(&self.dir, &new_dir)?;
+            lock.moved_to(&new_dir);
+        }
+        let old_dir = std::mem::replace(&mut self.dir, new_dir);
+        let old_dir_name = old_dir.file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+
+        self.ini.set_in("World", "Name", new_name.to_owned());
+        world_ini::write_ini(self.dir.join("World.ini"), &self.ini)?;
+
+        let default_path = default_savegame_path(&self.dir);
+        if default_path.is_file() {
+            let mut save = SaveGame::load(&default_path)?;
+            if save.world() == Some(old_dir_name) {
+                save.set_world(&dir_name);
+                save.write(&default_path)?;
+            }
+        }
+
+        let mut stale_saves = Vec::new();
+        if let Some(ks_dir) = self.dir.parent().and_then(|worlds| worlds.parent()) {
+            for slot in 1..=3 {
+                let path = slot_path(ks_dir, slot);
+                let refers_to_old = SaveGame::load(&path)
+                    .is_ok_and(|save| save.world() == Some(old_dir_name));
+                if refers_to_old {
+                    stale_saves.push(path);
+                }
+            }
+        }
+
+        Ok(RenameReport {
+            old_dir,
+            stale_saves,
+        })
+    }
+
+    /// Moves the screen at `from` to `to`, renaming its World.ini section and rewriting every
+    /// warp, shift, and flag warp that leads to or from it so that they still lead to the same
+    /// screens. The changes are made in memory; call [`World::save`] to write them.
+    ///
+    /// Returns the references that were rewritten, as they were before the move. See
+    /// [`world_ini::targets`]. DefaultSavegame.ini isn't updated; use [`SaveGame`] if the level
+    /// starts on the moved screen.
+    pub fn move_screen(&mut self, from: (i64, i64), to: (i64, i64)) -> Result<Vec<Target>> {
+        if self.screen(from).is_none() {
+            return Err(MapBinError::NoScreen { position: from }.into());
+        }
+        if from == to {
+            return Ok(Vec::new());
+        }
+        let to_key = format!("x{}y{}", to.0, to.1);
+        if self.screen(to).is_some() || self.ini.has_section(&to_key) {
+            return Err(MapBinError::ScreenExists { position: to }.into());
+        }
+
+        let moved = |position: (i64, i64)| if position == from { to } else { position };
+        let mut rewritten = Vec::new();
+
+        for target in world_ini::targets(&self.ini) {
+            let (source, dest) = (moved(target.source), moved(target.screen));
+            if source == target.source && dest == target.screen {
+                continue;
+            }
+
+            let (x_key, y_key, values) = match &target.kind {
+                TargetKind::Warp(edge) => {
+                    let edge = edge.as_str();
+                    (format!("Warp{edge}X"), format!("Warp{edge}Y"), (dest.0 - source.0, dest.1 - source.1))
+                },
+                TargetKind::FlagWarp(slot) =>
+                    (format!("FlagWarpX({slot})"), format!("FlagWarpY({slot})"), (dest.0 - source.0, dest.1 - source.1)),
+                TargetKind::Shift(slot) if target.position.is_some() =>
+                    (format!("ShiftXMap({slot})"), format!("ShiftYMap({slot})"), dest),
+                TargetKind::Shift(slot) =>
+                    (format!("ShiftXMap({slot})"), format!("ShiftYMap({slot})"), (dest.0 - source.0, dest.1 - source.1)),
+                TargetKind::TriggerSpawn(_) => continue,
+            };
+
+            let section = format!("x{}y{}", target.source.0, target.source.1);
+            for (key, value) in [(x_key, values.0), (y_key, values.1)] {
+                if value != 0 || self.ini.has_in(&section, &key) {
+                    self.ini.set_in(&section, &key, value.to_string());
+                }
+            }
+            rewritten.push(target);
+        }
+
+        self.ini.rename_section(&format!("x{}y{}", from.0, from.1), &to_key);
+        if let Some(screen) = self.screen_mut(from) {
+            screen.position = to;
+        }
+
+        Ok(rewritten)
+    }
+
+    /// Moves every screen of the level by `(dx, dy)`, renaming the World.ini sections to match.
+    /// Warps, flag warps, and relative shifts are offsets, so they're unaffected; absolute shifts
+    /// are rewritten. The changes are made in memory; call [`World::save`] to write them.
+    ///
+    /// Nothing is changed if a screen or section would be moved out of range. DefaultSavegame.ini
+    /// isn't updated; use [`SaveGame`] to move the start screen too.
+    pub fn translate(&mut self, dx: i64, dy: i64) -> Result<()> {
+        let offset = (dx, dy);
+        let translated = |(x, y): (i64, i64)| -> Result<(i64, i64)> {
+            x.checked_add(dx)
+                .zip(y.checked_add(dy))
+                .ok_or_else(|| MapBinError::MoveOutOfRange { position: (x, y), offset }.into())
+        };
+
+        let mut sections: Vec<((i64, i64), String)> = Vec::new();
+        for section in self.ini.iter_sections() {
+            let key = section.key().to_ascii_lowercase();
+            if let Some(position) = parse_xy(&key) {
+                if !sections.iter().any(|(_, existing)| *existing == key) {
+                    sections.push((position, key));
+                }
+            }
+        }
+
+        // Check everything before changing anything
+        let targets: Vec<Target> = world_ini::targets(&self.ini).into_iter()
+            .filter(|target| matches!(target.kind, TargetKind::Shift(_)) && target.position.is_some())
+            .collect();
+        let positions = self.screens.iter().map(|screen| screen.position)
+            .chain(sections.iter().map(|(position, _)| *position))
+            .chain(targets.iter().map(|target| target.screen));
+        for position in positions {
+            translated(position)?;
+        }
+
+        for target in targets {
+            let TargetKind::Shift(slot) = &target.kind else { continue };
+            let section = format!("x{}y{}", target.source.0, target.source.1);
+            let (x, y) = translated(target.screen)?;
+            self.ini.set_in(&section, &format!("ShiftXMap({slot})"), x.to_string());
+            self.ini.set_in(&section, &format!("ShiftYMap({slot})"), y.to_string());
+        }
+
+        // Rename in two passes so that no section is renamed onto one that hasn't moved yet
+        for (i, (_, key)) in sections.iter().enumerate() {
+            self.ini.rename_section(key, &format!("\0translate{i}"));
+        }
+        for (i, (position, _)) in sections.iter().enumerate() {
+            let (x, y) = translated(*position)?;
+            self.ini.rename_section(&format!("\0translate{i}"), &format!("x{x}y{y}"));
+        }
+
+        for screen in &mut self.screens {
+            screen.position = translated(screen.position)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes World.ini and Map.bin to the level's directory.
+    ///
+    /// The level is locked while it's written (see [`World::lock`]). Fails with
+    /// [`WorldError::Locked`] if another process holds the lock.
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let _lock = lock_for_write(&self.dir)?;
+        world_ini::write_ini(self.dir.join("World.ini"), &self.ini)?;
+        map_bin::write_map_file(self.dir.join("Map.bin"), &self.screens)?;
+
+        Ok(())
+    }
+
+    /// Writes World.ini and Map.bin to the level's directory without ever leaving either of them
+    /// missing or half-written, even if the process is killed partway through.
+    ///
+    /// Both files are first written in full to temporary files next to them. Each existing file
+    /// is then hard-linked (or copied, if that isn't supported) to a backup with a `.libks-bak`
+    /// extension, and the temporary file is renamed over it in one step. If a rename fails, the
+    /// files that were already replaced are restored from their backups. A crash between the two
+    /// renames can still leave the new World.ini alongside the old Map.bin, but both files are
+    /// always there and whole, and the backups are left behind.
+    ///
+    /// The level is locked while it's written, like with [`World::save`].
+    pub fn save_atomic(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let _lock = lock_for_write(&self.dir)?;
+        let files = [self.dir.join("World.ini"), self.dir.join("Map.bin")];
+        let temps = files.clone().map(|path| path.with_extension(SAVE_TEMP_EXTENSION));
+        let backups = files.clone().map(|path| path.with_extension(SAVE_BACKUP_EXTENSION));
+
+        let remove_temps = || {
+            for temp in &temps {
+                let _ = fs::remove_file(temp);
+            }
+        };
+
+        let written = world_ini::write_ini(&temps[0], &self.ini)
+            .and_then(|_| map_bin::write_map_file(&temps[1], &self.screens))
+            .and_then(|_| {
+                for temp in &temps {
+                    fs::File::open(temp).and_then(|file| file.sync_all()).with_path(temp)?;
+                }
+                Ok(())
+            })
+            .and_then(|_| {
+                for (path, backup) in files.iter().zip(&backups) {
+                    if path.exists() {
+                        back_up(path, backup)?;
+                    }
+                }
+                Ok(())
+            });
+        if let Err(err) = written {
+            remove_temps();
+            return Err(err);
+        }
+
+        for (i, (path, temp)) in files.iter().zip(&temps).enumerate() {
+            if let Err(err) = fs::rename(temp, path).with_path(temp) {
+                // Put back whatever was already replaced
+                for (path, backup) in files[..i].iter().zip(&backups) {
+                    if backup.exists() {
+                        let _ = fs::rename(backup, path);
+                    }
+                }
+                remove_temps();
+                return Err(err);
+            }
+        }
+
+        for backup in &backups {
+            let _ = fs::remove_file(backup);
+        }
+
+        Ok(())
+    }
+
+    /// Creates a standalone level in `dest_dir` containing only the screens within `bounds`.
+    ///
+    /// The new World.ini keeps every section that doesn't belong to a screen (`[World]`, custom
+    /// objects, etc.) and the sections of the extracted screens. The assets those screens need
+    /// are copied if the level provides them; assets from the KS Data directory are left alone.
+    /// Icon.png, Info.png, and DefaultSavegame.ini are copied too. If the saved start screen is
+    /// outside `bounds`, the player starts on the first extracted screen instead.
+    pub fn extract_region<P>(&self, bounds: Bounds, dest_dir: P) -> Result<World>
+    where
+        P: AsRef<Path>
+    {
+        let dest_dir = dest_dir.as_ref().to_owned();
+        fs::create_dir_all(&dest_dir)?;
+
+        let screens: Vec<ScreenData> = self.screens.iter()
+            .filter(|screen| bounds.contains(screen.position))
+            .cloned()
+            .collect();
+
+        let mut ini = self.ini.clone();
+        let excluded: Vec<String> = ini.iter_sections()
+            .filter(|section| {
+                section.kind().screen()
+                    .is_some_and(|position| !bounds.contains(position))
+            })
+            .map(|section| section.key().to_owned())
+            .collect();
+        for key in excluded {
+            ini.remove_section(&key);
+        }
+
+        // Copy the assets the level provides
+        let mut files: Vec<PathBuf> = screens.iter()
+            .filter_map(|screen| screen_dependencies(self, screen.position))
+            .flat_map(|deps| deps.files().cloned().collect::<Vec<_>>())
+            .collect();
+        files.sort();
+        files.dedup();
+        files.extend(["Icon.png", "Info.png"].map(PathBuf::from));
+
+        for file in files {
+            let source = self.dir.join(&file);
+            if source.is_file() {
+                let dest = dest_dir.join(&file);
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(source, dest)?;
+            }
+        }
+
+        if default_savegame_path(&self.dir).is_file() {
+            let mut save = SaveGame::load_default(&self.dir)?;
+            let start_outside = save.screen()
+                .is_none_or(|screen| !bounds.contains(screen));
+            if let (true, Some(first)) = (start_outside, screens.first()) {
+                save.set_screen(first.position);
+            }
+            save.write(default_savegame_path(&dest_dir))?;
+        }
+
+        let world = World {
+            dir: dest_dir,
+            ini,
+            screens,
+        };
+        world.save()?;
+
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rename_updates_directory_and_saves() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let ks_dir = temp_dir.path();
+        let dir = ks_dir.join("Worlds").join("Me - Old");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("World.ini"), "[World]\nName=Old\nAuthor=Me\n").unwrap();
+        SaveGame::new("Me - Old", (1000, 1000), (0, 0)).write(default_savegame_path(&dir)).unwrap();
+        fs::create_dir_all(ks_dir.join("Saves")).unwrap();
+        SaveGame::new("Me - Old", (1000, 1000), (0, 0)).write(slot_path(ks_dir, 2)).unwrap();
+
+        let mut world = World {
+            dir,
+            ini: world_ini::load_ini_from_dir(ks_dir.join("Worlds/Me - Old")).unwrap(),
+            screens: Vec::new(),
+        };
+        let report = world.rename("New").unwrap();
+
+        assert_eq!(world.dir, ks_dir.join("Worlds/Me - New"));
+        assert!(!report.old_dir.exists());
+        assert_eq!(report.stale_saves, [slot_path(ks_dir, 2)]);
+        assert_eq!(world_ini::load_ini_from_dir(&world.dir).unwrap().get_in("World", "Name"), Some("New"));
+        assert_eq!(SaveGame::load_default(&world.dir).unwrap().world(), Some("Me - New"));
+    }
+
+    #[test]
+    fn move_screen_rewrites_references() {
+        let blank = |position| map_bin::parse_screen_bytes(&[0; map_bin::SCREEN_DATA_LEN], position);
+        let mut world = World {
+            dir: "Test - Level".into(),
+            ini: Ini::new("\
+[x1000y1000]
+WarpUpY=-1
+ShiftXMap(A)=5
+[X1000Y999]
+WarpDownY=1
+ShiftAbsolute(A)=True
+ShiftXMap(A)=1000
+ShiftYMap(A)=999
+"),
+            screens: vec![blank((1000, 1000)), blank((1000, 999))],
+        };
+
+        let rewritten = world.move_screen((1000, 999), (1010, 990)).unwrap();
+        assert_eq!(rewritten.len(), 3);
+        assert_eq!(world.screen((1010, 990)).map(|screen| screen.position), Some((1010, 990)));
+
+        // References to the moved screen
+        assert_eq!(world.ini.get_in("x1000y1000", "WarpUpX"), Some("10"));
+        assert_eq!(world.ini.get_in("x1000y1000", "WarpUpY"), Some("-10"));
+        assert_eq!(world.ini.get_in("x1000y1000", "ShiftXMap(A)"), Some("5"));
+
+        // References from the moved screen
+        assert_eq!(world.ini.get_in("x1010y990", "WarpDownX"), Some("-10"));
+        assert_eq!(world.ini.get_in("x1010y990", "WarpDownY"), Some("10"));
+        assert_eq!(world.ini.get_in("x1010y990", "ShiftXMap(A)"), Some("1010"));
+        assert_eq!(world.ini.get_in("x1010y990", "ShiftYMap(A)"), Some("990"));
+        assert!(!world.ini.has_section("x1000y999"));
+
+        assert!(world.move_screen((1000, 1000), (1010, 990)).is_err());
+    }
+
+    #[test]
+    fn translate_moves_screens_and_absolute_shifts() {
+        let blank = |position| map_bin::parse_screen_bytes(&[0; map_bin::SCREEN_DATA_LEN], position);
+        let mut world = World {
+            dir: "Test - Level".into(),
+            ini: Ini::new("[x1000y1000]\nWarpUpY=-1\n[x1001y1000]\nShiftAbsolute(A)=True\nShiftXMap(A)=1000\nShiftYMap(A)=1000\n"),
+            screens: vec![blank((1000, 1000)), blank((1001, 1000))],
+        };
+
+        world.translate(1, 0).unwrap();
+        assert_eq!(world.screens[0].position, (1001, 1000));
+        assert_eq!(world.screens[1].position, (1002, 1000));
+        assert_eq!(world.ini.get_in("x1001y1000", "WarpUpY"), Some("-1"));
+        assert_eq!(world.ini.get_in("x1002y1000", "ShiftXMap(A)"), Some("1001"));
+        assert!(!world.ini.has_in("x1001y1000", "ShiftXMap(A)"));
+
+        assert!(world.translate(i64::MAX, 0).is_err());
+        assert_eq!(world.screens[0].position, (1001, 1000));
+    }
+
+    #[test]
+    fn replace_objects_swaps_every_instance() {
+        let mut screens = [(1000, 1000), (1001, 1000)]
+            .map(|position| map_bin::parse_screen_bytes(&[0; map_bin::SCREEN_DATA_LEN], position));
+        screens[0].layers[4].0[0] = Tile(0, 12);
+        screens[0].layers[7].0[5] = Tile(0, 12);
+        screens[0].layers[0].0[0] = Tile(0, 12);
+        screens[1].layers[5].0[3] = Tile(1, 12);
+        let mut world = World {
+            dir: "Test - Level".into(),
+            ini: Ini::new(""),
+            screens: screens.into(),
+        };
+
+        assert_eq!(world.find_objects(Tile(0, 12)), [((1000, 1000), 2)]);
+        assert_eq!(world.replace_objects(Tile(0, 12), Tile(2, 3)), 2);
+        assert_eq!(world.screens[0].layers[7].0[5], Tile(2, 3));
+        assert_eq!(world.screens[0].layers[0].0[0], Tile(0, 12));
+        assert_eq!(world.replace_objects(Tile(0, 0), Tile(2, 3)), 0);
+    }
+
+    #[test]
+    fn extract_region_copies_screens_and_assets() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let src_dir = temp_dir.path().join("Me - Level");
+        let mut screens = [(1000, 1000), (1001, 1000), (1005, 1000)]
+            .map(|position| map_bin::parse_screen_bytes(&[0; map_bin::SCREEN_DATA_LEN], position));
+        screens[1].assets.tileset_a = 3;
+        screens[2].assets.tileset_a = 5;
+        let world = World {
+            dir: src_dir.clone(),
+            ini: Ini::new("[World]\nName=Level\n[x1000y1000]\nTint=Red\n[x1005y1000]\nTint=Blue\n"),
+            screens: screens.into(),
+        };
+        world.save().unwrap();
+        for file in ["Tilesets/Tileset3.png", "Tilesets/Tileset5.png", "Icon.png"] {
+            fs::create_dir_all(src_dir.join(file).parent().unwrap()).unwrap();
+            fs::write(src_dir.join(file), file).unwrap();
+        }
+        SaveGame::new("Me - Level", (1005, 1000), (3, 4)).write(default_savegame_path(&src_dir)).unwrap();
+
+        let dest_dir = temp_dir.path().join("Me - Part");
+        let extracted = world.extract_region(Bounds::new((1001, 1001), (1000, 999)), &dest_dir).unwrap();
+        let positions: Vec<_> = extracted.screens.iter().map(|screen| screen.position).collect();
+        assert_eq!(positions, [(1000, 1000), (1001, 1000)]);
+
+        let loaded = World::load(&dest_dir).unwrap();
+        assert_eq!(loaded.screens, extracted.screens);
+        assert_eq!(loaded.ini.get_in("World", "Name"), Some("Level"));
+        assert_eq!(loaded.ini.get_in("x1000y1000", "Tint"), Some("Red"));
+        assert!(!loaded.ini.has_section("x1005y1000"));
+
+        assert_eq!(fs::read_to_string(dest_dir.join("Tilesets/Tileset3.png")).unwrap(), "Tilesets/Tileset3.png");
+        assert!(dest_dir.join("Icon.png").is_file());
+        assert!(!dest_dir.join("Tilesets/Tileset5.png").exists());
+        assert!(!dest_dir.join("Info.png").exists());
+
+        // The start screen was cut off, so the player starts on the first extracted screen
+        assert_eq!(SaveGame::load_default(&dest_dir).unwrap().screen(), Some((1000, 1000)));
+    }
+
+    #[test]
+    fn save_atomic_replaces_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let mut world = World {
+            dir: dir.to_path_buf(),
+            ini: Ini::new("[World]\nName=Old\n"),
+            screens: vec![map_bin::parse_screen_bytes(&[0; map_bin::SCREEN_DATA_LEN], (1000, 1000))],
+        };
+        world.save_atomic().unwrap();
+
+        // A backup left behind by a crash is replaced
+        fs::write(dir.join("World.libks-bak"), "[World]\nName=Stale\n").unwrap();
+        world.ini.set_in("World", "Name", "New".to_owned());
+        world.screens[0].position = (1001, 1000);
+        world.save_atomic().unwrap();
+
+        let loaded = World::load(dir).unwrap();
+        assert_eq!(loaded.ini.get_in("World", "Name"), Some("New"));
+        assert_eq!(loaded.screens[0].position, (1001, 1000));
+        let mut names: Vec<_> = fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["Map.bin", "World.ini"]);
+    }
+}
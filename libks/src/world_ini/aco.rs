@@ -0,0 +1,127 @@
+use libks_ini::Ini;
+
+use crate::common::parse_bool;
+use super::{DefaultScope, Defaults};
+
+/// The KS ACO properties of a `[Custom Object #]` section.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AcoObject {
+    /// `Does kill`: whether touching the object kills the player.
+    pub does_kill: Option<bool>,
+    /// `Type`. What its values mean hasn't been documented, so it's kept as written.
+    pub kind: Option<String>,
+}
+
+/// The KS ACO properties of a screen section.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AcoScreen {
+    /// `WarpSave`: presumed to save the game when the player warps into the screen.
+    pub warp_save: Option<bool>,
+}
+
+impl AcoObject {
+    /// Reads the KS ACO properties of custom object `index`. Booleans that can't be parsed are
+    /// read as `None`.
+    pub fn read(ini: &Ini, index: u8) -> Self {
+        let section = object_section(index);
+        Self {
+            does_kill: ini.get_in(&section, "Does kill").and_then(parse_bool),
+            kind: ini.get_in(&section, "Type").map(str::to_owned),
+        }
+    }
+
+    /// Writes the properties back to the section for custom object `index`. See [`write_bool`]
+    /// for how existing values are treated.
+    pub fn write(&self, ini: &mut Ini, index: u8) {
+        let section = object_section(index);
+        write_bool(ini, &section, "Does kill", self.does_kill);
+        match &self.kind {
+            Some(kind) if ini.get_in(&section, "Type") != Some(kind) => {
+                ini.set_in(&section, "Type", kind.clone());
+            },
+            Some(_) => {},
+            None => ini.remove_in(&section, "Type"),
+        }
+    }
+
+    /// Returns a copy with unset properties replaced by their defaults. See [`Defaults`].
+    pub fn resolve(&self, defaults: &Defaults) -> Self {
+        Self {
+            does_kill: defaults.resolve_bool(DefaultScope::CustomObject, "Does kill", self.does_kill),
+            kind: self.kind.clone(),
+        }
+    }
+}
+
+impl AcoScreen {
+    /// Reads the KS ACO properties of the screen at `screen`. Booleans that can't be parsed are
+    /// read as `None`.
+    pub fn read(ini: &Ini, screen: (i64, i64)) -> Self {
+        let section = screen_section(screen);
+        Self {
+            warp_save: ini.get_in(&section, "WarpSave").and_then(parse_bool),
+        }
+    }
+
+    /// Writes the properties back to the section for `screen`. See [`write_bool`] for how
+    /// existing values are treated.
+    pub fn write(&self, ini: &mut Ini, screen: (i64, i64)) {
+        write_bool(ini, &screen_section(screen), "WarpSave", self.warp_save);
+    }
+
+    /// Returns a copy with unset properties replaced by their defaults. See [`Defaults`].
+    pub fn resolve(&self, defaults: &Defaults) -> Self {
+        Self {
+            warp_save: defaults.resolve_bool(DefaultScope::Screen, "WarpSave", self.warp_save),
+        }
+    }
+}
+
+fn object_section(index: u8) -> String {
+    format!("Custom Object {index}")
+}
+
+fn screen_section(screen: (i64, i64)) -> String {
+    format!("x{}y{}", screen.0, screen.1)
+}
+
+/// Sets `key` to `value`, leaving it as written if it already means the same thing (e.g. `1` for
+/// `true`). `None` removes the key unless its value can't be parsed, so that values read as
+/// `None` survive a round trip.
+fn write_bool(ini: &mut Ini, section: &str, key: &str, value: Option<bool>) {
+    let existing = ini.get_in(section, key);
+    match value {
+        Some(value) if existing.and_then(parse_bool) != Some(value) => {
+            let value = if value { "True" } else { "False" };
+            ini.set_in(section, key, value.to_owned());
+        },
+        Some(_) => {},
+        None if existing.and_then(parse_bool).is_some() => ini.remove_in(section, key),
+        None => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aco_properties_round_trip() {
+        let source = "[Custom Object 1]\nDoes kill=1\nType=3\n[Custom Object 2]\nDoes kill=maybe\n[x1000y1000]\nWarpSave=True\n";
+        let mut ini = Ini::new(source);
+
+        let object = AcoObject::read(&ini, 1);
+        assert_eq!(object, AcoObject { does_kill: Some(true), kind: Some("3".to_owned()) });
+        assert_eq!(AcoObject::read(&ini, 2).does_kill, None);
+        assert_eq!(AcoScreen::read(&ini, (1000, 1000)).warp_save, Some(true));
+
+        object.write(&mut ini, 1);
+        AcoObject::read(&ini, 2).write(&mut ini, 2);
+        assert_eq!(ini.to_string(), source);
+
+        AcoScreen { warp_save: Some(false) }.write(&mut ini, (1000, 1000));
+        assert_eq!(ini.get_in("x1000y1000", "WarpSave"), Some("False"));
+    }
+}
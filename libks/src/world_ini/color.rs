@@ -0,0 +1,32 @@
+/// A color as written in World.ini: a decimal number in the Windows `COLORREF` layout, with red
+/// in the lowest byte and blue in the third, e.g. `255` for red and `16711680` for blue.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub fn new(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    /// Parses a value like `16711680`, ignoring surrounding whitespace. Returns `None` if it
+    /// isn't a number from 0 to `0xFFFFFF`.
+    pub fn parse(value: &str) -> Option<Color> {
+        let value: u32 = value.trim().parse().ok()?;
+        if value > 0xFFFFFF {
+            return None;
+        }
+
+        let [r, g, b, _] = value.to_le_bytes();
+        Some(Color { r, g, b })
+    }
+
+    /// Returns the number that [`Color::parse`] reads as this color.
+    pub fn value(self) -> u32 {
+        u32::from_le_bytes([self.r, self.g, self.b, 0])
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+
+use libks_ini::Ini;
+
+use crate::common::parse_bool;
+use super::Color;
+
+/// The `[Cutscene Color]` section added by KS Plus, which overrides the color used for
+/// cutscenes, keyed by cutscene name.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CutsceneColors {
+    pub colors: BTreeMap<String, Color>,
+}
+
+/// The `[Loop Music]` section added by KS Plus, which sets whether the music keeps playing in
+/// a loop during a cutscene, keyed by cutscene name.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoopMusic {
+    pub cutscenes: BTreeMap<String, bool>,
+}
+
+const CUTSCENE_COLOR: &str = "Cutscene Color";
+const LOOP_MUSIC: &str = "Loop Music";
+
+impl CutsceneColors {
+    /// Reads `[Cutscene Color]`. Values that can't be parsed as a [`Color`] are skipped. When
+    /// a cutscene is listed more than once, ignoring ASCII case, the last value wins.
+    pub fn read(ini: &Ini) -> Self {
+        let mut colors = BTreeMap::new();
+        for (name, value) in section_entries(ini, CUTSCENE_COLOR) {
+            if let Some(color) = Color::parse(value) {
+                insert_ignore_case(&mut colors, name, color);
+            }
+        }
+        Self { colors }
+    }
+
+    /// Writes the colors back to `[Cutscene Color]`. Values that already mean the same color
+    /// are left as written, and so are values that can't be parsed, so that they survive a
+    /// round trip.
+    pub fn write(&self, ini: &mut Ini) {
+        remove_missing(ini, CUTSCENE_COLOR, |name, value| {
+            Color::parse(value).is_some() && !contains_ignore_case(&self.colors, name)
+        });
+        for (name, &color) in &self.colors {
+            if ini.get_in(CUTSCENE_COLOR, name).and_then(Color::parse) != Some(color) {
+                ini.set_in(CUTSCENE_COLOR, name, color.value().to_string());
+            }
+        }
+    }
+}
+
+impl LoopMusic {
+    /// Reads `[Loop Music]`. Values that can't be parsed as booleans are skipped. When a
+    /// cutscene is listed more than once, ignoring ASCII case, the last value wins.
+    pub fn read(ini: &Ini) -> Self {
+        let mut cutscenes = BTreeMap::new();
+        for (name, value) in section_entries(ini, LOOP_MUSIC) {
+            if let Some(value) = parse_bool(value) {
+                insert_ignore_case(&mut cutscenes, name, value);
+            }
+        }
+        Self { cutscenes }
+    }
+
+    /// Writes the flags back to `[Loop Music]`. Values that already mean the same thing (e.g.
+    /// `1` for `true`) are left as written, and so are values that can't be parsed, so that
+    /// they survive a round trip.
+    pub fn write(&self, ini: &mut Ini) {
+        remove_missing(ini, LOOP_MUSIC, |name, value| {
+            parse_bool(value).is_some() && !contains_ignore_case(&self.cutscenes, name)
+        });
+        for (name, &value) in &self.cutscenes {
+            if ini.get_in(LOOP_MUSIC, name).and_then(parse_bool) != Some(value) {
+                let value = if value { "True" } else { "False" };
+                ini.set_in(LOOP_MUSIC, name, value.to_owned());
+            }
+        }
+    }
+}
+
+fn section_entries<'a>(ini: &'a Ini, section: &str) -> Vec<(&'a str, &'a str)> {
+    ini.iter_sections()
+        .filter(|candidate| candidate.key().eq_ignore_ascii_case(section))
+        .flat_map(|section| section.iter())
+        .collect()
+}
+
+fn insert_ignore_case<T>(map: &mut BTreeMap<String, T>, name: &str, value: T) {
+    map.retain(|key, _| !key.eq_ignore_ascii_case(name));
+    map.insert(name.to_owned(), value);
+}
+
+fn contains_ignore_case<T>(map: &BTreeMap<String, T>, name: &str) -> bool {
+    map.keys().any(|key| key.eq_ignore_ascii_case(name))
+}
+
+/// Removes the keys of `section` for which `should_remove` returns `true`.
+fn remove_missing<F>(ini: &mut Ini, section: &str, mut should_remove: F)
+where
+    F: FnMut(&str, &str) -> bool
+{
+    let removed: Vec<String> = section_entries(ini, section).into_iter()
+        .filter(|&(name, value)| should_remove(name, value))
+        .map(|(name, _)| name.to_owned())
+        .collect();
+
+    for name in removed {
+        ini.remove_in(section, &name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cutscene_sections_round_trip() {
+        let source = "[World]\nFormat=4\n[Cutscene Color]\nIntro=16711680\nEnding= 255\nCredits=red\n[Loop Music]\nIntro=1\nEnding=maybe\n";
+        let mut ini = Ini::new(source);
+
+        let mut colors = CutsceneColors::read(&ini);
+        let mut loops = LoopMusic::read(&ini);
+        assert_eq!(colors.colors, BTreeMap::from([
+            ("Intro".to_owned(), Color::new(0, 0, 255)),
+            ("Ending".to_owned(), Color::new(255, 0, 0)),
+        ]));
+        assert_eq!(loops.cutscenes, BTreeMap::from([("Intro".to_owned(), true)]));
+
+        colors.write(&mut ini);
+        loops.write(&mut ini);
+        assert_eq!(ini.to_string(), source);
+
+        colors.colors.remove("Ending");
+        colors.colors.insert("Intro".to_owned(), Color::new(1, 2, 3));
+        loops.cutscenes.insert("Credits".to_owned(), false);
+        colors.write(&mut ini);
+        loops.write(&mut ini);
+        assert_eq!(ini.get_in("Cutscene Color", "Intro"), Some("197121"));
+        assert_eq!(ini.get_in("Cutscene Color", "Ending"), None);
+        assert_eq!(ini.get_in("Cutscene Color", "Credits"), Some("red"));
+        assert_eq!(Color::parse("16777216"), None);
+        assert_eq!(ini.get_in("Loop Music", "Ending"), Some("maybe"));
+        assert_eq!(ini.get_in("Loop Music", "Credits"), Some("False"));
+    }
+}
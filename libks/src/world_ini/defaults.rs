@@ -0,0 +1,176 @@
+use std::str::FromStr;
+
+use crate::{common::{parse_bool, split_slot}, editions::KsEdition};
+use super::SectionKind;
+
+/// The kinds of sections that [`Defaults`] has values for.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefaultScope {
+    World,
+    Screen,
+    /// Custom objects in either bank.
+    CustomObject,
+}
+
+impl DefaultScope {
+    /// Returns the scope of a section of `kind`, or `None` if there are no defaults for it.
+    pub fn of(kind: SectionKind) -> Option<DefaultScope> {
+        match kind {
+            SectionKind::World => Some(DefaultScope::World),
+            SectionKind::Screen(_) => Some(DefaultScope::Screen),
+            SectionKind::CustomObject(_) | SectionKind::CustomObjectB(_) => Some(DefaultScope::CustomObject),
+            SectionKind::Cutscene | SectionKind::Unknown => None,
+        }
+    }
+}
+
+/// The value an edition of KS uses for a key that isn't set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultValue {
+    pub scope: DefaultScope,
+    /// The key without a slot, e.g. `ShiftType` for `ShiftType(A)`.
+    pub key: &'static str,
+    pub value: &'static str,
+}
+
+/// The default values of an edition of KS. See [`defaults`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defaults {
+    edition: KsEdition,
+    values: Vec<DefaultValue>,
+}
+
+const fn default(scope: DefaultScope, key: &'static str, value: &'static str) -> DefaultValue {
+    DefaultValue { scope, key, value }
+}
+
+/// The defaults shared by every edition.
+const VANILLA: [DefaultValue; 31] = [
+    default(DefaultScope::Screen, "WarpUpX", "0"),
+    default(DefaultScope::Screen, "WarpUpY", "0"),
+    default(DefaultScope::Screen, "WarpDownX", "0"),
+    default(DefaultScope::Screen, "WarpDownY", "0"),
+    default(DefaultScope::Screen, "WarpLeftX", "0"),
+    default(DefaultScope::Screen, "WarpLeftY", "0"),
+    default(DefaultScope::Screen, "WarpRightX", "0"),
+    default(DefaultScope::Screen, "WarpRightY", "0"),
+    default(DefaultScope::Screen, "FlagWarpX", "0"),
+    default(DefaultScope::Screen, "FlagWarpY", "0"),
+    default(DefaultScope::Screen, "ShiftType", "0"),
+    default(DefaultScope::Screen, "ShiftXMap", "0"),
+    default(DefaultScope::Screen, "ShiftYMap", "0"),
+    default(DefaultScope::Screen, "ShiftX", "0"),
+    default(DefaultScope::Screen, "ShiftY", "0"),
+    default(DefaultScope::Screen, "ShiftAbsolute", "False"),
+    default(DefaultScope::Screen, "ShiftVisible", "True"),
+    default(DefaultScope::Screen, "ShiftEffect", "True"),
+    default(DefaultScope::Screen, "ShiftQuantize", "True"),
+    default(DefaultScope::Screen, "ShiftSave", "False"),
+    default(DefaultScope::Screen, "ShiftHide", "False"),
+    default(DefaultScope::Screen, "ShiftDenyHologram", "False"),
+    default(DefaultScope::CustomObject, "Tile Width", "24"),
+    default(DefaultScope::CustomObject, "Tile Height", "24"),
+    default(DefaultScope::CustomObject, "Offset X", "0"),
+    default(DefaultScope::CustomObject, "Offset Y", "0"),
+    default(DefaultScope::CustomObject, "Init AnimFrom", "0"),
+    default(DefaultScope::CustomObject, "Init AnimTo", "0"),
+    default(DefaultScope::CustomObject, "Init AnimSpeed", "0"),
+    default(DefaultScope::CustomObject, "Init AnimLoopback", "False"),
+    default(DefaultScope::CustomObject, "Init AnimRepeat", "0"),
+];
+
+/// The defaults of the properties added by KS ACO. See [`super::AcoObject`].
+const ACO: [DefaultValue; 2] = [
+    default(DefaultScope::CustomObject, "Does kill", "False"),
+    default(DefaultScope::Screen, "WarpSave", "False"),
+];
+
+/// Returns the values `edition` uses for keys that aren't set.
+///
+/// This covers the warp, flag warp, and shift keys of screen sections (treated as 0 when missing,
+/// like in [`super::targets`]) and the sprite keys of custom object sections, plus the KS ACO
+/// keys modeled by [`super::AcoObject`] and [`super::AcoScreen`]. It isn't every key KS reads:
+/// keys without a meaningful default, like `Name` or `Flag(A)`, and keys whose defaults aren't
+/// known aren't included.
+pub fn defaults(edition: &KsEdition) -> Defaults {
+    let mut values = VANILLA.to_vec();
+    if *edition == KsEdition::AdvancedCustomObjects {
+        values.extend(ACO);
+    }
+
+    Defaults {
+        edition: edition.clone(),
+        values,
+    }
+}
+
+impl Defaults {
+    pub fn edition(&self) -> &KsEdition {
+        &self.edition
+    }
+
+    /// Returns the default value of `key` in sections of `scope`, ignoring ASCII case. Slots are
+    /// ignored, so `ShiftType(B)` has the default of `ShiftType`. Use [`DefaultScope::of`] to
+    /// find the scope of a section.
+    pub fn get(&self, scope: DefaultScope, key: &str) -> Option<&'static str> {
+        let name = split_slot(key).map_or(key, |(name, _)| name);
+
+        self.values.iter()
+            .find(|value| value.scope == scope && value.key.eq_ignore_ascii_case(name))
+            .map(|value| value.value)
+    }
+
+    /// Returns `value` if it's set, or else the default of `key` parsed as `T`. This resolves
+    /// the `Option` fields of typed models like [`super::AcoObject`] to the values KS uses.
+    pub fn resolve<T: FromStr>(&self, scope: DefaultScope, key: &str, value: Option<T>) -> Option<T> {
+        value.or_else(|| self.get(scope, key)?.parse().ok())
+    }
+
+    /// Like [`Defaults::resolve`], but parses booleans the way KS does, e.g. `1` or `True`.
+    pub fn resolve_bool(&self, scope: DefaultScope, key: &str, value: Option<bool>) -> Option<bool> {
+        value.or_else(|| parse_bool(self.get(scope, key)?))
+    }
+
+    /// Returns an iterator over every default value.
+    pub fn iter(&self) -> impl Iterator<Item = &DefaultValue> {
+        self.values.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_depend_on_edition_and_ignore_slots() {
+        let vanilla = defaults(&KsEdition::Vanilla);
+        let screen = DefaultScope::of(SectionKind::Screen((1000, 1000))).unwrap();
+
+        assert_eq!(vanilla.get(screen, "shifttype(B)"), Some("0"));
+        assert_eq!(vanilla.get(DefaultScope::CustomObject, "Tile Width"), Some("24"));
+        assert_eq!(vanilla.get(DefaultScope::World, "Tile Width"), None);
+        assert_eq!(vanilla.get(screen, "WarpSave"), None);
+        assert_eq!(vanilla.resolve(screen, "ShiftVisible(A)", None::<String>).as_deref(), Some("True"));
+        assert_eq!(vanilla.resolve(DefaultScope::CustomObject, "Tile Height", Some(48u32)), Some(48));
+
+        let aco = defaults(&KsEdition::AdvancedCustomObjects);
+        assert_eq!(aco.resolve_bool(screen, "WarpSave", None), Some(false));
+        assert_eq!(aco.iter().count(), vanilla.iter().count() + 2);
+    }
+
+    #[test]
+    fn target_keys_default_to_zero() {
+        let vanilla = defaults(&KsEdition::Vanilla);
+        let keys = ["ShiftXMap(A)", "ShiftYMap(B)", "ShiftX(C)", "ShiftY(A)", "FlagWarpX(A)", "FlagWarpY(C)"].into_iter()
+            .map(str::to_owned)
+            .chain(["Up", "Down", "Left", "Right"].into_iter().flat_map(|edge| [format!("Warp{edge}X"), format!("Warp{edge}Y")]));
+
+        for key in keys {
+            assert_eq!(vanilla.get(DefaultScope::Screen, &key), Some("0"), "{key}");
+        }
+        assert_eq!(vanilla.get(DefaultScope::Screen, "WarpX"), None);
+        assert_eq!(vanilla.get(DefaultScope::Screen, "ShiftAbsoluteTarget"), None);
+        assert_eq!(vanilla.resolve_bool(DefaultScope::Screen, "ShiftAbsolute(B)", None), Some(false));
+    }
+}
@@ -6,4 +6,8 @@ pub enum WorldIniError {
     BadEncoding {
         path: PathBuf,
     },
+    #[error("The INI data for `{path:?}` contains characters that cannot be encoded as Windows-1252.")]
+    Unencodable {
+        path: PathBuf,
+    },
 }
@@ -0,0 +1,128 @@
+use libks_ini::{Ini, SectionKind};
+
+use crate::{
+    common::split_slot,
+    editions::world_ini_heuristics::{
+        is_plus_artifact_warp,
+        is_plus_coin_flag,
+        is_plus_object_prop,
+        is_plus_screen_prop,
+        is_plus_world_prop,
+        PLUS_SECTIONS,
+    },
+};
+
+/// A value of `Format` in the `[World]` section, which opts a level into a mod's behavior. See
+/// [`crate::editions`].
+///
+/// Formats are ordered by their value. A higher value is presumed to satisfy a level that
+/// needs a lower one, but this hasn't been confirmed across mods.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Format {
+    /// No `Format` is needed.
+    #[default]
+    Vanilla,
+    /// `Format=3`, read by KS Ex.
+    Extended,
+    /// `Format=4`, which enables KS Plus features.
+    Plus,
+}
+
+impl Format {
+    /// Returns the value of the `Format` key, or `None` for [`Format::Vanilla`].
+    pub fn value(self) -> Option<u32> {
+        match self {
+            Format::Vanilla => None,
+            Format::Extended => Some(3),
+            Format::Plus => Some(4),
+        }
+    }
+
+    /// Returns the format `ini` declares. A missing or unrecognized `Format` is vanilla, except
+    /// that `FormatEx` declares KS Ex.
+    pub fn declared(ini: &Ini) -> Format {
+        let format = ini.get_in("World", "Format")
+            .and_then(|format| format.trim().parse::<u32>().ok());
+
+        match format {
+            Some(4..) => Format::Plus,
+            Some(3) => Format::Extended,
+            _ if ini.has_in("World", "FormatEx") => Format::Extended,
+            _ => Format::Vanilla,
+        }
+    }
+}
+
+/// Returns the minimum `Format` the level needs for every feature `ini` uses to work.
+///
+/// KS Plus ignores most of its features unless `Format=4`, so a level that uses them without
+/// it loads, but plays wrong.
+pub fn required_format(ini: &Ini) -> Format {
+    if uses_plus_features(ini) {
+        Format::Plus
+    }
+    else if ini.has_section("KS Ex") || ini.has_section("Templates") {
+        Format::Extended
+    }
+    else {
+        Format::Vanilla
+    }
+}
+
+/// Raises `Format` to [`required_format`] if `ini` declares less. Returns the new format if it
+/// was changed.
+pub fn fix_format(ini: &mut Ini) -> Option<Format> {
+    let required = required_format(ini);
+    if Format::declared(ini) >= required {
+        return None;
+    }
+
+    let value = required.value().expect("required format should be above vanilla");
+    ini.set_in("World", "Format", value.to_string());
+
+    Some(required)
+}
+
+fn uses_plus_features(ini: &Ini) -> bool {
+    if PLUS_SECTIONS.iter().any(|key| ini.has_section(key)) {
+        return true;
+    }
+
+    ini.iter_sections().any(|section| match section.kind() {
+        SectionKind::World => section.iter().any(|(key, _)| is_plus_world_prop(key)),
+        SectionKind::CustomObjectB(1..) => true,
+        SectionKind::CustomObject(_) | SectionKind::CustomObjectB(_) => {
+            section.iter().any(|(key, _)| is_plus_object_prop(key))
+        },
+        SectionKind::Screen(_) => section.iter().any(|(key, value)| {
+            let name = split_slot(key).map_or(key, |(name, _)| name);
+
+            is_plus_screen_prop(key)
+                || (name.eq_ignore_ascii_case("Flag") && is_plus_coin_flag(value))
+                || ((name.eq_ignore_ascii_case("FlagWarpX") || name.eq_ignore_ascii_case("FlagWarpY"))
+                    && is_plus_artifact_warp(value))
+        }),
+        SectionKind::Cutscene | SectionKind::Unknown => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn understated_format_is_fixed() {
+        let mut ini = Ini::new("[World]\nName=Level\nFormat=2\n[x1000y1000]\nFlag(A)=Coin3\n");
+        assert_eq!(Format::declared(&ini), Format::Vanilla);
+        assert_eq!(required_format(&ini), Format::Plus);
+
+        assert_eq!(fix_format(&mut ini), Some(Format::Plus));
+        assert_eq!(ini.get_in("World", "Format"), Some("4"));
+        assert_eq!(fix_format(&mut ini), None);
+
+        let mut vanilla = Ini::new("[World]\nName=Level\n[x1000y1000]\nFlag(A)=Power3\n");
+        assert_eq!(required_format(&vanilla), Format::Vanilla);
+        assert_eq!(fix_format(&mut vanilla), None);
+    }
+}
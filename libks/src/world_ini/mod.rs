@@ -1,12 +1,42 @@
 use std::{fs, path::Path};
 
-use libks_ini::Ini;
+use libks_ini::{Encoding, Ini};
 
-use crate::Result;
+use crate::{
+    error::{ErrorContext, ResultExt},
+    issues::Issues,
+    perf,
+    KsError,
+    Result,
+};
+
+mod aco;
+pub use aco::{AcoObject, AcoScreen};
+
+mod color;
+pub use color::Color;
+
+mod cutscenes;
+pub use cutscenes::{CutsceneColors, LoopMusic};
+
+mod defaults;
+pub use defaults::{defaults, DefaultScope, DefaultValue, Defaults};
 
 mod error;
 pub use error::WorldIniError;
 
+mod format;
+pub use format::{fix_format, required_format, Format};
+
+mod stamp;
+pub use stamp::{apply_template, MergePolicy, ScreenSection};
+
+mod symbols;
+pub use symbols::{symbols, PropertySymbol, SectionKind, SectionSymbol};
+
+mod targets;
+pub use targets::{screen_targets, targets, Edge, Target, TargetKind, SLOTS};
+
 /// Attempts to read and parse the World.ini for the level in `world_dir`.
 pub fn load_ini<P>(ini_path: P) -> Result<Ini>
 where
@@ -14,7 +44,8 @@ where
 {
     let ini_path = ini_path.as_ref();
     let ini_contents = {
-        let bytes = fs::read(ini_path)?;
+        let bytes = fs::read(ini_path).with_path(ini_path)?;
+        perf::count(|c| c.bytes_read += bytes.len() as u64);
         let (contents, _, had_errors) = encoding_rs::WINDOWS_1252.decode(&bytes);
 
         if had_errors {
@@ -26,9 +57,41 @@ where
         contents.to_string()
     };
 
+    perf::count(|c| c.inis_parsed += 1);
     Ok(Ini::new(&ini_contents))
 }
 
+/// Reads and parses the INI file at `ini_path`, tolerating problems the way KS does.
+/// 
+/// This variant never fails. Problems are recorded in `issues` instead: if the file can't be
+/// read, an empty INI is returned, and if it isn't valid Windows-1252, the invalid bytes are
+/// replaced with U+FFFD.
+pub fn load_ini_with_issues<P>(ini_path: P, issues: &mut Issues) -> Ini
+where
+    P: AsRef<Path>
+{
+    let ini_path = ini_path.as_ref();
+    let bytes = match fs::read(ini_path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            issues.push(KsError::from(err).with_context(ErrorContext::path(ini_path)));
+            return Ini::new("");
+        },
+    };
+
+    perf::count(|c| c.bytes_read += bytes.len() as u64);
+
+    let (contents, _, had_errors) = encoding_rs::WINDOWS_1252.decode(&bytes);
+    if had_errors {
+        issues.push(KsError::from(WorldIniError::BadEncoding {
+            path: ini_path.to_owned(),
+        }));
+    }
+
+    perf::count(|c| c.inis_parsed += 1);
+    Ini::new(&contents)
+}
+
 /// Attempts to read and parse the World.ini for the level in `world_dir`.
 pub fn load_ini_from_dir<P>(world_dir: P) -> Result<Ini>
 where
@@ -36,3 +99,19 @@ where
 {
     load_ini(world_dir.as_ref().join("World.ini"))
 }
+
+/// Encodes `ini` as Windows-1252 and writes it to `ini_path`.
+pub fn write_ini<P>(ini_path: P, ini: &Ini) -> Result<()>
+where
+    P: AsRef<Path>
+{
+    let ini_path = ini_path.as_ref();
+    let encoded = ini.encode(Encoding::Windows1252Strict)
+        .map_err(|_| WorldIniError::Unencodable {
+            path: ini_path.to_owned(),
+        })?;
+
+    fs::write(ini_path, encoded.bytes).with_path(ini_path)?;
+
+    Ok(())
+}
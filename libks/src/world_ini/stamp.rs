@@ -0,0 +1,92 @@
+use libks_ini::Ini;
+
+/// What [`apply_template`] does with keys a screen section already has.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Leave existing values alone and only add missing keys.
+    #[default]
+    KeepExisting,
+    /// Replace existing values with the template's.
+    Overwrite,
+}
+
+/// A set of properties to write to screen sections with [`apply_template`], e.g. the same
+/// `Tint` or music trigger across an area.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScreenSection {
+    /// The properties to write, in order.
+    pub properties: Vec<(String, String)>,
+    pub policy: MergePolicy,
+}
+
+impl ScreenSection {
+    /// Creates a template from the properties of the screen section at `position` in `ini`, or
+    /// `None` if there's no such section. If a key is repeated, only the last value is kept.
+    pub fn from_ini(ini: &Ini, position: (i64, i64)) -> Option<Self> {
+        let (x, y) = position;
+        let section = ini.section(&format!("x{x}y{y}"))?;
+
+        let mut template = Self::default();
+        for (key, value) in section.iter() {
+            template.set(key, value);
+        }
+
+        Some(template)
+    }
+
+    /// Sets `key` to `value`, replacing any existing value for the key regardless of case.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.properties.iter_mut().find(|(existing, _)| existing.eq_ignore_ascii_case(key)) {
+            Some((_, existing)) => *existing = value.to_owned(),
+            None => self.properties.push((key.to_owned(), value.to_owned())),
+        }
+    }
+}
+
+/// Writes the properties of `template` to the screen section of each position in `coords`,
+/// creating sections as needed. Returns the number of properties written.
+///
+/// Keys a section already has are kept or replaced according to `template.policy`. Keys compare
+/// without regard to case, as KS reads them.
+pub fn apply_template<I>(ini: &mut Ini, coords: I, template: &ScreenSection) -> usize
+where
+    I: IntoIterator<Item = (i64, i64)>
+{
+    let mut written = 0;
+
+    for (x, y) in coords {
+        let section = format!("x{x}y{y}");
+        for (key, value) in &template.properties {
+            if template.policy == MergePolicy::KeepExisting && ini.has_in(&section, key) {
+                continue;
+            }
+
+            ini.set_in(&section, key, value.clone());
+            written += 1;
+        }
+    }
+
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_respect_merge_policy() {
+        let mut ini = Ini::new("[x1y1]\nTint=Red\nMusic=3\n[x2y1]\ntint=Blue\n");
+        let mut template = ScreenSection::from_ini(&ini, (1, 1)).unwrap();
+        template.set("TINT", "Green");
+        assert_eq!(template.properties.len(), 2);
+
+        assert_eq!(apply_template(&mut ini, [(2, 1), (3, 1)], &template), 3);
+        assert_eq!(ini.get_in("x2y1", "Tint"), Some("Blue"));
+        assert_eq!(ini.get_in("x2y1", "Music"), Some("3"));
+        assert_eq!(ini.get_in("x3y1", "Tint"), Some("Green"));
+
+        template.policy = MergePolicy::Overwrite;
+        assert_eq!(apply_template(&mut ini, [(2, 1)], &template), 2);
+        assert_eq!(ini.get_in("x2y1", "Tint"), Some("Green"));
+    }
+}
@@ -0,0 +1,109 @@
+use std::ops::Range;
+
+pub use libks_ini::SectionKind;
+use libks_ini::{Event, Ini, Parser};
+
+use super::screen_targets;
+
+/// A property found by [`symbols`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertySymbol {
+    pub key: String,
+    /// The range of the whole line.
+    pub span: Range<usize>,
+    pub key_span: Range<usize>,
+    pub value_span: Range<usize>,
+    /// The screen the property leads to, if it's part of a warp, shift, flag warp, or trigger.
+    /// See [`screen_targets`].
+    pub target: Option<(i64, i64)>,
+}
+
+/// A section found by [`symbols`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionSymbol {
+    pub name: String,
+    pub kind: SectionKind,
+    /// The range from the start of the header to the start of the next section.
+    pub span: Range<usize>,
+    /// The range of the name within the header, excluding the brackets.
+    pub name_span: Range<usize>,
+    pub properties: Vec<PropertySymbol>,
+}
+
+/// Lists the sections of `ini` and their properties in file order, with their locations, for
+/// editor features like an outline or go-to-definition.
+///
+/// Spans are byte ranges into `ini.to_string()`, which is the original source unless the INI has
+/// been edited. Properties before the first section are ignored, as KS ignores them. A section
+/// that appears more than once is listed each time.
+pub fn symbols(ini: &Ini) -> Vec<SectionSymbol> {
+    let source = ini.to_string();
+    let mut sections: Vec<SectionSymbol> = Vec::new();
+
+    for event in Parser::new(&source).events() {
+        let line = event.line();
+        match event {
+            Event::Section { key, .. } => {
+                let name = source[key.clone()].to_owned();
+                sections.push(SectionSymbol {
+                    kind: SectionKind::of(&name),
+                    name,
+                    span: line,
+                    name_span: key,
+                    properties: Vec::new(),
+                });
+            },
+            Event::Property { key, value, .. } => {
+                if let Some(section) = sections.last_mut() {
+                    section.span.end = line.end;
+                    section.properties.push(PropertySymbol {
+                        key: source[key.clone()].to_owned(),
+                        span: line,
+                        key_span: key,
+                        value_span: value,
+                        target: None,
+                    });
+                }
+            },
+            _ => {
+                if let Some(section) = sections.last_mut() {
+                    section.span.end = line.end;
+                }
+            },
+        }
+    }
+
+    for section in &mut sections {
+        let SectionKind::Screen(screen) = section.kind else { continue };
+        for target in screen_targets(ini, screen) {
+            for property in &mut section.properties {
+                if target.keys.iter().any(|key| key.eq_ignore_ascii_case(&property.key)) {
+                    property.target = Some(target.screen);
+                }
+            }
+        }
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections_and_targets_are_indexed() {
+        let source = "[World]\nName=Test\n\n[x1000y1000]\nWarpUpY=-1\nSign(A)=Hi\n[Custom Object B2]\nImage=A.png\n";
+        let symbols = symbols(&Ini::new(source));
+
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[0].kind, SectionKind::World);
+        assert_eq!(&source[symbols[0].span.clone()], "[World]\nName=Test\n\n");
+        assert_eq!(symbols[1].kind, SectionKind::Screen((1000, 1000)));
+        assert_eq!(&source[symbols[1].name_span.clone()], "x1000y1000");
+        assert_eq!(symbols[1].properties[0].target, Some((1000, 999)));
+        assert_eq!(symbols[1].properties[1].target, None);
+        assert_eq!(&source[symbols[1].properties[1].value_span.clone()], "Hi");
+        assert_eq!(symbols[2].kind, SectionKind::CustomObjectB(2));
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeSet;
+
+use libks_ini::Ini;
+
+use crate::common::parse_bool;
+
+/// The slots shifts, triggers, and flag warps can occupy.
+pub const SLOTS: [&str; 3] = ["A", "B", "C"];
+
+/// A screen edge.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edge {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 4] = [Edge::Up, Edge::Down, Edge::Left, Edge::Right];
+
+    /// Returns the name used in World.ini warp keys, e.g. `Up` in `WarpUpX`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Edge::Up => "Up",
+            Edge::Down => "Down",
+            Edge::Left => "Left",
+            Edge::Right => "Right",
+        }
+    }
+}
+
+/// The kind of property that sends the player to another location.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TargetKind {
+    /// A shift in the given slot.
+    Shift(String),
+    /// A warp on the given edge.
+    Warp(Edge),
+    /// A flag warp in the given slot.
+    FlagWarp(String),
+    /// The spawn point of a KS Plus trigger in the given slot.
+    TriggerSpawn(String),
+}
+
+/// A location referenced by a screen section of World.ini.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    /// The screen whose section defines the target.
+    pub source: (i64, i64),
+    pub kind: TargetKind,
+    /// The keys of the properties that define the target.
+    pub keys: Vec<String>,
+    /// The destination screen.
+    pub screen: (i64, i64),
+    /// The destination tile position within the screen, if it's known statically.
+    pub position: Option<(i64, i64)>,
+}
+
+/// Lists every location referenced by the screen sections of `ini`.
+///
+/// The engine's semantics are:
+/// - Shifts (`ShiftXMap(X)`, `ShiftYMap(X)`, `ShiftX(X)`, `ShiftY(X)`): if `ShiftAbsolute(X)` is
+///   true, the values are the destination screen and tile position. Otherwise, they are
+///   offsets from the current screen and the player's position, so the position isn't known.
+/// - Warps (`WarpUpX`, `WarpUpY`, etc.): offsets from the current screen to the screen the
+///   player enters when leaving through that edge.
+/// - Flag warps (`FlagWarpX(X)`, `FlagWarpY(X)`): offsets from the current screen to the screen
+///   shown instead when the flag condition holds.
+/// - KS Plus triggers (`TrigSpawnX(X)`, `TrigSpawnY(X)`): a tile position on the current screen.
+///
+/// Missing or malformed numbers are treated as 0, as they are in-game.
+pub fn targets(ini: &Ini) -> Vec<Target> {
+    let mut screens = BTreeSet::new();
+    for section in ini.iter_sections() {
+        if let Some(position) = section.kind().screen() {
+            screens.insert(position);
+        }
+    }
+
+    screens.into_iter()
+        .flat_map(|screen| screen_targets(ini, screen))
+        .collect()
+}
+
+/// Lists every location referenced by the section of `ini` for `screen`. See [`targets`].
+pub fn screen_targets(ini: &Ini, screen: (i64, i64)) -> Vec<Target> {
+    let mut targets = Vec::new();
+    let Some(section) = ini.section(&format!("x{}y{}", screen.0, screen.1)) else {
+        return targets;
+    };
+
+    let number = |key: &str| {
+        section.get(key)
+            .map(|value| value.trim().parse::<i64>().unwrap_or(0))
+    };
+    let present = |keys: &[String]| -> Vec<String> {
+        keys.iter()
+            .filter(|key| section.has(key))
+            .cloned()
+            .collect()
+    };
+
+    for slot in SLOTS {
+        let keys = [
+            format!("ShiftXMap({slot})"),
+            format!("ShiftYMap({slot})"),
+            format!("ShiftX({slot})"),
+            format!("ShiftY({slot})"),
+        ];
+        let found = present(&keys);
+        if !found.is_empty() {
+            let values = keys.each_ref().map(|key| number(key).unwrap_or(0));
+            let absolute = section.get(&format!("ShiftAbsolute({slot})"))
+                .and_then(parse_bool)
+                .unwrap_or(false);
+
+            let (destination, position) =
+                if absolute {
+                    ((values[0], values[1]), Some((values[2], values[3])))
+                }
+                else {
+                    ((screen.0 + values[0], screen.1 + values[1]), None)
+                };
+
+            targets.push(Target {
+                source: screen,
+                kind: TargetKind::Shift(slot.to_owned()),
+                keys: found,
+                screen: destination,
+                position,
+            });
+        }
+    }
+
+    // Reads an X/Y pair of properties, returning `None` if neither is present
+    let pair = |x_key: String, y_key: String| {
+        let (x, y) = (number(&x_key), number(&y_key));
+        if x.is_none() && y.is_none() {
+            return None;
+        }
+
+        let keys = present(&[x_key, y_key]);
+        Some((keys, x.unwrap_or(0), y.unwrap_or(0)))
+    };
+
+    for edge in Edge::ALL {
+        let edge_name = edge.as_str();
+        if let Some((keys, x, y)) = pair(format!("Warp{edge_name}X"), format!("Warp{edge_name}Y")) {
+            targets.push(Target {
+                source: screen,
+                kind: TargetKind::Warp(edge),
+                keys,
+                screen: (screen.0 + x, screen.1 + y),
+                position: None,
+            });
+        }
+    }
+
+    for slot in SLOTS {
+        if let Some((keys, x, y)) = pair(format!("FlagWarpX({slot})"), format!("FlagWarpY({slot})")) {
+            targets.push(Target {
+                source: screen,
+                kind: TargetKind::FlagWarp(slot.to_owned()),
+                keys,
+                screen: (screen.0 + x, screen.1 + y),
+                position: None,
+            });
+        }
+
+        if let Some((keys, x, y)) = pair(format!("TrigSpawnX({slot})"), format!("TrigSpawnY({slot})")) {
+            targets.push(Target {
+                source: screen,
+                kind: TargetKind::TriggerSpawn(slot.to_owned()),
+                keys,
+                screen,
+                position: Some((x, y)),
+            });
+        }
+    }
+
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_resolve_relative_and_absolute() {
+        let ini = Ini::new("\
+[x1000y1000]
+ShiftXMap(A)=1
+ShiftAbsolute(B)=True
+ShiftXMap(B)=1005
+ShiftYMap(B)=995
+ShiftX(B)=3
+ShiftY(B)=4
+WarpLeftY=-2
+");
+        let targets = screen_targets(&ini, (1000, 1000));
+
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets[0].kind, TargetKind::Shift("A".to_owned()));
+        assert_eq!(targets[0].screen, (1001, 1000));
+        assert_eq!(targets[0].position, None);
+        assert_eq!(targets[1].screen, (1005, 995));
+        assert_eq!(targets[1].position, Some((3, 4)));
+        assert_eq!(targets[2].kind, TargetKind::Warp(Edge::Left));
+        assert_eq!(targets[2].keys, vec!["WarpLeftY".to_owned()]);
+        assert_eq!(targets[2].screen, (1000, 998));
+    }
+}
@@ -0,0 +1,42 @@
+#ifndef LIBKS_H
+#define LIBKS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Returned by fallible functions on success. Other results are KS_INVALID_ARGUMENT or a
+ * positive libks error code; ks_last_error() describes the error. */
+#define KS_OK 0
+#define KS_INVALID_ARGUMENT (-1)
+
+/* Each screen record in ks_map_data(): x and y as little endian int64_t, then the screen's
+ * 3006 bytes of Map.bin data. */
+#define KS_SCREEN_RECORD_LEN (16 + 3006)
+
+typedef struct KsMap KsMap;
+typedef struct KsIni KsIni;
+
+/* All strings are null-terminated UTF-8. */
+
+const char *ks_last_error(void);
+
+int32_t ks_unpack(const char *bin_path, const char *output_dir);
+
+int32_t ks_map_load(const char *path, KsMap **out);
+size_t ks_map_screen_count(const KsMap *map);
+const uint8_t *ks_map_data(const KsMap *map, size_t *len);
+void ks_map_free(KsMap *map);
+
+int32_t ks_ini_load(const char *path, KsIni **out);
+ptrdiff_t ks_ini_get(const KsIni *ini, const char *section, const char *key, char *buf, size_t buf_len);
+void ks_ini_free(KsIni *ini);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
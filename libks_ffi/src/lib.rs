@@ -0,0 +1,258 @@
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    path::PathBuf,
+    ptr,
+};
+
+use libks::{map_bin, world_ini, KsError};
+use libks_ini::Ini;
+
+/// Returned by every fallible function on success. Otherwise, the result is
+/// [`KS_INVALID_ARGUMENT`] or the positive [`KsError::code`] of the error, and
+/// [`ks_last_error`] describes it.
+pub const KS_OK: i32 = 0;
+/// Returned when a required pointer is null or a string isn't valid UTF-8.
+pub const KS_INVALID_ARGUMENT: i32 = -1;
+
+/// The length in bytes of one screen record in [`ks_map_data`]: the x and y coordinates as
+/// little endian 64-bit integers followed by the screen's 3006 bytes of Map.bin data.
+pub const KS_SCREEN_RECORD_LEN: usize = 16 + map_bin::SCREEN_DATA_LEN;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message)
+        .unwrap_or_else(|_| c"error message contained a null byte".to_owned());
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Records `err` as the last error and returns its code.
+fn fail(err: KsError) -> i32 {
+    set_last_error(err.to_string());
+    err.code().into()
+}
+
+fn invalid_argument(name: &str) -> i32 {
+    set_last_error(format!("`{name}` was null or not valid UTF-8"));
+    KS_INVALID_ARGUMENT
+}
+
+/// Converts a C string to a `&str`. Returns `None` if it's null or not UTF-8.
+///
+/// # Safety
+///
+/// `s` must be null or point to a null-terminated string that outlives `'a`.
+unsafe fn str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(s).to_str().ok()
+}
+
+/// Returns a description of the last error that occurred on this thread, or null if there
+/// hasn't been one. The string remains valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn ks_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Unpacks the .knytt.bin at `bin_path` into a subdirectory of `output_dir` with the default
+/// options. See `libks::knytt_bin::unpack`.
+///
+/// # Safety
+///
+/// Both arguments must be null-terminated UTF-8 strings.
+#[no_mangle]
+pub unsafe extern "C" fn ks_unpack(bin_path: *const c_char, output_dir: *const c_char) -> i32 {
+    let Some(bin_path) = str_arg(bin_path) else { return invalid_argument("bin_path") };
+    let Some(output_dir) = str_arg(output_dir) else { return invalid_argument("output_dir") };
+
+    match libks::knytt_bin::unpack(bin_path, output_dir) {
+        Ok(_) => KS_OK,
+        Err(err) => fail(err),
+    }
+}
+
+/// Parsed Map.bin data, flattened into consecutive screen records. See [`KS_SCREEN_RECORD_LEN`].
+pub struct KsMap {
+    data: Vec<u8>,
+    screen_count: usize,
+}
+
+/// Parses the Map.bin at `path` and stores a new [`KsMap`] in `*out`, which must be freed with
+/// [`ks_map_free`].
+///
+/// # Safety
+///
+/// `path` must be a null-terminated UTF-8 string and `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ks_map_load(path: *const c_char, out: *mut *mut KsMap) -> i32 {
+    let Some(path) = str_arg(path) else { return invalid_argument("path") };
+    if out.is_null() {
+        return invalid_argument("out");
+    }
+
+    let screens = match map_bin::parse_map_file(path) {
+        Ok(screens) => screens,
+        Err(err) => return fail(err),
+    };
+
+    let mut data = Vec::with_capacity(screens.len() * KS_SCREEN_RECORD_LEN);
+    for screen in &screens {
+        data.extend_from_slice(&screen.position.0.to_le_bytes());
+        data.extend_from_slice(&screen.position.1.to_le_bytes());
+        data.extend_from_slice(&map_bin::encode_screen(screen));
+    }
+
+    *out = Box::into_raw(Box::new(KsMap {
+        data,
+        screen_count: screens.len(),
+    }));
+
+    KS_OK
+}
+
+/// Returns the number of screens in `map`.
+///
+/// # Safety
+///
+/// `map` must have been created by [`ks_map_load`] and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn ks_map_screen_count(map: *const KsMap) -> usize {
+    map.as_ref().map_or(0, |map| map.screen_count)
+}
+
+/// Returns a pointer to the screen records of `map` and stores their total length in bytes in
+/// `*len`. The pointer is valid until `map` is freed.
+///
+/// # Safety
+///
+/// `map` must have been created by [`ks_map_load`] and not yet freed. `len` must be null or a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ks_map_data(map: *const KsMap, len: *mut usize) -> *const u8 {
+    let Some(map) = map.as_ref() else { return ptr::null() };
+    if let Some(len) = len.as_mut() {
+        *len = map.data.len();
+    }
+
+    map.data.as_ptr()
+}
+
+/// Frees `map`. Does nothing if it's null.
+///
+/// # Safety
+///
+/// `map` must have been created by [`ks_map_load`] and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn ks_map_free(map: *mut KsMap) {
+    if !map.is_null() {
+        drop(Box::from_raw(map));
+    }
+}
+
+/// A parsed INI file such as World.ini.
+pub struct KsIni {
+    ini: Ini,
+}
+
+/// Reads the INI file at `path`, which is decoded as Windows-1252, and stores a new [`KsIni`]
+/// in `*out`, which must be freed with [`ks_ini_free`].
+///
+/// # Safety
+///
+/// `path` must be a null-terminated UTF-8 string and `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ks_ini_load(path: *const c_char, out: *mut *mut KsIni) -> i32 {
+    let Some(path) = str_arg(path) else { return invalid_argument("path") };
+    if out.is_null() {
+        return invalid_argument("out");
+    }
+
+    match world_ini::load_ini(PathBuf::from(path)) {
+        Ok(ini) => {
+            *out = Box::into_raw(Box::new(KsIni { ini }));
+            KS_OK
+        },
+        Err(err) => fail(err),
+    }
+}
+
+/// Copies the value of `key` in `section` into `buf` as a null-terminated UTF-8 string,
+/// truncating it to fit in `buf_len` bytes.
+///
+/// Returns the length of the full value in bytes, not counting the null terminator, or -1 if
+/// the property doesn't exist. If the result is greater than or equal to `buf_len`, the value
+/// was truncated. `buf` may be null if `buf_len` is 0, e.g. to query the length.
+///
+/// # Safety
+///
+/// `ini` must have been created by [`ks_ini_load`] and not yet freed. `section` and `key` must
+/// be null-terminated UTF-8 strings. `buf` must be valid for writes of `buf_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ks_ini_get(
+    ini: *const KsIni,
+    section: *const c_char,
+    key: *const c_char,
+    buf: *mut c_char,
+    buf_len: usize,
+) -> isize {
+    let (Some(ini), Some(section), Some(key)) = (ini.as_ref(), str_arg(section), str_arg(key)) else {
+        return -1;
+    };
+    let Some(value) = ini.ini.get_in(section, key) else { return -1 };
+
+    if !buf.is_null() && buf_len > 0 {
+        let copy_len = value.len().min(buf_len - 1);
+        ptr::copy_nonoverlapping(value.as_ptr().cast(), buf, copy_len);
+        *buf.add(copy_len) = 0;
+    }
+
+    value.len().try_into().unwrap_or(isize::MAX)
+}
+
+/// Frees `ini`. Does nothing if it's null.
+///
+/// # Safety
+///
+/// `ini` must have been created by [`ks_ini_load`] and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn ks_ini_free(ini: *mut KsIni) {
+    if !ini.is_null() {
+        drop(Box::from_raw(ini));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ini_values_are_copied_and_truncated() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("World.ini");
+        std::fs::write(&path, "[World]\nName=Sunny Hills\n").unwrap();
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+
+        unsafe {
+            let mut ini = ptr::null_mut();
+            assert_eq!(ks_ini_load(path.as_ptr(), &mut ini), KS_OK);
+
+            let mut buf = [0 as c_char; 6];
+            let len = ks_ini_get(ini, c"World".as_ptr(), c"Name".as_ptr(), buf.as_mut_ptr(), buf.len());
+            assert_eq!(len, 11);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str(), Ok("Sunny"));
+            assert_eq!(ks_ini_get(ini, c"World".as_ptr(), c"Author".as_ptr(), ptr::null_mut(), 0), -1);
+
+            ks_ini_free(ini);
+        }
+    }
+}
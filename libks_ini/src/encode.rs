@@ -0,0 +1,123 @@
+use alloc::{string::ToString, vec::Vec};
+
+use encoding_rs::{EncoderResult, WINDOWS_1252};
+
+use crate::Ini;
+
+/// How [`Ini::encode`] turns the text into bytes.
+///
+/// Vanilla KS only reads Windows-1252. Newer builds of KS Plus also accept UTF-8, but levels
+/// saved that way won't load correctly anywhere else.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Windows-1252. Fails if any character can't be represented.
+    #[default]
+    Windows1252Strict,
+    /// Windows-1252, replacing characters that can't be represented with `?`.
+    Windows1252Lossy,
+    /// UTF-8, which can represent everything.
+    Utf8,
+}
+
+/// A character that couldn't be represented in the chosen [`Encoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fallback {
+    pub ch: char,
+    /// The byte offset of the character in the text, i.e. `ini.to_string()`.
+    pub offset: usize,
+}
+
+/// The result of [`Ini::encode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Encoded {
+    pub bytes: Vec<u8>,
+    /// The characters that were replaced with `?`, in order. Only
+    /// [`Encoding::Windows1252Lossy`] replaces characters.
+    pub fallbacks: Vec<Fallback>,
+}
+
+/// The error returned by [`Ini::encode`] with [`Encoding::Windows1252Strict`] when some
+/// characters can't be represented.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeError {
+    /// Every character that couldn't be represented, in order.
+    pub unencodable: Vec<Fallback>,
+}
+
+impl core::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} character(s) can't be encoded as Windows-1252", self.unencodable.len())?;
+        if let Some(Fallback { ch, offset }) = self.unencodable.first() {
+            write!(f, ", starting with {ch:?} at byte {offset}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodeError {}
+
+impl Ini {
+    /// Serializes the INI like [`to_string`](ToString::to_string) and encodes it.
+    pub fn encode(&self, encoding: Encoding) -> Result<Encoded, EncodeError> {
+        let text = self.to_string();
+        if encoding == Encoding::Utf8 {
+            return Ok(Encoded {
+                bytes: text.into_bytes(),
+                fallbacks: Vec::new(),
+            });
+        }
+
+        let mut encoder = WINDOWS_1252.new_encoder();
+        let mut bytes = Vec::with_capacity(text.len());
+        let mut fallbacks = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let (result, read) = encoder.encode_from_utf8_to_vec_without_replacement(&text[offset..], &mut bytes, true);
+            offset += read;
+
+            match result {
+                EncoderResult::InputEmpty => break,
+                EncoderResult::OutputFull => bytes.reserve(text.len() - offset),
+                EncoderResult::Unmappable(ch) => {
+                    fallbacks.push(Fallback {
+                        ch,
+                        offset: offset - ch.len_utf8(),
+                    });
+                    bytes.push(b'?');
+                },
+            }
+        }
+
+        if encoding == Encoding::Windows1252Strict && !fallbacks.is_empty() {
+            return Err(EncodeError { unencodable: fallbacks });
+        }
+
+        Ok(Encoded { bytes, fallbacks })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodings_report_fallbacks() {
+        let ini = Ini::new("[World]\nName=Caf\u{e9} \u{2603}\nAuthor=\u{20ac}\u{1f600}\n");
+
+        let lossy = ini.encode(Encoding::Windows1252Lossy).unwrap();
+        assert_eq!(lossy.bytes, b"[World]\nName=Caf\xe9 ?\nAuthor=\x80?\n");
+        assert_eq!(lossy.fallbacks, [
+            Fallback { ch: '\u{2603}', offset: 19 },
+            Fallback { ch: '\u{1f600}', offset: 33 },
+        ]);
+
+        let err = ini.encode(Encoding::Windows1252Strict).unwrap_err();
+        assert_eq!(err.unencodable, lossy.fallbacks);
+
+        let utf8 = ini.encode(Encoding::Utf8).unwrap();
+        assert_eq!(utf8.bytes, ini.to_string().into_bytes());
+        assert!(utf8.fallbacks.is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+use crate::Ini;
+
+/// How [`Ini::format`] lays out properties.
+///
+/// The formatter only touches the whitespace around keys and `=` signs. Values, comments, and
+/// blank lines are left as they are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Pad keys with spaces so the `=` signs line up within each section, e.g.
+    /// `Name   =Level` above `Author =Someone`.
+    ///
+    /// Whitespace around keys is ignored, as described in `specification.txt`, so the padding
+    /// doesn't change the keys.
+    pub align_equals: bool,
+    /// Format every section, not just the ones that were edited.
+    pub all_sections: bool,
+}
+
+impl Ini {
+    /// Removes indentation and spaces around `=` signs, or aligns them if
+    /// [`FormatOptions::align_equals`] is set. Returns the number of sections changed.
+    ///
+    /// Unless [`FormatOptions::all_sections`] is set, only sections with lines that were added
+    /// or changed since parsing are formatted, so the rest of the file is written back exactly
+    /// as it was read. Sections that only had lines removed aren't detected as edited.
+    pub fn format(&mut self, options: FormatOptions) -> usize {
+        let global = self.global_section_mut();
+        let mut changed = usize::from((options.all_sections || global.is_edited()) && global.format(options));
+
+        for section in self.iter_sections_mut() {
+            if (options.all_sections || section.is_edited()) && section.format(options) {
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_edited_sections_are_aligned() {
+        let source = "[World]\nName = Level\n  Author=Someone\n[x1000y1000]\nTile   =A\nShiftXMap(A)=2\n";
+        let mut ini = Ini::new(source);
+        let options = FormatOptions { align_equals: true, ..Default::default() };
+
+        assert_eq!(ini.format(options), 0);
+        assert_eq!(ini.to_string(), source);
+
+        ini.set_in("World", "Format", "4".into());
+        assert_eq!(ini.format(options), 1);
+        assert_eq!(ini.format(options), 0);
+        assert_eq!(ini.to_string(), "[World]\nName  =Level\nAuthor=Someone\nFormat=4\n[x1000y1000]\nTile   =A\nShiftXMap(A)=2\n");
+
+        assert_eq!(ini.format(FormatOptions { all_sections: true, ..Default::default() }), 2);
+        assert_eq!(ini.to_string(), "[World]\nName=Level\nAuthor=Someone\nFormat=4\n[x1000y1000]\nTile=A\nShiftXMap(A)=2\n");
+    }
+}
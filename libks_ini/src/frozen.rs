@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use crate::Ini;
+
+/// A read-only snapshot of an [`Ini`] with every section and property in hash maps, for
+/// analysis that looks up many values. See [`Ini::freeze`].
+///
+/// Lookups ignore ASCII case and give the same results as [`Ini::get_in`] at the time of the
+/// snapshot: when a key is repeated, the last value wins, even across repeated sections.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrozenIni {
+    sections: HashMap<String, HashMap<String, String>>,
+}
+
+impl FrozenIni {
+    pub fn has_section(&self, key: &str) -> bool {
+        self.sections.contains_key(&key.to_ascii_lowercase())
+    }
+
+    /// Returns the properties of the section, keyed by their lowercase keys.
+    pub fn section(&self, key: &str) -> Option<&HashMap<String, String>> {
+        self.sections.get(&key.to_ascii_lowercase())
+    }
+
+    pub fn has_in(&self, section_key: &str, prop_key: &str) -> bool {
+        self.get_in(section_key, prop_key).is_some()
+    }
+
+    pub fn get_in(&self, section_key: &str, prop_key: &str) -> Option<&str> {
+        self.section(section_key)?
+            .get(&prop_key.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+impl Ini {
+    /// Builds a [`FrozenIni`] from the current contents. Later changes to the `Ini` aren't
+    /// reflected in it.
+    pub fn freeze(&self) -> FrozenIni {
+        let mut sections = HashMap::<_, HashMap<_, _>>::new();
+
+        for section in self.iter_sections() {
+            let props = sections.entry(section.key().to_ascii_lowercase()).or_default();
+            for (key, value) in section.iter() {
+                props.insert(key.to_ascii_lowercase(), value.to_owned());
+            }
+        }
+
+        FrozenIni { sections }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frozen_lookups_match_ini() {
+        let ini = Ini::new("[World]\nName=A\nname=B\n[x1y1]\nMusic=1\n[X1Y1]\nMusic=2\nTileset=3\n");
+        let frozen = ini.freeze();
+
+        for (section, key) in [("world", "NAME"), ("x1y1", "music"), ("x1y1", "Tileset"), ("x1y1", "Gradient"), ("x2y2", "Music")] {
+            assert_eq!(frozen.get_in(section, key), ini.get_in(section, key));
+        }
+        assert_eq!(frozen.get_in("World", "Name"), Some("B"));
+        assert!(frozen.has_section("X1Y1"));
+    }
+}
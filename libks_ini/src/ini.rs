@@ -1,32 +1,43 @@
-use std::{
-    collections::HashMap,
+use alloc::{
+    collections::BTreeMap,
     rc::Rc,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
 };
 
 use crate::{
     item::Item,
-    parse::Parser,
+    parse::{ParseOptions, Parser},
     section::{
+        GlobalSection,
+        GlobalSectionMut,
         Section,
         VirtualSection,
         VirtualSectionMut,
     },
 };
 
+#[derive(Clone)]
 pub struct Ini {
     source: Rc<str>,
     global_section: Section,
     sections: Vec<Section>,
-    section_index: HashMap<String, Vec<usize>>,
+    section_index: BTreeMap<String, Vec<usize>>,
 }
 
 impl Ini {
     pub fn new(source: &str) -> Self {
+        Self::with_options(source, ParseOptions::default())
+    }
+
+    /// Parses `source` like [`Ini::new`], cleaning up values according to `options`.
+    pub fn with_options(source: &str, options: ParseOptions) -> Self {
         let source = Rc::<str>::from(source);
         let mut global_section = Section::new_global(Rc::clone(&source));
         let mut sections = Vec::new();
 
-        for item in Parser::new(&source).map(Item::from) {
+        for item in Parser::with_options(&source, options) {
             match item {
                 Item::Section(..) => {
                     let section = Section::new(Rc::clone(&source), item);
@@ -49,8 +60,8 @@ impl Ini {
         }
     }
 
-    fn build_section_index(sections: &[Section]) -> HashMap<String, Vec<usize>> {
-        let mut index = HashMap::<_, Vec<_>>::new();
+    fn build_section_index(sections: &[Section]) -> BTreeMap<String, Vec<usize>> {
+        let mut index = BTreeMap::<_, Vec<_>>::new();
 
         for (i, section) in sections.iter().enumerate() {
             let lower_key = section.key().to_ascii_lowercase();
@@ -72,6 +83,17 @@ impl Ini {
         VirtualSectionMut::new(sections)
     }
 
+    /// Returns the properties before the first section header.
+    pub fn global(&self) -> GlobalSection<'_> {
+        GlobalSection::new(&self.global_section)
+    }
+
+    /// Like [`Ini::global`], but allows editing. New properties are added after any existing
+    /// lines, before the first section header.
+    pub fn global_mut(&mut self) -> GlobalSectionMut<'_> {
+        GlobalSectionMut::new(&mut self.global_section)
+    }
+
     pub fn has_section(&self, key: &str) -> bool {
         self.section_index.contains_key(&key.to_ascii_lowercase())
     }
@@ -131,13 +153,93 @@ impl Ini {
         }
     }
 
-    pub fn iter_sections(&self) -> std::slice::Iter<'_, Section> {
+    /// Returns the section before the one at index `at`, which is the global section if `at`
+    /// is 0.
+    fn section_before(&mut self, at: usize) -> &mut Section {
+        match at.checked_sub(1) {
+            Some(previous) => &mut self.sections[previous],
+            None => &mut self.global_section,
+        }
+    }
+
+    /// Returns the text of the section as it would be written, including its header and any
+    /// comments and blank lines up to the next section. Repeated sections are joined in order.
+    pub fn section_text(&self, key: &str) -> Option<String> {
+        let indices = self.section_index.get(&key.to_ascii_lowercase())?;
+        let text = indices.iter()
+            .map(|&i| self.sections[i].to_string())
+            .collect();
+        Some(text)
+    }
+
+    /// Replaces the text of the section, as returned by [`Ini::section_text`], with `text`.
+    /// Returns `false` if there's no such section.
+    ///
+    /// The result is the same as editing the file: `text` replaces the first section with
+    /// the key and the rest are removed. Anything in `text` before its first header ends up in
+    /// the preceding section, and any headers in it start new sections. A line break is added
+    /// if `text` doesn't end with one.
+    pub fn replace_section_text(&mut self, key: &str, text: &str) -> bool {
+        let Some(indices) = self.section_index.get(&key.to_ascii_lowercase()) else {
+            return false;
+        };
+        let at = indices[0];
+
+        let source = Rc::<str>::from(text);
+        let mut new_sections = Vec::new();
+        let mut has_leading_items = false;
+        for item in Parser::new(&source) {
+            match item {
+                Item::Section(..) => new_sections.push(Section::new(Rc::clone(&source), item)),
+                _ => match new_sections.last_mut() {
+                    Some(section) => section.push_item(item),
+                    None => {
+                        has_leading_items = true;
+                        self.section_before(at).push_item_from(&source, item);
+                    },
+                },
+            }
+        }
+
+        // Keep the next section's header on its own line
+        match new_sections.last_mut() {
+            Some(section) => section.ensure_newline(),
+            None if has_leading_items => self.section_before(at).ensure_newline(),
+            None => (),
+        }
+
+        let rest = self.sections.split_off(at);
+        self.sections.extend(new_sections);
+        self.sections.extend(rest.into_iter().filter(|section| !section.key().eq_ignore_ascii_case(key)));
+        self.section_index = Self::build_section_index(&self.sections);
+
+        true
+    }
+
+    /// Parses a block of INI text, e.g. copied properties, into items that can be inserted
+    /// elsewhere with [`VirtualSectionMut::insert_items`]. Comments, blank lines, and padding
+    /// are kept.
+    pub fn parse_fragment(text: &str) -> Vec<Item> {
+        Parser::new(text)
+            .map(|item| item.detach(text))
+            .collect()
+    }
+
+    pub fn iter_sections(&self) -> core::slice::Iter<'_, Section> {
         self.sections.iter()
     }
 
+    pub(crate) fn iter_sections_mut(&mut self) -> core::slice::IterMut<'_, Section> {
+        self.sections.iter_mut()
+    }
+
+    pub(crate) fn global_section_mut(&mut self) -> &mut Section {
+        &mut self.global_section
+    }
+
     pub fn has_in(&self, section_key: &str, prop_key: &str) -> bool {
         self.section(section_key)
-            .map_or(false, |section| section.has(prop_key))
+            .is_some_and(|section| section.has(prop_key))
     }
 
     pub fn get_in(&self, section_key: &str, prop_key: &str) -> Option<&str> {
@@ -167,8 +269,8 @@ impl Ini {
     }
 }
 
-impl std::fmt::Display for Ini {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Ini {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_str(&self.global_section.to_string())?;
         for section in &self.sections {
             f.write_str(&section.to_string())?;
@@ -177,6 +279,14 @@ impl std::fmt::Display for Ini {
     }
 }
 
+impl core::fmt::Debug for Ini {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Ini")
+            .field(&self.to_string())
+            .finish()
+    }
+}
+
 fn borrow_indices<'a, T>(from: &'a [T], indices: &[usize]) -> Vec<&'a T> {
     indices.iter()
         .map(|&i| &from[i])
@@ -198,3 +308,60 @@ fn borrow_indices_mut<'a, T>(mut from: &'a mut [T], indices: &[usize]) -> Vec<&'
 
     refs
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_properties_are_accessible() {
+        let mut ini = Ini::new("; Header\nStray=1\n[World]\nName=A\n");
+        assert_eq!(ini.global().get("stray"), Some("1"));
+        assert_eq!(ini.global().iter().count(), 1);
+
+        ini.global_mut().set("Stray", "2".to_string());
+        ini.global_mut().set("Other", "3".to_string());
+        ini.global_mut().remove("Missing");
+        assert_eq!(ini.to_string(), "; Header\nStray=2\nOther=3\n[World]\nName=A\n");
+        assert_eq!(ini.get_in("World", "Other"), None);
+    }
+
+    #[test]
+    fn fragments_are_spliced_into_sections() {
+        let mut ini = Ini::new("[x1y1]\nTint=Red\n[x2y2]\nMusic=1\n[x1y1]\nAtmosA=2");
+        let fragment = Ini::parse_fragment("; Copied\n  ShiftXMap(A) = 5\n[Ignored]\nShiftYMap(A)=6");
+
+        let mut section = ini.section_mut("x1y1").unwrap();
+        assert_eq!(section.insert_items(1, fragment.clone()), 3);
+        assert_eq!(section.insert_items(99, fragment), 3);
+        assert_eq!(ini.to_string(), "[x1y1]\nTint=Red\n; Copied\n  ShiftXMap(A) = 5\nShiftYMap(A)=6\n[x2y2]\nMusic=1\n[x1y1]\nAtmosA=2\n; Copied\n  ShiftXMap(A) = 5\nShiftYMap(A)=6\n");
+        assert_eq!(ini.get_in("x1y1", "ShiftYMap(A)"), Some("6"));
+    }
+
+    #[test]
+    fn section_text_is_replaced_verbatim() {
+        let mut ini = Ini::new("[World]\nName=A\n\n[x1y1]\n; Note\nTint = Red\n[x2y2]\nMusic=1\n[X1Y1]\nMusic=2\n");
+        assert_eq!(ini.section_text("x1y1").as_deref(), Some("[x1y1]\n; Note\nTint = Red\n[X1Y1]\nMusic=2\n"));
+
+        assert!(ini.replace_section_text("x1y1", "[x1y1]\n;  Edited\nTint  =  Blue\n\n"));
+        assert_eq!(ini.to_string(), "[World]\nName=A\n\n[x1y1]\n;  Edited\nTint  =  Blue\n\n[x2y2]\nMusic=1\n");
+        assert_eq!(ini.get_in("x1y1", "Music"), None);
+        assert_eq!(ini.get_in("x2y2", "Music"), Some("1"));
+        assert!(!ini.replace_section_text("x3y3", ""));
+    }
+
+    #[test]
+    fn replaced_section_text_ends_with_newline() {
+        let mut ini = Ini::new("[x1y1]\nTint=Red\n[x2y2]\nMusic=1\n");
+        assert!(ini.replace_section_text("x1y1", "[x1y1]\nTint=Blue"));
+        assert_eq!(ini.to_string(), "[x1y1]\nTint=Blue\n[x2y2]\nMusic=1\n");
+
+        let mut ini = Ini::new("[World]\nName=A\n[x1y1]\nTint=Red\n[x2y2]\nMusic=1\n");
+        assert!(ini.replace_section_text("x1y1", "Moved=1"));
+        assert_eq!(ini.to_string(), "[World]\nName=A\nMoved=1\n[x2y2]\nMusic=1\n");
+
+        let reparsed = Ini::new(&ini.to_string());
+        assert_eq!(reparsed.get_in("World", "Moved"), Some("1"));
+        assert_eq!(reparsed.get_in("x2y2", "Music"), Some("1"));
+    }
+}
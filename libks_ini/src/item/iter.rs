@@ -1,3 +1,5 @@
+use alloc::string::{String, ToString};
+
 use super::{Item, SourcedItem};
 
 pub trait ItemsIteratorExt<'a, I>
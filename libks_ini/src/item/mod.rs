@@ -4,8 +4,12 @@ pub use iter::ItemsIteratorExt;
 mod padding;
 pub use padding::{Padding, Padding4};
 
+use alloc::borrow::ToOwned;
+
 use crate::span::Span;
 
+/// A line of an INI file. Spans are either ranges into the source the item was parsed from or
+/// owned text. See [`Ini::parse_fragment`](crate::Ini::parse_fragment).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Item {
     Error(Span),
@@ -19,6 +23,24 @@ pub enum Item {
 pub struct Prop {
     pub key: Span,
     pub value: Span,
+    /// What was stripped from the value when it was parsed. The stripped text is kept in the
+    /// padding, so it's still written out. See [`ParseOptions`](crate::ParseOptions).
+    pub stripped: Stripped,
+}
+
+/// What [`ParseOptions`](crate::ParseOptions) stripped from a value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stripped {
+    /// Matching quotes around the value.
+    pub quotes: bool,
+    /// An inline comment after the value.
+    pub comment: bool,
+}
+
+impl Stripped {
+    pub fn is_empty(&self) -> bool {
+        !self.quotes && !self.comment
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq)]
@@ -34,6 +56,55 @@ impl Item {
             item: self,
         }
     }
+
+    /// Makes sure the item's line ends with a line break, so another line can follow it.
+    pub(crate) fn ensure_newline(&mut self, source: &str) {
+        let trailing = match self {
+            Item::Error(line) | Item::Blank(line) => line,
+            Item::Section(_, Padding(_, after))
+            | Item::Property(_, Padding4(_, _, _, after))
+            | Item::Comment(_, Padding(_, after)) => after,
+        };
+
+        let text = trailing.of(source);
+        if !text.ends_with(['\n', '\r']) {
+            let mut text = text.to_owned();
+            text.push('\n');
+            *trailing = text.into();
+        }
+    }
+
+    /// Returns `true` if any of the item's text was replaced after parsing, or the item wasn't
+    /// parsed from the section's source at all.
+    pub(crate) fn is_edited(&self) -> bool {
+        let owned = |span: &Span| matches!(span, Span::Owned(_));
+
+        match self {
+            Item::Error(line) | Item::Blank(line) => owned(line),
+            Item::Section(text, Padding(before, after))
+            | Item::Comment(text, Padding(before, after)) => owned(text) || owned(before) || owned(after),
+            Item::Property(Prop { key, value, .. }, Padding4(before, before_eq, after_eq, after)) => {
+                [key, value, before, before_eq, after_eq, after].into_iter().any(owned)
+            },
+        }
+    }
+
+    /// Returns a copy of the item that owns its text, so it can be moved to another source.
+    pub(crate) fn detach(&self, source: &str) -> Item {
+        let span = |span: &Span| Span::from(span.of(source));
+        let padding = |Padding(before, after): &Padding| Padding(span(before), span(after));
+
+        match self {
+            Item::Error(line) => Item::Error(span(line)),
+            Item::Section(key, pad) => Item::Section(span(key), padding(pad)),
+            Item::Property(Prop { key, value, stripped }, Padding4(before, before_eq, after_eq, after)) => Item::Property(
+                Prop { key: span(key), value: span(value), stripped: *stripped },
+                Padding4(span(before), span(before_eq), span(after_eq), span(after)),
+            ),
+            Item::Comment(text, pad) => Item::Comment(span(text), padding(pad)),
+            Item::Blank(line) => Item::Blank(span(line)),
+        }
+    }
 }
 
 impl<K, V> From<(K, V)> for Prop
@@ -45,6 +116,7 @@ where
         Self {
             key: pair.0.into(),
             value: pair.1.into(),
+            stripped: Stripped::default(),
         }
     }
 }
@@ -65,8 +137,8 @@ impl<'a> PartialEq for SourcedItem<'a> {
                 && after1.of(src) == after2.of(src)
             },
             (
-                Item::Property(Prop { key: key1, value: value1 }, padding1),
-                Item::Property(Prop { key: key2, value: value2 }, padding2),
+                Item::Property(Prop { key: key1, value: value1, .. }, padding1),
+                Item::Property(Prop { key: key2, value: value2, .. }, padding2),
             ) => {
                 key1.of(src) == key2.of(src)
                 && value1.of(src) == value2.of(src)
@@ -91,8 +163,8 @@ impl<'a> PartialEq for SourcedItem<'a> {
     }
 }
 
-impl<'a> std::fmt::Display for SourcedItem<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<'a> core::fmt::Display for SourcedItem<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let src = self.source;
         match self.item {
             Item::Error(span) =>
@@ -100,7 +172,7 @@ impl<'a> std::fmt::Display for SourcedItem<'a> {
             Item::Section(span, Padding(before, after)) =>
                 write!(f, "{}[{}]{}", before.of(src), span.of(src), after.of(src)),
             Item::Property(
-                Prop { key, value },
+                Prop { key, value, .. },
                 Padding4(before, before_eq, after_eq, after),
             ) => {
                 write!(f, "{}{}{}={}{}{}",
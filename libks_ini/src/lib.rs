@@ -1,8 +1,25 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 mod section;
 mod ini;
+mod encode;
+mod format;
 mod item;
 mod parse;
+mod sanitize;
 mod span;
+#[cfg(feature = "std")]
+mod frozen;
 
 pub use ini::Ini;
-pub use parse::Parser;
+pub use item::{Item, Padding, Padding4, Prop, Stripped};
+pub use span::Span;
+pub use encode::{Encoded, EncodeError, Encoding, Fallback};
+pub use format::FormatOptions;
+#[cfg(feature = "std")]
+pub use frozen::FrozenIni;
+pub use parse::{Event, Events, IncrementalParser, ParseOptions, Parser};
+pub use section::{parse_xy, DuplicateKey, GlobalSection, GlobalSectionMut, KeyGroup, SectionKind, VirtualSection, VirtualSectionMut};
+pub use sanitize::{is_invalid_char, CharLocation, InvalidChar, Replacement};
@@ -0,0 +1,201 @@
+use core::ops::Range;
+
+use alloc::{string::String, vec::Vec};
+
+use super::Parser;
+use crate::{
+    item::{Item, Padding, Padding4},
+    span::Span,
+};
+
+/// A line of INI source, as reported by [`Parser::events`] and [`IncrementalParser`].
+///
+/// Every range is a byte range into the source. `line` covers the whole line, including leading
+/// whitespace and the line ending, so the lines of a source are contiguous and together cover
+/// all of it. Lines end at `\r\n`, `\n`, or `\r`. Keys, values, and comment text are trimmed of
+/// surrounding whitespace, except that comment text keeps the whitespace after the `;`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A section header like `[x1000y1000]`. `key` excludes the brackets.
+    Section {
+        line: Range<usize>,
+        key: Range<usize>,
+    },
+    /// A property like `Name=The Machine`, split at the first `=`. The key may be empty.
+    Property {
+        line: Range<usize>,
+        key: Range<usize>,
+        value: Range<usize>,
+    },
+    /// A comment like `; Hello`. `text` excludes the `;`.
+    Comment {
+        line: Range<usize>,
+        text: Range<usize>,
+    },
+    /// A line containing only whitespace.
+    Blank {
+        line: Range<usize>,
+    },
+    /// A line that isn't valid, e.g. a property without `=` or a header without a closing `]`
+    /// at the end of the line. KS ignores these.
+    Error {
+        line: Range<usize>,
+    },
+}
+
+impl Event {
+    /// Returns the range of the whole line.
+    pub fn line(&self) -> Range<usize> {
+        match self {
+            Event::Section { line, .. }
+            | Event::Property { line, .. }
+            | Event::Comment { line, .. }
+            | Event::Blank { line }
+            | Event::Error { line } => line.clone(),
+        }
+    }
+
+    fn offset(self, offset: usize) -> Self {
+        let shift = |range: Range<usize>| range.start + offset .. range.end + offset;
+        match self {
+            Event::Section { line, key } =>
+                Event::Section { line: shift(line), key: shift(key) },
+            Event::Property { line, key, value } =>
+                Event::Property { line: shift(line), key: shift(key), value: shift(value) },
+            Event::Comment { line, text } =>
+                Event::Comment { line: shift(line), text: shift(text) },
+            Event::Blank { line } =>
+                Event::Blank { line: shift(line) },
+            Event::Error { line } =>
+                Event::Error { line: shift(line) },
+        }
+    }
+}
+
+impl From<Item> for Event {
+    fn from(item: Item) -> Self {
+        match item {
+            Item::Error(line) => Event::Error { line: range(&line) },
+            Item::Section(key, Padding(before, after)) => Event::Section {
+                line: range(&before).start .. range(&after).end,
+                key: range(&key),
+            },
+            Item::Property(prop, Padding4(before, _, _, after)) => Event::Property {
+                line: range(&before).start .. range(&after).end,
+                key: range(&prop.key),
+                value: range(&prop.value),
+            },
+            Item::Comment(text, Padding(before, after)) => Event::Comment {
+                line: range(&before).start .. range(&after).end,
+                text: range(&text),
+            },
+            Item::Blank(line) => Event::Blank { line: range(&line) },
+        }
+    }
+}
+
+/// Returns the range of a span produced by the parser, which always slices the source.
+fn range(span: &Span) -> Range<usize> {
+    match span {
+        Span::Sliced(range) => range.clone(),
+        Span::Owned(_) => unreachable!("the parser only produces sliced spans"),
+    }
+}
+
+/// An iterator over the [`Event`]s of a source. See [`Parser::events`].
+pub struct Events<'a> {
+    parser: Parser<'a>,
+}
+
+impl<'a> Events<'a> {
+    pub(super) fn new(parser: Parser<'a>) -> Self {
+        Self { parser }
+    }
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.next().map(Event::from)
+    }
+}
+
+/// Parses INI source that arrives in pieces, e.g. while reading a large file, without keeping
+/// more than the current incomplete line.
+///
+/// Ranges in the events are relative to the start of the whole stream, so they're the same as
+/// if the pieces had been joined and given to [`Parser::events`].
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalParser {
+    buffer: String,
+    offset: usize,
+}
+
+impl IncrementalParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `chunk` to the stream and returns the events for the lines it completes. A line
+    /// is only complete once its line ending has been seen; a trailing `\r` is held back in case
+    /// the next chunk starts with `\n`.
+    pub fn push_str(&mut self, chunk: &str) -> Vec<Event> {
+        self.buffer.push_str(chunk);
+
+        let bytes = self.buffer.as_bytes();
+        let complete = bytes.iter()
+            .enumerate()
+            .rev()
+            .find(|&(i, &byte)| byte == b'\n' || (byte == b'\r' && i + 1 < bytes.len()))
+            .map(|(i, _)| i + 1);
+
+        match complete {
+            Some(end) => self.take(end),
+            None => Vec::new(),
+        }
+    }
+
+    /// Ends the stream and returns the events for whatever remains, which is at most one line.
+    pub fn finish(mut self) -> Vec<Event> {
+        let end = self.buffer.len();
+        self.take(end)
+    }
+
+    /// Parses the first `end` bytes of the buffer, which must end at a line boundary.
+    fn take(&mut self, end: usize) -> Vec<Event> {
+        let offset = self.offset;
+        let events = Parser::new(&self.buffer[..end])
+            .events()
+            .map(|event| event.offset(offset))
+            .collect();
+
+        self.buffer.drain(..end);
+        self.offset += end;
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incremental_events_match_whole_source() {
+        let source = "; Hi\r\n[World]\r\nName = The Machine\rBad\n\n[x1000y1000\nWarpUpY=-1";
+        let whole: Vec<Event> = Parser::new(source).events().collect();
+
+        for chunk_len in 1..source.len() {
+            let mut parser = IncrementalParser::new();
+            let mut events = Vec::new();
+            for chunk in source.as_bytes().chunks(chunk_len) {
+                events.extend(parser.push_str(core::str::from_utf8(chunk).unwrap()));
+            }
+            events.extend(parser.finish());
+            assert_eq!(events, whole, "chunk length {chunk_len}");
+        }
+
+        assert_eq!(whole[2], Event::Property { line: 15..34, key: 15..19, value: 22..33 });
+        assert_eq!(&source[whole[2].line()], "Name = The Machine\r");
+    }
+}
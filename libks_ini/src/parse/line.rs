@@ -1,4 +1,4 @@
-use std::cmp::min;
+use core::cmp::min;
 
 use memchr::{memchr2, memchr3};
 
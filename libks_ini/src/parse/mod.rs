@@ -4,23 +4,59 @@ use line::{Line, next_line};
 mod trim;
 use trim::{trimmed_range_start, trimmed_range_end};
 
+mod events;
+pub use events::{Event, Events, IncrementalParser};
+
+use core::ops::Range;
+
 use crate::{
-    item::{Item, Padding},
+    item::{Item, Padding, Prop, Stripped},
     span::Span,
 };
 
+/// Cleanup applied to values as they're parsed. Everything is off by default, since KS reads
+/// values literally: `Name="Level"` is named `"Level"`, quotes and all.
+///
+/// Some tools write values the way other INI dialects expect, though. Stripping is recorded in
+/// [`Prop::stripped`], and the stripped text is kept in the padding, so the file still
+/// round-trips unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Strip a matching pair of `"` or `'` around a value.
+    pub strip_quotes: bool,
+    /// Strip a `;` comment after a value. The `;` must start the value or follow whitespace,
+    /// so values like `A;B` are left alone.
+    pub strip_inline_comments: bool,
+}
+
+/// Splits INI source into lines without building an [`Ini`](crate::Ini).
+///
+/// Use [`Parser::events`] to read the lines as [`Event`]s, or [`IncrementalParser`] if the
+/// source arrives in pieces. Parsing never fails; lines that aren't valid are reported as
+/// [`Event::Error`].
 pub struct Parser<'a> {
     source: &'a str,
     start_line: usize,
+    options: ParseOptions,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(source: &'a str) -> Self {
+        Self::with_options(source, ParseOptions::default())
+    }
+
+    pub fn with_options(source: &'a str, options: ParseOptions) -> Self {
         Self {
             source,
             start_line: 0,
+            options,
         }
     }
+
+    /// Returns an iterator over the lines of the source as [`Event`]s, in order.
+    pub fn events(self) -> Events<'a> {
+        Events::new(self)
+    }
 }
 
 impl<'a> Iterator for Parser<'a> {
@@ -76,10 +112,11 @@ impl<'a> Iterator for Parser<'a> {
                         let start_value = start_untrimmed + trimmed_range_start(untrimmed);
                         (start_value..end_trimmed, start_untrimmed..start_value)
                     };
+                    let (value, stripped) = strip_value(source, value, self.options);
 
                     Item::Property(
-                        (key, value).into(),
-                        (line_padding.0, before_eq, after_eq, line_padding.1).into(),
+                        Prop { key: key.into(), value: value.clone().into(), stripped },
+                        (line_padding.0, before_eq, after_eq.start..value.start, value.end..start_next).into(),
                     )
                 },
                 _ => Item::Error(line),
@@ -94,6 +131,39 @@ impl<'a> Iterator for Parser<'a> {
     }
 }
 
+/// Narrows `value` according to `options`. Whatever is cut off becomes padding.
+fn strip_value(source: &str, mut value: Range<usize>, options: ParseOptions) -> (Range<usize>, Stripped) {
+    let mut stripped = Stripped::default();
+    let bytes = source.as_bytes();
+
+    // A `;` inside quotes doesn't start a comment
+    let quote_end = match bytes.get(value.start) {
+        Some(&quote @ (b'"' | b'\'')) if options.strip_quotes => {
+            memchr::memchr(quote, &bytes[value.start + 1..value.end])
+                .map(|i| value.start + 1 + i + 1)
+        },
+        _ => None,
+    };
+
+    if options.strip_inline_comments {
+        let search_start = quote_end.unwrap_or(value.start);
+        let comment = (search_start..value.end)
+            .find(|&i| bytes[i] == b';' && (i == value.start || matches!(bytes[i - 1], b' ' | b'\t')));
+
+        if let Some(comment) = comment {
+            value.end = value.start + trimmed_range_end(&source[value.start..comment]);
+            stripped.comment = true;
+        }
+    }
+
+    if options.strip_quotes && quote_end == Some(value.end) && value.len() >= 2 {
+        value = value.start + 1 .. value.end - 1;
+        stripped.quotes = true;
+    }
+
+    (value, stripped)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,6 +175,7 @@ mod tests {
                 Prop {
                     key: $key.into(),
                     value: $value.into(),
+                    stripped: Default::default(),
                 },
                 Padding4::from(("", "", "", "\n")),
             )
@@ -114,6 +185,7 @@ mod tests {
                 Prop {
                     key: $key.into(),
                     value: $value.into(),
+                    stripped: Default::default(),
                 },
                 Padding4::from(("", "", "", $nl)),
             )
@@ -123,6 +195,7 @@ mod tests {
                 Prop {
                     key: $key.into(),
                     value: $value.into(),
+                    stripped: Default::default(),
                 },
                 Padding4::from((
                     const_str::repeat!(" ", $p1),
@@ -137,6 +210,7 @@ mod tests {
                 Prop {
                     key: $key.into(),
                     value: $value.into(),
+                    stripped: Default::default(),
                 },
                 Padding4::from((
                     const_str::repeat!(" ", $p1),
@@ -179,8 +253,8 @@ ShiftSound(A)=None";
         ];
 
         assert_eq!(
-            items.iter().with_source(&source).collect::<Vec<_>>(),
-            truth.iter().with_source(&source).collect::<Vec<_>>()
+            items.iter().with_source(source).collect::<Vec<_>>(),
+            truth.iter().with_source(source).collect::<Vec<_>>()
         );
         assert_eq!(items_to_string(items, source), source);
     }
@@ -204,8 +278,8 @@ Name
         ];
 
         assert_eq!(
-            items.iter().with_source(&source).collect::<Vec<_>>(),
-            truth.iter().with_source(&source).collect::<Vec<_>>()
+            items.iter().with_source(source).collect::<Vec<_>>(),
+            truth.iter().with_source(source).collect::<Vec<_>>()
         );
         assert_eq!(items_to_string(items, source), source);
     }
@@ -236,8 +310,8 @@ ShiftEffect(A)=False
         ];
 
         assert_eq!(
-            items.iter().with_source(&source).collect::<Vec<_>>(),
-            truth.iter().with_source(&source).collect::<Vec<_>>()
+            items.iter().with_source(source).collect::<Vec<_>>(),
+            truth.iter().with_source(source).collect::<Vec<_>>()
         );
         assert_eq!(items_to_string(items, source), source);
     }
@@ -269,9 +343,42 @@ ShiftSound(A)=None\r\n\
         ];
 
         assert_eq!(
-            items.iter().with_source(&source).collect::<Vec<_>>(),
-            truth.iter().with_source(&source).collect::<Vec<_>>()
+            items.iter().with_source(source).collect::<Vec<_>>(),
+            truth.iter().with_source(source).collect::<Vec<_>>()
         );
         assert_eq!(items_to_string(items, source), source);
      }
+
+    #[test]
+    fn parser_strips_values_when_asked() {
+        let source = "\
+[World]
+Name=\"The Machine\" ; by Nifflas
+Author='Nifflas'
+Quote=\"a ;b\"
+Semi=A;B
+Empty=;nothing
+Open=\"half
+";
+        let options = ParseOptions { strip_quotes: true, strip_inline_comments: true };
+        let ini = crate::Ini::with_options(source, options);
+        let world = ini.section("World").unwrap();
+
+        let expected = [
+            ("Name", "The Machine", Stripped { quotes: true, comment: true }),
+            ("Author", "Nifflas", Stripped { quotes: true, comment: false }),
+            ("Quote", "a ;b", Stripped { quotes: true, comment: false }),
+            ("Semi", "A;B", Stripped::default()),
+            ("Empty", "", Stripped { quotes: false, comment: true }),
+            ("Open", "\"half", Stripped::default()),
+        ];
+        for (key, value, stripped) in expected {
+            assert_eq!(world.get(key), Some(value), "{key}");
+            assert_eq!(world.stripped(key), Some(stripped), "{key}");
+        }
+        assert_eq!(ini.to_string(), source);
+
+        let literal = crate::Ini::new(source);
+        assert_eq!(literal.get_in("World", "Name"), Some("\"The Machine\" ; by Nifflas"));
+    }
 }
@@ -0,0 +1,111 @@
+use core::ops::Range;
+
+use alloc::{string::String, vec::Vec};
+
+use crate::Ini;
+
+/// Returns `true` if KS may not handle `c` in a key or value: nulls and other control
+/// characters, except tabs.
+pub fn is_invalid_char(c: char) -> bool {
+    c.is_control() && c != '\t'
+}
+
+/// Where an [`InvalidChar`] was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharLocation {
+    Key,
+    Value,
+}
+
+/// A character in a key or value that [`is_invalid_char`]. See [`Ini::invalid_chars`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidChar {
+    /// The key of the section the property is in.
+    pub section: String,
+    /// The key of the property, including the invalid character if it's in the key.
+    pub key: String,
+    pub location: CharLocation,
+    pub ch: char,
+    /// The byte offset of the character in the key or value.
+    pub offset: usize,
+    /// The byte range of the character in the source the `Ini` was parsed from, or `None` if
+    /// the key or value has been changed since.
+    pub span: Option<Range<usize>>,
+}
+
+/// What [`Ini::sanitize_values`] does with invalid characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Replacement {
+    /// Delete them.
+    #[default]
+    Remove,
+    /// Replace each of them with this character.
+    Char(char),
+}
+
+impl Replacement {
+    /// Returns `value` with its invalid characters replaced, or `None` if it has none.
+    pub(crate) fn apply(self, value: &str) -> Option<String> {
+        if !value.chars().any(is_invalid_char) {
+            return None;
+        }
+
+        let sanitized = value.chars()
+            .filter_map(|c| match self {
+                _ if !is_invalid_char(c) => Some(c),
+                Replacement::Remove => None,
+                Replacement::Char(replacement) => Some(replacement),
+            })
+            .collect();
+
+        Some(sanitized)
+    }
+}
+
+impl Ini {
+    /// Finds every invalid character in the keys and values of the sections, in order.
+    /// Properties before the first section are ignored, as KS ignores them.
+    pub fn invalid_chars(&self) -> Vec<InvalidChar> {
+        let mut found = Vec::new();
+        for section in self.iter_sections() {
+            section.find_invalid_chars(&mut found);
+        }
+        found
+    }
+
+    /// Replaces the invalid characters in every value according to `policy` and returns the
+    /// number of values changed. Keys are left alone, since changing one could merge it with
+    /// another property; use [`Ini::invalid_chars`] to find them.
+    pub fn sanitize_values(&mut self, policy: Replacement) -> usize {
+        self.iter_sections_mut()
+            .map(|section| section.sanitize_values(policy))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_chars_are_found_and_replaced() {
+        let source = "[World]\nName=Bad\0Level\n[x1000y1000]\nTi\x07nt=Red\nSign(A)=Hi\tthere\n";
+        let mut ini = Ini::new(source);
+
+        let found = ini.invalid_chars();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].ch, '\0');
+        assert_eq!(found[0].location, CharLocation::Value);
+        assert_eq!(found[0].offset, 3);
+        assert_eq!(&source[found[0].span.clone().unwrap()], "\0");
+        assert_eq!((found[1].section.as_str(), found[1].location), ("x1000y1000", CharLocation::Key));
+
+        assert_eq!(ini.sanitize_values(Replacement::Char('?')), 1);
+        assert_eq!(ini.get_in("World", "Name"), Some("Bad?Level"));
+        assert_eq!(ini.get_in("x1000y1000", "Sign(A)"), Some("Hi\tthere"));
+
+        let found = ini.invalid_chars();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].span, Some(38..39));
+    }
+}
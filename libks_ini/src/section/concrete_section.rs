@@ -1,10 +1,24 @@
-use std::rc::Rc;
+use core::ops::Range;
 
-use crate::item::{
-    Item,
-    ItemsIteratorExt,
-    Padding4,
-    Prop,
+use alloc::{
+    borrow::ToOwned,
+    collections::BTreeMap,
+    rc::Rc,
+    string::String,
+    vec::Vec,
+};
+
+use crate::{
+    item::{
+        Item,
+        ItemsIteratorExt,
+        Padding4,
+        Prop,
+        Stripped,
+    },
+    format::FormatOptions,
+    sanitize::{is_invalid_char, CharLocation, InvalidChar, Replacement},
+    span::Span,
 };
 
 #[derive(Debug, Clone)]
@@ -33,6 +47,18 @@ impl ConcreteSection {
         self.items.push(item);
     }
 
+    /// Pushes an item that was parsed from `source` rather than the section's source.
+    pub(crate) fn push_item_from(&mut self, source: &str, item: Item) {
+        self.items.push(item.detach(source));
+    }
+
+    /// Makes sure the last line ends with a line break, so another section can follow it.
+    pub(crate) fn ensure_newline(&mut self) {
+        if let Some(last) = self.items.last_mut() {
+            last.ensure_newline(&self.source);
+        }
+    }
+
     /// # Panics
     /// 
     /// This method panics if called on the global section.
@@ -84,6 +110,13 @@ impl ConcreteSection {
             .map(|prop| prop.value.of(&self.source))
     }
 
+    /// Returns what was stripped from the value of `key` when it was parsed. See
+    /// [`ParseOptions`](crate::ParseOptions).
+    pub fn stripped(&self, key: &str) -> Option<Stripped> {
+        self.find_prop(key)
+            .map(|prop| prop.stripped)
+    }
+
     pub fn set(&mut self, key: &str, value: String) {
         if let Some(kvp) = self.find_prop_mut(key) {
             kvp.value = value.into();
@@ -110,7 +143,7 @@ impl ConcreteSection {
     pub fn remove(&mut self, key: &str) {
         self.items = self.items.iter()
             .filter(|item| match item {
-                Item::Property(prop, _) => prop.key.of(&self.source).eq_ignore_ascii_case(key),
+                Item::Property(prop, _) => !prop.key.of(&self.source).eq_ignore_ascii_case(key),
                 _ => true,
             })
             .cloned()
@@ -132,10 +165,143 @@ impl ConcreteSection {
     pub fn iter(&self) -> ConcreteSectionIter<'_> {
         ConcreteSectionIter::new(&self.source, &self.items)
     }
+
+    /// Inserts `items` before the `at`th line after the header, or at the end if there are
+    /// fewer lines. Section headers in `items` are skipped. Returns the number of items
+    /// inserted.
+    pub(crate) fn insert_items(&mut self, at: usize, items: Vec<Item>) -> usize {
+        let mut items: Vec<Item> = items.into_iter()
+            .filter(|item| !matches!(item, Item::Section(..)))
+            .collect();
+        if items.is_empty() {
+            return 0;
+        }
+
+        let header_len = usize::from(matches!(self.items.first(), Some(Item::Section(..))));
+        let at = (header_len + at).min(self.items.len());
+        if let Some(previous) = at.checked_sub(1) {
+            self.items[previous].ensure_newline(&self.source);
+        }
+        for item in &mut items {
+            item.ensure_newline(&self.source);
+        }
+
+        let count = items.len();
+        self.items.splice(at..at, items);
+        count
+    }
+
+    /// Returns the number of lines after the header.
+    pub(crate) fn line_count(&self) -> usize {
+        self.items.len() - usize::from(matches!(self.items.first(), Some(Item::Section(..))))
+    }
+
+    /// Returns the keys that appear more than once in this section, ignoring ASCII case, in the
+    /// order they first appear. KS uses the last value.
+    ///
+    /// Only this section is checked, not other sections with the same key.
+    pub fn duplicate_keys(&self) -> Vec<DuplicateKey> {
+        let mut index = BTreeMap::new();
+        let mut keys: Vec<DuplicateKey> = Vec::new();
+
+        for item in &self.items {
+            let Item::Property(prop, _) = item else { continue };
+            let key = prop.key.of(&self.source);
+            let span = match &prop.key {
+                Span::Sliced(range) => Some(range.clone()),
+                Span::Owned(_) => None,
+            };
+
+            let i = *index.entry(key.to_ascii_lowercase()).or_insert_with(|| {
+                keys.push(DuplicateKey { key: key.to_owned(), spans: Vec::new() });
+                keys.len() - 1
+            });
+            keys[i].spans.push(span);
+        }
+
+        keys.retain(|key| key.spans.len() > 1);
+        keys
+    }
+
+    /// Appends the invalid characters in the keys and values of the section to `found`.
+    pub(crate) fn find_invalid_chars(&self, found: &mut Vec<InvalidChar>) {
+        for item in &self.items {
+            let Item::Property(prop, _) = item else { continue };
+            let key = prop.key.of(&self.source);
+
+            for (location, span) in [(CharLocation::Key, &prop.key), (CharLocation::Value, &prop.value)] {
+                for (offset, ch) in span.of(&self.source).char_indices().filter(|&(_, c)| is_invalid_char(c)) {
+                    found.push(InvalidChar {
+                        section: self.key().to_owned(),
+                        key: key.to_owned(),
+                        location,
+                        ch,
+                        offset,
+                        span: match span {
+                            Span::Sliced(range) => Some(range.start + offset .. range.start + offset + ch.len_utf8()),
+                            Span::Owned(_) => None,
+                        },
+                    });
+                }
+            }
+        }
+    }
+
+    /// Replaces the invalid characters in the values of the section. Returns the number of
+    /// values changed.
+    pub(crate) fn sanitize_values(&mut self, policy: Replacement) -> usize {
+        let mut changed = 0;
+        for item in &mut self.items {
+            let Item::Property(prop, _) = item else { continue };
+            if let Some(value) = policy.apply(prop.value.of(&self.source)) {
+                prop.value = value.into();
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Returns `true` if any line of the section was added or changed after parsing.
+    pub(crate) fn is_edited(&self) -> bool {
+        self.items.iter().any(Item::is_edited)
+    }
+
+    /// Rewrites the padding of the section's properties according to `options`. Returns `true`
+    /// if anything changed.
+    pub(crate) fn format(&mut self, options: FormatOptions) -> bool {
+        let width = |key: &Span| key.of(&self.source).chars().count();
+        let key_width = match options.align_equals {
+            true => self.items.iter()
+                .filter_map(|item| match item {
+                    Item::Property(prop, _) => Some(width(&prop.key)),
+                    _ => None,
+                })
+                .max()
+                .unwrap_or(0),
+            false => 0,
+        };
+
+        let mut changed = false;
+        for item in &mut self.items {
+            let Item::Property(prop, Padding4(before, before_eq, after_eq, _)) = item else { continue };
+
+            let new_before_eq = " ".repeat(key_width.saturating_sub(width(&prop.key)));
+            // Only spaces are dropped, since a stripped opening quote also lives here
+            let new_after_eq = after_eq.of(&self.source).trim_start_matches(' ').to_owned();
+
+            for (span, new) in [(before, String::new()), (before_eq, new_before_eq), (after_eq, new_after_eq)] {
+                if span.of(&self.source) != new {
+                    *span = new.into();
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
 }
 
-impl std::fmt::Display for ConcreteSection {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ConcreteSection {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let output = self.items.iter()
             .with_source(&self.source)
             .collect::<String>();
@@ -143,9 +309,19 @@ impl std::fmt::Display for ConcreteSection {
     }
 }
 
+/// A key that appears more than once in a section. See [`ConcreteSection::duplicate_keys`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    /// The key as first written.
+    pub key: String,
+    /// The byte range of the key in the source the `Ini` was parsed from for each occurrence,
+    /// in order, or `None` where the key has been changed since.
+    pub spans: Vec<Option<Range<usize>>>,
+}
+
 pub struct ConcreteSectionIter<'a> {
     source: &'a str,
-    items: std::slice::Iter<'a, Item>,
+    items: core::slice::Iter<'a, Item>,
 }
 
 impl<'a> ConcreteSectionIter<'a> {
@@ -171,3 +347,33 @@ impl<'a> Iterator for ConcreteSectionIter<'a> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Ini;
+
+    #[test]
+    fn duplicate_keys_are_found() {
+        let source = "[x1y1]\nTint=Red\nMusic=1\ntint=Blue\nTINT=Green\nMusic=2\nAtmosA=3\n[x1y1]\nAtmosA=4\n";
+        let ini = Ini::new(source);
+        let duplicates = ini.iter_sections().next().unwrap().duplicate_keys();
+
+        assert_eq!(duplicates.len(), 2);
+        assert_eq!(duplicates[0].key, "Tint");
+        assert_eq!(duplicates[0].spans.len(), 3);
+        assert_eq!(&source[duplicates[0].spans[2].clone().unwrap()], "TINT");
+        assert_eq!(duplicates[1].key, "Music");
+    }
+
+    #[test]
+    fn remove_keeps_other_keys() {
+        let mut ini = Ini::new("[World]\nName=Level\nFormatEx=1\nformatex=2\nAuthor=Me\n[x1y1]\nFormatEx=3\n");
+        ini.remove_in("World", "FormatEx");
+
+        assert_eq!(ini.get_in("World", "FormatEx"), None);
+        assert_eq!(ini.get_in("World", "Name"), Some("Level"));
+        assert_eq!(ini.get_in("World", "Author"), Some("Me"));
+        assert_eq!(ini.get_in("x1y1", "FormatEx"), Some("3"));
+        assert_eq!(ini.to_string(), "[World]\nName=Level\nAuthor=Me\n[x1y1]\nFormatEx=3\n");
+    }
+}
@@ -0,0 +1,73 @@
+use alloc::string::String;
+
+use super::{Section, SectionIter};
+use crate::item::Stripped;
+
+/// The properties before the first section header. See [`Ini::global`](crate::Ini::global).
+///
+/// Unlike [`VirtualSection`](super::VirtualSection), it has no key.
+#[derive(Debug)]
+pub struct GlobalSection<'a> {
+    section: &'a Section,
+}
+
+/// Like [`GlobalSection`], but allows editing. See [`Ini::global_mut`](crate::Ini::global_mut).
+#[derive(Debug)]
+pub struct GlobalSectionMut<'a> {
+    section: &'a mut Section,
+}
+
+impl<'a> GlobalSection<'a> {
+    pub(crate) fn new(section: &'a Section) -> Self {
+        Self { section }
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        self.section.has(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.section.get(key)
+    }
+
+    /// Returns what was stripped from the value of `key` when it was parsed. See
+    /// [`ParseOptions`](crate::ParseOptions).
+    pub fn stripped(&self, key: &str) -> Option<Stripped> {
+        self.section.stripped(key)
+    }
+
+    pub fn iter(&self) -> SectionIter<'_> {
+        self.section.iter()
+    }
+}
+
+impl<'a> GlobalSectionMut<'a> {
+    pub(crate) fn new(section: &'a mut Section) -> Self {
+        Self { section }
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        self.section.has(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.section.get(key)
+    }
+
+    pub fn iter(&self) -> SectionIter<'_> {
+        self.section.iter()
+    }
+
+    /// Sets `key` to `value`, adding it after any existing lines if it isn't present.
+    pub fn set(&mut self, key: &str, value: String) {
+        self.section.set(key, value);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.section.remove(key);
+    }
+
+    pub fn rename(&mut self, from_key: &str, to_key: &str) {
+        self.section.rename(from_key, to_key);
+    }
+}
@@ -0,0 +1,136 @@
+use alloc::vec::Vec;
+
+use super::VirtualSection;
+
+/// The properties of a section that share a prefix and slot, like `ShiftXMap(A)`,
+/// `ShiftYMap(A)`, and `ShiftVisible(A)`. See [`VirtualSection::get_group`].
+///
+/// Properties are looked up by the part of the key between the prefix and the slot, e.g.
+/// `XMap`, ignoring ASCII case. When a key is repeated, the last value wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyGroup<'a> {
+    prefix: &'a str,
+    slot: &'a str,
+    entries: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> KeyGroup<'a> {
+    pub fn prefix(&self) -> &'a str {
+        self.prefix
+    }
+
+    pub fn slot(&self) -> &'a str {
+        self.slot
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.entries.iter().rev()
+            .find(|(entry_name, _)| entry_name.eq_ignore_ascii_case(name))
+            .map(|&(_, value)| value)
+    }
+
+    /// Returns an iterator over the names and values, in order, including repeated names.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.entries.iter().copied()
+    }
+}
+
+impl<'a> VirtualSection<'a> {
+    /// Returns the properties whose keys have the form `{prefix}{name}({slot})`, e.g.
+    /// `get_group("Shift", "A")` for `ShiftXMap(A)`, `ShiftSound(A)`, and the rest.
+    pub fn get_group<'b>(&'b self, prefix: &'b str, slot: &'b str) -> KeyGroup<'b> {
+        let entries = self.iter()
+            .filter_map(|(key, value)| {
+                let name = strip_prefix_ignore_case(key, prefix)?
+                    .strip_suffix(')')?;
+                let name = strip_suffix_ignore_case(name, slot)?
+                    .strip_suffix('(')?;
+                Some((name, value))
+            })
+            .collect();
+
+        KeyGroup { prefix, slot, entries }
+    }
+
+    /// Returns an iterator over the properties whose keys match `glob`, ignoring ASCII case.
+    /// In the pattern, `*` matches any number of characters and `?` matches one, so
+    /// `Shift*(?)` matches every shift property.
+    pub fn iter_matching<'b>(&'b self, glob: &'b str) -> impl Iterator<Item = (&'b str, &'b str)> + 'b {
+        self.iter()
+            .filter(move |(key, _)| glob_matches(glob, key))
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix).then(|| &s[prefix.len()..])
+}
+
+fn strip_suffix_ignore_case<'a>(s: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = s.len().checked_sub(suffix.len())?;
+    let tail = s.get(split..)?;
+    tail.eq_ignore_ascii_case(suffix).then(|| &s[..split])
+}
+
+/// Returns `true` if `s` matches `glob`, ignoring ASCII case.
+fn glob_matches(glob: &str, s: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let s: Vec<char> = s.chars().collect();
+
+    // Backtrack to the most recent `*` on a mismatch, letting it match one more character
+    let (mut g, mut i) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while i < s.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, i));
+                g += 1;
+            },
+            Some(&c) if c == '?' || c.eq_ignore_ascii_case(&s[i]) => {
+                g += 1;
+                i += 1;
+            },
+            _ => match star {
+                Some((star_g, star_i)) => {
+                    star = Some((star_g, star_i + 1));
+                    g = star_g + 1;
+                    i = star_i + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Ini;
+
+    #[test]
+    fn groups_and_globs_match_slotted_keys() {
+        let ini = Ini::new("[x1y1]\nShiftXMap(A)=2\nshiftymap(a)=3\nShiftXMap(B)=4\nShift(A)=5\nShiftXMap(A)=6\nWarpX=1\n");
+        let section = ini.section("x1y1").unwrap();
+
+        let group = section.get_group("Shift", "A");
+        assert_eq!(group.get("xmap"), Some("6"));
+        assert_eq!(group.get("YMap"), Some("3"));
+        assert_eq!(group.get(""), Some("5"));
+        assert!(!group.has("Visible"));
+        assert_eq!(group.iter().count(), 4);
+
+        let keys: Vec<_> = section.iter_matching("shift*map(?)").map(|(key, _)| key).collect();
+        assert_eq!(keys, ["ShiftXMap(A)", "shiftymap(a)", "ShiftXMap(B)", "ShiftXMap(A)"]);
+        assert_eq!(section.iter_matching("*").count(), 6);
+        assert_eq!(section.iter_matching("Warp?").count(), 1);
+    }
+}
@@ -0,0 +1,108 @@
+use super::Section;
+use crate::Ini;
+
+/// What a World.ini section describes, judging by its key. See [`Ini::section_kinds`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SectionKind {
+    /// `[World]`.
+    World,
+    /// A screen section like `[x1000y1000]`.
+    Screen((i64, i64)),
+    /// A custom object section like `[Custom Object 3]`.
+    CustomObject(u8),
+    /// A KS Plus custom object section like `[Custom Object B3]`.
+    CustomObjectB(u8),
+    /// A section configuring cutscenes, such as `[Cutscene Color]`.
+    Cutscene,
+    /// Any other section.
+    Unknown,
+}
+
+impl SectionKind {
+    /// Classifies a section by its key, ignoring ASCII case.
+    pub fn of(key: &str) -> SectionKind {
+        let lower = key.to_ascii_lowercase();
+        if lower == "world" {
+            SectionKind::World
+        }
+        else if let Some(screen) = parse_xy(&lower) {
+            SectionKind::Screen(screen)
+        }
+        else if let Some(number) = lower.strip_prefix("custom object ") {
+            match number.strip_prefix('b') {
+                Some(number) => number.parse().map_or(SectionKind::Unknown, SectionKind::CustomObjectB),
+                None => number.parse().map_or(SectionKind::Unknown, SectionKind::CustomObject),
+            }
+        }
+        else if lower.starts_with("cutscene ") {
+            SectionKind::Cutscene
+        }
+        else {
+            SectionKind::Unknown
+        }
+    }
+
+    /// Returns the position of a [`SectionKind::Screen`], or `None` for other kinds.
+    pub fn screen(self) -> Option<(i64, i64)> {
+        match self {
+            SectionKind::Screen(position) => Some(position),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a lowercase screen key like `x1000y1000`, as used by World.ini sections and Map.bin.
+pub fn parse_xy(s: &str) -> Option<(i64, i64)> {
+    let (x, y) = s.strip_prefix('x')?.split_once('y')?;
+    Some((x.parse().ok()?, y.parse().ok()?))
+}
+
+impl Section {
+    /// # Panics
+    ///
+    /// This method panics if called on the global section.
+    pub fn kind(&self) -> SectionKind {
+        SectionKind::of(self.key())
+    }
+}
+
+impl Ini {
+    /// Returns an iterator over the sections in file order with their indices. A section that
+    /// appears more than once is visited each time, with a different index.
+    pub fn iter_sections_indexed(&self) -> impl Iterator<Item = (usize, &Section)> {
+        self.iter_sections().enumerate()
+    }
+
+    /// Returns an iterator over the index and [`SectionKind`] of each section, in file order.
+    pub fn section_kinds(&self) -> impl Iterator<Item = (usize, SectionKind)> + '_ {
+        self.iter_sections_indexed()
+            .map(|(i, section)| (i, section.kind()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn sections_are_classified_in_file_order() {
+        let ini = Ini::new("[WORLD]\n[x-1y20]\n[Custom Object 4]\n[custom object b255]\n[Custom Object 256]\n[Cutscene Color]\n[x1y]\n[x-1Y20]\n");
+        let kinds: Vec<_> = ini.section_kinds().map(|(_, kind)| kind).collect();
+
+        assert_eq!(kinds, [
+            SectionKind::World,
+            SectionKind::Screen((-1, 20)),
+            SectionKind::CustomObject(4),
+            SectionKind::CustomObjectB(255),
+            SectionKind::Unknown,
+            SectionKind::Cutscene,
+            SectionKind::Unknown,
+            SectionKind::Screen((-1, 20)),
+        ]);
+        assert_eq!(ini.section_kinds().last().map(|(i, _)| i), Some(7));
+        assert_eq!(kinds[1].screen(), Some((-1, 20)));
+        assert_eq!(kinds[0].screen(), None);
+    }
+}
@@ -1,10 +1,17 @@
 mod concrete_section;
 mod virtual_section;
+mod global_section;
 mod section_group_iter;
+mod key_group;
+mod kind;
 
 pub use concrete_section::{
     ConcreteSection as Section,
     ConcreteSectionIter as SectionIter,
+    DuplicateKey,
 };
 pub use virtual_section::{VirtualSection, VirtualSectionMut};
+pub use global_section::{GlobalSection, GlobalSectionMut};
 pub use section_group_iter::SectionGroupIter;
+pub use key_group::KeyGroup;
+pub use kind::{parse_xy, SectionKind};
@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use super::{Section, SectionIter};
 
 pub struct SectionGroupIter<'a> {
@@ -1,4 +1,7 @@
+use alloc::{string::String, vec::Vec};
+
 use super::{Section, SectionGroupIter};
+use crate::item::{Item, Stripped};
 
 #[derive(Debug)]
 pub struct VirtualSection<'a> {
@@ -29,7 +32,14 @@ impl<'a> VirtualSection<'a> {
             .find_map(|section| section.get(key))
     }
 
-    pub fn iter(&self) -> SectionGroupIter {
+    /// Returns what was stripped from the value of `key` when it was parsed. See
+    /// [`ParseOptions`](crate::ParseOptions).
+    pub fn stripped(&self, key: &str) -> Option<Stripped> {
+        self.sections.iter().rev()
+            .find_map(|section| section.stripped(key))
+    }
+
+    pub fn iter(&self) -> SectionGroupIter<'_> {
         SectionGroupIter::new(self.sections.clone())
     }
 }
@@ -69,6 +79,22 @@ impl<'a> VirtualSectionMut<'a> {
         self.sections[0].set(key, value);
     }
 
+    /// Inserts `items`, e.g. from [`Ini::parse_fragment`](crate::Ini::parse_fragment), before
+    /// the `at`th line after the header, or at the end if there are fewer lines. Repeated
+    /// sections are treated as one, with their lines in order. Section headers in `items` are
+    /// skipped. Returns the number of items inserted.
+    pub fn insert_items(&mut self, mut at: usize, items: Vec<Item>) -> usize {
+        let last = self.sections.len() - 1;
+        for (i, section) in self.sections.iter_mut().enumerate() {
+            let line_count = section.line_count();
+            if at <= line_count || i == last {
+                return section.insert_items(at, items);
+            }
+            at -= line_count;
+        }
+        unreachable!("a virtual section should have at least one section")
+    }
+
     pub fn remove(&mut self, key: &str) {
         for section in &mut self.sections {
             section.remove(key);
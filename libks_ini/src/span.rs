@@ -1,4 +1,6 @@
-use std::ops::Range;
+use core::ops::Range;
+
+use alloc::{borrow::ToOwned, string::String};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Span {
@@ -7,7 +9,7 @@ pub enum Span {
 }
 
 impl Span {
-    pub fn of<'a>(&'a self, s: &'a str) -> &'_ str {
+    pub fn of<'a>(&'a self, s: &'a str) -> &'a str {
         match self {
             Span::Sliced(range) => &s[range.start .. range.end],
             Span::Owned(value) => value,